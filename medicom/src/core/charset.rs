@@ -21,6 +21,18 @@ use std::fmt::Debug;
 use encoding_rs::{Encoding, WINDOWS_1252};
 use thiserror::Error;
 
+use crate::core::charset::iso2022::CodeExtensions;
+
+mod iso2022;
+
+/// The delimiters of text values, after which the initial character sets are restored when using
+/// code extensions. Control characters are always delimiters.
+const TEXT_DELIMITERS: [char; 1] = ['\\'];
+
+/// The delimiters of person name values, which include those separating components and component
+/// groups.
+const PERSON_NAME_DELIMITERS: [char; 3] = ['\\', '^', '='];
+
 #[derive(Error, Debug)]
 /// Errors that can occur during writing of a DICOM dataset.
 pub enum CSError {
@@ -31,28 +43,67 @@ pub enum CSError {
     DecodingError { encoder: &'static str },
 }
 
+/// The character set used to decode and encode text values, as specified by
+/// `SpecificCharacterSet`. This is either a single encoding, or a set of character sets which are
+/// switched between using ISO 2022 code extensions.
 #[derive(Clone, Copy)]
 pub struct CSRef {
     encoding: &'static Encoding,
+    extensions: Option<CodeExtensions>,
 }
 
 impl CSRef {
     #[must_use]
     pub const fn of(encoding: &'static Encoding) -> Self {
-        CSRef { encoding }
+        CSRef {
+            encoding,
+            extensions: None,
+        }
     }
 
+    /// The name of the encoding. For character sets using code extensions this is the name of the
+    /// encoding which best describes them, such as `ISO-2022-JP` for Japanese character sets.
     #[must_use]
     pub fn name(&self) -> &str {
         self.encoding.name()
     }
 
+    /// The Defined Terms of `SpecificCharacterSet` for character sets using code extensions,
+    /// starting with the first value, which is empty if the default repertoire is used. This is
+    /// empty for character sets which don't use code extensions.
+    #[must_use]
+    pub fn code_extensions(&self) -> Vec<&'static str> {
+        self.extensions
+            .map(CodeExtensions::defined_terms)
+            .unwrap_or_default()
+    }
+
     /// Encodes the given text using the encoding.
     ///
     /// # Errors
     /// Errors during encoding.
     pub fn encode(&self, text: &str) -> Result<Vec<u8>, CSError> {
-        Ok(self.encoding.encode(text).0.into_owned())
+        self.encode_with_delimiters(text, &TEXT_DELIMITERS)
+    }
+
+    /// Encodes the given person name using the encoding. When using code extensions the initial
+    /// character sets are restored before each component and component group.
+    ///
+    /// # Errors
+    /// Errors during encoding.
+    pub fn encode_person_name(&self, text: &str) -> Result<Vec<u8>, CSError> {
+        self.encode_with_delimiters(text, &PERSON_NAME_DELIMITERS)
+    }
+
+    fn encode_with_delimiters(&self, text: &str, delimiters: &[char]) -> Result<Vec<u8>, CSError> {
+        let Some(extensions) = self.extensions else {
+            return Ok(self.encoding.encode(text).0.into_owned());
+        };
+        extensions
+            .encode(text, delimiters)
+            .ok_or_else(|| CSError::EncodingError {
+                encoder: self.encoding.name(),
+            })
     }
 
     /// Decodes the given text using the encoding.
@@ -60,12 +111,67 @@ impl CSRef {
     /// # Errors
     /// Errors during decoding.
     pub fn decode(&self, data: &[u8]) -> Result<String, CSError> {
-        self.encoding
-            .decode_without_bom_handling_and_without_replacement(data)
-            .map(|s| s.to_string())
-            .ok_or_else(|| CSError::DecodingError {
-                encoder: self.encoding.name(),
-            })
+        self.decode_with_delimiters(data, &TEXT_DELIMITERS)
+    }
+
+    /// Decodes the given person name using the encoding. When using code extensions the initial
+    /// character sets are restored for each component and component group.
+    ///
+    /// # Errors
+    /// Errors during decoding.
+    pub fn decode_person_name(&self, data: &[u8]) -> Result<String, CSError> {
+        self.decode_with_delimiters(data, &PERSON_NAME_DELIMITERS)
+    }
+
+    /// Decodes the given text, or person name, replacing any malformed sequences with U+FFFD
+    /// rather than failing. The returned flag is set if any replacements were made, i.e. the text
+    /// could not be decoded losslessly.
+    pub(crate) fn decode_with_replacement(&self, data: &[u8], person_name: bool) -> (String, bool) {
+        let delimiters: &[char] = if person_name {
+            &PERSON_NAME_DELIMITERS
+        } else {
+            &TEXT_DELIMITERS
+        };
+        if let Some(extensions) = self.extensions {
+            extensions.decode_with_replacement(data, &byte_delimiters(delimiters))
+        } else {
+            let (text, had_errors) = self.encoding.decode_without_bom_handling(data);
+            (text.to_string(), had_errors)
+        }
+    }
+
+    fn decode_with_delimiters(&self, data: &[u8], delimiters: &[char]) -> Result<String, CSError> {
+        let decoded: Option<String> = if let Some(extensions) = self.extensions {
+            extensions.decode(data, &byte_delimiters(delimiters))
+        } else {
+            self.encoding
+                .decode_without_bom_handling_and_without_replacement(data)
+                .map(|s| s.to_string())
+        };
+        decoded.ok_or_else(|| CSError::DecodingError {
+            encoder: self.encoding.name(),
+        })
+    }
+
+    /// Looks up the character set for all values of `SpecificCharacterSet`. If any value is a
+    /// Defined Term using code extensions, such as `ISO 2022 IR 87`, the character set switches
+    /// between those of all values as escape sequences are encountered. Otherwise the first
+    /// non-empty value is looked up with `CSRef::lookup_charset()`.
+    ///
+    /// Returns `None` if there are no non-empty values or none are recognized.
+    #[must_use]
+    pub fn lookup_specific_character_set<S: AsRef<str>>(values: &[S]) -> Option<CSRef> {
+        if let Some(extensions) = CodeExtensions::from_values(values) {
+            return Some(CSRef {
+                encoding: extensions.encoding(),
+                extensions: Some(extensions),
+            });
+        }
+        values
+            .iter()
+            .map(|value| value.as_ref().trim())
+            .find(|value| !value.is_empty())
+            .and_then(CSRef::lookup_charset)
     }
 
     /// This is based off `encoding::label::encoding_from_whatwg_label` with a few minor changes
@@ -163,6 +269,14 @@ impl CSRef {
     }
 }
 
+/// The delimiters as the bytes they're encoded as, which are the same in all character sets.
+fn byte_delimiters(delimiters: &[char]) -> Vec<u8> {
+    delimiters
+        .iter()
+        .filter_map(|c| u8::try_from(*c).ok())
+        .collect()
+}
+
 impl Default for CSRef {
     /// The default character set for DICOM.
     fn default() -> Self {
@@ -172,6 +286,10 @@ impl Default for CSRef {
 
 impl Debug for CSRef {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.encoding.name())
+        if self.extensions.is_some() {
+            write!(f, "{}", self.code_extensions().join("\\"))
+        } else {
+            write!(f, "{}", self.encoding.name())
+        }
     }
 }
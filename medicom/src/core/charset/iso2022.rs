@@ -0,0 +1,539 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! ISO 2022 code extension techniques, used when `SpecificCharacterSet` has the Defined Terms of
+//! Part 3, Section C.12.1.1.2 which begin with `ISO 2022`. Character sets are designated to G0 or
+//! G1 by escape sequences within a value, with bytes below 0x80 decoded using G0 and bytes above
+//! using G1. Refer to Part 5, Section 6.1.2.5.
+//!
+//! The character sets designated by the first value of `SpecificCharacterSet`, or the default
+//! repertoire if it's empty, are in effect at the start of each value, and again after each
+//! delimiter: control characters such as CR, LF, and TAB, the backslash separating values, and the
+//! `^` and `=` separating the components and component groups of person names.
+
+use encoding_rs::{
+    Encoding, EUC_JP, EUC_KR, GBK, ISO_2022_JP, ISO_8859_15, ISO_8859_2, ISO_8859_3, ISO_8859_4,
+    ISO_8859_5, ISO_8859_6, ISO_8859_7, ISO_8859_8, SHIFT_JIS, WINDOWS_1252, WINDOWS_1254,
+    WINDOWS_874,
+};
+
+/// The escape character, which begins an escape sequence.
+const ESC: u8 = 0x1B;
+
+/// The prefix of JIS X 0212 characters within EUC-JP.
+const EUC_JP_JIS_X_0212: u8 = 0x8F;
+
+/// The graphic characters of 94-character sets are in this range, in either G0 or G1.
+const GRAPHIC_RANGE: std::ops::RangeInclusive<u8> = 0x21..=0x7E;
+
+/// The Defined Terms which use code extensions, with the code elements they designate to G0 and
+/// G1.
+const DEFINED_TERMS: [(&str, Option<CodeElement>, Option<CodeElement>); 17] = [
+    ("ISO 2022 IR 6", Some(CodeElement::Ascii), None),
+    (
+        "ISO 2022 IR 100",
+        Some(CodeElement::Ascii),
+        Some(CodeElement::Latin1),
+    ),
+    (
+        "ISO 2022 IR 101",
+        Some(CodeElement::Ascii),
+        Some(CodeElement::Latin2),
+    ),
+    (
+        "ISO 2022 IR 109",
+        Some(CodeElement::Ascii),
+        Some(CodeElement::Latin3),
+    ),
+    (
+        "ISO 2022 IR 110",
+        Some(CodeElement::Ascii),
+        Some(CodeElement::Latin4),
+    ),
+    (
+        "ISO 2022 IR 144",
+        Some(CodeElement::Ascii),
+        Some(CodeElement::Cyrillic),
+    ),
+    (
+        "ISO 2022 IR 127",
+        Some(CodeElement::Ascii),
+        Some(CodeElement::Arabic),
+    ),
+    (
+        "ISO 2022 IR 126",
+        Some(CodeElement::Ascii),
+        Some(CodeElement::Greek),
+    ),
+    (
+        "ISO 2022 IR 138",
+        Some(CodeElement::Ascii),
+        Some(CodeElement::Hebrew),
+    ),
+    (
+        "ISO 2022 IR 148",
+        Some(CodeElement::Ascii),
+        Some(CodeElement::Latin5),
+    ),
+    (
+        "ISO 2022 IR 203",
+        Some(CodeElement::Ascii),
+        Some(CodeElement::Latin9),
+    ),
+    (
+        "ISO 2022 IR 13",
+        Some(CodeElement::JisRoman),
+        Some(CodeElement::JisKatakana),
+    ),
+    (
+        "ISO 2022 IR 166",
+        Some(CodeElement::Ascii),
+        Some(CodeElement::Thai),
+    ),
+    ("ISO 2022 IR 87", Some(CodeElement::JisX0208), None),
+    ("ISO 2022 IR 159", Some(CodeElement::JisX0212), None),
+    ("ISO 2022 IR 149", None, Some(CodeElement::KsX1001)),
+    ("ISO 2022 IR 58", None, Some(CodeElement::Gb2312)),
+];
+
+/// A character set which can be designated to G0 or G1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CodeElement {
+    /// ISO-IR 6, the default repertoire.
+    Ascii,
+    /// ISO-IR 14, JIS X 0201 Romaji. This differs from ASCII only in the glyphs of two characters,
+    /// including the value delimiter, so it is treated as ASCII.
+    JisRoman,
+    /// ISO-IR 13, JIS X 0201 Katakana.
+    JisKatakana,
+    /// ISO-IR 100.
+    Latin1,
+    /// ISO-IR 101.
+    Latin2,
+    /// ISO-IR 109.
+    Latin3,
+    /// ISO-IR 110.
+    Latin4,
+    /// ISO-IR 144.
+    Cyrillic,
+    /// ISO-IR 127.
+    Arabic,
+    /// ISO-IR 126.
+    Greek,
+    /// ISO-IR 138.
+    Hebrew,
+    /// ISO-IR 148.
+    Latin5,
+    /// ISO-IR 203.
+    Latin9,
+    /// ISO-IR 166.
+    Thai,
+    /// ISO-IR 87, JIS X 0208 Kanji.
+    JisX0208,
+    /// ISO-IR 159, JIS X 0212 Supplementary Kanji. This can only be decoded.
+    JisX0212,
+    /// ISO-IR 149, KS X 1001 Hangul and Hanja.
+    KsX1001,
+    /// ISO-IR 58, GB 2312 Simplified Chinese.
+    Gb2312,
+}
+
+impl CodeElement {
+    /// All code elements, for matching escape sequences.
+    const ALL: [CodeElement; 18] = [
+        CodeElement::Ascii,
+        CodeElement::JisRoman,
+        CodeElement::JisKatakana,
+        CodeElement::Latin1,
+        CodeElement::Latin2,
+        CodeElement::Latin3,
+        CodeElement::Latin4,
+        CodeElement::Cyrillic,
+        CodeElement::Arabic,
+        CodeElement::Greek,
+        CodeElement::Hebrew,
+        CodeElement::Latin5,
+        CodeElement::Latin9,
+        CodeElement::Thai,
+        CodeElement::JisX0208,
+        CodeElement::JisX0212,
+        CodeElement::KsX1001,
+        CodeElement::Gb2312,
+    ];
+
+    /// The escape sequence which designates this code element.
+    fn escape(self) -> &'static [u8] {
+        match self {
+            CodeElement::Ascii => b"\x1B(B",
+            CodeElement::JisRoman => b"\x1B(J",
+            CodeElement::JisKatakana => b"\x1B)I",
+            CodeElement::Latin1 => b"\x1B-A",
+            CodeElement::Latin2 => b"\x1B-B",
+            CodeElement::Latin3 => b"\x1B-C",
+            CodeElement::Latin4 => b"\x1B-D",
+            CodeElement::Cyrillic => b"\x1B-L",
+            CodeElement::Arabic => b"\x1B-G",
+            CodeElement::Greek => b"\x1B-F",
+            CodeElement::Hebrew => b"\x1B-H",
+            CodeElement::Latin5 => b"\x1B-M",
+            CodeElement::Latin9 => b"\x1B-b",
+            CodeElement::Thai => b"\x1B-T",
+            CodeElement::JisX0208 => b"\x1B$B",
+            CodeElement::JisX0212 => b"\x1B$(D",
+            CodeElement::KsX1001 => b"\x1B$)C",
+            CodeElement::Gb2312 => b"\x1B$)A",
+        }
+    }
+
+    /// Finds the code element designated by the escape sequence at the start of the bytes,
+    /// returning it along with the length of the escape sequence.
+    fn from_escape(bytes: &[u8]) -> Option<(CodeElement, usize)> {
+        CodeElement::ALL
+            .into_iter()
+            .find(|element| bytes.starts_with(element.escape()))
+            .map(|element| (element, element.escape().len()))
+    }
+
+    /// Whether this code element is designated to G1 rather than G0.
+    fn is_g1(self) -> bool {
+        !matches!(
+            self,
+            CodeElement::Ascii
+                | CodeElement::JisRoman
+                | CodeElement::JisX0208
+                | CodeElement::JisX0212
+        )
+    }
+
+    /// Whether each character of this code element is two bytes.
+    fn is_multi_byte(self) -> bool {
+        matches!(
+            self,
+            CodeElement::JisX0208
+                | CodeElement::JisX0212
+                | CodeElement::KsX1001
+                | CodeElement::Gb2312
+        )
+    }
+
+    /// The encoding used to decode and encode characters of this code element, after converting
+    /// them to the form used by the encoding.
+    fn encoding(self) -> &'static Encoding {
+        match self {
+            CodeElement::Ascii | CodeElement::JisRoman | CodeElement::Latin1 => WINDOWS_1252,
+            CodeElement::JisKatakana => SHIFT_JIS,
+            CodeElement::Latin2 => ISO_8859_2,
+            CodeElement::Latin3 => ISO_8859_3,
+            CodeElement::Latin4 => ISO_8859_4,
+            CodeElement::Cyrillic => ISO_8859_5,
+            CodeElement::Arabic => ISO_8859_6,
+            CodeElement::Greek => ISO_8859_7,
+            CodeElement::Hebrew => ISO_8859_8,
+            CodeElement::Latin5 => WINDOWS_1254,
+            CodeElement::Latin9 => ISO_8859_15,
+            CodeElement::Thai => WINDOWS_874,
+            CodeElement::JisX0208 | CodeElement::JisX0212 => EUC_JP,
+            CodeElement::KsX1001 => EUC_KR,
+            CodeElement::Gb2312 => GBK,
+        }
+    }
+
+    /// Decodes bytes of this code element, as they appear in the value. Malformed bytes are
+    /// replaced with U+FFFD, and the returned flag is set if any replacements were made.
+    fn decode(self, bytes: &[u8]) -> (String, bool) {
+        let converted: Vec<u8> = match self {
+            // G0 characters are converted to the upper half used for them by EUC-JP.
+            CodeElement::JisX0208 => bytes.iter().map(|byte| byte | 0x80).collect(),
+            CodeElement::JisX0212 => bytes
+                .chunks(2)
+                .flat_map(|pair| [EUC_JP_JIS_X_0212, pair[0] | 0x80, pair[1] | 0x80])
+                .collect(),
+            _ => bytes.to_vec(),
+        };
+        let (text, had_errors) = self.encoding().decode_without_bom_handling(&converted);
+        (text.to_string(), had_errors)
+    }
+
+    /// Encodes the character with this code element, as it would appear in the value.
+    fn encode(self, c: char) -> Option<Vec<u8>> {
+        if matches!(self, CodeElement::Ascii | CodeElement::JisRoman) {
+            return c.is_ascii().then(|| vec![c as u8]);
+        }
+        if self == CodeElement::JisX0212 {
+            return None;
+        }
+
+        let mut buf: [u8; 4] = [0; 4];
+        let (encoded, _encoding, had_errors) = self.encoding().encode(c.encode_utf8(&mut buf));
+        if had_errors {
+            return None;
+        }
+        match (self, encoded.as_ref()) {
+            (CodeElement::JisKatakana, [byte]) if (0xA1..=0xDF).contains(byte) => Some(vec![*byte]),
+            (CodeElement::JisX0208, [lead, trail]) if is_upper_graphic(*lead, *trail) => {
+                Some(vec![lead & 0x7F, trail & 0x7F])
+            }
+            (CodeElement::KsX1001 | CodeElement::Gb2312, [lead, trail])
+                if is_upper_graphic(*lead, *trail) =>
+            {
+                Some(vec![*lead, *trail])
+            }
+            (element, [byte]) if !element.is_multi_byte() && *byte >= 0xA0 => Some(vec![*byte]),
+            _ => None,
+        }
+    }
+}
+
+/// Whether both bytes are in the upper half of the graphic range, which is how EUC encodings place
+/// two-byte characters.
+fn is_upper_graphic(lead: u8, trail: u8) -> bool {
+    GRAPHIC_RANGE.contains(&(lead & 0x7F))
+        && GRAPHIC_RANGE.contains(&(trail & 0x7F))
+        && lead >= 0x80
+        && trail >= 0x80
+}
+
+/// Decodes the bytes collected for a code element onto the text, clearing them. Returns whether any
+/// replacements were made.
+fn flush_run(element: CodeElement, run: &mut Vec<u8>, text: &mut String) -> bool {
+    if run.is_empty() {
+        return false;
+    }
+    let (decoded, had_errors) = element.decode(run);
+    text.push_str(&decoded);
+    run.clear();
+    had_errors
+}
+
+/// The code elements designated to G0 and G1.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Designations {
+    g0: CodeElement,
+    g1: Option<CodeElement>,
+}
+
+impl Designations {
+    fn designate(&mut self, element: CodeElement) {
+        if element.is_g1() {
+            self.g1 = Some(element);
+        } else {
+            self.g0 = element;
+        }
+    }
+}
+
+/// The Defined Terms of a `SpecificCharacterSet` which uses code extensions.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(super) struct CodeExtensions {
+    /// The index within `DEFINED_TERMS` of the first value, or `None` if the first value is empty
+    /// and the default repertoire is used.
+    initial: Option<u8>,
+    /// The set of indexes within `DEFINED_TERMS` of all values.
+    terms: u32,
+}
+
+impl CodeExtensions {
+    /// Resolves the values of `SpecificCharacterSet`, returning `None` if none of them use code
+    /// extensions. Values which are not recognized are ignored.
+    pub(super) fn from_values<S: AsRef<str>>(values: &[S]) -> Option<CodeExtensions> {
+        let index_of = |value: &S| {
+            DEFINED_TERMS
+                .iter()
+                .position(|(term, _g0, _g1)| *term == value.as_ref().trim())
+        };
+        let terms: u32 = values
+            .iter()
+            .filter_map(index_of)
+            .fold(0, |terms, index| terms | (1 << index));
+        if terms == 0 {
+            return None;
+        }
+        let initial: Option<u8> = values
+            .first()
+            .and_then(index_of)
+            .and_then(|index| u8::try_from(index).ok());
+        Some(CodeExtensions { initial, terms })
+    }
+
+    /// The Defined Terms of the values, with the first value first. The first value is empty if
+    /// the default repertoire is used.
+    pub(super) fn defined_terms(self) -> Vec<&'static str> {
+        let mut values: Vec<&'static str> = vec![self
+            .initial
+            .map_or("", |index| DEFINED_TERMS[usize::from(index)].0)];
+        values.extend(
+            self.term_indexes()
+                .filter(|index| Some(*index) != self.initial.map(usize::from))
+                .map(|index| DEFINED_TERMS[index].0),
+        );
+        values
+    }
+
+    /// The encoding which best describes the character sets, used for its name.
+    pub(super) fn encoding(self) -> &'static Encoding {
+        let elements: Vec<CodeElement> = self.elements();
+        if let Some(element) = elements.iter().find(|element| element.is_multi_byte()) {
+            return match element {
+                CodeElement::JisX0208 | CodeElement::JisX0212 => ISO_2022_JP,
+                other => other.encoding(),
+            };
+        }
+        elements
+            .iter()
+            .find(|element| element.is_g1())
+            .map_or(WINDOWS_1252, |element| element.encoding())
+    }
+
+    fn term_indexes(self) -> impl Iterator<Item = usize> {
+        (0..DEFINED_TERMS.len()).filter(move |index| self.terms & (1 << index) != 0)
+    }
+
+    /// The code elements designated at the start of each value and after each delimiter.
+    fn initial_designations(self) -> Designations {
+        let (_term, g0, g1) = self
+            .initial
+            .map_or(("", None, None), |index| DEFINED_TERMS[usize::from(index)]);
+        Designations {
+            g0: g0.unwrap_or(CodeElement::Ascii),
+            g1,
+        }
+    }
+
+    /// The code elements which may be used for encoding, those of the first value first.
+    fn elements(self) -> Vec<CodeElement> {
+        let initial: Designations = self.initial_designations();
+        let mut elements: Vec<CodeElement> = vec![initial.g0];
+        elements.extend(initial.g1);
+        for index in self.term_indexes() {
+            let (_term, g0, g1) = DEFINED_TERMS[index];
+            for element in g0.into_iter().chain(g1) {
+                if !elements.contains(&element) {
+                    elements.push(element);
+                }
+            }
+        }
+        elements
+    }
+
+    /// Decodes the value, following escape sequences. The designations are reset after control
+    /// characters and the given delimiters. Returns `None` if any part of the value is malformed.
+    pub(super) fn decode(self, data: &[u8], delimiters: &[u8]) -> Option<String> {
+        let (text, had_errors) = self.decode_with_replacement(data, delimiters);
+        (!had_errors).then_some(text)
+    }
+
+    /// Decodes the value as with `decode()`, but replaces malformed bytes, unrecognized escape
+    /// sequences, and truncated multi-byte characters with U+FFFD. The returned flag is set if any
+    /// replacements were made.
+    pub(super) fn decode_with_replacement(self, data: &[u8], delimiters: &[u8]) -> (String, bool) {
+        let initial: Designations = self.initial_designations();
+        let mut designations: Designations = initial;
+        let mut text: String = String::with_capacity(data.len());
+        let mut had_errors: bool = false;
+        let mut run: Vec<u8> = Vec::new();
+        let mut run_element: CodeElement = initial.g0;
+
+        let mut index: usize = 0;
+        while index < data.len() {
+            let byte: u8 = data[index];
+            if byte == ESC {
+                if let Some((element, len)) = CodeElement::from_escape(&data[index..]) {
+                    designations.designate(element);
+                    index += len;
+                } else {
+                    flush_run(run_element, &mut run, &mut text);
+                    text.push(char::REPLACEMENT_CHARACTER);
+                    had_errors = true;
+                    index += 1;
+                }
+                continue;
+            }
+
+            let element: CodeElement = if byte >= 0x80 {
+                // High bytes without a designation are decoded as the default character set.
+                designations.g1.unwrap_or(CodeElement::Latin1)
+            } else if designations.g0.is_multi_byte() && GRAPHIC_RANGE.contains(&byte) {
+                designations.g0
+            } else if designations.g0.is_multi_byte() {
+                // Space and control characters are unchanged by multi-byte sets.
+                CodeElement::Ascii
+            } else {
+                designations.g0
+            };
+            let len: usize = if element.is_multi_byte() { 2 } else { 1 };
+            let Some(bytes) = data.get(index..index + len) else {
+                // A multi-byte character truncated by the end of the value.
+                flush_run(run_element, &mut run, &mut text);
+                text.push(char::REPLACEMENT_CHARACTER);
+                had_errors = true;
+                break;
+            };
+
+            if element != run_element {
+                had_errors |= flush_run(run_element, &mut run, &mut text);
+            }
+            run_element = element;
+            run.extend_from_slice(bytes);
+            index += len;
+
+            if len == 1 && (byte.is_ascii_control() || delimiters.contains(&byte)) {
+                designations = initial;
+            }
+        }
+        had_errors |= flush_run(run_element, &mut run, &mut text);
+        (text, had_errors)
+    }
+
+    /// Encodes the text, designating code elements with escape sequences as needed. The initial
+    /// designations are restored before control characters and the given delimiters, and at the
+    /// end of the text.
+    pub(super) fn encode(self, text: &str, delimiters: &[char]) -> Option<Vec<u8>> {
+        let initial: Designations = self.initial_designations();
+        let elements: Vec<CodeElement> = self.elements();
+        let mut designations: Designations = initial;
+        let mut data: Vec<u8> = Vec::with_capacity(text.len());
+        for c in text.chars() {
+            if c.is_ascii_control() || delimiters.contains(&c) {
+                if designations.g0 != initial.g0 {
+                    data.extend_from_slice(initial.g0.escape());
+                }
+                designations = initial;
+                data.push(u8::try_from(c).ok()?);
+                continue;
+            }
+
+            let designated: Option<Vec<u8>> = designations
+                .g0
+                .encode(c)
+                .or_else(|| designations.g1.and_then(|g1| g1.encode(c)));
+            if let Some(bytes) = designated {
+                data.extend(bytes);
+                continue;
+            }
+
+            let (element, bytes) = elements
+                .iter()
+                .find_map(|element| element.encode(c).map(|bytes| (*element, bytes)))?;
+            data.extend_from_slice(element.escape());
+            designations.designate(element);
+            data.extend(bytes);
+        }
+        if designations.g0 != initial.g0 {
+            data.extend_from_slice(initial.g0.escape());
+        }
+        Some(data)
+    }
+}
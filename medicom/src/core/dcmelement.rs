@@ -35,7 +35,7 @@ use crate::core::{
         vl::ValueLength,
         vr::{VRRef, INVALID_VR, SQ},
     },
-    read::parser::ParseResult,
+    read::{deferred::DeferredValue, parser::ParseResult, shared::SharedBytes},
     values::{ElementWithVr, RawValue},
     write::valencode::ElemAndRawValue,
 };
@@ -53,6 +53,22 @@ pub struct DicomElement {
 
     ts: TSRef,
     cs: CSRef,
+
+    /// If the value was not read into `data` during parsing, where the value is instead held.
+    external: Option<ExternalValue>,
+}
+
+/// The value of an element which is held outside of the element itself.
+#[derive(Clone)]
+enum ExternalValue {
+    /// The position of the value within the dataset for loading on demand. See
+    /// `ParseBehavior::defer_values()`. Boxed as deferred values are only used for larger values,
+    /// and this keeps the size of all elements down.
+    Deferred(Box<DeferredValue>),
+
+    /// A view of the value within an in-memory buffer rather than a copy. See
+    /// `ParserBuilder::build_shared()`.
+    Shared(SharedBytes),
 }
 
 impl fmt::Debug for DicomElement {
@@ -91,6 +107,7 @@ impl DicomElement {
             sq_path,
             ts,
             cs,
+            external: None,
         }
     }
 
@@ -109,6 +126,7 @@ impl DicomElement {
             sq_path: Vec::with_capacity(0),
             ts,
             cs,
+            external: None,
         }
     }
 
@@ -127,6 +145,7 @@ impl DicomElement {
             sq_path: Vec::with_capacity(0),
             ts: &ExplicitVRLittleEndian,
             cs: CSRef::default(),
+            external: None,
         }
     }
 
@@ -155,21 +174,126 @@ impl DicomElement {
         self.cs
     }
 
+    /// The bytes of this element's value field. If the value is deferred and has not yet been
+    /// loaded this will be empty, see `load_value()`.
     #[must_use]
-    pub fn data(&self) -> &Vec<u8> {
-        &self.data
+    pub fn data(&self) -> &[u8] {
+        match &self.external {
+            Some(ExternalValue::Deferred(deferred)) => deferred.loaded().unwrap_or(&self.data),
+            Some(ExternalValue::Shared(shared)) => shared.as_slice(),
+            None => &self.data,
+        }
     }
 
+    /// The mutable bytes of this element's value field. If the value is deferred then this element
+    /// will no longer be associated with the dataset, keeping the loaded value if it was loaded, or
+    /// otherwise having an empty value. If the value is a view of a shared buffer then it's copied.
     #[must_use]
     pub fn mut_data(&mut self) -> &mut Vec<u8> {
+        match self.external.take() {
+            Some(ExternalValue::Deferred(deferred)) => {
+                if let Some(loaded) = deferred.into_loaded() {
+                    self.data = loaded;
+                }
+            }
+            Some(ExternalValue::Shared(shared)) => self.data = shared.as_slice().to_vec(),
+            None => {}
+        }
         &mut self.data
     }
 
+    /// The view of this element's value within a shared buffer, if it was parsed with a parser
+    /// created by `ParserBuilder::build_shared()`.
+    #[must_use]
+    pub fn shared(&self) -> Option<&SharedBytes> {
+        match &self.external {
+            Some(ExternalValue::Shared(shared)) => Some(shared),
+            _ => None,
+        }
+    }
+
+    /// Whether this element's value is a view of a shared buffer rather than an owned copy.
+    #[must_use]
+    pub fn is_shared(&self) -> bool {
+        self.shared().is_some()
+    }
+
+    pub(crate) fn set_shared(&mut self, shared: SharedBytes) {
+        self.data = Vec::with_capacity(0);
+        self.external = Some(ExternalValue::Shared(shared));
+    }
+
+    /// The position of this element's value within the dataset, if the value was not read during
+    /// parsing. See `ParseBehavior::defer_values()`.
+    #[must_use]
+    pub fn deferred(&self) -> Option<&DeferredValue> {
+        match &self.external {
+            Some(ExternalValue::Deferred(deferred)) => Some(deferred),
+            _ => None,
+        }
+    }
+
+    /// Whether this element's value was not read during parsing, and is instead loaded on demand.
+    #[must_use]
+    pub fn is_deferred(&self) -> bool {
+        self.deferred().is_some()
+    }
+
+    pub(crate) fn set_deferred(&mut self, deferred: DeferredValue) {
+        self.data = Vec::with_capacity(0);
+        self.external = Some(ExternalValue::Deferred(Box::new(deferred)));
+    }
+
+    /// Loads this element's value from the dataset if it's deferred and not yet loaded, returning
+    /// the bytes of the value field. For values which are not deferred this is the same as
+    /// `data()`.
+    ///
+    /// # Errors
+    /// I/O errors reading the deferred value from the dataset.
+    pub fn load_value(&self) -> ParseResult<&[u8]> {
+        match self.deferred() {
+            Some(deferred) => deferred.load().map(Vec::as_slice),
+            None => Ok(self.data()),
+        }
+    }
+
+    /// Releases the memory of a deferred value which was loaded. The value will be loaded from the
+    /// dataset again the next time it's needed. This has no effect on values which are not
+    /// deferred.
+    pub fn unload_value(&mut self) {
+        if let Some(ExternalValue::Deferred(deferred)) = self.external.as_mut() {
+            deferred.unload();
+        }
+    }
+
+    /// The number of bytes of this element's value field, including deferred values which have not
+    /// yet been loaded.
+    #[must_use]
+    pub fn value_size(&self) -> usize {
+        match &self.external {
+            Some(ExternalValue::Deferred(deferred)) => deferred.padded_length(),
+            Some(ExternalValue::Shared(shared)) => shared.len(),
+            None => self.data.len(),
+        }
+    }
+
     #[must_use]
     pub fn sq_path(&self) -> &Vec<SequenceElement> {
         &self.sq_path
     }
 
+    /// Sets the path of sequences and items this element is within, such as after it has been
+    /// moved within a `DicomObject`.
+    pub(crate) fn set_sq_path(&mut self, sq_path: Vec<SequenceElement>) {
+        self.sq_path = sq_path;
+    }
+
+    /// Sets the value length, for sequences and items whose length is computed from their
+    /// contents.
+    pub(crate) fn set_vl(&mut self, vl: ValueLength) {
+        self.vl = vl;
+    }
+
     /// Returns the number of bytes this element will consist of when encoded into a dataset.
     /// Refer to Part 5, Chapter 7.1
     #[must_use]
@@ -202,7 +326,7 @@ impl DicomElement {
         };
 
         // value
-        byte_len += self.data.len();
+        byte_len += self.value_size();
 
         byte_len
     }
@@ -240,7 +364,7 @@ impl DicomElement {
     /// Returns whether the the size of the value field for this element is zero.
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.data.is_empty()
+        self.value_size() == 0
     }
 
     /// Checks if this `DicomElement` is a sentinel value, identified by having the following
@@ -277,8 +401,9 @@ impl DicomElement {
     /// Parses this element's data into native/raw value type.
     ///
     /// # Errors
-    /// Decoding of the element's value may fail.
+    /// Decoding of the element's value may fail, or loading the value if it's deferred.
     pub fn parse_value(&self) -> ParseResult<RawValue> {
+        self.load_value()?;
         RawValue::try_from(self)
     }
 
@@ -286,14 +411,17 @@ impl DicomElement {
     /// representation.
     ///
     /// # Errors
-    /// Decoding of the element's value based on the given VR may fail.
+    /// Decoding of the element's value based on the given VR may fail, or loading the value if it's
+    /// deferred.
     pub fn parse_value_as(&self, vr: VRRef) -> ParseResult<RawValue> {
+        self.load_value()?;
         RawValue::try_from(&ElementWithVr(self, vr))
     }
 
     /// Encodes a `RawValue` into the binary data for this element.
     ///
-    /// This will overwrite any existing value in this element's `self.data`.
+    /// This will overwrite any existing value in this element's `self.data`, and if the existing
+    /// value was deferred it will no longer be associated with the dataset.
     ///
     /// # Errors
     /// Encoding string values may fail.
@@ -323,6 +451,7 @@ impl DicomElement {
         vl: Option<ValueLength>,
     ) -> ParseResult<()> {
         self.data = ElemAndRawValue(self, value).try_into()?;
+        self.external = None;
 
         let calc_vl = u32::try_from(self.data.len())
             .map(ValueLength::Explicit)
@@ -78,6 +78,11 @@ impl DicomRoot {
         &self.sentinel
     }
 
+    /// Get the mutable root object, the parent of all root-level elements.
+    pub(crate) fn as_obj_mut(&mut self) -> &mut DicomObject {
+        &mut self.sentinel
+    }
+
     /// Get the transfer syntax used to encode the dataset.
     #[must_use]
     pub fn ts(&self) -> TSRef {
@@ -269,11 +274,23 @@ impl DicomRoot {
     /// # Errors
     /// The parser over the dataset stream may fail parsing DICOM.
     pub fn parse<R: Read>(parser: &mut Parser<R>) -> Result<Option<DicomRoot>, ParseError> {
+        DicomRoot::parse_with(parser, |_parser, _element| {})
+    }
+
+    /// Parses as `parse()` does, calling `inspect` with the parser after each element has been
+    /// parsed, such as for recording the position of elements within the dataset.
+    ///
+    /// # Errors
+    /// The parser over the dataset stream may fail parsing DICOM.
+    pub(crate) fn parse_with<R: Read, F: FnMut(&Parser<'_, R>, &DicomElement)>(
+        parser: &mut Parser<R>,
+        mut inspect: F,
+    ) -> Result<Option<DicomRoot>, ParseError> {
         let mut child_nodes: BTreeMap<u32, DicomObject> = BTreeMap::new();
         let mut items: Vec<DicomObject> = Vec::new();
 
         let parse_result: Option<Result<DicomElement, ParseError>> =
-            DicomRoot::parse_recurse(parser, &mut child_nodes, &mut items, true);
+            DicomRoot::parse_recurse(parser, &mut inspect, &mut child_nodes, &mut items, true);
 
         if !parser.behavior().allow_partial_object() {
             if let Some(Err(e)) = parse_result {
@@ -299,17 +316,20 @@ impl DicomRoot {
     /// sequence can be the end of multiple sequences).
     ///
     /// `parser` The parser elements are being read from
+    /// `inspect` Called with the parser after each element is parsed
     /// `child_nodes` The map of child nodes which elements should be parsed into
     /// `items` The list of nodes which item elements should be parsed into
     /// `is_root_level` Whether the root level is being parsed, or within child nodes
-    fn parse_recurse<R: Read>(
+    fn parse_recurse<R: Read, F: FnMut(&Parser<'_, R>, &DicomElement)>(
         parser: &mut Parser<'_, R>,
+        inspect: &mut F,
         child_nodes: &mut BTreeMap<u32, DicomObject>,
         items: &mut Vec<DicomObject>,
         is_root_level: bool,
     ) -> Option<Result<DicomElement, ParseError>> {
         let mut prev_seq_path_len: usize = 0;
-        let mut next_element: Option<Result<DicomElement, ParseError>> = parser.next();
+        let mut next_element: Option<Result<DicomElement, ParseError>> =
+            DicomRoot::parse_next(parser, inspect);
 
         // If the first element at the root level is an error then this is probably not valid dicom.
         if is_root_level {
@@ -335,14 +355,17 @@ impl DicomRoot {
             let mut possible_next_elem: Option<Result<DicomElement, ParseError>> = None;
             // Checking sequence or item tag should match dcmparser.read_dicom_element() which
             // does not read a value for those elements but lets the parser read its value as
-            // separate elements which we're considering child elements.
+            // separate elements which we're considering child elements. Items within `PixelData`
+            // are fragments which have a value rather than child elements.
             let dcmobj: DicomObject = if element.is_sq_like()
-                || (tag == tags::ITEM && element.vl() != ValueLength::Explicit(0))
+                || (tag == tags::ITEM
+                    && element.vl() != ValueLength::Explicit(0)
+                    && !element.is_within_pixel_data())
             {
                 let mut child_nodes: BTreeMap<u32, DicomObject> = BTreeMap::new();
                 let mut items: Vec<DicomObject> = Vec::new();
                 possible_next_elem =
-                    DicomRoot::parse_recurse(parser, &mut child_nodes, &mut items, false);
+                    DicomRoot::parse_recurse(parser, inspect, &mut child_nodes, &mut items, false);
                 DicomObject::new_with_children(element, child_nodes, items)
             } else {
                 DicomObject::new(element)
@@ -380,12 +403,24 @@ impl DicomRoot {
             }
 
             // Parse the next element from the dataset.
-            next_element = parser.next();
+            next_element = DicomRoot::parse_next(parser, inspect);
         }
 
         // Return the last value from the parser which will either be None or Error.
         next_element
     }
+
+    /// Parses the next element, passing it to `inspect` if successfully parsed.
+    fn parse_next<R: Read, F: FnMut(&Parser<'_, R>, &DicomElement)>(
+        parser: &mut Parser<'_, R>,
+        inspect: &mut F,
+    ) -> Option<Result<DicomElement, ParseError>> {
+        let next_element: Option<Result<DicomElement, ParseError>> = parser.next();
+        if let Some(Ok(element)) = &next_element {
+            inspect(parser, element);
+        }
+        next_element
+    }
 }
 
 impl std::fmt::Debug for DicomRoot {
@@ -586,6 +621,17 @@ impl DicomObject {
             .and_then(|o| o.element().parse_value_as(vr).ok())
     }
 
+    /// Get the value of the private creator which reserves the block of the given private tag, if
+    /// the private creator is a child of this object. Refer to Part 5, Section 7.8.1.
+    #[must_use]
+    pub fn get_private_creator(&self, tag: u32) -> Option<String> {
+        let creator: &DicomObject = self.get_child_by_tag(Tag::private_creator_of(tag)?)?;
+        Vec::<String>::try_from(creator.element())
+            .ok()
+            .and_then(|values| values.into_iter().next())
+            .map(|value| value.trim().to_string())
+    }
+
     /// Gets the total number of bytes that will be needed to encode this `DicomObject` and its
     /// child/index nodes into a dataset.
     #[must_use]
@@ -624,6 +670,26 @@ impl DicomObject {
         let obj = DicomObject::new(elem);
         self.child_nodes.entry(tag).or_insert(obj)
     }
+
+    /// Inserts the given object as a child node, replacing any existing child with the same tag.
+    pub(crate) fn replace_child(&mut self, obj: DicomObject) -> Option<DicomObject> {
+        self.child_nodes.insert(obj.element.tag(), obj)
+    }
+
+    /// Removes the child node with the given tag.
+    pub(crate) fn remove_child(&mut self, tag: u32) -> Option<DicomObject> {
+        self.child_nodes.remove(&tag)
+    }
+
+    /// Inserts the item at the given 0-based index, or at the end if the index is past the end.
+    pub(crate) fn insert_item_at(&mut self, index: usize, item: DicomObject) {
+        self.items.insert(index.min(self.items.len()), item);
+    }
+
+    /// Removes the item at the given 0-based index.
+    pub(crate) fn remove_item_at(&mut self, index: usize) -> Option<DicomObject> {
+        (index < self.items.len()).then(|| self.items.remove(index))
+    }
 }
 
 impl fmt::Debug for DicomObject {
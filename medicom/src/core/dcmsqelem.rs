@@ -62,6 +62,10 @@ pub struct SequenceElement {
     /// included in an encapsulated Data Set, then the Specific Character Set value of the
     /// encapsulating Data Set applies.
     cs: CSRef,
+
+    /// Whether the sequence/item is excluded by the parser's `TagFilter`, in which case all
+    /// elements within it are also excluded.
+    excluded: bool,
 }
 
 impl SequenceElement {
@@ -79,6 +83,7 @@ impl SequenceElement {
             vr,
             vl,
             cs,
+            excluded: false,
         }
     }
 
@@ -121,6 +126,22 @@ impl SequenceElement {
         self.cs = cs;
     }
 
+    /// Whether the sequence/item is excluded by the parser's `TagFilter`. See
+    /// `ParseBehavior::tag_filter()`.
+    #[must_use]
+    pub fn is_excluded(&self) -> bool {
+        self.excluded
+    }
+
+    pub(crate) fn set_excluded(&mut self, excluded: bool) {
+        self.excluded = excluded;
+    }
+
+    /// Sets the 1-based number of the item within the sequence.
+    pub(crate) fn set_item(&mut self, item: Option<usize>) {
+        *self.node.item_mut() = item;
+    }
+
     /// Returns if this element is for `PixelData`, `FloatPixelData`, or `DoubleFloatPixelData`.
     #[must_use]
     pub fn is_pixel_data(&self) -> bool {
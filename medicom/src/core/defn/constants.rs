@@ -19,12 +19,21 @@
 /// The minimal set of tags needed when parsing a DICOM dataset.
 pub mod tags {
     pub const FILE_META_INFORMATION_GROUP_LENGTH: u32 = 0x0002_0000;
+    pub const FILE_META_INFORMATION_VERSION: u32 = 0x0002_0001;
+    pub const MEDIA_STORAGE_SOP_CLASS_UID: u32 = 0x0002_0002;
+    pub const MEDIA_STORAGE_SOP_INSTANCE_UID: u32 = 0x0002_0003;
     pub const TRANSFER_SYNTAX_UID: u32 = 0x0002_0010;
+    pub const IMPLEMENTATION_CLASS_UID: u32 = 0x0002_0012;
+    pub const IMPLEMENTATION_VERSION_NAME: u32 = 0x0002_0013;
+    pub const SOURCE_APPLICATION_ENTITY_TITLE: u32 = 0x0002_0016;
     pub const FILE_META_GROUP_END: u32 = 0x0002_FFFF;
 
     pub const SPECIFIC_CHARACTER_SET: u32 = 0x0008_0005;
+    pub const SOP_CLASS_UID: u32 = 0x0008_0016;
     pub const SOP_INSTANCE_UID: u32 = 0x0008_0018;
 
+    pub const EXTENDED_OFFSET_TABLE: u32 = 0x7FE0_0001;
+    pub const EXTENDED_OFFSET_TABLE_LENGTHS: u32 = 0x7FE0_0002;
     pub const FLOAT_PIXEL_DATA: u32 = 0x7FE0_0008;
     pub const DOUBLE_PIXEL_DATA: u32 = 0x7FE0_0009;
     pub const PIXEL_DATA: u32 = 0x7FE0_0010;
@@ -28,6 +28,13 @@ pub trait DicomDictionary {
     fn get_tag_by_name(&self, name: &str) -> Option<TagRef>;
     /// Look up a `Tag` definition by its tag number.
     fn get_tag_by_number(&self, number: u32) -> Option<TagRef>;
+    /// Look up a private `Tag` definition by the value of the private creator which reserves its
+    /// block, and its tag number. Only the group and the lower byte of the element number identify
+    /// the tag, as the block reserved by a private creator varies between datasets. Refer to Part
+    /// 5, Section 7.8.1.
+    fn get_private_tag(&self, _creator: &str, _number: u32) -> Option<TagRef> {
+        None
+    }
 
     /// Look up a `UID` definition by its name, case-insensitive.
     fn get_uid_by_name(&self, name: &str) -> Option<UIDRef>;
@@ -76,6 +83,12 @@ impl DicomDictionary for MultiDicomDictionary<'_> {
         self.dicts.iter().find_map(|d| d.get_tag_by_number(number))
     }
 
+    fn get_private_tag(&self, creator: &str, number: u32) -> Option<TagRef> {
+        self.dicts
+            .iter()
+            .find_map(|d| d.get_private_tag(creator, number))
+    }
+
     fn get_uid_by_name(&self, name: &str) -> Option<UIDRef> {
         self.dicts.iter().find_map(|d| d.get_uid_by_name(name))
     }
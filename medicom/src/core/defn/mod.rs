@@ -26,6 +26,7 @@ use crate::core::{
 
 pub mod constants;
 pub mod dcmdict;
+pub mod rtdict;
 pub mod tag;
 pub mod ts;
 pub mod uid;
@@ -0,0 +1,546 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! A `DicomDictionary` of tag definitions loaded at runtime, such as the private tags of vendors.
+//!
+//! Definitions are read from one of the following formats, where each entry has a tag, a VR, a
+//! keyword, and optionally the private creator, VM, and a longer name:
+//! - The dcmtk data dictionary format, as used by its `private.dic`. Each line is an entry of the
+//!   form `(0029,"SIEMENS CSA HEADER",08)  CS  CSAImageHeaderType  1  PrivateTag`, the private
+//!   creator being omitted for public tags, and lines starting with `#` are comments. Entries for
+//!   ranges of groups or elements, such as `(6000-60FF,3000)` or `(50xx,0005)`, are
+//!   skipped.
+//! - CSV, with a header row naming the columns `tag`, `vr`, `keyword`, `creator`, `vm`, and
+//!   `name`, of which only `tag` and `keyword` are required.
+//! - JSON, requiring the `json` feature, as an array of objects with the same fields as CSV.
+//!
+//! In CSV and JSON, tags are written as `ggggeeee` or `(gggg,eeee)` in hexadecimal. Only the group
+//! and the lower byte of the element number identify a private tag, as the block reserved by its
+//! private creator varies between datasets, so the upper byte may be written as `xx`.
+//!
+//! As `TagRef` is a static reference, the definitions loaded are never freed. Each distinct
+//! definition is only allocated once, so loading the same dictionary again doesn't allocate more,
+//! and definitions replaced by later entries of a dictionary are never allocated.
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock, PoisonError},
+};
+
+use thiserror::Error;
+
+use crate::core::defn::{
+    dcmdict::DicomDictionary,
+    tag::{Tag, TagRef},
+    ts::TSRef,
+    uid::UIDRef,
+    vm::VM,
+    vr::{VRRef, VR},
+};
+
+/// The element number used for the definitions of private tags, which is within the first block
+/// that can be reserved by a private creator.
+const FIRST_PRIVATE_BLOCK: u32 = 0x1000;
+
+/// Errors that can occur loading a `RuntimeDicomDictionary`.
+#[derive(Error, Debug)]
+pub enum RuntimeDictError {
+    /// The format of the dictionary could not be determined from its file extension.
+    #[error("unrecognized dictionary format: {0}")]
+    UnknownFormat(PathBuf),
+
+    /// An entry of the dictionary could not be read. The entry is the line number for the dcmtk
+    /// and CSV formats, or the position within the array for JSON, starting at 1. For JSON which
+    /// is not an array the entry is 0.
+    #[error("invalid dictionary entry {entry}: {reason}")]
+    InvalidEntry { entry: usize, reason: String },
+
+    /// The JSON dictionary could not be parsed.
+    #[cfg(feature = "json")]
+    #[error("invalid JSON dictionary: {0}")]
+    JsonError(#[from] serde_json::Error),
+
+    /// Wrapper around `std::io::Error`.
+    #[error("i/o error reading dictionary")]
+    IOError {
+        #[from]
+        source: std::io::Error,
+    },
+}
+
+pub type RuntimeDictResult<T> = Result<T, RuntimeDictError>;
+
+/// The formats a `RuntimeDicomDictionary` can be loaded from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuntimeDictFormat {
+    /// The dcmtk data dictionary format, as used by `private.dic`.
+    Dcmtk,
+    /// CSV with a header row naming the columns.
+    Csv,
+    /// A JSON array of objects.
+    #[cfg(feature = "json")]
+    Json,
+}
+
+impl RuntimeDictFormat {
+    /// Determines the format from the file extension: `.dic`, `.csv`, or `.json`.
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension: String = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "dic" => Some(RuntimeDictFormat::Dcmtk),
+            "csv" => Some(RuntimeDictFormat::Csv),
+            #[cfg(feature = "json")]
+            "json" => Some(RuntimeDictFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// A dictionary of tag definitions loaded at runtime. Only tags are defined, and private tags are
+/// only found through `get_private_tag()`, by their private creator.
+#[derive(Default)]
+pub struct RuntimeDicomDictionary {
+    tags_by_name: HashMap<String, TagRef>,
+    tags_by_number: HashMap<u32, TagRef>,
+    private_tags: HashMap<(String, u32), TagRef>,
+}
+
+impl RuntimeDicomDictionary {
+    /// Loads the dictionary from a file, with the format determined by its extension.
+    ///
+    /// # Errors
+    /// - `RuntimeDictError::UnknownFormat` if the file extension is not recognized.
+    /// - I/O errors reading the file, or errors reading its entries.
+    pub fn from_path(path: &Path) -> RuntimeDictResult<Self> {
+        let format: RuntimeDictFormat = RuntimeDictFormat::from_path(path)
+            .ok_or_else(|| RuntimeDictError::UnknownFormat(path.to_path_buf()))?;
+        let text: String = std::fs::read_to_string(path)?;
+        Self::parse(&text, format)
+    }
+
+    /// Parses the dictionary from text in the given format. Later definitions of the same tag
+    /// replace earlier ones.
+    ///
+    /// # Errors
+    /// `RuntimeDictError::InvalidEntry` if an entry has a malformed tag, VR, or VM, or is missing
+    /// required fields.
+    pub fn parse(text: &str, format: RuntimeDictFormat) -> RuntimeDictResult<Self> {
+        let mut definitions = Definitions::default();
+        match format {
+            RuntimeDictFormat::Dcmtk => definitions.parse_dcmtk(text)?,
+            RuntimeDictFormat::Csv => definitions.parse_csv(text)?,
+            #[cfg(feature = "json")]
+            RuntimeDictFormat::Json => definitions.parse_json(text)?,
+        }
+        Ok(definitions.into_dictionary())
+    }
+
+    /// The number of tags defined.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.tags_by_number.len() + self.private_tags.len()
+    }
+
+    /// Whether no tags are defined.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The static tags allocated for definitions, by tag number and keyword.
+type InternedTags = HashMap<(u32, String), Vec<TagRef>>;
+
+/// A tag definition read from an entry, which is only made static once all entries have been read
+/// so that definitions replaced by later entries are not kept.
+struct Definition {
+    keyword: String,
+    number: u32,
+    vr: Option<VRRef>,
+    vm: VM,
+    name: String,
+}
+
+impl Definition {
+    /// The static tag for the definition, allocated only the first time the same definition is
+    /// seen by any dictionary.
+    fn intern(self) -> TagRef {
+        static INTERNED: OnceLock<Mutex<InternedTags>> = OnceLock::new();
+        let mut interned = INTERNED
+            .get_or_init(Mutex::default)
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let tags: &mut Vec<TagRef> = interned
+            .entry((self.number, self.keyword.clone()))
+            .or_default();
+        if let Some(tag) = tags.iter().find(|tag| {
+            tag.implicit_vr() == self.vr && *tag.vm() == self.vm && tag.desc() == self.name
+        }) {
+            return tag;
+        }
+        let tag: TagRef = Box::leak(Box::new(Tag::new(
+            leak_str(&self.keyword),
+            self.number,
+            self.vr,
+            Box::leak(Box::new(self.vm)),
+            leak_str(&self.name),
+        )));
+        tags.push(tag);
+        tag
+    }
+}
+
+/// The definitions read from the entries of a dictionary, where later definitions of the same
+/// tag replace earlier ones.
+#[derive(Default)]
+struct Definitions {
+    /// Public definitions by tag number, along with the order they were defined in.
+    public: HashMap<u32, (usize, Definition)>,
+    private: HashMap<(String, u32), Definition>,
+    /// The number of public definitions read, for ordering them.
+    public_count: usize,
+}
+
+impl Definitions {
+    /// Creates the dictionary of the definitions read.
+    fn into_dictionary(self) -> RuntimeDicomDictionary {
+        let mut public: Vec<(usize, Definition)> = self.public.into_values().collect();
+        public.sort_by_key(|(order, _definition)| *order);
+        let mut dict = RuntimeDicomDictionary::default();
+        for (_order, definition) in public {
+            let tag: TagRef = definition.intern();
+            dict.tags_by_name.insert(tag.ident().to_lowercase(), tag);
+            dict.tags_by_number.insert(tag.tag(), tag);
+        }
+        for (key, definition) in self.private {
+            dict.private_tags.insert(key, definition.intern());
+        }
+        dict
+    }
+
+    fn parse_dcmtk(&mut self, text: &str) -> RuntimeDictResult<()> {
+        for (index, line) in text.lines().enumerate() {
+            let line: &str = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let entry: usize = index + 1;
+            let Some(fields) = dcmtk_fields(line) else {
+                return Err(invalid_entry(entry, "malformed tag"));
+            };
+            if fields.tag.contains('-') || (fields.creator.is_none() && fields.tag.contains('x')) {
+                continue;
+            }
+            self.insert(&fields)
+                .map_err(|reason| invalid_entry(entry, reason))?;
+        }
+        Ok(())
+    }
+
+    fn parse_csv(&mut self, text: &str) -> RuntimeDictResult<()> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .filter(|(_index, line)| !line.trim().is_empty());
+        let Some((header_index, header)) = lines.next() else {
+            return Ok(());
+        };
+        let columns: Vec<String> = csv_fields(header)
+            .iter()
+            .map(|column| column.trim().to_lowercase())
+            .collect();
+        let column = |name: &str| columns.iter().position(|c| c == name);
+        let (Some(tag), Some(keyword)) = (column("tag"), column("keyword")) else {
+            return Err(invalid_entry(
+                header_index + 1,
+                "header must name the tag and keyword columns",
+            ));
+        };
+        let (vr, creator, vm, name) = (
+            column("vr"),
+            column("creator"),
+            column("vm"),
+            column("name"),
+        );
+
+        for (index, line) in lines {
+            let values: Vec<String> = csv_fields(line);
+            let value = |column: Option<usize>| {
+                column
+                    .and_then(|c| values.get(c))
+                    .map(|v| v.trim())
+                    .filter(|v| !v.is_empty())
+            };
+            let fields = EntryFields {
+                tag: Cow::Borrowed(value(Some(tag)).unwrap_or_default()),
+                creator: value(creator),
+                vr: value(vr),
+                keyword: value(Some(keyword)).unwrap_or_default(),
+                vm: value(vm),
+                name: value(name),
+            };
+            self.insert(&fields)
+                .map_err(|reason| invalid_entry(index + 1, reason))?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "json")]
+    fn parse_json(&mut self, text: &str) -> RuntimeDictResult<()> {
+        use serde_json::Value;
+
+        let root: Value = serde_json::from_str(text)?;
+        let Some(entries) = root.as_array() else {
+            return Err(invalid_entry(0, "expected an array of entries"));
+        };
+        for (index, entry) in entries.iter().enumerate() {
+            let value = |field: &str| {
+                entry
+                    .get(field)
+                    .and_then(Value::as_str)
+                    .map(str::trim)
+                    .filter(|v| !v.is_empty())
+            };
+            let fields = EntryFields {
+                tag: Cow::Borrowed(value("tag").unwrap_or_default()),
+                creator: value("creator"),
+                vr: value("vr"),
+                keyword: value("keyword").unwrap_or_default(),
+                vm: value("vm"),
+                name: value("name"),
+            };
+            self.insert(&fields)
+                .map_err(|reason| invalid_entry(index + 1, reason))?;
+        }
+        Ok(())
+    }
+
+    /// Defines the tag for the entry, returning the reason if the entry is invalid.
+    fn insert(&mut self, fields: &EntryFields<'_>) -> Result<(), String> {
+        let creator: Option<&str> = fields.creator.map(str::trim_end);
+        let number: u32 = parse_tag(&fields.tag, creator.is_some())
+            .ok_or_else(|| format!("malformed tag: {}", fields.tag))?;
+        if fields.keyword.is_empty() {
+            return Err("missing keyword".to_string());
+        }
+        let vr: Option<VRRef> = match fields.vr {
+            None => None,
+            Some(vr) => parse_vr(vr)?,
+        };
+        let vm: VM = match fields.vm {
+            None => VM::Distinct(1),
+            Some(vm) => parse_vm(vm).ok_or_else(|| format!("malformed VM: {vm}"))?,
+        };
+
+        let definition = Definition {
+            keyword: fields.keyword.to_string(),
+            number,
+            vr,
+            vm,
+            name: fields.name.unwrap_or(fields.keyword).to_string(),
+        };
+        if let Some(creator) = creator {
+            self.private
+                .insert((creator.to_string(), private_key(number)), definition);
+        } else {
+            self.public.insert(number, (self.public_count, definition));
+            self.public_count += 1;
+        }
+        Ok(())
+    }
+}
+
+impl DicomDictionary for RuntimeDicomDictionary {
+    fn get_ts_by_name(&self, _name: &str) -> Option<TSRef> {
+        None
+    }
+
+    fn get_ts_by_uid(&self, _uid: &str) -> Option<TSRef> {
+        None
+    }
+
+    fn get_tag_by_name(&self, name: &str) -> Option<TagRef> {
+        self.tags_by_name.get(&name.to_lowercase()).copied()
+    }
+
+    fn get_tag_by_number(&self, number: u32) -> Option<TagRef> {
+        self.tags_by_number.get(&number).copied()
+    }
+
+    /// Private tags are defined with the element number of the first block, `(gggg,10ee)`.
+    fn get_private_tag(&self, creator: &str, number: u32) -> Option<TagRef> {
+        self.private_tags
+            .get(&(creator.trim_end().to_string(), private_key(number)))
+            .copied()
+    }
+
+    fn get_uid_by_name(&self, _name: &str) -> Option<UIDRef> {
+        None
+    }
+
+    fn get_uid_by_uid(&self, _uid: &str) -> Option<UIDRef> {
+        None
+    }
+}
+
+/// The fields of a dictionary entry, with empty values as `None`.
+struct EntryFields<'a> {
+    tag: Cow<'a, str>,
+    creator: Option<&'a str>,
+    vr: Option<&'a str>,
+    keyword: &'a str,
+    vm: Option<&'a str>,
+    name: Option<&'a str>,
+}
+
+fn invalid_entry<S: Into<String>>(entry: usize, reason: S) -> RuntimeDictError {
+    RuntimeDictError::InvalidEntry {
+        entry,
+        reason: reason.into(),
+    }
+}
+
+/// Copies the string to the heap for the remainder of the program, as tag definitions are static.
+fn leak_str(value: &str) -> &'static str {
+    Box::leak(value.to_owned().into_boxed_str())
+}
+
+/// The key identifying a private tag within any block of its group, which is the group and lower
+/// byte of the element number.
+fn private_key(number: u32) -> u32 {
+    (number & 0xFFFF_0000) | (number & 0x0000_00FF)
+}
+
+/// Parses a tag written as `ggggeeee` or `(gggg,eeee)`. Private tags may write the upper byte of
+/// the element number as `xx`, and are numbered within the first block.
+fn parse_tag(value: &str, private: bool) -> Option<u32> {
+    let digits: String = value
+        .chars()
+        .filter(|c| !matches!(c, '(' | ')' | ',') && !c.is_whitespace())
+        .collect();
+    if digits.len() != 8 || !digits.is_ascii() {
+        return None;
+    }
+    let (group, element) = digits.split_at(4);
+    let group: u32 = u32::from_str_radix(group, 16).ok()?;
+    let (block, offset) = element.split_at(2);
+    let offset: u32 = u32::from_str_radix(offset, 16).ok()?;
+    if private {
+        if !block.eq_ignore_ascii_case("xx") {
+            u32::from_str_radix(block, 16).ok()?;
+        }
+        Some((group << 16) | FIRST_PRIVATE_BLOCK | offset)
+    } else {
+        let block: u32 = u32::from_str_radix(block, 16).ok()?;
+        Some((group << 16) | (block << 8) | offset)
+    }
+}
+
+/// Parses a VR, including the pseudo-VRs dcmtk uses for elements whose VR depends on other values.
+/// Returns `None` for VRs which are not applicable.
+fn parse_vr(value: &str) -> Result<Option<VRRef>, String> {
+    let ident: String = match value {
+        "na" => return Ok(None),
+        "up" => "UL".to_string(),
+        "xs" => "US".to_string(),
+        "ox" | "px" => "OB".to_string(),
+        "lt" => "OW".to_string(),
+        _ => value.to_uppercase(),
+    };
+    VR::from_ident(&ident)
+        .map(Some)
+        .ok_or_else(|| format!("unknown VR: {value}"))
+}
+
+/// Parses a VM, such as `1`, `1-n`, `2-2n`, or `1-3`.
+fn parse_vm(value: &str) -> Option<VM> {
+    if value == "1-n or 1" {
+        return Some(VM::OneOrMore);
+    }
+    if let Ok(count) = value.parse::<u32>() {
+        return Some(VM::Distinct(count));
+    }
+    let (start, end) = value.split_once('-')?;
+    let start: u32 = start.parse::<u32>().ok()?;
+    if end == "n" {
+        Some(VM::AtLeast(start))
+    } else if let Ok(end) = end.parse::<u32>() {
+        Some(VM::AtMost(end))
+    } else if end.strip_suffix('n')?.parse::<u32>().ok()? == start {
+        Some(VM::MultipleOf(start))
+    } else {
+        None
+    }
+}
+
+/// Splits a line of the dcmtk format into its fields, with the tag written as `ggggeeee` and the
+/// upper byte of private element numbers as `xx`. Returns `None` if the tag is malformed.
+fn dcmtk_fields(line: &str) -> Option<EntryFields<'_>> {
+    let rest: &str = line.strip_prefix('(')?;
+    let (group, rest) = rest.split_once(',')?;
+    let (creator, rest) = match rest.strip_prefix('"') {
+        Some(rest) => {
+            let (creator, rest) = rest.split_once('"')?;
+            (Some(creator), rest.strip_prefix(',')?)
+        }
+        None => (None, rest),
+    };
+    let (element, rest) = rest.split_once(')')?;
+    let element: &str = element.trim();
+    let element: String = if creator.is_some() && element.len() == 2 {
+        format!("xx{element}")
+    } else {
+        element.to_string()
+    };
+
+    let mut fields = rest.split_whitespace();
+    let vr: Option<&str> = fields.next();
+    let keyword: &str = fields.next().unwrap_or_default();
+    let vm: Option<&str> = fields.next();
+    Some(EntryFields {
+        tag: Cow::Owned(format!("{}{element}", group.trim())),
+        creator,
+        vr,
+        keyword,
+        vm,
+        name: None,
+    })
+}
+
+/// Splits a line of CSV into its fields, which may be quoted to contain commas, with quotes within
+/// quoted fields escaped by doubling them.
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields: Vec<String> = Vec::new();
+    let mut field: String = String::new();
+    let mut quoted: bool = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
@@ -129,6 +129,21 @@ impl Tag {
         }
     }
 
+    /// The tag of the private creator which reserves the block of the given private tag, or `None`
+    /// if the tag is not a private tag within a reserved block. Refer to Part 5, Section 7.8.1.
+    pub fn private_creator_of<T>(tag: T) -> Option<u32>
+    where
+        u32: From<T>,
+    {
+        let tag: u32 = u32::from(tag);
+        let tag_elem: u32 = tag & 0x0000_FFFF;
+        if Tag::is_private::<u32>(tag) && tag_elem >= 0x1000 {
+            Some((tag & 0xFFFF_0000) | (tag_elem >> 8))
+        } else {
+            None
+        }
+    }
+
     /// Detects if the given tag is a private group length. These tags are deprecated according to
     /// the dicom standard.
     pub fn is_private_group_length<T>(tag: T) -> bool
@@ -213,6 +228,7 @@ impl From<&Tag> for u32 {
 /// associated tag number. Non-leaf nodes are sequence tags, and will also contain an `item`
 /// specifying which 1-based item child node is being referenced in the path.
 #[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TagNode {
     tag: u32,
     item: Option<usize>,
@@ -354,6 +370,7 @@ where
 ///         ReferencedSOPInstanceUID
 /// ```
 #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TagPath {
     nodes: Vec<TagNode>,
 }
@@ -547,7 +564,7 @@ impl From<&[SequenceElement]> for TagPath {
 #[cfg(feature = "stddicom")]
 mod tests {
     use crate::{
-        core::defn::tag::{TagNode, TagPath},
+        core::defn::tag::{Tag, TagNode, TagPath},
         dict::{
             stdlookup::STANDARD_DICOM_DICTIONARY,
             tags::{
@@ -620,4 +637,12 @@ mod tests {
 
         assert_eq!(tagpath, parsed);
     }
+
+    #[test]
+    fn test_private_creator_of() {
+        assert_eq!(Some(0x0029_0010), Tag::private_creator_of(0x0029_1008_u32));
+        assert_eq!(Some(0x0029_00FF), Tag::private_creator_of(0x0029_FF01_u32));
+        assert_eq!(None, Tag::private_creator_of(0x0029_0010_u32));
+        assert_eq!(None, Tag::private_creator_of(0x0010_1010_u32));
+    }
 }
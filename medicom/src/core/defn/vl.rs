@@ -24,6 +24,7 @@ pub const UNDEFINED_LENGTH: u32 = 0xFFFF_FFFF;
 
 /// Value Length Definition
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ValueLength {
     UndefinedLength,
     Explicit(u32),
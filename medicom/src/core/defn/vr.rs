@@ -143,6 +143,15 @@ impl fmt::Debug for VR {
 }
 
 impl VR {
+    /// Gets the VR by its two-letter identifier, e.g. `"PN"`, or `None` if the VR is unknown.
+    #[must_use]
+    pub fn from_ident(ident: &str) -> Option<VRRef> {
+        match ident.as_bytes() {
+            [first, second] => VR::from_code(u16::from_be_bytes([*first, *second])),
+            _ => None,
+        }
+    }
+
     /// Gets the VR based on the encoded VR value, or `None` if the VR is unknown.
     ///
     /// Note that the code is effectively the ASCII encoding of the two letters making
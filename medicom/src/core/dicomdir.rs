@@ -0,0 +1,973 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Reading and generating DICOMDIR files, the Media Storage Directory of a File-set. Refer to
+//! Part 3, Section F and Part 10, Section 8.
+//!
+//! The DICOMDIR's `DirectoryRecordSequence` has an item for each directory record, such as
+//! PATIENT, STUDY, SERIES, and IMAGE records. The records form a tree by the byte offsets each
+//! contains: the offset of the next record at the same level, and the offset of the first record
+//! of the level below it. Records at the lowest level refer to the files of the File-set by their
+//! `ReferencedFileID`, a path relative to the DICOMDIR.
+
+use std::{
+    collections::{hash_map::RandomState, BTreeMap, HashMap, HashSet},
+    fs::{self, File},
+    hash::{BuildHasher, Hasher},
+    io::{BufReader, Read},
+    path::{Component, Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use thiserror::Error;
+
+use crate::{
+    core::{
+        charset::CSRef,
+        dcmelement::DicomElement,
+        dcmobject::{DicomObject, DicomRoot},
+        defn::{
+            constants::{
+                tags::{
+                    ITEM, MEDIA_STORAGE_SOP_CLASS_UID, MEDIA_STORAGE_SOP_INSTANCE_UID,
+                    SPECIFIC_CHARACTER_SET,
+                },
+                ts::ExplicitVRLittleEndian,
+            },
+            dcmdict::DicomDictionary,
+            tag::Tag,
+            ts::TSRef,
+            vl::ValueLength,
+            vr::{VRRef, CS, UI, UL, UN, US},
+        },
+        edit::{new_item_element, new_sequence_element, repath},
+        read::{stop::ParseStop, ParseError, Parser, ParserBuilder},
+        values::RawValue,
+        write::{
+            error::WriteError,
+            file::{write_dcmroot_to_path, WriteFileOptions},
+            filemeta::generate_file_meta,
+            sqlength::{flatten_with_file_offsets, update_lengths},
+        },
+    },
+    dict::{
+        tags::{
+            AccessionNumber, DirectoryRecordSequence, DirectoryRecordType, FilesetConsistencyFlag,
+            FilesetID, InstanceNumber, Modality, OffsetofReferencedLowerLevelDirectoryEntity,
+            OffsetoftheFirstDirectoryRecordoftheRootDirectoryEntity,
+            OffsetoftheLastDirectoryRecordoftheRootDirectoryEntity, OffsetoftheNextDirectoryRecord,
+            PatientID, PatientsName, PixelData, RecordInuseFlag, ReferencedFileID,
+            ReferencedSOPClassUIDinFile, ReferencedSOPInstanceUIDinFile,
+            ReferencedTransferSyntaxUIDinFile, SOPClassUID, SOPInstanceUID, SeriesInstanceUID,
+            SeriesNumber, StudyDate, StudyDescription, StudyID, StudyInstanceUID, StudyTime,
+        },
+        uids::MediaStorageDirectoryStorage,
+    },
+};
+
+/// The file name of a DICOMDIR within the root folder of a File-set.
+pub const DICOMDIR_FILE_NAME: &str = "DICOMDIR";
+
+/// The value of `RecordInuseFlag` for records which are in use.
+const RECORD_IN_USE: u16 = 0xFFFF;
+
+/// Length of the Item tag and value length which precede the contents of each item.
+const ITEM_HEADER_LENGTH: u64 = 8;
+
+/// The maximum number of components of a File ID, Part 10 Section 8.2.
+const MAX_FILE_ID_COMPONENTS: usize = 8;
+
+/// The maximum length of each component of a File ID, Part 10 Section 8.2.
+const MAX_FILE_ID_COMPONENT_LENGTH: usize = 8;
+
+/// The record type, identifying attribute, and attributes copied into the record for each level
+/// above the records which refer to files: PATIENT, STUDY, and SERIES.
+const RECORD_LEVELS: [(&str, &Tag, &[&Tag]); 3] = [
+    ("PATIENT", &PatientID, &[&PatientsName, &PatientID]),
+    (
+        "STUDY",
+        &StudyInstanceUID,
+        &[
+            &StudyDate,
+            &StudyTime,
+            &StudyDescription,
+            &StudyInstanceUID,
+            &StudyID,
+            &AccessionNumber,
+        ],
+    ),
+    (
+        "SERIES",
+        &SeriesInstanceUID,
+        &[&Modality, &SeriesInstanceUID, &SeriesNumber],
+    ),
+];
+
+/// Errors that can occur reading or generating a DICOMDIR.
+#[derive(Error, Debug)]
+pub enum DicomDirError {
+    /// The dataset is not a DICOMDIR.
+    #[error("not a DICOMDIR: {0}")]
+    NotDicomDir(String),
+
+    /// A record offset does not refer to a directory record.
+    #[error("no directory record at offset {0}")]
+    InvalidOffset(u32),
+
+    /// The offsets of directory records refer back to a record already visited.
+    #[error("directory records form a cycle at offset {0}")]
+    CyclicRecords(u32),
+
+    /// A file's path can't be referred to by a `ReferencedFileID`.
+    #[error("invalid file id for {path:?}: {reason}")]
+    InvalidFileId { path: PathBuf, reason: String },
+
+    /// A file of the File-set is DICOM but could not be parsed.
+    #[error("error parsing {path:?}: {source}")]
+    InvalidFile { path: PathBuf, source: ParseError },
+
+    /// Errors parsing the DICOMDIR or the files of the File-set.
+    #[error("error parsing: {0}")]
+    ParseError(#[from] ParseError),
+
+    /// Errors writing the DICOMDIR.
+    #[error("error writing: {0}")]
+    WriteError(#[from] WriteError),
+
+    /// Wrapper around `std::io::Error`.
+    #[error("i/o error: {0}")]
+    IOError(#[from] std::io::Error),
+}
+
+pub type DicomDirResult<T> = Result<T, DicomDirError>;
+
+/// A directory record, with the records of the level below it.
+#[derive(Clone)]
+pub struct DirectoryRecord {
+    offset: u32,
+    record_type: String,
+    in_use: bool,
+    file_id: Vec<String>,
+    item: DicomObject,
+    children: Vec<DirectoryRecord>,
+}
+
+impl DirectoryRecord {
+    /// The byte offset of the record's item within the DICOMDIR.
+    #[must_use]
+    pub fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    /// The `DirectoryRecordType`, such as `PATIENT`, `STUDY`, `SERIES`, or `IMAGE`.
+    #[must_use]
+    pub fn record_type(&self) -> &str {
+        &self.record_type
+    }
+
+    /// Whether the record is in use. Records which are not in use, and the records below them,
+    /// should be ignored.
+    #[must_use]
+    pub fn is_in_use(&self) -> bool {
+        self.in_use
+    }
+
+    /// The components of the `ReferencedFileID`, empty if the record doesn't refer to a file.
+    #[must_use]
+    pub fn file_id(&self) -> &[String] {
+        &self.file_id
+    }
+
+    /// The record's item from the `DirectoryRecordSequence`, for its other attributes.
+    #[must_use]
+    pub fn item(&self) -> &DicomObject {
+        &self.item
+    }
+
+    /// The records of the level below this one.
+    #[must_use]
+    pub fn children(&self) -> &[DirectoryRecord] {
+        &self.children
+    }
+}
+
+impl Drop for DirectoryRecord {
+    /// Drops the records below this one without recursing, as the depth of the records is
+    /// determined by the DICOMDIR.
+    fn drop(&mut self) {
+        let mut records: Vec<DirectoryRecord> = std::mem::take(&mut self.children);
+        while let Some(mut record) = records.pop() {
+            records.append(&mut record.children);
+        }
+    }
+}
+
+/// A parsed DICOMDIR, with its directory records resolved into a tree.
+pub struct DicomDir {
+    dcmroot: DicomRoot,
+    base_dir: PathBuf,
+    records: Vec<DirectoryRecord>,
+}
+
+impl DicomDir {
+    /// Parses the DICOMDIR at the given path. Files are referenced relative to its folder.
+    ///
+    /// # Errors
+    /// - Errors from `DicomDir::parse()`, or I/O errors opening the file.
+    pub fn open<P: AsRef<Path>>(
+        path: P,
+        dictionary: &dyn DicomDictionary,
+    ) -> DicomDirResult<DicomDir> {
+        let path: &Path = path.as_ref();
+        let mut parser =
+            ParserBuilder::default().build(BufReader::new(File::open(path)?), dictionary);
+        let base_dir: PathBuf = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        DicomDir::parse(&mut parser, base_dir)
+    }
+
+    /// Parses a DICOMDIR from the parser and resolves its directory records. Files are referenced
+    /// relative to `base_dir`.
+    ///
+    /// The records are located by the position of each item of the `DirectoryRecordSequence` as
+    /// it is parsed, so the parser should be positioned at the start of the file.
+    ///
+    /// # Errors
+    /// - `DicomDirError::NotDicomDir` if the dataset is not DICOM or has no directory records.
+    /// - `DicomDirError::InvalidOffset` if an offset does not refer to a directory record.
+    /// - `DicomDirError::CyclicRecords` if the offsets form a cycle.
+    /// - Errors from parsing the dataset.
+    pub fn parse<R: Read>(
+        parser: &mut Parser<'_, R>,
+        base_dir: PathBuf,
+    ) -> DicomDirResult<DicomDir> {
+        let mut item_offsets: Vec<u32> = Vec::new();
+        let dcmroot: DicomRoot = DicomRoot::parse_with(parser, |parser, element| {
+            if is_record_item(element) {
+                // Items are parsed once their header has been read.
+                let offset: u64 = parser.bytes_read().saturating_sub(ITEM_HEADER_LENGTH);
+                item_offsets.push(u32::try_from(offset).unwrap_or(u32::MAX));
+            }
+        })?
+        .ok_or_else(|| DicomDirError::NotDicomDir("not DICOM".to_string()))?;
+        DicomDir::resolve(dcmroot, base_dir, &item_offsets)
+    }
+
+    /// Resolves the directory records of a DICOMDIR which was not parsed, such as one created by
+    /// `DicomDirBuilder::build()`. Files are referenced relative to `base_dir`.
+    ///
+    /// The records are located by computing the offset of each item as the `DicomRoot` would be
+    /// written as a file. Use `DicomDir::parse()` for DICOMDIRs read from a file.
+    ///
+    /// # Errors
+    /// - `DicomDirError::NotDicomDir` if there are no directory records.
+    /// - `DicomDirError::InvalidOffset` if an offset does not refer to a directory record.
+    /// - `DicomDirError::CyclicRecords` if the offsets form a cycle.
+    pub fn from_root(dcmroot: DicomRoot, base_dir: PathBuf) -> DicomDirResult<DicomDir> {
+        let item_offsets: Vec<u32> = record_offsets(&dcmroot);
+        DicomDir::resolve(dcmroot, base_dir, &item_offsets)
+    }
+
+    /// Resolves the directory records given the offset of each item of the
+    /// `DirectoryRecordSequence`, in order.
+    fn resolve(
+        dcmroot: DicomRoot,
+        base_dir: PathBuf,
+        item_offsets: &[u32],
+    ) -> DicomDirResult<DicomDir> {
+        let sequence: &DicomObject = dcmroot
+            .get_child_by_tag(&DirectoryRecordSequence)
+            .ok_or_else(|| DicomDirError::NotDicomDir("no DirectoryRecordSequence".to_string()))?;
+        let first: u32 = uint_value(
+            dcmroot.as_obj(),
+            &OffsetoftheFirstDirectoryRecordoftheRootDirectoryEntity,
+        )
+        .ok_or_else(|| DicomDirError::NotDicomDir("no offset of the first record".to_string()))?;
+
+        let offsets: HashMap<u32, usize> = item_offsets
+            .iter()
+            .enumerate()
+            .map(|(index, offset)| (*offset, index + 1))
+            .collect();
+        let records: Vec<DirectoryRecord> = read_records(sequence, &offsets, first)?;
+
+        Ok(DicomDir {
+            dcmroot,
+            base_dir,
+            records,
+        })
+    }
+
+    /// The parsed DICOMDIR.
+    #[must_use]
+    pub fn dcmroot(&self) -> &DicomRoot {
+        &self.dcmroot
+    }
+
+    /// The folder which files are referenced relative to.
+    #[must_use]
+    pub fn base_dir(&self) -> &Path {
+        &self.base_dir
+    }
+
+    /// The records of the root directory entity, typically PATIENT records.
+    #[must_use]
+    pub fn records(&self) -> &[DirectoryRecord] {
+        &self.records
+    }
+
+    /// Iterates through all records depth-first, with the depth of each record starting at 0 for
+    /// the records of the root directory entity.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &DirectoryRecord)> {
+        let mut stack: Vec<(usize, &DirectoryRecord)> = self
+            .records
+            .iter()
+            .rev()
+            .map(|record| (0, record))
+            .collect();
+        std::iter::from_fn(move || {
+            let (depth, record) = stack.pop()?;
+            stack.extend(record.children.iter().rev().map(|child| (depth + 1, child)));
+            Some((depth, record))
+        })
+    }
+
+    /// The path of the file referred to by the record, or `None` if the record doesn't refer to a
+    /// file. Records whose `ReferencedFileID` has components which are not plain names, such as
+    /// `..` or a root, also return `None` so the path can't refer to a file outside of `base_dir`.
+    #[must_use]
+    pub fn file_path(&self, record: &DirectoryRecord) -> Option<PathBuf> {
+        if record.file_id.is_empty() {
+            return None;
+        }
+        let mut path: PathBuf = self.base_dir.clone();
+        for component in record.file_id.iter().map(Path::new) {
+            let is_name: bool = component
+                .components()
+                .all(|part| matches!(part, Component::Normal(_)));
+            if !is_name {
+                return None;
+            }
+            path.push(component);
+        }
+        Some(path)
+    }
+}
+
+/// A chain of records being read, along with the record above it whose children they are.
+struct RecordLevel {
+    parent: Option<DirectoryRecord>,
+    records: Vec<DirectoryRecord>,
+    next: u32,
+}
+
+/// Reads the chain of records starting at the given offset, along with the records below each.
+/// The levels being read are kept on a stack rather than recursing, as the depth of the records is
+/// determined by the DICOMDIR.
+fn read_records(
+    sequence: &DicomObject,
+    offsets: &HashMap<u32, usize>,
+    first: u32,
+) -> DicomDirResult<Vec<DirectoryRecord>> {
+    let mut visited: HashSet<u32> = HashSet::new();
+    let mut levels: Vec<RecordLevel> = vec![RecordLevel {
+        parent: None,
+        records: Vec::new(),
+        next: first,
+    }];
+    while let Some(mut level) = levels.pop() {
+        let offset: u32 = level.next;
+        if offset == 0 {
+            let Some(mut parent) = level.parent else {
+                return Ok(level.records);
+            };
+            parent.children = level.records;
+            if let Some(above) = levels.last_mut() {
+                above.records.push(parent);
+            }
+            continue;
+        }
+
+        if !visited.insert(offset) {
+            return Err(DicomDirError::CyclicRecords(offset));
+        }
+        let item: &DicomObject = offsets
+            .get(&offset)
+            .and_then(|index| sequence.get_item_by_index(*index))
+            .ok_or(DicomDirError::InvalidOffset(offset))?;
+        level.next = uint_value(item, &OffsetoftheNextDirectoryRecord).unwrap_or(0);
+        let lower: u32 =
+            uint_value(item, &OffsetofReferencedLowerLevelDirectoryEntity).unwrap_or(0);
+        let record = DirectoryRecord {
+            offset,
+            record_type: string_values(item, &DirectoryRecordType)
+                .into_iter()
+                .next()
+                .unwrap_or_default(),
+            in_use: item
+                .get_value_by_tag(&RecordInuseFlag)
+                .and_then(|value| value.ushort())
+                .is_none_or(|flag| flag != 0),
+            file_id: string_values(item, &ReferencedFileID),
+            item: item.clone(),
+            children: Vec::new(),
+        };
+        levels.push(level);
+        levels.push(RecordLevel {
+            parent: Some(record),
+            records: Vec::new(),
+            next: lower,
+        });
+    }
+    Ok(Vec::new())
+}
+
+/// The offset of each item of the `DirectoryRecordSequence`, in order, as the `DicomRoot` would be
+/// written as a file.
+fn record_offsets(dcmroot: &DicomRoot) -> Vec<u32> {
+    flatten_with_file_offsets(dcmroot)
+        .into_iter()
+        .filter(|(_offset, element)| is_record_item(element))
+        .map(|(offset, _element)| u32::try_from(offset).unwrap_or(u32::MAX))
+        .collect()
+}
+
+/// Whether the element is an item of the `DirectoryRecordSequence`, which is a directory record.
+fn is_record_item(element: &DicomElement) -> bool {
+    element.tag() == ITEM
+        && element.sq_path().len() == 1
+        && element.sq_path()[0].sq_tag() == DirectoryRecordSequence.tag()
+}
+
+fn uint_value(obj: &DicomObject, tag: &Tag) -> Option<u32> {
+    obj.get_child_by_tag(tag)
+        .and_then(|child| child.element().parse_value_as(&UL).ok())
+        .and_then(|value| value.uint())
+}
+
+fn string_values(obj: &DicomObject, tag: &Tag) -> Vec<String> {
+    obj.get_child_by_tag(tag)
+        .and_then(|child| Vec::<String>::try_from(child.element()).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+/// Generates a DICOMDIR for the DICOM files within a folder.
+#[derive(Default, Clone)]
+pub struct DicomDirBuilder {
+    file_set_id: Option<String>,
+    sop_instance_uid: Option<String>,
+    allow_nonconforming_file_ids: bool,
+}
+
+impl DicomDirBuilder {
+    /// Sets the `FilesetID`, by default empty.
+    #[must_use]
+    pub fn file_set_id<S: Into<String>>(mut self, file_set_id: S) -> Self {
+        self.file_set_id = Some(file_set_id.into());
+        self
+    }
+
+    /// Sets the `MediaStorageSOPInstanceUID` of the DICOMDIR, by default a generated UID.
+    #[must_use]
+    pub fn sop_instance_uid<S: Into<String>>(mut self, sop_instance_uid: S) -> Self {
+        self.sop_instance_uid = Some(sop_instance_uid.into());
+        self
+    }
+
+    /// Sets whether files whose paths don't conform to the File ID requirements of Part 10,
+    /// Section 8.2, are included anyway. File IDs must be at most 8 components, each of up to 8
+    /// characters of uppercase letters, digits, and underscore. By default such files are an
+    /// error.
+    #[must_use]
+    pub fn allow_nonconforming_file_ids(mut self, allow: bool) -> Self {
+        self.allow_nonconforming_file_ids = allow;
+        self
+    }
+
+    /// Creates a DICOMDIR for the DICOM files within the folder and its sub-folders, with a
+    /// PATIENT, STUDY, SERIES, and IMAGE record hierarchy. Files which are not DICOM, from which no
+    /// element can be parsed, or which have no SOP Class or Instance UID, are skipped. Text values
+    /// are re-encoded as UTF-8.
+    ///
+    /// The offsets within the records are computed for the DICOMDIR being written as a file with
+    /// the default `WriterBuilder::for_file()`, as `write()` does.
+    ///
+    /// # Errors
+    /// - `DicomDirError::InvalidFileId` if a file's path is not a valid File ID.
+    /// - `DicomDirError::InvalidFile` if a file which is DICOM fails to parse.
+    /// - I/O errors reading the folder or its files.
+    pub fn build<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        dictionary: &dyn DicomDictionary,
+    ) -> DicomDirResult<DicomRoot> {
+        let dir: &Path = dir.as_ref();
+        let mut paths: Vec<PathBuf> = Vec::new();
+        collect_files(dir, &mut paths)?;
+        paths.sort();
+
+        let mut instances: Vec<(Vec<String>, DicomRoot)> = Vec::new();
+        for path in paths {
+            let is_dicomdir: bool = path
+                .file_name()
+                .is_some_and(|name| name.eq_ignore_ascii_case(DICOMDIR_FILE_NAME));
+            if is_dicomdir {
+                continue;
+            }
+            let Some(dcmroot) = parse_instance(&path, dictionary)? else {
+                continue;
+            };
+            let file_id: Vec<String> = self.file_id(dir, &path)?;
+            instances.push((file_id, dcmroot));
+        }
+
+        let ts: TSRef = &ExplicitVRLittleEndian;
+        let cs: CSRef = CSRef::of(encoding_rs::UTF_8);
+        let records: Vec<RecordEntry> = group_instances(&instances);
+        let mut items: Vec<DicomObject> = Vec::new();
+        let mut links: Vec<RecordLinks> = Vec::new();
+        let roots: Vec<usize> =
+            flatten_records(&records, 0, &instances, ts, cs, &mut items, &mut links)?;
+
+        let mut children: BTreeMap<u32, DicomObject> = BTreeMap::new();
+        let sop_instance_uid: String = self.sop_instance_uid.clone().unwrap_or_else(generate_uid);
+        let mut supplied: Vec<DicomElement> = Vec::new();
+        for (tag, uid) in [
+            (
+                MEDIA_STORAGE_SOP_CLASS_UID,
+                MediaStorageDirectoryStorage.uid(),
+            ),
+            (MEDIA_STORAGE_SOP_INSTANCE_UID, sop_instance_uid.as_str()),
+        ] {
+            let mut element = DicomElement::new_empty(tag, &UI, ts);
+            element.encode_val(RawValue::of_uid(uid))?;
+            supplied.push(element);
+        }
+        let supplied: Vec<&DicomElement> = supplied.iter().collect();
+        for element in generate_file_meta(&supplied, &[], ts, None)? {
+            children.insert(element.tag(), DicomObject::new(element));
+        }
+
+        let is_ascii: bool = items
+            .iter()
+            .flat_map(DicomObject::iter_child_nodes)
+            .all(|(_tag, child)| child.element().data().is_ascii());
+        let fields: [(&Tag, VRRef, Option<RawValue>); 4] = [
+            (
+                &FilesetID,
+                &CS,
+                self.file_set_id.as_deref().map(RawValue::of_string),
+            ),
+            (
+                &OffsetoftheFirstDirectoryRecordoftheRootDirectoryEntity,
+                &UL,
+                Some(RawValue::of_uint(0)),
+            ),
+            (
+                &OffsetoftheLastDirectoryRecordoftheRootDirectoryEntity,
+                &UL,
+                Some(RawValue::of_uint(0)),
+            ),
+            (&FilesetConsistencyFlag, &US, Some(RawValue::of_ushort(0))),
+        ];
+        for (tag, vr, value) in fields {
+            children.insert(
+                tag.tag(),
+                DicomObject::new(new_field(tag, vr, value, ts, cs)?),
+            );
+        }
+        if !is_ascii {
+            let mut element = DicomElement::new_empty(SPECIFIC_CHARACTER_SET, &CS, ts);
+            element.encode_val(RawValue::of_string("ISO_IR 192"))?;
+            children.insert(SPECIFIC_CHARACTER_SET, DicomObject::new(element));
+        }
+        children.insert(
+            DirectoryRecordSequence.tag(),
+            DicomObject::new_with_children(
+                new_sequence_element(DirectoryRecordSequence.tag(), ts),
+                BTreeMap::new(),
+                items,
+            ),
+        );
+        for child in children.values_mut() {
+            repath(child, &[], cs);
+            update_lengths(child, ts);
+        }
+        let mut dcmroot = DicomRoot::new(ts, cs, children, Vec::with_capacity(0));
+
+        set_offsets(&mut dcmroot, &links, &roots)?;
+        Ok(dcmroot)
+    }
+
+    /// Creates a DICOMDIR for the DICOM files within the folder, as with `build()`, and writes it
+    /// into the folder. Returns the path of the written DICOMDIR.
+    ///
+    /// # Errors
+    /// - Errors from `build()`, or errors writing the DICOMDIR.
+    pub fn write<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        dictionary: &dyn DicomDictionary,
+    ) -> DicomDirResult<PathBuf> {
+        let dir: &Path = dir.as_ref();
+        let dcmroot: DicomRoot = self.build(dir, dictionary)?;
+        let path: PathBuf = dir.join(DICOMDIR_FILE_NAME);
+        write_dcmroot_to_path(&dcmroot, &path, &WriteFileOptions::default())?;
+        Ok(path)
+    }
+
+    /// The File ID components for the file's path relative to the folder.
+    fn file_id(&self, dir: &Path, path: &Path) -> DicomDirResult<Vec<String>> {
+        let invalid = |reason: &str| DicomDirError::InvalidFileId {
+            path: path.to_path_buf(),
+            reason: reason.to_string(),
+        };
+        let relative: &Path = path
+            .strip_prefix(dir)
+            .map_err(|_| invalid("not within the folder"))?;
+        let components: Vec<String> = relative
+            .iter()
+            .map(|component| {
+                component
+                    .to_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| invalid("not valid unicode"))
+            })
+            .collect::<DicomDirResult<Vec<String>>>()?;
+
+        if self.allow_nonconforming_file_ids {
+            return Ok(components);
+        }
+        if components.len() > MAX_FILE_ID_COMPONENTS {
+            return Err(invalid("more than 8 components"));
+        }
+        let conforms = |component: &String| {
+            !component.is_empty()
+                && component.len() <= MAX_FILE_ID_COMPONENT_LENGTH
+                && component
+                    .chars()
+                    .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+        };
+        if !components.iter().all(conforms) {
+            return Err(invalid(
+                "components must be up to 8 uppercase letters, digits, or underscores",
+            ));
+        }
+        Ok(components)
+    }
+}
+
+/// A record being generated: the instance its values are taken from, and the records below it.
+struct RecordEntry {
+    key: String,
+    instance: usize,
+    children: Vec<RecordEntry>,
+}
+
+/// The indexes of the items a record's offsets refer to.
+struct RecordLinks {
+    next: Option<usize>,
+    lower: Option<usize>,
+}
+
+/// Groups the instances into patients, studies, and series, in the order first seen.
+fn group_instances(instances: &[(Vec<String>, DicomRoot)]) -> Vec<RecordEntry> {
+    let mut patients: Vec<RecordEntry> = Vec::new();
+    for (index, (_file_id, dcmroot)) in instances.iter().enumerate() {
+        let mut entries: &mut Vec<RecordEntry> = &mut patients;
+        for (_record_type, key_tag, _tags) in RECORD_LEVELS {
+            let key: String = dcmroot
+                .get_value_by_tag(key_tag)
+                .and_then(|value| value.string().map(|key| key.trim().to_string()))
+                .unwrap_or_default();
+            let position: usize = if let Some(position) = entries.iter().position(|e| e.key == key)
+            {
+                position
+            } else {
+                entries.push(RecordEntry {
+                    key,
+                    instance: index,
+                    children: Vec::new(),
+                });
+                entries.len() - 1
+            };
+            entries = &mut entries[position].children;
+        }
+        entries.push(RecordEntry {
+            key: String::new(),
+            instance: index,
+            children: Vec::new(),
+        });
+    }
+    patients
+}
+
+/// Creates the items for the records and those below them, depth-first, returning the indexes of
+/// the items for the given records.
+fn flatten_records(
+    records: &[RecordEntry],
+    level: usize,
+    instances: &[(Vec<String>, DicomRoot)],
+    ts: TSRef,
+    cs: CSRef,
+    items: &mut Vec<DicomObject>,
+    links: &mut Vec<RecordLinks>,
+) -> DicomDirResult<Vec<usize>> {
+    let mut indexes: Vec<usize> = Vec::with_capacity(records.len());
+    for record in records {
+        let index: usize = items.len();
+        if let Some(prev) = indexes.last() {
+            links[*prev].next = Some(index);
+        }
+        indexes.push(index);
+
+        let (file_id, dcmroot) = &instances[record.instance];
+        items.push(new_record(level, file_id, dcmroot, ts, cs)?);
+        links.push(RecordLinks {
+            next: None,
+            lower: None,
+        });
+        let lower: Vec<usize> =
+            flatten_records(&record.children, level + 1, instances, ts, cs, items, links)?;
+        links[index].lower = lower.first().copied();
+    }
+    Ok(indexes)
+}
+
+/// Creates the item of a record at the given level, with values from the instance. Records below
+/// the SERIES level refer to the instance's file.
+fn new_record(
+    level: usize,
+    file_id: &[String],
+    dcmroot: &DicomRoot,
+    ts: TSRef,
+    cs: CSRef,
+) -> DicomDirResult<DicomObject> {
+    let mut fields: Vec<(&Tag, VRRef, Option<RawValue>)> = Vec::new();
+    let record_type: &str = if let Some((record_type, _key, tags)) = RECORD_LEVELS.get(level) {
+        for tag in *tags {
+            fields.push((
+                tag,
+                tag.implicit_vr().unwrap_or(&UN),
+                dcmroot.get_value_by_tag(*tag),
+            ));
+        }
+        record_type
+    } else {
+        let value = |tag: &Tag| dcmroot.get_value_by_tag(tag);
+        fields.push((
+            &ReferencedFileID,
+            &CS,
+            Some(RawValue::Strings(file_id.to_vec())),
+        ));
+        fields.push((&ReferencedSOPClassUIDinFile, &UI, value(&SOPClassUID)));
+        fields.push((&ReferencedSOPInstanceUIDinFile, &UI, value(&SOPInstanceUID)));
+        fields.push((
+            &ReferencedTransferSyntaxUIDinFile,
+            &UI,
+            Some(RawValue::of_uid(dcmroot.ts().uid().uid())),
+        ));
+        fields.push((
+            &InstanceNumber,
+            InstanceNumber.implicit_vr().unwrap_or(&UN),
+            value(&InstanceNumber),
+        ));
+        instance_record_type(dcmroot)
+    };
+    fields.push((
+        &OffsetoftheNextDirectoryRecord,
+        &UL,
+        Some(RawValue::of_uint(0)),
+    ));
+    fields.push((
+        &RecordInuseFlag,
+        &US,
+        Some(RawValue::of_ushort(RECORD_IN_USE)),
+    ));
+    fields.push((
+        &OffsetofReferencedLowerLevelDirectoryEntity,
+        &UL,
+        Some(RawValue::of_uint(0)),
+    ));
+    fields.push((
+        &DirectoryRecordType,
+        &CS,
+        Some(RawValue::of_string(record_type)),
+    ));
+
+    let mut children: BTreeMap<u32, DicomObject> = BTreeMap::new();
+    for (tag, vr, value) in fields {
+        children.insert(
+            tag.tag(),
+            DicomObject::new(new_field(tag, vr, value, ts, cs)?),
+        );
+    }
+    Ok(DicomObject::new_with_children(
+        new_item_element(ts),
+        children,
+        Vec::with_capacity(0),
+    ))
+}
+
+/// The `DirectoryRecordType` for an instance, based on its modality.
+fn instance_record_type(dcmroot: &DicomRoot) -> &'static str {
+    let modality: Option<String> = dcmroot
+        .get_value_by_tag(&Modality)
+        .and_then(|value| value.string().map(|modality| modality.trim().to_string()));
+    match modality.as_deref() {
+        Some("SR") => "SR DOCUMENT",
+        Some("PR") => "PRESENTATION",
+        Some("KO") => "KEY OBJECT DOC",
+        Some("RTSTRUCT") => "RT STRUCTURE SET",
+        Some("RTPLAN") => "RT PLAN",
+        Some("RTDOSE") => "RT DOSE",
+        Some("REG") => "REGISTRATION",
+        _ => "IMAGE",
+    }
+}
+
+fn new_field(
+    tag: &Tag,
+    vr: VRRef,
+    value: Option<RawValue>,
+    ts: TSRef,
+    cs: CSRef,
+) -> DicomDirResult<DicomElement> {
+    let mut element = DicomElement::new(
+        tag.tag(),
+        vr,
+        ValueLength::Explicit(0),
+        ts,
+        cs,
+        Vec::with_capacity(0),
+        Vec::with_capacity(0),
+    );
+    if let Some(value) = value {
+        element.encode_val(value)?;
+    }
+    Ok(element)
+}
+
+/// Sets the offsets of the records and of the first and last records of the root directory
+/// entity, from the indexes of the items they refer to.
+fn set_offsets(
+    dcmroot: &mut DicomRoot,
+    links: &[RecordLinks],
+    roots: &[usize],
+) -> DicomDirResult<()> {
+    // The offsets are fixed-size values, so setting them doesn't change the offsets of the
+    // records.
+    let offsets: Vec<u32> = record_offsets(dcmroot);
+    let offset_of = |index: Option<usize>| index.map_or(0, |index| offsets[index]);
+    if let Some(sequence) = dcmroot.get_child_by_tag_mut(&DirectoryRecordSequence) {
+        for (index, link) in links.iter().enumerate() {
+            let Some(item) = sequence.get_item_by_index_mut(index + 1) else {
+                continue;
+            };
+            set_uint(item, &OffsetoftheNextDirectoryRecord, offset_of(link.next))?;
+            set_uint(
+                item,
+                &OffsetofReferencedLowerLevelDirectoryEntity,
+                offset_of(link.lower),
+            )?;
+        }
+    }
+    set_uint(
+        dcmroot.as_obj_mut(),
+        &OffsetoftheFirstDirectoryRecordoftheRootDirectoryEntity,
+        offset_of(roots.first().copied()),
+    )?;
+    set_uint(
+        dcmroot.as_obj_mut(),
+        &OffsetoftheLastDirectoryRecordoftheRootDirectoryEntity,
+        offset_of(roots.last().copied()),
+    )?;
+    Ok(())
+}
+
+fn set_uint(obj: &mut DicomObject, tag: &Tag, value: u32) -> DicomDirResult<()> {
+    if let Some(child) = obj.get_child_by_tag_mut(tag) {
+        child.element_mut().encode_val(RawValue::of_uint(value))?;
+    }
+    Ok(())
+}
+
+/// Parses the file up to its Pixel Data, or `None` if it's not DICOM or has no SOP Class or
+/// Instance UID. A file is not DICOM if no elements could be parsed from it.
+///
+/// # Errors
+/// - `DicomDirError::InvalidFile` if the file fails to parse after its first element.
+/// - I/O errors opening the file.
+fn parse_instance(
+    path: &Path,
+    dictionary: &dyn DicomDictionary,
+) -> DicomDirResult<Option<DicomRoot>> {
+    let file: File = File::open(path)?;
+    let mut parser = ParserBuilder::default()
+        .stop(ParseStop::before(&PixelData))
+        .build(BufReader::new(file), dictionary);
+    let mut is_dicom: bool = false;
+    let parsed = DicomRoot::parse_with(&mut parser, |_parser, _element| is_dicom = true);
+    let dcmroot: DicomRoot = match parsed {
+        Ok(Some(dcmroot)) => dcmroot,
+        Ok(None) => return Ok(None),
+        Err(_) if !is_dicom => return Ok(None),
+        Err(source) => {
+            return Err(DicomDirError::InvalidFile {
+                path: path.to_path_buf(),
+                source,
+            })
+        }
+    };
+    let has_uids: bool = [&SOPClassUID, &SOPInstanceUID].iter().all(|tag| {
+        dcmroot
+            .get_value_by_tag(*tag)
+            .is_some_and(|v| v.string().is_some())
+    });
+    Ok(has_uids.then_some(dcmroot))
+}
+
+/// Adds the paths of all files within the folder and its sub-folders.
+fn collect_files(dir: &Path, paths: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path: PathBuf = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, paths)?;
+        } else if path.is_file() {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Generates a UID under the `2.25` root from a random 128-bit number, Part 5 Section B.2.
+fn generate_uid() -> String {
+    let nanos: u128 = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    let random = |salt: u64| {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(nanos);
+        hasher.write_u64(salt);
+        hasher.finish()
+    };
+    let value: u128 = (u128::from(random(0)) << 64) | u128::from(random(1));
+    format!("2.25.{value}")
+}
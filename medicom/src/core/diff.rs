@@ -0,0 +1,432 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Structural comparison of two `DicomRoot`s, and patches which apply the differences to another
+//! `DicomRoot`.
+//!
+//! Elements are compared by their decoded values, so the same dataset encoded in different
+//! transfer syntaxes has no differences. Sequences are compared item by item, with each difference
+//! located by its `TagPath`.
+
+use std::collections::BTreeSet;
+
+use thiserror::Error;
+
+use crate::core::{
+    dcmelement::DicomElement,
+    dcmobject::{DicomObject, DicomRoot},
+    defn::{
+        constants::tags::{ITEM_DELIMITATION_ITEM, SEQUENCE_DELIMITATION_ITEM},
+        dcmdict::DicomDictionary,
+        tag::{TagNode, TagPath},
+        ts::TSRef,
+        vr::{VRRef, UN},
+    },
+    values::RawValue,
+    write::{error::WriteError, transcode::transcode_object},
+};
+
+/// Errors that can occur applying a `DicomPatch`.
+#[derive(Error, Debug)]
+pub enum PatchError {
+    /// The sequence, item, or element the patch refers to does not exist in the `DicomRoot`.
+    #[error("patch path not found: {0}")]
+    PathNotFound(String),
+
+    /// An element or item being set or inserted could not be re-encoded in the transfer syntax of
+    /// the `DicomRoot`.
+    #[error("error encoding patch: {0}")]
+    EncodeError(#[from] WriteError),
+}
+
+/// The kind of difference between two `DicomRoot`s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    /// The element or item is only present in the second `DicomRoot`.
+    Added,
+
+    /// The element or item is only present in the first `DicomRoot`.
+    Removed,
+
+    /// The element is present in both `DicomRoot`s with different values.
+    Changed,
+}
+
+/// A single difference between two `DicomRoot`s.
+#[derive(Debug)]
+pub struct DiffEntry<'a> {
+    path: TagPath,
+    kind: DiffKind,
+    old: Option<&'a DicomObject>,
+    new: Option<&'a DicomObject>,
+}
+
+impl<'a> DiffEntry<'a> {
+    /// The path to the element or item which differs. For items the last node of the path refers
+    /// to the sequence with the 1-based index of the item.
+    #[must_use]
+    pub fn path(&self) -> &TagPath {
+        &self.path
+    }
+
+    /// The kind of difference.
+    #[must_use]
+    pub fn kind(&self) -> DiffKind {
+        self.kind
+    }
+
+    /// Whether this difference is for a whole item of a sequence, rather than an element.
+    #[must_use]
+    pub fn is_item(&self) -> bool {
+        self.path
+            .nodes()
+            .last()
+            .is_some_and(|node| node.item().is_some())
+    }
+
+    /// The element or item from the first `DicomRoot`, if present.
+    #[must_use]
+    pub fn old_obj(&self) -> Option<&'a DicomObject> {
+        self.old
+    }
+
+    /// The element or item from the second `DicomRoot`, if present.
+    #[must_use]
+    pub fn new_obj(&self) -> Option<&'a DicomObject> {
+        self.new
+    }
+
+    /// The decoded value of the element from the first `DicomRoot`, if present.
+    #[must_use]
+    pub fn old_value(&self) -> Option<RawValue<'a>> {
+        self.old.and_then(|obj| obj.element().parse_value().ok())
+    }
+
+    /// The decoded value of the element from the second `DicomRoot`, if present.
+    #[must_use]
+    pub fn new_value(&self) -> Option<RawValue<'a>> {
+        self.new.and_then(|obj| obj.element().parse_value().ok())
+    }
+}
+
+/// The differences between two `DicomRoot`s, ordered by their location in the dataset.
+#[derive(Debug)]
+pub struct DicomDiff<'a> {
+    entries: Vec<DiffEntry<'a>>,
+}
+
+impl<'a> DicomDiff<'a> {
+    /// All differences.
+    #[must_use]
+    pub fn entries(&self) -> &[DiffEntry<'a>] {
+        &self.entries
+    }
+
+    /// Whether the `DicomRoot`s have no differences.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Creates a patch which, when applied to the first `DicomRoot`, results in the second.
+    #[must_use]
+    pub fn to_patch(&self) -> DicomPatch {
+        let ops: Vec<PatchOp> = self
+            .entries
+            .iter()
+            .flat_map(|entry| {
+                let path: TagPath = entry.path.clone();
+                match (entry.kind, entry.new, entry.is_item()) {
+                    (DiffKind::Removed, _, true) => vec![PatchOp::RemoveItem(path)],
+                    (DiffKind::Removed, _, false) | (_, None, _) => vec![PatchOp::Remove(path)],
+                    (DiffKind::Added, Some(new), true) => {
+                        vec![PatchOp::InsertItem(path, new.clone())]
+                    }
+                    // Changed items are fragments of encapsulated pixel data, which are replaced.
+                    (DiffKind::Changed, Some(new), true) => vec![
+                        PatchOp::RemoveItem(path.clone()),
+                        PatchOp::InsertItem(path, new.clone()),
+                    ],
+                    (_, Some(new), false) => vec![PatchOp::Set(path, new.clone())],
+                }
+            })
+            .collect();
+        DicomPatch { ops }
+    }
+}
+
+/// Compares two `DicomRoot`s, returning the differences from `old` to `new`.
+#[must_use]
+pub fn diff<'a>(old: &'a DicomRoot, new: &'a DicomRoot) -> DicomDiff<'a> {
+    let mut differ = Differ {
+        entries: Vec::new(),
+    };
+    differ.contents(&[], old.as_obj(), new.as_obj());
+    DicomDiff {
+        entries: differ.entries,
+    }
+}
+
+struct Differ<'a> {
+    entries: Vec<DiffEntry<'a>>,
+}
+
+impl<'a> Differ<'a> {
+    fn push(
+        &mut self,
+        path: Vec<TagNode>,
+        kind: DiffKind,
+        old: Option<&'a DicomObject>,
+        new: Option<&'a DicomObject>,
+    ) {
+        self.entries.push(DiffEntry {
+            path: TagPath::from(path),
+            kind,
+            old,
+            new,
+        });
+    }
+
+    /// Compares the child nodes of two objects, which are both the root or items at `path`.
+    fn contents(&mut self, path: &[TagNode], old: &'a DicomObject, new: &'a DicomObject) {
+        let tags: BTreeSet<u32> = old
+            .iter_child_nodes()
+            .chain(new.iter_child_nodes())
+            .map(|(tag, _child)| *tag)
+            .filter(|tag| *tag != ITEM_DELIMITATION_ITEM && *tag != SEQUENCE_DELIMITATION_ITEM)
+            .collect();
+
+        for tag in tags {
+            let mut child_path: Vec<TagNode> = path.to_vec();
+            child_path.push(TagNode::new(tag, None));
+            match (old.get_child_by_tag(tag), new.get_child_by_tag(tag)) {
+                (Some(old), Some(new)) => self.objects(child_path, old, new),
+                (Some(old), None) => self.push(child_path, DiffKind::Removed, Some(old), None),
+                (None, Some(new)) => self.push(child_path, DiffKind::Added, None, Some(new)),
+                (None, None) => {}
+            }
+        }
+    }
+
+    /// Compares two elements with the same tag at `path`, recursing into the items of sequences.
+    fn objects(&mut self, path: Vec<TagNode>, old: &'a DicomObject, new: &'a DicomObject) {
+        let old_is_sq: bool = old.element().is_sq_like() || old.item_count() > 0;
+        let new_is_sq: bool = new.element().is_sq_like() || new.item_count() > 0;
+        if !old_is_sq && !new_is_sq {
+            if !same_value(old.element(), new.element()) {
+                self.push(path, DiffKind::Changed, Some(old), Some(new));
+            }
+            return;
+        }
+        if old_is_sq != new_is_sq {
+            self.push(path, DiffKind::Changed, Some(old), Some(new));
+            return;
+        }
+
+        let (parent, node) = path.split_at(path.len() - 1);
+        let tag: u32 = node[0].tag();
+        let item_path = |index: usize| -> Vec<TagNode> {
+            let mut item_path: Vec<TagNode> = parent.to_vec();
+            item_path.push(TagNode::new(tag, Some(index + 1)));
+            item_path
+        };
+
+        for (index, (old_item, new_item)) in old.iter_items().zip(new.iter_items()).enumerate() {
+            if old_item.element().is_within_pixel_data() {
+                // Fragments of encapsulated pixel data have values rather than elements.
+                if !same_value(old_item.element(), new_item.element()) {
+                    self.push(
+                        item_path(index),
+                        DiffKind::Changed,
+                        Some(old_item),
+                        Some(new_item),
+                    );
+                }
+            } else {
+                self.contents(&item_path(index), old_item, new_item);
+            }
+        }
+        for (index, new_item) in new.iter_items().enumerate().skip(old.item_count()) {
+            self.push(item_path(index), DiffKind::Added, None, Some(new_item));
+        }
+        // Removed items are listed last-first so that removing them in order keeps the indices of
+        // the remaining items to remove valid.
+        for (index, old_item) in old
+            .iter_items()
+            .enumerate()
+            .skip(new.item_count())
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+        {
+            self.push(item_path(index), DiffKind::Removed, Some(old_item), None);
+        }
+    }
+}
+
+/// Whether the two elements have the same value. Values which are encoded identically are the
+/// same, otherwise their decoded values are compared. If only one of the elements has a resolved
+/// VR, both are decoded using it.
+fn same_value(old: &DicomElement, new: &DicomElement) -> bool {
+    if old.ts() == new.ts() && old.vr() == new.vr() {
+        if let (Ok(old), Ok(new)) = (old.load_value(), new.load_value()) {
+            if old == new {
+                return true;
+            }
+        }
+    }
+
+    let vr: VRRef = if old.vr() == &UN { new.vr() } else { old.vr() };
+    match (old.parse_value_as(vr), new.parse_value_as(vr)) {
+        (Ok(old), Ok(new)) => old == new,
+        _ => false,
+    }
+}
+
+/// An operation of a `DicomPatch`.
+#[derive(Debug, Clone)]
+pub enum PatchOp {
+    /// Sets the element at the path, replacing any existing element and its contents.
+    Set(TagPath, DicomObject),
+
+    /// Removes the element at the path.
+    Remove(TagPath),
+
+    /// Inserts the item into the sequence at the path, at the 1-based index of the path's last
+    /// node. The sequence must exist.
+    InsertItem(TagPath, DicomObject),
+
+    /// Removes the item of the sequence at the path, at the 1-based index of the path's last node.
+    RemoveItem(TagPath),
+}
+
+/// A set of changes which can be applied to a `DicomRoot`, created from `DicomDiff::to_patch()`.
+/// Unlike a `DicomDiff` this does not borrow from the compared `DicomRoot`s.
+#[derive(Debug, Clone, Default)]
+pub struct DicomPatch {
+    ops: Vec<PatchOp>,
+}
+
+impl DicomPatch {
+    /// Creates a patch from the given operations, which are applied in order.
+    #[must_use]
+    pub fn new(ops: Vec<PatchOp>) -> DicomPatch {
+        DicomPatch { ops }
+    }
+
+    /// The operations of this patch.
+    #[must_use]
+    pub fn ops(&self) -> &[PatchOp] {
+        &self.ops
+    }
+
+    /// Whether this patch makes no changes.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Applies each operation of the patch to the `DicomRoot`, in order. Elements being removed
+    /// which are already absent are ignored.
+    ///
+    /// Elements and items being set or inserted are re-encoded in the `DicomRoot`'s transfer
+    /// syntax, resolving VRs with the dictionary as `transcode` does. The `sq_path` and lengths of
+    /// each edited root-level element are then updated for its new contents.
+    ///
+    /// # Errors
+    /// - `PatchError::PathNotFound` if the sequence or item containing an element or item being
+    ///   set, inserted, or removed does not exist.
+    /// - `PatchError::EncodeError` if an element or item could not be re-encoded.
+    ///
+    /// Operations prior to the error remain applied.
+    pub fn apply(
+        &self,
+        root: &mut DicomRoot,
+        dictionary: &dyn DicomDictionary,
+    ) -> Result<(), PatchError> {
+        for op in &self.ops {
+            let ts: TSRef = root.ts();
+            match op {
+                PatchOp::Set(path, obj) => {
+                    let obj: Option<DicomObject> = transcode_object(obj, ts, dictionary)?;
+                    let (parent, _node) = resolve_parent(root, path)?;
+                    if let Some(obj) = obj {
+                        parent.replace_child(obj);
+                    }
+                }
+                PatchOp::Remove(path) => {
+                    let (parent, node) = resolve_parent(root, path)?;
+                    parent.remove_child(node.tag());
+                }
+                PatchOp::InsertItem(path, item) => {
+                    let item: Option<DicomObject> = transcode_object(item, ts, dictionary)?;
+                    let (sequence, index) = resolve_sequence(root, path)?;
+                    if let Some(item) = item {
+                        sequence.insert_item_at(index, item);
+                    }
+                }
+                PatchOp::RemoveItem(path) => {
+                    let (sequence, index) = resolve_sequence(root, path)?;
+                    sequence
+                        .remove_item_at(index)
+                        .ok_or_else(|| PatchError::PathNotFound(path.to_string()))?;
+                }
+            }
+            let (PatchOp::Set(path, _)
+            | PatchOp::Remove(path)
+            | PatchOp::InsertItem(path, _)
+            | PatchOp::RemoveItem(path)) = op;
+            root.refresh(path);
+        }
+        Ok(())
+    }
+}
+
+/// Resolves the object containing the last node of the path, returning it with the last node.
+fn resolve_parent<'r>(
+    root: &'r mut DicomRoot,
+    path: &'r TagPath,
+) -> Result<(&'r mut DicomObject, &'r TagNode), PatchError> {
+    let not_found = || PatchError::PathNotFound(path.to_string());
+    let (node, parents) = path.nodes().split_last().ok_or_else(not_found)?;
+    let mut parent: &mut DicomObject = root.as_obj_mut();
+    for parent_node in parents {
+        if parent_node.item() == Some(0) {
+            return Err(not_found());
+        }
+        parent = parent
+            .get_child_by_tagnode_mut(parent_node)
+            .ok_or_else(not_found)?;
+    }
+    Ok((parent, node))
+}
+
+/// Resolves the sequence of an item path, returning it with the item's 0-based index.
+fn resolve_sequence<'r>(
+    root: &'r mut DicomRoot,
+    path: &'r TagPath,
+) -> Result<(&'r mut DicomObject, usize), PatchError> {
+    let not_found = || PatchError::PathNotFound(path.to_string());
+    let (parent, node) = resolve_parent(root, path)?;
+    let index: usize = node
+        .item()
+        .and_then(|item| item.checked_sub(1))
+        .ok_or_else(not_found)?;
+    let sequence: &mut DicomObject = parent
+        .get_child_by_tag_mut(node.tag())
+        .ok_or_else(not_found)?;
+    Ok((sequence, index))
+}
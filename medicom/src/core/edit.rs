@@ -0,0 +1,331 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Editing a `DicomRoot` by `TagPath`: setting and removing elements, and inserting and removing
+//! items of sequences.
+//!
+//! Sequences and items along a path are created as needed. After each edit the `sq_path` of the
+//! affected elements is rebuilt and the explicit value lengths of the sequences and items which
+//! contain them are recomputed, so the `DicomRoot` remains as it would be if it were parsed.
+
+use thiserror::Error;
+
+use crate::core::{
+    charset::CSRef,
+    dcmelement::DicomElement,
+    dcmobject::{DicomObject, DicomRoot},
+    dcmsqelem::SequenceElement,
+    defn::{
+        constants::tags::ITEM,
+        dcmdict::DicomDictionary,
+        tag::{Tag, TagNode, TagPath},
+        ts::TSRef,
+        vl::ValueLength,
+        vr::{VRRef, SQ, UN},
+    },
+    read::ParseError,
+    values::RawValue,
+    write::sqlength::update_lengths,
+};
+
+/// Errors that can occur editing a `DicomRoot`.
+#[derive(Error, Debug)]
+pub enum EditError {
+    /// The path is empty, or refers to an item where an element is expected.
+    #[error("invalid path for editing: {0}")]
+    InvalidPath(String),
+
+    /// An element along the path exists but is not a sequence.
+    #[error("element is not a sequence: {0}")]
+    NotASequence(String),
+
+    /// An item along the path does not exist, and is not the next item of its sequence.
+    #[error("item not found: {0}")]
+    ItemNotFound(String),
+
+    /// The value could not be encoded with the element's VR.
+    #[error("error encoding value: {0}")]
+    EncodeError(#[from] ParseError),
+}
+
+impl DicomRoot {
+    /// Sets the value of the element at the path, creating the element if needed along with any
+    /// sequences and items leading to it. A node along the path without an item number refers to
+    /// the first item of its sequence, and an item one past the end of a sequence is created.
+    ///
+    /// The VR of an existing element is kept unless it's `UN`, otherwise it's looked up in the
+    /// dictionary, falling back to `UN` for unknown tags. The value is encoded with this
+    /// `DicomRoot`'s transfer syntax and the character set in effect at the path. An existing
+    /// element is replaced along with any items it has.
+    ///
+    /// # Errors
+    /// - `EditError::InvalidPath` if the path is empty or its last node has an item number.
+    /// - `EditError::NotASequence` if an element along the path is not a sequence.
+    /// - `EditError::ItemNotFound` if an item along the path is past the end of its sequence.
+    /// - `EditError::EncodeError` if the value could not be encoded.
+    pub fn set_by_tagpath(
+        &mut self,
+        path: &TagPath,
+        value: RawValue,
+        dict: &dyn DicomDictionary,
+    ) -> Result<(), EditError> {
+        let (node, parents) = split_element_path(path)?;
+        let ts: TSRef = self.ts();
+        let (parent, cs) = resolve_dataset(self, parents, path, true)?;
+
+        let vr: VRRef = parent
+            .get_child_by_tag(node.tag())
+            .map(|existing| existing.element().vr())
+            .filter(|vr| *vr != &UN)
+            .or_else(|| {
+                dict.get_tag_by_number(node.tag())
+                    .and_then(Tag::implicit_vr)
+            })
+            .unwrap_or(&UN);
+        let mut element = DicomElement::new(
+            node.tag(),
+            vr,
+            ValueLength::Explicit(0),
+            ts,
+            cs,
+            Vec::with_capacity(0),
+            Vec::with_capacity(0),
+        );
+        element.encode_val(value)?;
+        parent.replace_child(DicomObject::new(element));
+
+        self.refresh(path);
+        Ok(())
+    }
+
+    /// Removes the element at the path, returning it. If the last node of the path has an item
+    /// number then that item is removed from the sequence instead, as with `remove_item()`. A node
+    /// along the path without an item number refers to the first item of its sequence.
+    pub fn remove_by_tagpath(&mut self, path: &TagPath) -> Option<DicomObject> {
+        let (node, parents) = path.nodes().split_last()?;
+        if let Some(index) = node.item() {
+            let mut seq_path: TagPath = path.clone();
+            *seq_path.nodes_mut().last_mut()?.item_mut() = None;
+            return self.remove_item(&seq_path, index);
+        }
+
+        let (parent, _cs) = resolve_dataset(self, parents, path, false).ok()?;
+        let removed: Option<DicomObject> = parent.remove_child(node.tag());
+        self.refresh(path);
+        removed
+    }
+
+    /// Inserts an item into the sequence at the path, at the given 1-based index. The index may be
+    /// one past the last item to append the item. The sequence is created if needed, along with any
+    /// sequences and items leading to it.
+    ///
+    /// The element of the given object is replaced with an `Item` element unless it already is
+    /// one, and its contents should be encoded in this `DicomRoot`'s transfer syntax.
+    ///
+    /// # Errors
+    /// - `EditError::InvalidPath` if the path is empty or its last node has an item number.
+    /// - `EditError::NotASequence` if an element along the path is not a sequence.
+    /// - `EditError::ItemNotFound` if the index, or an item along the path, is past the end of its
+    ///   sequence.
+    pub fn insert_item(
+        &mut self,
+        seq_path: &TagPath,
+        index: usize,
+        mut item: DicomObject,
+    ) -> Result<(), EditError> {
+        let (node, parents) = split_element_path(seq_path)?;
+        let ts: TSRef = self.ts();
+        let (parent, _cs) = resolve_dataset(self, parents, seq_path, true)?;
+        if parent.get_child_by_tag(node.tag()).is_none() {
+            parent.replace_child(DicomObject::new(new_sequence_element(node.tag(), ts)));
+        }
+        let sequence: &mut DicomObject = parent
+            .get_child_by_tag_mut(node.tag())
+            .ok_or_else(|| EditError::NotASequence(seq_path.to_string()))?;
+        if !is_sequence(sequence) {
+            return Err(EditError::NotASequence(seq_path.to_string()));
+        }
+        if index == 0 || index > sequence.item_count() + 1 {
+            return Err(EditError::ItemNotFound(format!("{seq_path}[{index}]")));
+        }
+
+        if item.element().tag() != ITEM {
+            *item.element_mut() = new_item_element(ts);
+        }
+        sequence.insert_item_at(index - 1, item);
+
+        self.refresh(seq_path);
+        Ok(())
+    }
+
+    /// Removes the item at the given 1-based index from the sequence at the path, returning it. A
+    /// node along the path without an item number refers to the first item of its sequence.
+    pub fn remove_item(&mut self, seq_path: &TagPath, index: usize) -> Option<DicomObject> {
+        let (node, parents) = seq_path.nodes().split_last()?;
+        let (parent, _cs) = resolve_dataset(self, parents, seq_path, false).ok()?;
+        let removed: Option<DicomObject> = parent
+            .get_child_by_tag_mut(node.tag())
+            .and_then(|sequence| sequence.remove_item_at(index.checked_sub(1)?));
+        self.refresh(seq_path);
+        removed
+    }
+
+    /// Rebuilds the `sq_path` and recomputes the lengths of the root-level element containing the
+    /// path, after it has been edited.
+    pub(crate) fn refresh(&mut self, path: &TagPath) {
+        let ts: TSRef = self.ts();
+        let cs: CSRef = self.cs();
+        let Some(node) = path.nodes().first() else {
+            return;
+        };
+        if let Some(obj) = self.get_child_by_tag_mut(node.tag()) {
+            repath(obj, &[], cs);
+            update_lengths(obj, ts);
+        }
+    }
+}
+
+/// Splits the path into its last node, which must refer to an element rather than an item, and
+/// the nodes leading to it.
+fn split_element_path(path: &TagPath) -> Result<(&TagNode, &[TagNode]), EditError> {
+    match path.nodes().split_last() {
+        Some((node, parents)) if node.item().is_none() => Ok((node, parents)),
+        _ => Err(EditError::InvalidPath(path.to_string())),
+    }
+}
+
+/// Resolves the item referred to by the nodes, or the root if there are none, returning it along
+/// with the character set in effect for its contents. If `create` is set then missing sequences
+/// are created, as well as an item one past the end of its sequence.
+fn resolve_dataset<'r>(
+    root: &'r mut DicomRoot,
+    nodes: &[TagNode],
+    path: &TagPath,
+    create: bool,
+) -> Result<(&'r mut DicomObject, CSRef), EditError> {
+    let ts: TSRef = root.ts();
+    let mut cs: CSRef = root.cs();
+    let mut dataset: &mut DicomObject = root.as_obj_mut();
+    for node in nodes {
+        let index: usize = node.item().unwrap_or(1);
+        let not_found = || EditError::ItemNotFound(path.to_string());
+        if index == 0 {
+            return Err(not_found());
+        }
+
+        if create && dataset.get_child_by_tag(node.tag()).is_none() {
+            dataset.replace_child(DicomObject::new(new_sequence_element(node.tag(), ts)));
+        }
+        let sequence: &mut DicomObject = dataset
+            .get_child_by_tag_mut(node.tag())
+            .ok_or_else(not_found)?;
+        if !is_sequence(sequence) {
+            return Err(EditError::NotASequence(path.to_string()));
+        }
+        if create && index == sequence.item_count() + 1 {
+            sequence.insert_item_at(index - 1, DicomObject::new(new_item_element(ts)));
+        }
+
+        dataset = sequence
+            .get_item_by_index_mut(index)
+            .ok_or_else(not_found)?;
+        cs = item_cs(dataset, cs);
+    }
+    Ok((dataset, cs))
+}
+
+/// Whether the object is a sequence whose items contain elements. Encapsulated pixel data is not.
+fn is_sequence(obj: &DicomObject) -> bool {
+    !obj.element().is_pixel_data() && (obj.element().is_sq_like() || obj.item_count() > 0)
+}
+
+/// Creates an empty sequence element, whose length is computed as it's edited.
+pub(crate) fn new_sequence_element(tag: u32, ts: TSRef) -> DicomElement {
+    DicomElement::new(
+        tag,
+        &SQ,
+        ValueLength::Explicit(0),
+        ts,
+        CSRef::default(),
+        Vec::with_capacity(0),
+        Vec::with_capacity(0),
+    )
+}
+
+/// Creates an empty item element, whose length is computed as it's edited.
+pub(crate) fn new_item_element(ts: TSRef) -> DicomElement {
+    DicomElement::new(
+        ITEM,
+        &UN,
+        ValueLength::Explicit(0),
+        ts,
+        CSRef::default(),
+        Vec::with_capacity(0),
+        Vec::with_capacity(0),
+    )
+}
+
+/// The character set in effect for the contents of an item. Parsing tracks the item's
+/// `SpecificCharacterSet` in the `sq_path` of its contents, otherwise the item inherits the
+/// character set of its sequence.
+fn item_cs(item: &DicomObject, inherited: CSRef) -> CSRef {
+    item.iter_child_nodes()
+        .next()
+        .and_then(|(_tag, child)| child.element().sq_path().last())
+        .filter(|sq| sq.sq_tag() == ITEM)
+        .map_or(inherited, SequenceElement::cs)
+}
+
+/// Sets the `sq_path` of the object's element, then those of its items and their contents, as the
+/// parser would, numbering each item by its position within its sequence.
+pub(crate) fn repath(obj: &mut DicomObject, sq_path: &[SequenceElement], cs: CSRef) {
+    obj.element_mut().set_sq_path(sq_path.to_vec());
+    if obj.item_count() == 0 && obj.child_count() == 0 {
+        return;
+    }
+
+    let element: &DicomElement = obj.element();
+    let mut sequence = SequenceElement::new(element.tag(), None, element.vr(), element.vl(), cs);
+    for (index, item) in obj.iter_items_mut().enumerate() {
+        sequence.set_item(Some(index + 1));
+        let mut item_path: Vec<SequenceElement> = sq_path.to_vec();
+        item_path.push(sequence.clone());
+        item.element_mut().set_sq_path(item_path.clone());
+        if item.element().is_within_pixel_data() {
+            continue;
+        }
+
+        let item_cs: CSRef = item_cs(item, cs);
+        let element: &DicomElement = item.element();
+        item_path.push(SequenceElement::new(
+            ITEM,
+            None,
+            element.vr(),
+            element.vl(),
+            item_cs,
+        ));
+        for (_tag, child) in item.iter_child_nodes_mut() {
+            repath(child, &item_path, item_cs);
+        }
+    }
+
+    // The remaining children are delimiters, which follow the last item.
+    let mut delim_path: Vec<SequenceElement> = sq_path.to_vec();
+    delim_path.push(sequence);
+    for (_tag, child) in obj.iter_child_nodes_mut() {
+        child.element_mut().set_sq_path(delim_path.clone());
+    }
+}
@@ -16,7 +16,7 @@
 
 //! Utilities for inspecting or debugging DICOM.
 
-use std::fmt;
+use std::{collections::HashMap, fmt};
 
 use crate::core::{
     dcmelement::DicomElement,
@@ -28,7 +28,7 @@ use crate::core::{
     },
     defn::{
         dcmdict::{DicomDictionary, MultiDicomDictionary},
-        tag::Tag,
+        tag::{Tag, TagNode, TagPath, TagRef},
     },
     values::RawValue,
 };
@@ -86,6 +86,40 @@ impl fmt::Display for FormattedTagValue {
     }
 }
 
+/// Tracks the values of private creators as elements are parsed, to resolve the private creator of
+/// private tags when elements are processed individually rather than within a `DicomObject`.
+#[derive(Default)]
+pub struct PrivateCreators {
+    /// The values of private creators, by the path to the private creator element.
+    creators: HashMap<TagPath, String>,
+}
+
+impl PrivateCreators {
+    /// Records the value of the element if it's a private creator.
+    pub fn record(&mut self, elem: &DicomElement) {
+        if !Tag::is_private_creator(elem.tag()) {
+            return;
+        }
+        let value: Option<String> = Vec::<String>::try_from(elem)
+            .ok()
+            .and_then(|values| values.into_iter().next());
+        if let Some(value) = value {
+            self.creators
+                .insert(elem.create_tagpath(), value.trim().to_string());
+        }
+    }
+
+    /// The value of the private creator which reserves the block of the element's tag, if it was
+    /// recorded within the same dataset or item.
+    #[must_use]
+    pub fn creator_of(&self, elem: &DicomElement) -> Option<&str> {
+        let creator_tag: u32 = Tag::private_creator_of(elem.tag())?;
+        let mut tagpath: TagPath = elem.create_tagpath();
+        *tagpath.nodes_mut().last_mut()? = TagNode::from(creator_tag);
+        self.creators.get(&tagpath).map(String::as_str)
+    }
+}
+
 /// Convenience for coordinating an element's display with formatting options.
 pub struct FormattedElement<'e> {
     /// The element to render.
@@ -100,6 +134,9 @@ pub struct FormattedElement<'e> {
     hide_groups: bool,
     /// Dictionary to resolve VRs, tag names, UID names, etc.
     dict: MultiDicomDictionary<'e>,
+    /// The value of the private creator reserving the block of the element's tag, to resolve
+    /// private tags with the dictionary.
+    private_creator: Option<String>,
 }
 
 impl<'e> FormattedElement<'e> {
@@ -117,6 +154,7 @@ impl<'e> FormattedElement<'e> {
             hide_delims: false,
             hide_groups: false,
             dict,
+            private_creator: None,
         }
     }
 
@@ -150,6 +188,12 @@ impl<'e> FormattedElement<'e> {
         self
     }
 
+    #[must_use]
+    pub fn with_private_creator(mut self, private_creator: Option<String>) -> Self {
+        self.private_creator = private_creator;
+        self
+    }
+
     #[must_use]
     pub fn elem(&self) -> &'e DicomElement {
         self.elem
@@ -180,6 +224,11 @@ impl<'e> FormattedElement<'e> {
         &self.dict
     }
 
+    #[must_use]
+    pub fn private_creator(&self) -> Option<&str> {
+        self.private_creator.as_deref()
+    }
+
     #[must_use]
     pub fn should_omit(&self) -> bool {
         (self.hide_delims
@@ -204,10 +253,24 @@ impl<'e> FormattedElement<'e> {
         (formatted.len() < vec_len, formatted)
     }
 
+    /// Looks up the definition of the element's tag. Private tags are looked up by their private
+    /// creator, if known.
+    fn lookup_tag(&self) -> Option<TagRef> {
+        let tag: u32 = self.elem.tag();
+        self.private_creator
+            .as_deref()
+            .filter(|_| Tag::is_private(tag))
+            .and_then(|creator| self.dict.get_private_tag(creator, tag))
+            .or_else(|| self.dict.get_tag_by_number(tag))
+    }
+
     #[must_use]
     pub fn get_tag_type(&self) -> FormattedTagType {
+        let known: Option<TagRef> = self.lookup_tag();
         if Tag::is_private_creator(self.elem.tag()) {
             FormattedTagType::PrivateCreator(self.elem.tag())
+        } else if let Some(tag) = known.filter(|_| Tag::is_private(self.elem.tag())) {
+            FormattedTagType::Known(self.elem.tag(), tag.ident().to_string())
         } else if Tag::is_private(self.elem.tag()) && self.elem.is_sq_like() {
             FormattedTagType::PrivateSequence(self.elem.tag())
         } else if Tag::is_private_group_length(self.elem.tag()) {
@@ -217,7 +280,7 @@ impl<'e> FormattedElement<'e> {
         } else if Tag::is_group_length(self.elem.tag()) {
             FormattedTagType::GroupLength(self.elem.tag())
         } else {
-            if let Some(tag) = self.dict.get_tag_by_number(self.elem.tag()) {
+            if let Some(tag) = known {
                 return FormattedTagType::Known(tag.tag(), tag.ident().to_string());
             }
 
@@ -235,11 +298,7 @@ impl<'e> FormattedElement<'e> {
 
         let mut vr = self.elem.vr();
         if !self.elem.ts().explicit_vr() || vr == &UN {
-            vr = self
-                .dict
-                .get_tag_by_number(self.elem.tag())
-                .and_then(Tag::implicit_vr)
-                .unwrap_or(vr);
+            vr = self.lookup_tag().and_then(Tag::implicit_vr).unwrap_or(vr);
         }
 
         let elem_value = match self.elem.parse_value_as(vr) {
@@ -371,11 +430,7 @@ impl fmt::Display for FormattedElement<'_> {
 
         let mut vr = self.elem.vr();
         if vr == &UN {
-            vr = self
-                .dict
-                .get_tag_by_number(self.elem.tag())
-                .and_then(Tag::implicit_vr)
-                .unwrap_or(vr);
+            vr = self.lookup_tag().and_then(Tag::implicit_vr).unwrap_or(vr);
         }
         let vr: &str = vr.ident;
 
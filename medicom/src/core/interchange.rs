@@ -0,0 +1,375 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! The structure shared by the DICOM JSON and XML models, in which each attribute has a VR and
+//! either a list of values, nested items for sequences, or a binary value. Refer to Part 18,
+//! Annex F and Part 19, Annex A.
+//!
+//! Binary values are always little endian in these models. Encapsulated pixel data is represented
+//! as its encoded value field, the item for each fragment followed by a sequence delimiter.
+
+use std::{borrow::Cow, collections::BTreeMap};
+
+use crate::core::{
+    charset::CSRef,
+    dcmelement::DicomElement,
+    dcmobject::{DicomObject, DicomRoot},
+    defn::{
+        constants::{
+            tags::{
+                FILE_META_GROUP_END, ITEM, ITEM_DELIMITATION_ITEM, PIXEL_DATA,
+                SEQUENCE_DELIMITATION_ITEM,
+            },
+            ts::ExplicitVRLittleEndian,
+        },
+        dcmdict::DicomDictionary,
+        ts::TSRef,
+        vl::ValueLength,
+        vr::{VRRef, OB, SQ, UN},
+    },
+    edit::{new_item_element, new_sequence_element, repath},
+    read::{ParseError, ParseResult},
+    values::{Attribute, RawValue},
+    write::sqlength::update_lengths,
+};
+
+/// Creates the URI a bulk data value can be retrieved from, or `None` to include it inline.
+pub type BulkDataUriFn<'a> = &'a dyn Fn(&DicomElement) -> Option<String>;
+
+/// Resolves a bulk data URI to the bytes of the value, little endian.
+pub type BulkDataResolverFn<'a> = &'a dyn Fn(&str) -> Result<Vec<u8>, String>;
+
+/// The value of an attribute, as represented in the models.
+pub(crate) enum ModelValue<'a> {
+    /// The attribute has no value.
+    Empty,
+
+    /// A sequence, whose items are the object's items.
+    Sequence,
+
+    /// The little endian bytes of a binary VR, or encapsulated pixel data.
+    Binary(Cow<'a, [u8]>),
+
+    /// The decoded values. Text VRs, including numeric strings, are always decoded as strings.
+    Values(RawValue<'a>),
+}
+
+/// The attributes of the object in tag order, skipping delimitation items which the models don't
+/// represent.
+pub(crate) fn model_attributes(obj: &DicomObject) -> impl Iterator<Item = (u32, &DicomObject)> {
+    obj.iter_child_nodes()
+        .filter(|(tag, _child)| {
+            **tag != ITEM_DELIMITATION_ITEM && **tag != SEQUENCE_DELIMITATION_ITEM
+        })
+        .map(|(tag, child)| (*tag, child))
+}
+
+/// The VR of the object in the models. Elements which have items, other than pixel data, are
+/// sequences even if their VR is not SQ, such as private sequences with a VR of UN.
+pub(crate) fn model_vr(obj: &DicomObject) -> VRRef {
+    let element: &DicomElement = obj.element();
+    if !element.is_pixel_data() && obj.item_count() > 0 {
+        &SQ
+    } else {
+        element.vr()
+    }
+}
+
+/// Decodes the value of the object for the models.
+///
+/// # Errors
+/// - `ParseError` if the value could not be decoded, or a deferred value loaded.
+pub(crate) fn model_value(obj: &DicomObject) -> Result<ModelValue<'_>, ParseError> {
+    let element: &DicomElement = obj.element();
+    let vr: VRRef = model_vr(obj);
+    if vr == &SQ {
+        return Ok(ModelValue::Sequence);
+    }
+    if obj.item_count() > 0 {
+        return encapsulated_value(obj).map(|bytes| ModelValue::Binary(Cow::Owned(bytes)));
+    }
+
+    let data: &[u8] = element.load_value()?;
+    if data.is_empty() {
+        return Ok(ModelValue::Empty);
+    }
+    if let Some(width) = binary_width(vr) {
+        let bytes: Cow<'_, [u8]> = if element.ts().big_endian() {
+            Cow::Owned(swap_bytes(data, width))
+        } else {
+            Cow::Borrowed(data)
+        };
+        return Ok(ModelValue::Binary(bytes));
+    }
+    if is_binary_number(vr) {
+        return element.parse_value().map(ModelValue::Values);
+    }
+    Vec::<String>::try_from(element)
+        .map(RawValue::Strings)
+        .map(ModelValue::Values)
+}
+
+/// The URI to refer to a binary value by, if it's larger than the threshold and a URI is created
+/// for the element.
+pub(crate) fn bulk_data_uri(
+    element: &DicomElement,
+    bytes: &[u8],
+    threshold: Option<usize>,
+    uri: Option<BulkDataUriFn<'_>>,
+) -> Option<String> {
+    if threshold.is_some_and(|threshold| bytes.len() > threshold) {
+        uri.and_then(|uri| uri(element))
+    } else {
+        None
+    }
+}
+
+/// Parses a tag given as eight hex digits, `GGGGEEEE`.
+pub(crate) fn parse_tag(tag: &str) -> Option<u32> {
+    if tag.len() != 8 {
+        return None;
+    }
+    u32::from_str_radix(tag, 16).ok()
+}
+
+/// Resolves the transfer syntax for a dataset from the value of its Transfer Syntax UID, defaulting
+/// to Explicit VR Little Endian.
+pub(crate) fn resolve_ts(uid: Option<&str>, dictionary: &dyn DicomDictionary) -> TSRef {
+    uid.and_then(|uid| dictionary.get_ts_by_uid(uid.trim_end_matches('\0').trim()))
+        .unwrap_or(&ExplicitVRLittleEndian)
+}
+
+/// Resolves the character set for a dataset from the values of its Specific Character Set.
+pub(crate) fn resolve_cs(values: &[&str]) -> CSRef {
+    CSRef::lookup_specific_character_set(values).unwrap_or_default()
+}
+
+/// Converts the string form of an attribute's values into a `RawValue` for its VR. Values of
+/// binary numeric VRs and AT are parsed, otherwise the strings are kept as-is.
+///
+/// # Errors
+/// A description of the value which could not be parsed.
+pub(crate) fn values_from_strings(
+    vr: VRRef,
+    values: Vec<String>,
+) -> Result<RawValue<'static>, String> {
+    let value: RawValue = match vr.ident {
+        "AT" => RawValue::Attributes(parse_each(&values, |v| parse_tag(v).map(Attribute))?),
+        "FL" => RawValue::Floats(parse_each(&values, |v| v.parse::<f32>().ok())?),
+        "FD" => RawValue::Doubles(parse_each(&values, |v| v.parse::<f64>().ok())?),
+        "SS" => RawValue::Shorts(parse_each(&values, |v| v.parse::<i16>().ok())?),
+        "US" => RawValue::UShorts(parse_each(&values, |v| v.parse::<u16>().ok())?),
+        "SL" => RawValue::Ints(parse_each(&values, |v| v.parse::<i32>().ok())?),
+        "UL" => RawValue::UInts(parse_each(&values, |v| v.parse::<u32>().ok())?),
+        "SV" => RawValue::Longs(parse_each(&values, |v| v.parse::<i64>().ok())?),
+        "UV" => RawValue::ULongs(parse_each(&values, |v| v.parse::<u64>().ok())?),
+        _ => RawValue::Strings(values),
+    };
+    Ok(value)
+}
+
+fn parse_each<T, F>(values: &[String], parse: F) -> Result<Vec<T>, String>
+where
+    F: Fn(&str) -> Option<T>,
+{
+    values
+        .iter()
+        .map(|value| parse(value.trim()).ok_or_else(|| format!("invalid number: {value}")))
+        .collect()
+}
+
+/// Whether the VR is encoded as binary numbers, which are represented in the models as numbers
+/// rather than as binary values.
+fn is_binary_number(vr: VRRef) -> bool {
+    matches!(
+        vr.ident,
+        "AT" | "FL" | "FD" | "SS" | "US" | "SL" | "UL" | "SV" | "UV"
+    )
+}
+
+/// The size of each number within the value of a binary VR, for byte-swapping, or `None` if the
+/// VR is not one represented as a binary value in the models.
+fn binary_width(vr: VRRef) -> Option<usize> {
+    match vr.ident {
+        "OB" | "UN" => Some(1),
+        "OW" => Some(2),
+        "OF" | "OL" => Some(4),
+        "OD" | "OV" => Some(8),
+        _ => None,
+    }
+}
+
+/// Reverses the byte order of each number in the value.
+fn swap_bytes(data: &[u8], width: usize) -> Vec<u8> {
+    data.chunks(width)
+        .flat_map(|chunk| chunk.iter().rev().copied())
+        .collect()
+}
+
+/// Encodes the fragments of encapsulated pixel data as they are within its value field.
+///
+/// # Errors
+/// - `ParseError` if the value of a deferred fragment could not be loaded.
+fn encapsulated_value(obj: &DicomObject) -> ParseResult<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::new();
+    for fragment in obj.iter_items() {
+        let data: &[u8] = fragment.element().load_value()?;
+        bytes.extend_from_slice(&item_header(ITEM, data.len()));
+        bytes.extend_from_slice(data);
+    }
+    bytes.extend_from_slice(&item_header(SEQUENCE_DELIMITATION_ITEM, 0));
+    Ok(bytes)
+}
+
+fn item_header(tag: u32, length: usize) -> [u8; 8] {
+    let group: [u8; 2] = u16::try_from(tag >> 16).unwrap_or_default().to_le_bytes();
+    let elem: [u8; 2] = u16::try_from(tag & 0xFFFF)
+        .unwrap_or_default()
+        .to_le_bytes();
+    let length: [u8; 4] = u32::try_from(length).unwrap_or(u32::MAX).to_le_bytes();
+    [
+        group[0], group[1], elem[0], elem[1], length[0], length[1], length[2], length[3],
+    ]
+}
+
+/// Parses the fragments of encapsulated pixel data from its value field, or `None` if the value
+/// is not encapsulated.
+fn parse_fragments(data: &[u8]) -> Option<Vec<Vec<u8>>> {
+    let mut fragments: Vec<Vec<u8>> = Vec::new();
+    let mut rest: &[u8] = data;
+    while rest.len() >= 8 {
+        let tag: u32 = (u32::from(u16::from_le_bytes([rest[0], rest[1]])) << 16)
+            | u32::from(u16::from_le_bytes([rest[2], rest[3]]));
+        let length: usize =
+            usize::try_from(u32::from_le_bytes([rest[4], rest[5], rest[6], rest[7]])).ok()?;
+        rest = &rest[8..];
+        if tag == SEQUENCE_DELIMITATION_ITEM {
+            return Some(fragments);
+        }
+        if tag != ITEM || length > rest.len() {
+            return None;
+        }
+        fragments.push(rest[..length].to_vec());
+        rest = &rest[length..];
+    }
+    None
+}
+
+/// Builds the objects of a `DicomRoot` from the attributes read from a model.
+pub(crate) struct ModelBuilder {
+    ts: TSRef,
+    cs: CSRef,
+}
+
+impl ModelBuilder {
+    pub(crate) fn new(ts: TSRef, cs: CSRef) -> Self {
+        Self { ts, cs }
+    }
+
+    /// Creates the `DicomRoot` from its attributes, setting the sequence paths and explicit
+    /// lengths of sequences and items.
+    pub(crate) fn build(self, mut children: BTreeMap<u32, DicomObject>) -> DicomRoot {
+        for child in children.values_mut() {
+            repath(child, &[], self.cs);
+            update_lengths(child, self.ts);
+        }
+        DicomRoot::new(self.ts, self.cs, children, Vec::with_capacity(0))
+    }
+
+    /// Creates a sequence with the given items.
+    pub(crate) fn sequence(&self, tag: u32, items: Vec<BTreeMap<u32, DicomObject>>) -> DicomObject {
+        let ts: TSRef = self.ts_for(tag);
+        let items: Vec<DicomObject> = items
+            .into_iter()
+            .map(|item| {
+                DicomObject::new_with_children(new_item_element(ts), item, Vec::with_capacity(0))
+            })
+            .collect();
+        DicomObject::new_with_children(new_sequence_element(tag, ts), BTreeMap::new(), items)
+    }
+
+    /// Creates an element with the given value, or no value.
+    ///
+    /// # Errors
+    /// - `ParseError` if the value could not be encoded for the VR.
+    pub(crate) fn element(
+        &self,
+        tag: u32,
+        vr: VRRef,
+        value: Option<RawValue<'_>>,
+    ) -> Result<DicomObject, ParseError> {
+        let mut element =
+            self.new_element(tag, vr, ValueLength::Explicit(0), Vec::with_capacity(0));
+        if let Some(value) = value {
+            element.encode_val(value)?;
+        }
+        Ok(DicomObject::new(element))
+    }
+
+    /// Creates an element with the given little endian binary value. Encapsulated pixel data is
+    /// parsed into an item for each fragment.
+    pub(crate) fn binary(&self, tag: u32, vr: VRRef, bytes: Vec<u8>) -> DicomObject {
+        let ts: TSRef = self.ts_for(tag);
+        if tag == PIXEL_DATA && ts.encapsulated() {
+            if let Some(fragments) = parse_fragments(&bytes) {
+                let items: Vec<DicomObject> = fragments
+                    .into_iter()
+                    .map(|fragment| {
+                        let vl = ValueLength::Explicit(
+                            u32::try_from(fragment.len()).unwrap_or_default(),
+                        );
+                        DicomObject::new(self.new_element(ITEM, &UN, vl, fragment))
+                    })
+                    .collect();
+                let element = self.new_element(
+                    tag,
+                    &OB,
+                    ValueLength::UndefinedLength,
+                    Vec::with_capacity(0),
+                );
+                return DicomObject::new_with_children(element, BTreeMap::new(), items);
+            }
+        }
+
+        let bytes: Vec<u8> = match binary_width(vr) {
+            Some(width) if ts.big_endian() => swap_bytes(&bytes, width),
+            _ => bytes,
+        };
+        let vl = ValueLength::Explicit(u32::try_from(bytes.len()).unwrap_or_default());
+        DicomObject::new(self.new_element(tag, vr, vl, bytes))
+    }
+
+    fn new_element(&self, tag: u32, vr: VRRef, vl: ValueLength, data: Vec<u8>) -> DicomElement {
+        DicomElement::new(
+            tag,
+            vr,
+            vl,
+            self.ts_for(tag),
+            self.cs,
+            data,
+            Vec::with_capacity(0),
+        )
+    }
+
+    /// File Meta is always encoded in Explicit VR Little Endian.
+    fn ts_for(&self, tag: u32) -> TSRef {
+        if tag <= FILE_META_GROUP_END {
+            &ExplicitVRLittleEndian
+        } else {
+            self.ts
+        }
+    }
+}
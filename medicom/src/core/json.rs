@@ -0,0 +1,502 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Conversion between `DicomRoot` and the DICOM JSON Model, enabled with the `json` feature.
+//! Refer to Part 18, Annex F.
+//!
+//! Each attribute is keyed by its tag as eight uppercase hex digits, `"GGGGEEEE"`, and holds its
+//! `vr` along with either a `Value` array, the base64-encoded `InlineBinary`, or a `BulkDataURI`
+//! referring to the value stored elsewhere. Sequence items are nested datasets and person names
+//! are objects of their `Alphabetic`, `Ideographic`, and `Phonetic` component groups.
+//!
+//! Binary values are always encoded as little endian, as the model specifies. When reading, the
+//! transfer syntax of the resulting `DicomRoot` is taken from the Transfer Syntax UID attribute if
+//! present, otherwise Explicit VR Little Endian is used. String values are encoded with the
+//! character set named by the Specific Character Set attribute.
+
+use std::{
+    collections::BTreeMap,
+    io::{Read, Write},
+};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde_json::{Map, Number, Value};
+use thiserror::Error;
+
+pub use crate::core::interchange::{BulkDataResolverFn, BulkDataUriFn};
+
+use crate::core::{
+    dcmelement::DicomElement,
+    dcmobject::{DicomObject, DicomRoot},
+    defn::{
+        constants::{
+            lookup::MINIMAL_DICOM_DICTIONARY,
+            tags::{SPECIFIC_CHARACTER_SET, TRANSFER_SYNTAX_UID},
+        },
+        dcmdict::DicomDictionary,
+        vr::{VRRef, SQ, VR},
+    },
+    interchange::{
+        bulk_data_uri, model_attributes, model_value, model_vr, parse_tag, resolve_cs, resolve_ts,
+        values_from_strings, ModelBuilder, ModelValue,
+    },
+    read::ParseError,
+    values::{Attribute, RawValue},
+};
+
+/// The separator between the component groups of a person name.
+const PN_GROUP_SEPARATOR: char = '=';
+
+/// The names of the component groups of a person name, in order.
+const PN_GROUPS: [&str; 3] = ["Alphabetic", "Ideographic", "Phonetic"];
+
+/// The maximum length of a Decimal String value.
+const DS_MAX_LENGTH: usize = 16;
+
+/// Errors that can occur converting to or from the DICOM JSON Model.
+#[derive(Error, Debug)]
+pub enum JsonError {
+    /// The JSON could not be parsed or written.
+    #[error("invalid json: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+
+    /// An attribute does not follow the DICOM JSON Model.
+    #[error("invalid attribute {tag}: {detail}")]
+    InvalidAttribute { tag: String, detail: String },
+
+    /// An attribute's value is a `BulkDataURI` which could not be resolved.
+    #[error("unable to resolve bulk data for {tag}: {uri}")]
+    UnresolvedBulkData { tag: String, uri: String },
+
+    /// A value could not be decoded from, or encoded into, an element.
+    #[error("error converting element value: {0}")]
+    ValueError(#[from] ParseError),
+
+    /// Wrapper around `std::io::Error`.
+    #[error("i/o error: {0}")]
+    IOError(#[from] std::io::Error),
+}
+
+pub type JsonResult<T> = Result<T, JsonError>;
+
+/// Options for converting a `DicomRoot` to JSON.
+#[derive(Default, Clone, Copy)]
+pub struct JsonWriteOptions<'a> {
+    /// Binary values larger than this many bytes are referred to by `BulkDataURI`.
+    bulk_data_threshold: Option<usize>,
+
+    /// Creates the `BulkDataURI` for values over the threshold.
+    bulk_data_uri: Option<BulkDataUriFn<'a>>,
+
+    /// Whether the written JSON is indented.
+    pretty: bool,
+}
+
+impl<'a> JsonWriteOptions<'a> {
+    /// Refers to binary values larger than `threshold` bytes by the URI created for the element,
+    /// rather than including them as `InlineBinary`. The caller is responsible for storing the
+    /// value such that it can be retrieved from the URI. If no URI is created the value is included
+    /// inline.
+    #[must_use]
+    pub fn bulk_data(mut self, threshold: usize, uri: BulkDataUriFn<'a>) -> Self {
+        self.bulk_data_threshold = Some(threshold);
+        self.bulk_data_uri = Some(uri);
+        self
+    }
+
+    /// Sets whether the JSON written by `write_json()` is indented.
+    #[must_use]
+    pub fn pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+}
+
+/// Options for converting JSON to a `DicomRoot`.
+#[derive(Default, Clone, Copy)]
+pub struct JsonReadOptions<'a> {
+    /// Resolves `BulkDataURI` values.
+    bulk_data_resolver: Option<BulkDataResolverFn<'a>>,
+
+    /// The dictionary for resolving the Transfer Syntax UID.
+    dictionary: Option<&'a dyn DicomDictionary>,
+}
+
+impl<'a> JsonReadOptions<'a> {
+    /// Sets the function for retrieving the values of attributes given as a `BulkDataURI`.
+    /// Without one, reading JSON which has bulk data fails.
+    #[must_use]
+    pub fn bulk_data_resolver(mut self, resolver: BulkDataResolverFn<'a>) -> Self {
+        self.bulk_data_resolver = Some(resolver);
+        self
+    }
+
+    /// Sets the dictionary for resolving the Transfer Syntax UID. By default only the uncompressed
+    /// transfer syntaxes can be resolved.
+    #[must_use]
+    pub fn dictionary(mut self, dictionary: &'a dyn DicomDictionary) -> Self {
+        self.dictionary = Some(dictionary);
+        self
+    }
+}
+
+/// Converts the `DicomRoot` to a JSON object in the DICOM JSON Model.
+///
+/// # Errors
+/// - `JsonError::ValueError` if a value could not be decoded, or a deferred value loaded.
+pub fn to_json(dcmroot: &DicomRoot, options: &JsonWriteOptions<'_>) -> JsonResult<Value> {
+    write_dataset(dcmroot.as_obj(), options).map(Value::Object)
+}
+
+/// Writes the `DicomRoot` as JSON in the DICOM JSON Model.
+///
+/// # Errors
+/// - `JsonError::ValueError` if a value could not be decoded, or a deferred value loaded.
+/// - `JsonError::InvalidJson` if writing fails.
+pub fn write_json<W: Write>(
+    dcmroot: &DicomRoot,
+    writer: W,
+    options: &JsonWriteOptions<'_>,
+) -> JsonResult<()> {
+    let json: Value = to_json(dcmroot, options)?;
+    if options.pretty {
+        serde_json::to_writer_pretty(writer, &json)?;
+    } else {
+        serde_json::to_writer(writer, &json)?;
+    }
+    Ok(())
+}
+
+/// Converts a JSON object in the DICOM JSON Model to a `DicomRoot`.
+///
+/// # Errors
+/// - `JsonError::InvalidAttribute` if the JSON is not a dataset, or an attribute is invalid.
+/// - `JsonError::UnresolvedBulkData` if a `BulkDataURI` could not be resolved.
+/// - `JsonError::ValueError` if a value could not be encoded for its VR.
+pub fn from_json(json: &Value, options: &JsonReadOptions<'_>) -> JsonResult<DicomRoot> {
+    let dataset: &Map<String, Value> =
+        json.as_object()
+            .ok_or_else(|| JsonError::InvalidAttribute {
+                tag: String::new(),
+                detail: "dataset is not an object".to_string(),
+            })?;
+
+    let dictionary: &dyn DicomDictionary = options.dictionary.unwrap_or(&MINIMAL_DICOM_DICTIONARY);
+    let reader = DatasetReader {
+        builder: ModelBuilder::new(
+            resolve_ts(first_string(dataset, TRANSFER_SYNTAX_UID), dictionary),
+            resolve_cs(&string_values(dataset, SPECIFIC_CHARACTER_SET)),
+        ),
+        options,
+    };
+    let children: BTreeMap<u32, DicomObject> = reader.dataset(dataset)?;
+    Ok(reader.builder.build(children))
+}
+
+/// Reads JSON in the DICOM JSON Model as a `DicomRoot`.
+///
+/// # Errors
+/// - `JsonError::InvalidJson` if the JSON could not be parsed.
+/// - Errors from `from_json()`.
+pub fn read_json<R: Read>(reader: R, options: &JsonReadOptions<'_>) -> JsonResult<DicomRoot> {
+    let json: Value = serde_json::from_reader(reader)?;
+    from_json(&json, options)
+}
+
+/// Converts the child nodes of the object into a JSON dataset.
+fn write_dataset(
+    obj: &DicomObject,
+    options: &JsonWriteOptions<'_>,
+) -> JsonResult<Map<String, Value>> {
+    let mut dataset: Map<String, Value> = Map::new();
+    for (tag, child) in model_attributes(obj) {
+        dataset.insert(format!("{tag:08X}"), write_attribute(child, options)?);
+    }
+    Ok(dataset)
+}
+
+/// Converts the object into a JSON attribute, with its VR and value.
+fn write_attribute(obj: &DicomObject, options: &JsonWriteOptions<'_>) -> JsonResult<Value> {
+    let vr: VRRef = model_vr(obj);
+    let mut attribute: Map<String, Value> = Map::new();
+    attribute.insert("vr".to_string(), Value::from(vr.ident));
+
+    match model_value(obj)? {
+        ModelValue::Empty => {}
+        ModelValue::Sequence => {
+            let items: Vec<Value> = obj
+                .iter_items()
+                .map(|item| write_dataset(item, options).map(Value::Object))
+                .collect::<JsonResult<Vec<Value>>>()?;
+            if !items.is_empty() {
+                attribute.insert("Value".to_string(), Value::Array(items));
+            }
+        }
+        ModelValue::Binary(bytes) => {
+            insert_binary(&mut attribute, obj.element(), &bytes, options);
+        }
+        ModelValue::Values(value) => {
+            attribute.insert("Value".to_string(), Value::Array(write_values(vr, value)));
+        }
+    }
+    Ok(Value::Object(attribute))
+}
+
+/// Adds the binary value as either `InlineBinary` or a `BulkDataURI`.
+fn insert_binary(
+    attribute: &mut Map<String, Value>,
+    element: &DicomElement,
+    bytes: &[u8],
+    options: &JsonWriteOptions<'_>,
+) {
+    let uri: Option<String> = bulk_data_uri(
+        element,
+        bytes,
+        options.bulk_data_threshold,
+        options.bulk_data_uri,
+    );
+    if let Some(uri) = uri {
+        attribute.insert("BulkDataURI".to_string(), Value::from(uri));
+    } else {
+        attribute.insert(
+            "InlineBinary".to_string(),
+            Value::from(BASE64.encode(bytes)),
+        );
+    }
+}
+
+/// Converts the decoded values of an element to JSON values.
+fn write_values(vr: VRRef, value: RawValue<'_>) -> Vec<Value> {
+    match value {
+        RawValue::Strings(strings) => strings
+            .into_iter()
+            .map(|string| write_string(vr, string))
+            .collect(),
+        RawValue::Uid(uid) => vec![Value::from(uid)],
+        RawValue::Attributes(attrs) => attrs
+            .into_iter()
+            .map(|Attribute(tag)| Value::from(format!("{tag:08X}")))
+            .collect(),
+        RawValue::Shorts(shorts) => shorts.into_iter().map(Value::from).collect(),
+        RawValue::UShorts(ushorts) => ushorts.into_iter().map(Value::from).collect(),
+        RawValue::Ints(ints) => ints.into_iter().map(Value::from).collect(),
+        RawValue::UInts(uints) => uints.into_iter().map(Value::from).collect(),
+        RawValue::Longs(longs) => longs.into_iter().map(Value::from).collect(),
+        RawValue::ULongs(ulongs) => ulongs.into_iter().map(Value::from).collect(),
+        // Convert through the shortest decimal representation of the float, otherwise widening to
+        // f64 would show its imprecision.
+        RawValue::Floats(floats) => floats
+            .into_iter()
+            .map(|float| float_value(float.to_string().parse::<f64>().unwrap_or(f64::NAN)))
+            .collect(),
+        RawValue::Doubles(doubles) => doubles.into_iter().map(float_value).collect(),
+        RawValue::Bytes(_)
+        | RawValue::Words(_)
+        | RawValue::DWords(_)
+        | RawValue::QWords(_)
+        | RawValue::BytesView(_) => Vec::new(),
+    }
+}
+
+/// Converts a string value to JSON according to its VR. Empty values are null.
+fn write_string(vr: VRRef, string: String) -> Value {
+    let trimmed: &str = string.trim();
+    if trimmed.is_empty() {
+        return Value::Null;
+    }
+    match vr.ident {
+        "PN" => {
+            let groups: Map<String, Value> = PN_GROUPS
+                .iter()
+                .zip(string.split(PN_GROUP_SEPARATOR))
+                .filter(|(_name, group)| !group.is_empty())
+                .map(|(name, group)| ((*name).to_string(), Value::from(group)))
+                .collect();
+            Value::Object(groups)
+        }
+        // Numeric strings which can't be parsed are kept as strings, which the model allows.
+        "IS" => trimmed
+            .parse::<i64>()
+            .map(Value::from)
+            .or_else(|_| trimmed.parse::<f64>().map(float_value))
+            .unwrap_or_else(|_| Value::from(trimmed)),
+        "DS" => trimmed
+            .parse::<f64>()
+            .map_or_else(|_| Value::from(trimmed), float_value),
+        _ => Value::from(string),
+    }
+}
+
+fn float_value(float: f64) -> Value {
+    Number::from_f64(float).map_or(Value::Null, Value::Number)
+}
+
+/// The first value of the attribute with the given tag, if it's a string.
+fn first_string(dataset: &Map<String, Value>, tag: u32) -> Option<&str> {
+    dataset
+        .get(&format!("{tag:08X}"))
+        .and_then(|attribute| attribute.get("Value"))
+        .and_then(|value| value.get(0))
+        .and_then(Value::as_str)
+}
+
+/// The string values of an attribute of the dataset, with null values as empty strings.
+fn string_values(dataset: &Map<String, Value>, tag: u32) -> Vec<&str> {
+    dataset
+        .get(&format!("{tag:08X}"))
+        .and_then(|attribute| attribute.get("Value"))
+        .and_then(Value::as_array)
+        .map(|values| {
+            values
+                .iter()
+                .map(|value| value.as_str().unwrap_or_default())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Converts JSON datasets into `DicomObject`s.
+struct DatasetReader<'o, 'a> {
+    builder: ModelBuilder,
+    options: &'o JsonReadOptions<'a>,
+}
+
+impl DatasetReader<'_, '_> {
+    /// Converts the attributes of a JSON dataset into the child nodes of an object.
+    fn dataset(&self, dataset: &Map<String, Value>) -> JsonResult<BTreeMap<u32, DicomObject>> {
+        let mut children: BTreeMap<u32, DicomObject> = BTreeMap::new();
+        for (key, attribute) in dataset {
+            let tag: u32 = parse_tag(key).ok_or_else(|| invalid(key, "key is not a tag"))?;
+            children.insert(tag, self.attribute(key, tag, attribute)?);
+        }
+        Ok(children)
+    }
+
+    /// Converts a JSON attribute into an object.
+    fn attribute(&self, key: &str, tag: u32, attribute: &Value) -> JsonResult<DicomObject> {
+        let vr: VRRef = attribute
+            .get("vr")
+            .and_then(Value::as_str)
+            .and_then(VR::from_ident)
+            .ok_or_else(|| invalid(key, "missing or unknown vr"))?;
+        let values: &[Value] = match attribute.get("Value") {
+            Some(Value::Array(values)) => values,
+            Some(_) => return Err(invalid(key, "Value is not an array")),
+            None => &[],
+        };
+
+        if vr == &SQ {
+            let items: Vec<BTreeMap<u32, DicomObject>> = values
+                .iter()
+                .map(|item| {
+                    item.as_object()
+                        .ok_or_else(|| invalid(key, "sequence item is not an object"))
+                        .and_then(|item| self.dataset(item))
+                })
+                .collect::<JsonResult<Vec<BTreeMap<u32, DicomObject>>>>()?;
+            return Ok(self.builder.sequence(tag, items));
+        }
+
+        if let Some(bytes) = self.binary(key, attribute)? {
+            return Ok(self.builder.binary(tag, vr, bytes));
+        }
+
+        let value: Option<RawValue> = if values.is_empty() {
+            None
+        } else {
+            let strings: Vec<String> = values
+                .iter()
+                .map(|value| read_string(key, vr, value))
+                .collect::<JsonResult<Vec<String>>>()?;
+            Some(values_from_strings(vr, strings).map_err(|detail| invalid(key, &detail))?)
+        };
+        Ok(self.builder.element(tag, vr, value)?)
+    }
+
+    /// The bytes of the attribute's `InlineBinary` or `BulkDataURI`, if it has either.
+    fn binary(&self, key: &str, attribute: &Value) -> JsonResult<Option<Vec<u8>>> {
+        if let Some(inline) = attribute.get("InlineBinary") {
+            let bytes: Vec<u8> = inline
+                .as_str()
+                .and_then(|inline| BASE64.decode(inline).ok())
+                .ok_or_else(|| invalid(key, "InlineBinary is not base64"))?;
+            return Ok(Some(bytes));
+        }
+        if let Some(uri) = attribute.get("BulkDataURI") {
+            let uri: &str = uri
+                .as_str()
+                .ok_or_else(|| invalid(key, "BulkDataURI is not a string"))?;
+            let unresolved = || JsonError::UnresolvedBulkData {
+                tag: key.to_string(),
+                uri: uri.to_string(),
+            };
+            let resolver: BulkDataResolverFn<'_> =
+                self.options.bulk_data_resolver.ok_or_else(unresolved)?;
+            return resolver(uri).map(Some).map_err(|_| unresolved());
+        }
+        Ok(None)
+    }
+}
+
+/// Converts a JSON value into the string form of the VR. Null is an empty value.
+fn read_string(key: &str, vr: VRRef, value: &Value) -> JsonResult<String> {
+    match value {
+        Value::Null => Ok(String::new()),
+        Value::String(string) => Ok(string.clone()),
+        Value::Number(number) if vr.ident == "DS" => Ok(format_ds(number)),
+        Value::Number(number) => Ok(number.to_string()),
+        Value::Object(groups) if vr.ident == "PN" => {
+            let mut name: Vec<&str> = PN_GROUPS
+                .iter()
+                .map(|group| {
+                    groups
+                        .get(*group)
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                })
+                .collect();
+            while name.last().is_some_and(|group| group.is_empty()) {
+                name.pop();
+            }
+            Ok(name.join(&PN_GROUP_SEPARATOR.to_string()))
+        }
+        _ => Err(invalid(
+            key,
+            &format!("invalid {} value: {value}", vr.ident),
+        )),
+    }
+}
+
+/// Formats a number as a Decimal String, reducing its precision to fit within 16 characters.
+fn format_ds(number: &Number) -> String {
+    let formatted: String = number.to_string();
+    if formatted.len() <= DS_MAX_LENGTH {
+        return formatted;
+    }
+    let float: f64 = number.as_f64().unwrap_or_default();
+    (0..DS_MAX_LENGTH)
+        .rev()
+        .map(|precision| format!("{float:.precision$e}"))
+        .find(|formatted| formatted.len() <= DS_MAX_LENGTH)
+        .unwrap_or(formatted)
+}
+
+fn invalid(tag: &str, detail: &str) -> JsonError {
+    JsonError::InvalidAttribute {
+        tag: tag.to_string(),
+        detail: detail.to_string(),
+    }
+}
@@ -25,12 +25,24 @@ pub mod dcmelement;
 pub mod dcmobject;
 pub mod dcmsqelem;
 pub mod defn;
+#[cfg(feature = "stddicom")]
+pub mod dicomdir;
+pub mod diff;
+pub mod edit;
 pub mod inspect;
+#[cfg(any(feature = "json", feature = "xml"))]
+pub(crate) mod interchange;
+#[cfg(feature = "json")]
+pub mod json;
 #[cfg(feature = "stddicom")]
 pub mod pixeldata;
 pub mod read;
+#[cfg(feature = "serde")]
+pub mod serialize;
 pub mod values;
 pub mod write;
+#[cfg(feature = "xml")]
+pub mod xml;
 
 //pub use values::Attribute;
 //pub use values::RawValue;
@@ -43,3 +55,10 @@ pub const DICOM_PREFIX_LENGTH: usize = 4;
 
 /// The length of a File Preamble.
 pub const FILE_PREAMBLE_LENGTH: usize = 128;
+
+/// The Implementation Class UID identifying medicom, used in generated File Meta.
+pub const MEDICOM_IMPLEMENTATION_CLASS_UID: &str = "2.25.288990055143928785595508087532243550136";
+
+/// The Implementation Version Name of medicom, used in generated File Meta.
+pub const MEDICOM_IMPLEMENTATION_VERSION_NAME: &str =
+    concat!("MEDICOM_", env!("CARGO_PKG_VERSION"));
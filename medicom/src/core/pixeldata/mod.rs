@@ -18,6 +18,7 @@ use thiserror::Error;
 
 use crate::core::{defn::vr::VRRef, read::ParseError};
 
+pub mod pdencap;
 pub mod pdinfo;
 pub mod pdslice;
 pub mod pdwinlevel;
@@ -48,6 +49,18 @@ pub enum PixelDataError {
     #[error("Invalid source location to interpret pixel data: {0}")]
     InvalidPixelSource(usize),
 
+    #[error("Pixel Data is encapsulated and must be decoded")]
+    EncapsulatedPixelData,
+
+    #[error("Invalid frame index: {0}")]
+    InvalidFrameIndex(usize),
+
+    #[error("Offset table entry does not refer to a fragment: {0}")]
+    InvalidFrameOffset(u64),
+
+    #[error("Unable to determine the fragments of {0} frames from {1} fragments")]
+    UndeterminedFrames(usize, usize),
+
     #[error("Error parsing DICOM")]
     ParseError {
         #[from]
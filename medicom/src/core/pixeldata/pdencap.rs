@@ -0,0 +1,401 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Encapsulated Pixel Data, where the compressed frames are encoded as a series of fragments
+//! following a Basic Offset Table. Refer to Part 5, Annex A.4.
+
+use std::{borrow::Cow, ops::Range};
+
+use crate::{
+    core::{dcmobject::DicomRoot, defn::constants::tags::ITEM, pixeldata::PixelDataError},
+    dict::tags,
+};
+
+/// Length of the Item tag and value length which precede the value of each fragment.
+const ITEM_HEADER_LENGTH: u64 = 8;
+
+/// Markers which begin the codestream of a compressed frame, used to locate frame boundaries when
+/// there's no offset table: JPEG/JPEG-LS Start of Image, and JPEG 2000 Start of Codestream.
+const FRAME_START_MARKERS: [[u8; 2]; 2] = [[0xFF, 0xD8], [0xFF, 0x4F]];
+
+/// A single fragment of encapsulated Pixel Data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fragment {
+    offset: u64,
+    data: Vec<u8>,
+}
+
+impl Fragment {
+    /// The byte offset of this fragment's Item, relative to the first byte of the first fragment's
+    /// Item following the Basic Offset Table. This is the value used by offset tables.
+    #[must_use]
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// The bytes of this fragment.
+    #[must_use]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// The fragments of encapsulated Pixel Data along with the offset tables for locating the
+/// fragments of each frame.
+///
+/// Frames are located using the Extended Offset Table if present, otherwise the Basic Offset Table.
+/// If neither table has entries then a single frame is made of all fragments, or if there's one
+/// fragment per frame then each fragment is a frame. Otherwise frames are located by fragments
+/// which begin with the start of a JPEG or JPEG 2000 codestream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncapsulatedPixelData {
+    num_frames: usize,
+    basic_offset_table: Option<Vec<u32>>,
+    extended_offset_table: Vec<u64>,
+    extended_offset_table_lengths: Vec<u64>,
+    fragments: Vec<Fragment>,
+}
+
+impl Default for EncapsulatedPixelData {
+    fn default() -> Self {
+        Self {
+            num_frames: 1,
+            basic_offset_table: None,
+            extended_offset_table: Vec::with_capacity(0),
+            extended_offset_table_lengths: Vec::with_capacity(0),
+            fragments: Vec::new(),
+        }
+    }
+}
+
+impl EncapsulatedPixelData {
+    /// Creates `EncapsulatedPixelData` from the value of the Basic Offset Table item and the
+    /// following fragments.
+    #[must_use]
+    pub fn new(basic_offset_table: &[u8], fragments: Vec<Vec<u8>>) -> EncapsulatedPixelData {
+        let mut encap = EncapsulatedPixelData::default();
+        encap.push_item(basic_offset_table.to_vec());
+        for fragment in fragments {
+            encap.push_item(fragment);
+        }
+        encap
+    }
+
+    /// Creates `EncapsulatedPixelData` from the `PixelData` of a parsed dataset, using the Number
+    /// of Frames and Extended Offset Table elements if present.
+    ///
+    /// Returns `None` if the dataset has no `PixelData` or it's not encapsulated.
+    ///
+    /// # Errors
+    /// - I/O errors loading deferred values of the fragments.
+    pub fn from_dcmroot(
+        dcmroot: &DicomRoot,
+    ) -> Result<Option<EncapsulatedPixelData>, PixelDataError> {
+        let Some(pixdata) = dcmroot.get_child_by_tag(&tags::PixelData) else {
+            return Ok(None);
+        };
+        if !pixdata.element().has_fragments() {
+            return Ok(None);
+        }
+
+        let mut encap = EncapsulatedPixelData::default();
+        for item in pixdata.iter_items() {
+            let elem = item.element();
+            if elem.tag() == ITEM {
+                encap.push_item(elem.load_value()?.to_vec());
+            }
+        }
+        if let Some(num_frames) = dcmroot
+            .get_value_by_tag(&tags::NumberofFrames)
+            .and_then(|v| v.int())
+        {
+            encap.set_num_frames(usize::try_from(num_frames).unwrap_or(1));
+        }
+        let offsets = dcmroot.get_child_by_tag(&tags::ExtendedOffsetTable);
+        let lengths = dcmroot.get_child_by_tag(&tags::ExtendedOffsetTableLengths);
+        if let (Some(offsets), Some(lengths)) = (offsets, lengths) {
+            encap.set_extended_offset_table(
+                read_u64s(offsets.element().load_value()?),
+                read_u64s(lengths.element().load_value()?),
+            );
+        }
+        Ok(Some(encap))
+    }
+
+    /// Adds the next Item of the encapsulated Pixel Data. The first Item added is the Basic Offset
+    /// Table, and all following Items are fragments.
+    pub fn push_item(&mut self, item: Vec<u8>) {
+        if self.basic_offset_table.is_none() {
+            self.basic_offset_table = Some(
+                item.chunks_exact(size_of::<u32>())
+                    .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                    .collect(),
+            );
+            return;
+        }
+
+        let offset = self.fragments.last().map_or(0, |last| {
+            last.offset + ITEM_HEADER_LENGTH + last.data.len() as u64
+        });
+        self.fragments.push(Fragment { offset, data: item });
+    }
+
+    /// Sets the Extended Offset Table and Extended Offset Table Lengths, which take precedence over
+    /// the Basic Offset Table for locating frames.
+    pub fn set_extended_offset_table(&mut self, offsets: Vec<u64>, lengths: Vec<u64>) {
+        self.extended_offset_table = offsets;
+        self.extended_offset_table_lengths = lengths;
+    }
+
+    /// Sets the number of frames, from the Number of Frames element. This is only needed for
+    /// locating frames when there are no offset tables. The default is 1.
+    pub fn set_num_frames(&mut self, num_frames: usize) {
+        self.num_frames = num_frames;
+    }
+
+    /// The Basic Offset Table, which is empty if it has no entries.
+    #[must_use]
+    pub fn basic_offset_table(&self) -> &[u32] {
+        self.basic_offset_table.as_deref().unwrap_or_default()
+    }
+
+    /// The Extended Offset Table, which is empty if not present.
+    #[must_use]
+    pub fn extended_offset_table(&self) -> &[u64] {
+        &self.extended_offset_table
+    }
+
+    /// The Extended Offset Table Lengths, which is empty if not present.
+    #[must_use]
+    pub fn extended_offset_table_lengths(&self) -> &[u64] {
+        &self.extended_offset_table_lengths
+    }
+
+    /// All fragments, excluding the Basic Offset Table.
+    #[must_use]
+    pub fn fragments(&self) -> &[Fragment] {
+        &self.fragments
+    }
+
+    /// The number of frames. This is the number of entries in the offset table if present,
+    /// otherwise the Number of Frames.
+    #[must_use]
+    pub fn num_frames(&self) -> usize {
+        if !self.extended_offset_table.is_empty() {
+            self.extended_offset_table.len()
+        } else if !self.basic_offset_table().is_empty() {
+            self.basic_offset_table().len()
+        } else {
+            self.num_frames
+        }
+    }
+
+    /// The range of indices into `fragments()` for the fragments of each frame.
+    ///
+    /// # Errors
+    /// - `PixelDataError::InvalidFrameOffset` if an offset table entry does not refer to the start
+    ///   of a fragment.
+    /// - `PixelDataError::UndeterminedFrames` if there is no offset table and the fragments of
+    ///   each frame cannot otherwise be determined.
+    pub fn frame_fragments(&self) -> Result<Vec<Range<usize>>, PixelDataError> {
+        let offsets: Vec<u64> = if self.extended_offset_table.is_empty() {
+            self.basic_offset_table()
+                .iter()
+                .map(|offset| u64::from(*offset))
+                .collect()
+        } else {
+            self.extended_offset_table.clone()
+        };
+
+        let starts: Vec<usize> = if !offsets.is_empty() {
+            offsets
+                .iter()
+                .map(|offset| {
+                    self.fragments
+                        .binary_search_by_key(offset, Fragment::offset)
+                        .map_err(|_| PixelDataError::InvalidFrameOffset(*offset))
+                })
+                .collect::<Result<Vec<usize>, PixelDataError>>()?
+        } else if self.num_frames <= 1 {
+            vec![0]
+        } else if self.fragments.len() == self.num_frames {
+            (0..self.fragments.len()).collect()
+        } else {
+            let starts: Vec<usize> = self
+                .fragments
+                .iter()
+                .enumerate()
+                .filter(|(_, fragment)| {
+                    FRAME_START_MARKERS
+                        .iter()
+                        .any(|marker| fragment.data.starts_with(marker))
+                })
+                .map(|(i, _)| i)
+                .collect();
+            if starts.len() != self.num_frames || starts.first() != Some(&0) {
+                return Err(PixelDataError::UndeterminedFrames(
+                    self.num_frames,
+                    self.fragments.len(),
+                ));
+            }
+            starts
+        };
+
+        Ok(starts
+            .iter()
+            .enumerate()
+            .map(|(i, start)| {
+                let end = starts.get(i + 1).copied().unwrap_or(self.fragments.len());
+                *start..end.max(*start)
+            })
+            .collect())
+    }
+
+    /// The compressed bytes of the frame at the given 0-based index. This is borrowed if the frame
+    /// is a single fragment, otherwise the frame's fragments are concatenated. If the Extended
+    /// Offset Table Lengths are present then trailing padding of the frame is excluded.
+    ///
+    /// # Errors
+    /// - `PixelDataError::InvalidFrameIndex` if the index is not less than `num_frames()`.
+    /// - Errors from `frame_fragments()`.
+    pub fn frame(&self, index: usize) -> Result<Cow<'_, [u8]>, PixelDataError> {
+        let ranges = self.frame_fragments()?;
+        let range = ranges
+            .get(index)
+            .cloned()
+            .ok_or(PixelDataError::InvalidFrameIndex(index))?;
+        let fragments = &self.fragments[range];
+        let mut frame: Cow<'_, [u8]> = match fragments {
+            [] => Cow::Borrowed(&[]),
+            [fragment] => Cow::Borrowed(&fragment.data),
+            _ => Cow::Owned(
+                fragments
+                    .iter()
+                    .flat_map(|fragment| fragment.data.iter().copied())
+                    .collect(),
+            ),
+        };
+        if let Some(length) = self
+            .extended_offset_table_lengths
+            .get(index)
+            .and_then(|length| usize::try_from(*length).ok())
+        {
+            if length < frame.len() {
+                frame = match frame {
+                    Cow::Borrowed(bytes) => Cow::Borrowed(&bytes[..length]),
+                    Cow::Owned(mut bytes) => {
+                        bytes.truncate(length);
+                        Cow::Owned(bytes)
+                    }
+                };
+            }
+        }
+        Ok(frame)
+    }
+}
+
+/// Reads the values of an `OV` element, such as the Extended Offset Table. Encapsulated Pixel Data
+/// is always encoded as little endian.
+pub(crate) fn read_u64s(bytes: &[u8]) -> Vec<u64> {
+    bytes
+        .chunks_exact(size_of::<u64>())
+        .map(|chunk| {
+            let mut buf = [0u8; size_of::<u64>()];
+            buf.copy_from_slice(chunk);
+            u64::from_le_bytes(buf)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::pixeldata::{
+        pdencap::{EncapsulatedPixelData, Fragment},
+        PixelDataError,
+    };
+
+    fn bot(offsets: &[u32]) -> Vec<u8> {
+        offsets.iter().flat_map(|o| o.to_le_bytes()).collect()
+    }
+
+    #[test]
+    pub fn test_frames_from_basic_offset_table() -> Result<(), PixelDataError> {
+        // Fragment offsets are 0, 12, 22, 34.
+        let encap = EncapsulatedPixelData::new(
+            &bot(&[0, 22]),
+            vec![vec![1; 4], vec![2; 2], vec![3; 4], vec![4; 2]],
+        );
+        let offsets: Vec<u64> = encap.fragments().iter().map(Fragment::offset).collect();
+        assert_eq!(vec![0, 12, 22, 34], offsets);
+        assert_eq!(2, encap.num_frames());
+        assert_eq!(vec![0..2, 2..4], encap.frame_fragments()?);
+        assert_eq!(&[1, 1, 1, 1, 2, 2][..], &*encap.frame(0)?);
+        assert_eq!(&[3, 3, 3, 3, 4, 4][..], &*encap.frame(1)?);
+        assert!(matches!(
+            encap.frame(2),
+            Err(PixelDataError::InvalidFrameIndex(2))
+        ));
+
+        let invalid = EncapsulatedPixelData::new(&bot(&[0, 20]), vec![vec![1; 4], vec![2; 2]]);
+        assert!(matches!(
+            invalid.frame_fragments(),
+            Err(PixelDataError::InvalidFrameOffset(20))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_frames_from_extended_offset_table() -> Result<(), PixelDataError> {
+        let mut encap = EncapsulatedPixelData::new(
+            &bot(&[0]),
+            vec![vec![1; 4], vec![2; 2], vec![3; 4], vec![4; 2]],
+        );
+        encap.set_extended_offset_table(vec![0, 12, 22], vec![4, 2, 5]);
+        assert_eq!(3, encap.num_frames());
+        assert_eq!(vec![0..1, 1..2, 2..4], encap.frame_fragments()?);
+        assert_eq!(&[2, 2][..], &*encap.frame(1)?);
+        assert_eq!(&[3, 3, 3, 3, 4][..], &*encap.frame(2)?);
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_frames_without_offset_table() -> Result<(), PixelDataError> {
+        let fragments = vec![
+            vec![0xFF, 0xD8, 1],
+            vec![2],
+            vec![0xFF, 0xD8, 3],
+            vec![4],
+            vec![5],
+        ];
+        let mut encap = EncapsulatedPixelData::new(&[], fragments);
+        assert_eq!(1, encap.num_frames());
+        assert_eq!(vec![0..5], encap.frame_fragments()?);
+
+        encap.set_num_frames(2);
+        assert_eq!(vec![0..2, 2..5], encap.frame_fragments()?);
+        assert_eq!(&[0xFF, 0xD8, 3, 4, 5][..], &*encap.frame(1)?);
+
+        encap.set_num_frames(5);
+        assert_eq!(vec![0..1, 1..2, 2..3, 3..4, 4..5], encap.frame_fragments()?);
+
+        encap.set_num_frames(3);
+        assert!(matches!(
+            encap.frame_fragments(),
+            Err(PixelDataError::UndeterminedFrames(3, 5))
+        ));
+        Ok(())
+    }
+}
@@ -19,12 +19,18 @@ use std::io::Read;
 use crate::{
     core::{
         dcmelement::DicomElement,
+        defn::constants::tags::ITEM,
         defn::vr::{self, VRRef},
         pixeldata::{
-            pdslice::PixelDataSlice, pdwinlevel::WindowLevel, pixel_i16::PixelDataSliceI16,
-            pixel_i32::PixelDataSliceI32, pixel_u16::PixelDataSliceU16,
-            pixel_u32::PixelDataSliceU32, pixel_u8::PixelDataSliceU8, BitsAlloc, PhotoInterp,
-            PixelDataError,
+            pdencap::{read_u64s, EncapsulatedPixelData},
+            pdslice::PixelDataSlice,
+            pdwinlevel::WindowLevel,
+            pixel_i16::PixelDataSliceI16,
+            pixel_i32::PixelDataSliceI32,
+            pixel_u16::PixelDataSliceU16,
+            pixel_u32::PixelDataSliceU32,
+            pixel_u8::PixelDataSliceU8,
+            BitsAlloc, PhotoInterp, PixelDataError,
         },
         read::Parser,
         values::RawValue,
@@ -59,6 +65,7 @@ pub struct PixelDataSliceInfo {
     unit: String,
     win_levels: Vec<WindowLevel>,
     pd_bytes: Vec<u8>,
+    encapsulated: Option<EncapsulatedPixelData>,
 }
 
 impl Default for PixelDataSliceInfo {
@@ -82,6 +89,7 @@ impl Default for PixelDataSliceInfo {
             unit: String::new(),
             win_levels: Vec::with_capacity(0),
             pd_bytes: Vec::with_capacity(0),
+            encapsulated: None,
         }
     }
 }
@@ -132,6 +140,15 @@ impl std::fmt::Debug for PixelDataSliceInfo {
             .field("unit", &self.unit)
             .field("win_levels", &self.win_levels)
             .field("pd_bytes", &self.pd_bytes.len())
+            .field(
+                "encapsulated",
+                &self
+                    .encapsulated
+                    .as_ref()
+                    .map_or("None".to_string(), |encap| {
+                        encap.fragments().len().to_string()
+                    }),
+            )
             .finish()
     }
 }
@@ -232,6 +249,12 @@ impl PixelDataSliceInfo {
         &self.pd_bytes
     }
 
+    /// The fragments of Pixel Data if it's encapsulated, in which case `bytes()` is empty.
+    #[must_use]
+    pub fn encapsulated(&self) -> Option<&EncapsulatedPixelData> {
+        self.encapsulated.as_ref()
+    }
+
     /// Whether the byte values in Pixel Data are signed or unsigned values.
     #[must_use]
     pub fn is_signed(&self) -> bool {
@@ -246,6 +269,9 @@ impl PixelDataSliceInfo {
     ///   `PixelDataInfo::process_dcm_parser`.
     pub fn validate(&mut self) -> Result<(), PixelDataError> {
         if self.pd_bytes.is_empty() {
+            if self.encapsulated.is_some() {
+                return Err(PixelDataError::EncapsulatedPixelData);
+            }
             return Err(PixelDataError::MissingPixelData);
         }
 
@@ -345,9 +371,12 @@ impl PixelDataSliceInfo {
             let mut elem = elem?;
             Self::process_element(&mut pixdata_info, &elem)?;
             if elem.is_pixel_data() || elem.is_within_pixel_data() {
-                Self::process_pixdata_element(&mut pixdata_info, &mut elem);
+                Self::process_pixdata_element(&mut pixdata_info, &mut elem)?;
             }
         }
+        if let Some(encap) = pixdata_info.encapsulated.as_mut() {
+            encap.set_num_frames(usize::try_from(pixdata_info.num_frames).unwrap_or(1));
+        }
         Ok(pixdata_info)
     }
 
@@ -451,6 +480,20 @@ impl PixelDataSliceInfo {
                     val.clone_into(&mut pixdata_info.unit);
                 }
             }
+        } else if elem.tag() == tags::ExtendedOffsetTable.tag() {
+            let offsets = read_u64s(elem.load_value()?);
+            let encap = pixdata_info
+                .encapsulated
+                .get_or_insert_with(Default::default);
+            let lengths = encap.extended_offset_table_lengths().to_vec();
+            encap.set_extended_offset_table(offsets, lengths);
+        } else if elem.tag() == tags::ExtendedOffsetTableLengths.tag() {
+            let lengths = read_u64s(elem.load_value()?);
+            let encap = pixdata_info
+                .encapsulated
+                .get_or_insert_with(Default::default);
+            let offsets = encap.extended_offset_table().to_vec();
+            encap.set_extended_offset_table(offsets, lengths);
         } else if elem.tag() == tags::WindowCenter_and_WidthExplanation.tag() {
             if let RawValue::Strings(vals) = elem.parse_value()? {
                 for (i, val) in vals.into_iter().enumerate() {
@@ -474,9 +517,35 @@ impl PixelDataSliceInfo {
 
     /// Process the relevant `PixelData` element/fragments by copying the data/bytes into the
     /// `PixelDataInfo::pd_bytes` field, replacing the element's data/bytes with an empty vec.
-    fn process_pixdata_element(pixdata_info: &mut PixelDataSliceInfo, elem: &mut DicomElement) {
+    /// Fragments of encapsulated `PixelData` are instead kept separate, in
+    /// `PixelDataInfo::encapsulated`.
+    ///
+    /// # Errors
+    /// - I/O errors loading the element's value, if it was deferred during parsing.
+    fn process_pixdata_element(
+        pixdata_info: &mut PixelDataSliceInfo,
+        elem: &mut DicomElement,
+    ) -> Result<(), PixelDataError> {
+        if elem.is_pixel_data() {
+            if elem.has_fragments() {
+                pixdata_info
+                    .encapsulated
+                    .get_or_insert_with(Default::default);
+                return Ok(());
+            }
+            pixdata_info.encapsulated = None;
+        } else if let Some(encap) = pixdata_info.encapsulated.as_mut() {
+            if elem.tag() == ITEM {
+                elem.load_value()?;
+                encap.push_item(std::mem::take(elem.mut_data()));
+            }
+            return Ok(());
+        }
+
+        elem.load_value()?;
         // Transfer ownership of the fragment's bytes to a local to copy into pixdata_info.pd_bytes.
         let data = std::mem::replace(elem.mut_data(), Vec::with_capacity(0));
         pixdata_info.pd_bytes.extend_from_slice(&data);
+        Ok(())
     }
 }
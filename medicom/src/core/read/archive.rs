@@ -0,0 +1,176 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Reading DICOM datasets from within zip, tar, and gzip-compressed tar archives, without first
+//! extracting the archive to disk.
+//!
+//! Each file entry within the archive is read into memory, and entries which are DICOM datasets
+//! are produced along with a `Parser` for them. See `ParserBuilder::build_archive()`.
+
+use std::{
+    io::{Cursor, Read, Seek},
+    path::Path,
+};
+
+use libflate::gzip::MultiDecoder;
+
+use crate::core::{
+    defn::dcmdict::DicomDictionary,
+    read::{
+        archive::{tar::TarEntries, zip::ZipEntries},
+        ParseResult, Parser, ParserBuilder,
+    },
+    DICOM_PREFIX, DICOM_PREFIX_LENGTH, FILE_PREAMBLE_LENGTH,
+};
+
+mod tar;
+mod zip;
+
+/// A DICOM dataset within an archive: the path of the entry within the archive, and a `Parser` over
+/// the contents of the entry.
+pub type ArchiveEntry<'d> = (String, Parser<'d, Cursor<Vec<u8>>>);
+
+/// The formats of archives which DICOM datasets can be read from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// A zip archive, with entries either stored or deflated.
+    Zip,
+
+    /// An uncompressed tar archive.
+    Tar,
+
+    /// A tar archive compressed with gzip.
+    TarGz,
+}
+
+impl ArchiveFormat {
+    /// Determines the archive format from the extension of the given path, one of `.zip`, `.tar`,
+    /// `.tar.gz`, or `.tgz`. Returns `None` if the path does not have one of these extensions.
+    #[must_use]
+    #[allow(clippy::case_sensitive_file_extension_comparisons)]
+    pub fn from_path(path: &Path) -> Option<ArchiveFormat> {
+        let name: String = path.file_name()?.to_string_lossy().to_ascii_lowercase();
+        if name.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveFormat::Tar)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else {
+            None
+        }
+    }
+
+    /// Determines the archive format from the leading bytes of an archive. Detecting a tar archive
+    /// requires at least the first 262 bytes. Returns `None` if the bytes are not the start of a
+    /// known archive format. A gzip-compressed file is assumed to be a tar archive.
+    #[must_use]
+    pub fn sniff(bytes: &[u8]) -> Option<ArchiveFormat> {
+        if bytes.starts_with(zip::LOCAL_FILE_HEADER_SIGNATURE)
+            || bytes.starts_with(zip::END_OF_CENTRAL_DIRECTORY_SIGNATURE)
+        {
+            Some(ArchiveFormat::Zip)
+        } else if bytes.starts_with(GZIP_MAGIC) {
+            Some(ArchiveFormat::TarGz)
+        } else if tar::is_ustar(bytes) {
+            Some(ArchiveFormat::Tar)
+        } else {
+            None
+        }
+    }
+}
+
+/// The magic bytes which begin gzip-compressed files.
+const GZIP_MAGIC: &[u8] = &[0x1F, 0x8B];
+
+/// The entries of an archive, in whichever format it is.
+enum Entries<R: Read + Seek> {
+    Zip(ZipEntries<R>),
+    Tar(TarEntries<R>),
+    TarGz(TarEntries<MultiDecoder<R>>),
+}
+
+impl<R: Read + Seek> Entries<R> {
+    fn next_entry(&mut self) -> Option<ParseResult<(String, Vec<u8>)>> {
+        match self {
+            Entries::Zip(entries) => entries.next_entry(),
+            Entries::Tar(entries) => entries.next_entry(),
+            Entries::TarGz(entries) => entries.next_entry(),
+        }
+    }
+}
+
+/// Iterates through the entries of an archive, producing an `ArchiveEntry` for each entry which is
+/// a DICOM dataset. Entries which are not DICOM datasets are skipped.
+///
+/// An entry is considered a DICOM dataset if it has a File Preamble followed by the DICOM prefix,
+/// or otherwise if its transfer syntax can be detected and its first element parsed.
+///
+/// Create with `ParserBuilder::build_archive()`.
+pub struct ArchiveWalker<'d, R: Read + Seek> {
+    entries: Entries<R>,
+    builder: ParserBuilder,
+    dictionary: &'d dyn DicomDictionary,
+}
+
+impl<'d, R: Read + Seek> ArchiveWalker<'d, R> {
+    pub(crate) fn new(
+        archive: R,
+        format: ArchiveFormat,
+        builder: ParserBuilder,
+        dictionary: &'d dyn DicomDictionary,
+    ) -> ParseResult<ArchiveWalker<'d, R>> {
+        let entries: Entries<R> = match format {
+            ArchiveFormat::Zip => Entries::Zip(ZipEntries::new(archive)?),
+            ArchiveFormat::Tar => Entries::Tar(TarEntries::new(archive)),
+            ArchiveFormat::TarGz => Entries::TarGz(TarEntries::new(MultiDecoder::new(archive)?)),
+        };
+        Ok(ArchiveWalker {
+            entries,
+            builder,
+            dictionary,
+        })
+    }
+
+    /// Whether the contents of an entry are a DICOM dataset, as parsed with the walker's builder.
+    fn is_dicom(&self, data: &[u8]) -> bool {
+        let prefix_end: usize = FILE_PREAMBLE_LENGTH + DICOM_PREFIX_LENGTH;
+        if data.len() >= prefix_end && data[FILE_PREAMBLE_LENGTH..prefix_end] == DICOM_PREFIX[..] {
+            return true;
+        }
+        matches!(
+            self.builder.build(data, self.dictionary).next(),
+            Some(Ok(_))
+        )
+    }
+}
+
+impl<'d, R: Read + Seek> Iterator for ArchiveWalker<'d, R> {
+    type Item = ParseResult<ArchiveEntry<'d>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (path, data) = match self.entries.next_entry()? {
+                Ok(entry) => entry,
+                Err(e) => return Some(Err(e)),
+            };
+            if self.is_dicom(&data) {
+                let parser = self.builder.build(Cursor::new(data), self.dictionary);
+                return Some(Ok((path, parser)));
+            }
+        }
+    }
+}
@@ -0,0 +1,191 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Reading file entries sequentially from tar archives. Supports the ustar format along with GNU
+//! long names and pax extended headers for paths.
+
+use std::io::{self, ErrorKind, Read};
+
+use crate::core::read::{ParseError, ParseResult};
+
+const BLOCK_LENGTH: usize = 512;
+
+const TYPE_REGULAR: u8 = b'0';
+const TYPE_REGULAR_OLD: u8 = b'\0';
+const TYPE_CONTIGUOUS: u8 = b'7';
+const TYPE_GNU_LONG_NAME: u8 = b'L';
+const TYPE_PAX_HEADER: u8 = b'x';
+
+/// The magic value of ustar headers, at offset 257.
+const USTAR_MAGIC: &[u8] = b"ustar";
+const USTAR_MAGIC_OFFSET: usize = 257;
+
+/// Whether the bytes begin with a ustar header.
+pub(super) fn is_ustar(bytes: &[u8]) -> bool {
+    bytes
+        .get(USTAR_MAGIC_OFFSET..USTAR_MAGIC_OFFSET + USTAR_MAGIC.len())
+        .is_some_and(|magic| magic == USTAR_MAGIC)
+}
+
+/// Reads the file entries of a tar archive.
+pub(super) struct TarEntries<R: Read> {
+    archive: R,
+    finished: bool,
+}
+
+impl<R: Read> TarEntries<R> {
+    pub(super) fn new(archive: R) -> TarEntries<R> {
+        TarEntries {
+            archive,
+            finished: false,
+        }
+    }
+
+    /// Reads the next regular file entry, skipping other entry types. As entries must be read
+    /// sequentially, any error ends iteration.
+    pub(super) fn next_entry(&mut self) -> Option<ParseResult<(String, Vec<u8>)>> {
+        if self.finished {
+            return None;
+        }
+        let result = self.read_entry().transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.finished = true;
+        }
+        result
+    }
+
+    fn read_entry(&mut self) -> ParseResult<Option<(String, Vec<u8>)>> {
+        let mut long_name: Option<String> = None;
+        loop {
+            let Some(header) = self.read_header()? else {
+                return Ok(None);
+            };
+            let size = parse_numeric(&header[124..136])?;
+            let mut data: Vec<u8> = Vec::new();
+            (&mut self.archive).take(size).read_to_end(&mut data)?;
+            if data.len() as u64 != size {
+                return Err(io::Error::from(ErrorKind::UnexpectedEof).into());
+            }
+            let padding = (BLOCK_LENGTH as u64 - size % BLOCK_LENGTH as u64) % BLOCK_LENGTH as u64;
+            io::copy(&mut (&mut self.archive).take(padding), &mut io::sink())?;
+
+            match header[156] {
+                TYPE_GNU_LONG_NAME => long_name = Some(trim_nul(&data)),
+                TYPE_PAX_HEADER => {
+                    if let Some(path) = pax_path(&data) {
+                        long_name = Some(path);
+                    }
+                }
+                TYPE_REGULAR | TYPE_REGULAR_OLD | TYPE_CONTIGUOUS => {
+                    let name = long_name.unwrap_or_else(|| header_name(&header));
+                    return Ok(Some((name, data)));
+                }
+                // Directories, links, global pax headers, etc.
+                _ => long_name = None,
+            }
+        }
+    }
+
+    /// Reads the next header block, returning `None` at the end of the archive.
+    fn read_header(&mut self) -> ParseResult<Option<[u8; BLOCK_LENGTH]>> {
+        let mut header = [0u8; BLOCK_LENGTH];
+        let mut read: usize = 0;
+        while read < BLOCK_LENGTH {
+            match self.archive.read(&mut header[read..]) {
+                Ok(0) if read == 0 => return Ok(None),
+                Ok(0) => return Err(io::Error::from(ErrorKind::UnexpectedEof).into()),
+                Ok(n) => read += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        if header.iter().all(|b| *b == 0) {
+            return Ok(None);
+        }
+
+        let checksum = parse_numeric(&header[148..156])?;
+        let actual: u64 = header
+            .iter()
+            .enumerate()
+            .map(|(i, b)| {
+                if (148..156).contains(&i) {
+                    u64::from(b' ')
+                } else {
+                    u64::from(*b)
+                }
+            })
+            .sum();
+        if checksum != actual {
+            return Err(ParseError::InvalidArchive(
+                "tar header checksum mismatch".to_string(),
+            ));
+        }
+        Ok(Some(header))
+    }
+}
+
+/// The path of the entry from its header, joining the ustar prefix if present.
+fn header_name(header: &[u8]) -> String {
+    let name = trim_nul(&header[0..100]);
+    if is_ustar(header) {
+        let prefix = trim_nul(&header[345..500]);
+        if !prefix.is_empty() {
+            return format!("{prefix}/{name}");
+        }
+    }
+    name
+}
+
+/// Finds the `path` record within pax extended header data. Records are formatted as
+/// `"<length> <key>=<value>\n"`.
+fn pax_path(data: &[u8]) -> Option<String> {
+    let mut rest = data;
+    while !rest.is_empty() {
+        let space = rest.iter().position(|b| *b == b' ')?;
+        let len: usize = std::str::from_utf8(&rest[..space]).ok()?.parse().ok()?;
+        let record = rest.get(space + 1..len)?;
+        let record = record.strip_suffix(b"\n").unwrap_or(record);
+        if let Some(path) = record.strip_prefix(b"path=") {
+            return Some(String::from_utf8_lossy(path).into_owned());
+        }
+        rest = &rest[len..];
+    }
+    None
+}
+
+/// Parses a numeric header field, which is either octal text or, if the high bit of the first byte
+/// is set, a big-endian base-256 value.
+fn parse_numeric(field: &[u8]) -> ParseResult<u64> {
+    if field[0] & 0x80 != 0 {
+        return Ok(field[1..]
+            .iter()
+            .fold(u64::from(field[0] & 0x7F), |acc, b| {
+                (acc << 8) | u64::from(*b)
+            }));
+    }
+    let text = String::from_utf8_lossy(field);
+    let text = text.trim_matches(|c: char| c == '\0' || c == ' ');
+    if text.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(text, 8)
+        .map_err(|_| ParseError::InvalidArchive(format!("invalid tar header number: {text}")))
+}
+
+fn trim_nul(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
@@ -0,0 +1,254 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Reading file entries from zip archives, using the archive's central directory. Entries may be
+//! stored or deflated, and zip64 archives are supported.
+
+use std::{
+    io::{Read, Seek, SeekFrom},
+    vec::IntoIter,
+};
+
+use libflate::deflate::Decoder;
+
+use crate::core::read::{ParseError, ParseResult};
+
+pub(super) const LOCAL_FILE_HEADER_SIGNATURE: &[u8] = b"PK\x03\x04";
+pub(super) const END_OF_CENTRAL_DIRECTORY_SIGNATURE: &[u8] = b"PK\x05\x06";
+const CENTRAL_DIRECTORY_SIGNATURE: &[u8] = b"PK\x01\x02";
+const ZIP64_LOCATOR_SIGNATURE: &[u8] = b"PK\x06\x07";
+const ZIP64_END_OF_CENTRAL_DIRECTORY_SIGNATURE: &[u8] = b"PK\x06\x06";
+
+/// Length of the end of central directory record, excluding its trailing comment.
+const END_OF_CENTRAL_DIRECTORY_LENGTH: usize = 22;
+/// The maximum length of the comment trailing the end of central directory record.
+const MAX_COMMENT_LENGTH: usize = 0xFFFF;
+const ZIP64_LOCATOR_LENGTH: usize = 20;
+const ZIP64_END_OF_CENTRAL_DIRECTORY_LENGTH: usize = 56;
+const CENTRAL_DIRECTORY_ENTRY_LENGTH: usize = 46;
+const LOCAL_FILE_HEADER_LENGTH: usize = 30;
+
+/// Header ID of the extra field holding 64-bit sizes and offsets.
+const ZIP64_EXTRA_FIELD_ID: u16 = 0x0001;
+
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATED: u16 = 8;
+const FLAG_ENCRYPTED: u16 = 0x0001;
+
+/// An entry from the central directory.
+struct CentralEntry {
+    name: String,
+    method: u16,
+    flags: u16,
+    compressed_size: u64,
+    local_header_offset: u64,
+}
+
+/// Reads the file entries of a zip archive.
+pub(super) struct ZipEntries<R: Read + Seek> {
+    archive: R,
+    entries: IntoIter<CentralEntry>,
+}
+
+impl<R: Read + Seek> ZipEntries<R> {
+    /// Reads the central directory of the archive.
+    ///
+    /// # Errors
+    /// I/O errors reading the archive, or if the central directory is not able to be located.
+    pub(super) fn new(mut archive: R) -> ParseResult<ZipEntries<R>> {
+        let entries = read_central_directory(&mut archive)?;
+        Ok(ZipEntries {
+            archive,
+            entries: entries.into_iter(),
+        })
+    }
+
+    /// Reads the next file entry, skipping directories. Errors are for an individual entry and
+    /// iteration may continue afterwards.
+    pub(super) fn next_entry(&mut self) -> Option<ParseResult<(String, Vec<u8>)>> {
+        let entry = self.entries.find(|entry| !entry.name.ends_with('/'))?;
+        Some(self.read_entry(&entry).map(|data| (entry.name, data)))
+    }
+
+    fn read_entry(&mut self, entry: &CentralEntry) -> ParseResult<Vec<u8>> {
+        if entry.flags & FLAG_ENCRYPTED != 0 {
+            return Err(ParseError::InvalidArchive(format!(
+                "entry is encrypted: {}",
+                entry.name
+            )));
+        }
+
+        self.archive
+            .seek(SeekFrom::Start(entry.local_header_offset))?;
+        let mut header = [0u8; LOCAL_FILE_HEADER_LENGTH];
+        self.archive.read_exact(&mut header)?;
+        if !header.starts_with(LOCAL_FILE_HEADER_SIGNATURE) {
+            return Err(ParseError::InvalidArchive(format!(
+                "missing local file header: {}",
+                entry.name
+            )));
+        }
+        let name_len = u16_at(&header, 26);
+        let extra_len = u16_at(&header, 28);
+        self.archive.seek(SeekFrom::Current(
+            i64::from(name_len) + i64::from(extra_len),
+        ))?;
+
+        let mut data: Vec<u8> = Vec::new();
+        let mut compressed = (&mut self.archive).take(entry.compressed_size);
+        match entry.method {
+            METHOD_STORED => {
+                compressed.read_to_end(&mut data)?;
+            }
+            METHOD_DEFLATED => {
+                Decoder::new(compressed).read_to_end(&mut data)?;
+            }
+            method => {
+                return Err(ParseError::InvalidArchive(format!(
+                    "unsupported compression method {method}: {}",
+                    entry.name
+                )));
+            }
+        }
+        Ok(data)
+    }
+}
+
+/// Locates and reads all entries of the central directory.
+fn read_central_directory<R: Read + Seek>(archive: &mut R) -> ParseResult<Vec<CentralEntry>> {
+    let archive_len = archive.seek(SeekFrom::End(0))?;
+    let tail_len = archive_len.min((END_OF_CENTRAL_DIRECTORY_LENGTH + MAX_COMMENT_LENGTH) as u64);
+    let tail_start = archive_len - tail_len;
+    archive.seek(SeekFrom::Start(tail_start))?;
+    let mut tail: Vec<u8> = Vec::new();
+    archive.read_to_end(&mut tail)?;
+
+    let eocd_pos = tail
+        .windows(END_OF_CENTRAL_DIRECTORY_SIGNATURE.len())
+        .rposition(|window| window == END_OF_CENTRAL_DIRECTORY_SIGNATURE)
+        .filter(|pos| pos + END_OF_CENTRAL_DIRECTORY_LENGTH <= tail.len())
+        .ok_or_else(|| {
+            ParseError::InvalidArchive("end of central directory not found".to_string())
+        })?;
+    let eocd = &tail[eocd_pos..];
+
+    let mut num_entries = u64::from(u16_at(eocd, 10));
+    let mut cd_size = u64::from(u32_at(eocd, 12));
+    let mut cd_offset = u64::from(u32_at(eocd, 16));
+    if num_entries == 0xFFFF || cd_size == 0xFFFF_FFFF || cd_offset == 0xFFFF_FFFF {
+        let locator_pos = eocd_pos
+            .checked_sub(ZIP64_LOCATOR_LENGTH)
+            .filter(|pos| tail[*pos..].starts_with(ZIP64_LOCATOR_SIGNATURE))
+            .ok_or_else(|| {
+                ParseError::InvalidArchive("zip64 end of central directory not found".to_string())
+            })?;
+        let eocd64_offset = u64_at(&tail[locator_pos..], 8);
+        archive.seek(SeekFrom::Start(eocd64_offset))?;
+        let mut eocd64 = [0u8; ZIP64_END_OF_CENTRAL_DIRECTORY_LENGTH];
+        archive.read_exact(&mut eocd64)?;
+        if !eocd64.starts_with(ZIP64_END_OF_CENTRAL_DIRECTORY_SIGNATURE) {
+            return Err(ParseError::InvalidArchive(
+                "invalid zip64 end of central directory".to_string(),
+            ));
+        }
+        num_entries = u64_at(&eocd64, 32);
+        cd_size = u64_at(&eocd64, 40);
+        cd_offset = u64_at(&eocd64, 48);
+    }
+
+    archive.seek(SeekFrom::Start(cd_offset))?;
+    let mut cd: Vec<u8> = Vec::new();
+    archive.take(cd_size).read_to_end(&mut cd)?;
+
+    let mut entries: Vec<CentralEntry> = Vec::new();
+    let mut pos: usize = 0;
+    while (entries.len() as u64) < num_entries {
+        let record = cd
+            .get(pos..pos + CENTRAL_DIRECTORY_ENTRY_LENGTH)
+            .filter(|record| record.starts_with(CENTRAL_DIRECTORY_SIGNATURE))
+            .ok_or_else(|| ParseError::InvalidArchive("invalid central directory".to_string()))?;
+        let name_len = usize::from(u16_at(record, 28));
+        let extra_len = usize::from(u16_at(record, 30));
+        let comment_len = usize::from(u16_at(record, 32));
+        let name_start = pos + CENTRAL_DIRECTORY_ENTRY_LENGTH;
+        let extra_start = name_start + name_len;
+        let extra_end = extra_start + extra_len;
+        let (Some(name), Some(extra)) = (
+            cd.get(name_start..extra_start),
+            cd.get(extra_start..extra_end),
+        ) else {
+            return Err(ParseError::InvalidArchive(
+                "invalid central directory".to_string(),
+            ));
+        };
+
+        let mut entry = CentralEntry {
+            name: String::from_utf8_lossy(name).into_owned(),
+            method: u16_at(record, 10),
+            flags: u16_at(record, 8),
+            compressed_size: u64::from(u32_at(record, 20)),
+            local_header_offset: u64::from(u32_at(record, 42)),
+        };
+        apply_zip64_extra(&mut entry, u32_at(record, 24) == 0xFFFF_FFFF, extra);
+        entries.push(entry);
+        pos = extra_end + comment_len;
+    }
+    Ok(entries)
+}
+
+/// Replaces the sizes and offset of the entry which did not fit in 32 bits with those from the
+/// zip64 extra field, if present. The fields within the zip64 extra field are only present for the
+/// values which overflowed, in a fixed order.
+fn apply_zip64_extra(entry: &mut CentralEntry, uncompressed_overflow: bool, extra: &[u8]) {
+    let mut pos: usize = 0;
+    while pos + 4 <= extra.len() {
+        let id = u16_at(extra, pos);
+        let len = usize::from(u16_at(extra, pos + 2));
+        let Some(mut field) = extra.get(pos + 4..pos + 4 + len) else {
+            return;
+        };
+        if id == ZIP64_EXTRA_FIELD_ID {
+            if uncompressed_overflow && field.len() >= 8 {
+                field = &field[8..];
+            }
+            if entry.compressed_size == 0xFFFF_FFFF && field.len() >= 8 {
+                entry.compressed_size = u64_at(field, 0);
+                field = &field[8..];
+            }
+            if entry.local_header_offset == 0xFFFF_FFFF && field.len() >= 8 {
+                entry.local_header_offset = u64_at(field, 0);
+            }
+            return;
+        }
+        pos += 4 + len;
+    }
+}
+
+fn u16_at(bytes: &[u8], pos: usize) -> u16 {
+    u16::from_le_bytes([bytes[pos], bytes[pos + 1]])
+}
+
+fn u32_at(bytes: &[u8], pos: usize) -> u32 {
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(&bytes[pos..pos + 4]);
+    u32::from_le_bytes(buf)
+}
+
+fn u64_at(bytes: &[u8], pos: usize) -> u64 {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&bytes[pos..pos + 8]);
+    u64::from_le_bytes(buf)
+}
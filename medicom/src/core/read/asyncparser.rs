@@ -0,0 +1,374 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Parsing of datasets from asynchronous sources.
+//!
+//! `AsyncParser` produces the same `DicomElement`s as `Parser`, as a `Stream`, over a dataset
+//! implementing `futures_io::AsyncRead`. With the `tokio` feature enabled, `TokioReader` adapts a
+//! `tokio::io::AsyncRead` for use with `AsyncParser`.
+//!
+//! Bytes are read from the source into a buffer which the `Parser` reads from. If the parser runs
+//! out of buffered bytes partway through an element, it's rewound to the start of the element and
+//! the element is parsed again once more bytes are available. The buffer only holds the bytes of
+//! the element currently being parsed.
+
+use std::{
+    io::{self, ErrorKind, Read},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+use futures_io::AsyncRead;
+
+use crate::core::{
+    charset::CSRef,
+    dcmelement::DicomElement,
+    dcmsqelem::SequenceElement,
+    defn::{
+        ts::{TSRef, TransferSyntax},
+        vl::ValueLength,
+        vr::VRRef,
+    },
+    read::{ds::dataset::Dataset, ParseError, ParseResult, Parser, ParserState},
+    DICOM_PREFIX_LENGTH, FILE_PREAMBLE_LENGTH,
+};
+
+/// The minimum number of bytes to request from the source with each read.
+const READ_CHUNK_SIZE: usize = 8 * 1024;
+
+/// The maximum number of bytes to request from the source with each read.
+const MAX_READ_SIZE: usize = 1024 * 1024;
+
+/// The number of bytes the buffer needs to hold before the first attempt at parsing. Detecting the
+/// transfer syntax may read through the File Preamble and DICOM prefix.
+const INITIAL_FILL: usize = FILE_PREAMBLE_LENGTH + DICOM_PREFIX_LENGTH + 64;
+
+/// The bytes read from the asynchronous source which have not yet been parsed. This is the dataset
+/// read by the `Parser` wrapped by `AsyncParser`.
+///
+/// Reading beyond the buffered bytes results in an error of `ErrorKind::WouldBlock`, unless the
+/// source has ended, in which case it reads as the end of the dataset.
+#[derive(Debug, Default)]
+pub struct AsyncBuffer {
+    data: Vec<u8>,
+    pos: usize,
+    source_ended: bool,
+}
+
+impl AsyncBuffer {
+    /// The number of bytes buffered which have not been read.
+    #[must_use]
+    pub fn available(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    /// Whether the source has ended, and all remaining bytes of the dataset are buffered.
+    #[must_use]
+    pub fn is_source_ended(&self) -> bool {
+        self.source_ended
+    }
+
+    /// Drops the bytes which have already been read.
+    fn compact(&mut self) {
+        if self.pos > 0 {
+            self.data.drain(..self.pos);
+            self.pos = 0;
+        }
+    }
+}
+
+impl Read for AsyncBuffer {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let available = &self.data[self.pos..];
+        if available.is_empty() {
+            return if self.source_ended {
+                Ok(0)
+            } else {
+                Err(ErrorKind::WouldBlock.into())
+            };
+        }
+        let len = buf.len().min(available.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+/// The state of the `Parser` prior to parsing an element, which is restored if the parser runs out
+/// of buffered bytes before the element is fully parsed.
+struct Checkpoint {
+    pos: usize,
+    pushback: Vec<u8>,
+    diagnostics: usize,
+    state: ParserState,
+    bytes_read: u64,
+    file_preamble: Option<[u8; FILE_PREAMBLE_LENGTH]>,
+    dicom_prefix: Option<[u8; DICOM_PREFIX_LENGTH]>,
+    fmi_start: u64,
+    fmi_grouplength: u32,
+    tag_last_read: u32,
+    vr_last_used: Option<VRRef>,
+    ts_last_used: Option<TSRef>,
+    partial_tag: Option<u32>,
+    partial_vr: Option<VRRef>,
+    partial_vl: Option<ValueLength>,
+    detected_ts: TSRef,
+    dataset_ts: Option<TSRef>,
+    declared_ts: Option<TSRef>,
+    cs: CSRef,
+    current_path: Vec<SequenceElement>,
+    iterator_ended: bool,
+    element_last_excluded: bool,
+}
+
+impl Checkpoint {
+    fn of(parser: &mut Parser<'_, AsyncBuffer>) -> Checkpoint {
+        Checkpoint {
+            pos: parser.dataset.get_mut().pos,
+            pushback: parser.dataset.pushback().to_vec(),
+            diagnostics: parser.diagnostics.len(),
+            state: parser.state,
+            bytes_read: parser.bytes_read,
+            file_preamble: parser.file_preamble,
+            dicom_prefix: parser.dicom_prefix,
+            fmi_start: parser.fmi_start,
+            fmi_grouplength: parser.fmi_grouplength,
+            tag_last_read: parser.tag_last_read,
+            vr_last_used: parser.vr_last_used,
+            ts_last_used: parser.ts_last_used,
+            partial_tag: parser.partial_tag,
+            partial_vr: parser.partial_vr,
+            partial_vl: parser.partial_vl,
+            detected_ts: parser.detected_ts,
+            dataset_ts: parser.dataset_ts,
+            declared_ts: parser.declared_ts,
+            cs: parser.cs,
+            current_path: parser.current_path.clone(),
+            iterator_ended: parser.iterator_ended,
+            element_last_excluded: parser.element_last_excluded,
+        }
+    }
+
+    fn restore(self, parser: &mut Parser<'_, AsyncBuffer>) {
+        // The decoder for deflated datasets can't be rewound. Detecting the transfer syntax may
+        // have used the decoder to sniff for a deflated dataset, so start over with a new one.
+        if self.state == ParserState::DetectTransferSyntax {
+            let buffer: AsyncBuffer = std::mem::take(parser.dataset.get_mut());
+            parser.dataset = Dataset::new(buffer);
+        }
+        parser.dataset.get_mut().pos = self.pos;
+        parser.dataset.set_pushback(self.pushback);
+        parser.diagnostics.truncate(self.diagnostics);
+        parser.state = self.state;
+        parser.bytes_read = self.bytes_read;
+        parser.file_preamble = self.file_preamble;
+        parser.dicom_prefix = self.dicom_prefix;
+        parser.fmi_start = self.fmi_start;
+        parser.fmi_grouplength = self.fmi_grouplength;
+        parser.tag_last_read = self.tag_last_read;
+        parser.vr_last_used = self.vr_last_used;
+        parser.vl_last_used = None;
+        parser.ts_last_used = self.ts_last_used;
+        parser.partial_tag = self.partial_tag;
+        parser.partial_vr = self.partial_vr;
+        parser.partial_vl = self.partial_vl;
+        parser.detected_ts = self.detected_ts;
+        parser.dataset_ts = self.dataset_ts;
+        parser.declared_ts = self.declared_ts;
+        parser.cs = self.cs;
+        parser.current_path = self.current_path;
+        parser.iterator_ended = self.iterator_ended;
+        parser.element_last_excluded = self.element_last_excluded;
+    }
+}
+
+/// Parses a dataset from an asynchronous source, producing the same `DicomElement`s as `Parser`
+/// as a `Stream`. Create with `ParserBuilder::build_async()`.
+///
+/// Datasets encoded with a deflated transfer syntax are supported, however once the deflated
+/// portion of the dataset is reached the remainder of the source is read into memory before
+/// parsing continues.
+#[derive(Debug)]
+pub struct AsyncParser<'d, R: AsyncRead + Unpin> {
+    source: R,
+    parser: Parser<'d, AsyncBuffer>,
+
+    /// The number of unread bytes which should be buffered before attempting to parse the next
+    /// element.
+    wanted: usize,
+}
+
+impl<'d, R: AsyncRead + Unpin> AsyncParser<'d, R> {
+    pub(crate) fn new(source: R, parser: Parser<'d, AsyncBuffer>) -> AsyncParser<'d, R> {
+        AsyncParser {
+            source,
+            parser,
+            wanted: INITIAL_FILL,
+        }
+    }
+
+    /// Get the `Parser` which parses the buffered bytes, for inspecting its state such as
+    /// `Parser::ts()` or `Parser::bytes_read()`.
+    #[must_use]
+    pub fn parser(&self) -> &Parser<'d, AsyncBuffer> {
+        &self.parser
+    }
+
+    /// Consumes this parser, returning the asynchronous source.
+    #[must_use]
+    pub fn into_inner(self) -> R {
+        self.source
+    }
+
+    fn buffer(&mut self) -> &mut AsyncBuffer {
+        self.parser.dataset.get_mut()
+    }
+
+    /// Whether the dataset is deflated from the current position onwards. The decoder for deflated
+    /// datasets can't be rewound, so the entire source is buffered before parsing it.
+    fn is_deflated(&self) -> bool {
+        self.parser.state == ParserState::ReadElement
+            && self.parser.dataset_ts.is_some_and(TransferSyntax::deflated)
+    }
+
+    /// Reads from the source into the buffer until it holds `self.wanted` unread bytes, or the
+    /// source ends.
+    fn poll_fill(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        loop {
+            let buffer = self.parser.dataset.get_mut();
+            let available = buffer.available();
+            if buffer.source_ended || available >= self.wanted {
+                return Poll::Ready(Ok(()));
+            }
+
+            let read_size = (self.wanted - available).clamp(READ_CHUNK_SIZE, MAX_READ_SIZE);
+            let start = buffer.data.len();
+            buffer.data.resize(start + read_size, 0);
+            let result = Pin::new(&mut self.source).poll_read(cx, &mut buffer.data[start..]);
+            match result {
+                Poll::Ready(Ok(read)) => {
+                    buffer.data.truncate(start + read);
+                    buffer.source_ended = read == 0;
+                }
+                Poll::Ready(Err(e)) if e.kind() == ErrorKind::Interrupted => {
+                    buffer.data.truncate(start);
+                }
+                Poll::Ready(Err(e)) => {
+                    buffer.data.truncate(start);
+                    return Poll::Ready(Err(e));
+                }
+                Poll::Pending => {
+                    buffer.data.truncate(start);
+                    return Poll::Pending;
+                }
+            }
+        }
+    }
+
+    /// The number of unread bytes needed to parse the element which just failed from running out
+    /// of buffered bytes. If the failure was while reading a value then the value length indicates
+    /// the number of bytes needed, otherwise more bytes are requested.
+    fn wanted_after(&self, checkpoint: &Checkpoint, available: usize) -> usize {
+        let mut wanted = available + READ_CHUNK_SIZE;
+        if let Some(ValueLength::Explicit(len)) = self.parser.vl_last_used {
+            let header = self.parser.bytes_read.saturating_sub(checkpoint.bytes_read);
+            let needed = usize::try_from(header + u64::from(len)).unwrap_or(usize::MAX);
+            wanted = wanted.max(needed);
+        }
+        wanted
+    }
+
+    /// Whether the error resulted from the parser reading beyond the buffered bytes.
+    fn is_out_of_bytes(error: &ParseError) -> bool {
+        match error {
+            ParseError::DetailedError { source, .. } => AsyncParser::<R>::is_out_of_bytes(source),
+            ParseError::IOError { source } => source.kind() == ErrorKind::WouldBlock,
+            _ => false,
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> Stream for AsyncParser<'_, R> {
+    type Item = ParseResult<DicomElement>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if this.parser.iterator_ended {
+                return Poll::Ready(None);
+            }
+            if this.is_deflated() {
+                this.wanted = usize::MAX;
+            }
+            match this.poll_fill(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Err(e)) => {
+                    this.parser.iterator_ended = true;
+                    let detail = this.parser.current_debug_str();
+                    return Poll::Ready(Some(Err(ParseError::DetailedError {
+                        source: Box::new(ParseError::IOError { source: e }),
+                        detail,
+                    })));
+                }
+                Poll::Ready(Ok(())) => {}
+            }
+
+            let available = this.buffer().available();
+            let checkpoint = Checkpoint::of(&mut this.parser);
+            this.parser.vl_last_used = None;
+            match this.parser.next() {
+                Some(Err(e))
+                    if !this.buffer().is_source_ended()
+                        && AsyncParser::<R>::is_out_of_bytes(&e) =>
+                {
+                    this.wanted = this.wanted_after(&checkpoint, available);
+                    checkpoint.restore(&mut this.parser);
+                }
+                result => {
+                    this.buffer().compact();
+                    this.wanted = 1;
+                    return Poll::Ready(result);
+                }
+            }
+        }
+    }
+}
+
+/// Adapts a `tokio::io::AsyncRead` to `futures_io::AsyncRead`, for use with `AsyncParser`. See
+/// `ParserBuilder::build_tokio()`.
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct TokioReader<R: tokio::io::AsyncRead + Unpin>(pub R);
+
+#[cfg(feature = "tokio")]
+impl<R: tokio::io::AsyncRead + Unpin> AsyncRead for TokioReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let mut read_buf = tokio::io::ReadBuf::new(buf);
+        match Pin::new(&mut self.get_mut().0).poll_read(cx, &mut read_buf) {
+            Poll::Ready(Ok(())) => Poll::Ready(Ok(read_buf.filled().len())),
+            Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
@@ -16,7 +16,9 @@
 
 //! Specify behavior while parsing.
 
-use crate::core::read::stop::ParseStop;
+use crate::core::read::{
+    deferred::DeferValues, filter::TagFilter, recovery::RecoveryPolicy, stop::ParseStop,
+};
 
 #[derive(Clone, Debug)]
 pub struct ParseBehavior {
@@ -34,6 +36,32 @@ pub struct ParseBehavior {
     ///
     /// Default: `false`.
     allow_partial_object: bool,
+
+    /// Specifies which element values should not be read during parsing, and instead be loaded on
+    /// demand from the dataset. This only has an effect for parsers created with
+    /// `ParserBuilder::build_seekable()`.
+    ///
+    /// Refer to documentation on `DeferValues`.
+    ///
+    /// Default: `DeferValues::Never`.
+    defer_values: DeferValues,
+
+    /// Specifies which elements the parser should produce. Values of excluded elements are
+    /// skipped over in the dataset without being read.
+    ///
+    /// Refer to documentation on `TagFilter`.
+    ///
+    /// Default: `TagFilter::All`.
+    tag_filter: TagFilter,
+
+    /// Specifies which repairs the parser may make to malformed datasets in order to continue
+    /// parsing. Repairs made are recorded and available from `Parser::diagnostics()`. Unlike
+    /// `allow_partial_object`, this allows the remainder of the dataset to be parsed.
+    ///
+    /// Refer to documentation on `RecoveryPolicy`.
+    ///
+    /// Default: `RecoveryPolicy::strict()`.
+    recovery: RecoveryPolicy,
 }
 
 impl ParseBehavior {
@@ -58,6 +86,41 @@ impl ParseBehavior {
         self.allow_partial_object
     }
 
+    /// Specifies which element values should not be read during parsing, and instead be loaded on
+    /// demand from the dataset. This only has an effect for parsers created with
+    /// `ParserBuilder::build_seekable()`.
+    ///
+    /// Refer to documentation on `DeferValues`.
+    ///
+    /// Default: `DeferValues::Never`.
+    #[must_use]
+    pub fn defer_values(&self) -> &DeferValues {
+        &self.defer_values
+    }
+
+    /// Specifies which elements the parser should produce. Values of excluded elements are
+    /// skipped over in the dataset without being read.
+    ///
+    /// Refer to documentation on `TagFilter`.
+    ///
+    /// Default: `TagFilter::All`.
+    #[must_use]
+    pub fn tag_filter(&self) -> &TagFilter {
+        &self.tag_filter
+    }
+
+    /// Specifies which repairs the parser may make to malformed datasets in order to continue
+    /// parsing. Repairs made are recorded and available from `Parser::diagnostics()`. Unlike
+    /// `allow_partial_object`, this allows the remainder of the dataset to be parsed.
+    ///
+    /// Refer to documentation on `RecoveryPolicy`.
+    ///
+    /// Default: `RecoveryPolicy::strict()`.
+    #[must_use]
+    pub fn recovery(&self) -> &RecoveryPolicy {
+        &self.recovery
+    }
+
     /// Specify when the parser should stop parsing the dataset.
     ///
     /// Refer to documentation on `ParseStop`.
@@ -72,6 +135,28 @@ impl ParseBehavior {
     pub fn set_allow_partial_object(&mut self, allow_partial_object: bool) {
         self.allow_partial_object = allow_partial_object;
     }
+
+    /// Specify which element values should not be read during parsing, and instead be loaded on
+    /// demand from the dataset.
+    ///
+    /// Refer to documentation on `DeferValues`.
+    pub fn set_defer_values(&mut self, defer_values: DeferValues) {
+        self.defer_values = defer_values;
+    }
+
+    /// Specify which elements the parser should produce.
+    ///
+    /// Refer to documentation on `TagFilter`.
+    pub fn set_tag_filter(&mut self, tag_filter: TagFilter) {
+        self.tag_filter = tag_filter;
+    }
+
+    /// Specify which repairs the parser may make to malformed datasets.
+    ///
+    /// Refer to documentation on `RecoveryPolicy`.
+    pub fn set_recovery(&mut self, recovery: RecoveryPolicy) {
+        self.recovery = recovery;
+    }
 }
 
 impl Default for ParseBehavior {
@@ -79,6 +164,9 @@ impl Default for ParseBehavior {
         Self {
             stop: ParseStop::EndOfDataset,
             allow_partial_object: false,
+            defer_values: DeferValues::Never,
+            tag_filter: TagFilter::All,
+            recovery: RecoveryPolicy::strict(),
         }
     }
 }
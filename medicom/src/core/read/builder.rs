@@ -16,21 +16,39 @@
 
 //! Configurable builder to create a DICOM parser.
 
-use std::io::Read;
+use std::{
+    io::{Read, Seek},
+    sync::Arc,
+};
 
 use crate::core::{
     charset::CSRef,
     defn::{constants::ts::ExplicitVRLittleEndian, dcmdict::DicomDictionary, ts::TSRef},
     read::{
         behavior::ParseBehavior,
+        deferred::{DeferValues, SeekableDataset},
         ds::dataset::Dataset,
+        filter::TagFilter,
+        parallel::ParallelParser,
         parser::{Parser, ParserState},
+        recovery::RecoveryPolicy,
+        shared::{SharedBuffer, SharedDataset},
         stop::ParseStop,
     },
 };
 
+#[cfg(feature = "tokio")]
+use crate::core::read::asyncparser::TokioReader;
+#[cfg(feature = "async")]
+use crate::core::read::asyncparser::{AsyncBuffer, AsyncParser};
+#[cfg(feature = "archive")]
+use crate::core::read::{
+    archive::{ArchiveFormat, ArchiveWalker},
+    ParseResult,
+};
+
 /// A builder for constructing a `Parser`.
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct ParserBuilder {
     /// Initial parse state. Default is `ParseState::DetectTransferSyntax`.
     state: Option<ParserState>,
@@ -66,6 +84,30 @@ impl ParserBuilder {
         self
     }
 
+    /// Sets which element values should not be read during parsing, and instead be loaded on demand
+    /// from the dataset. This only has an effect for parsers created with `build_seekable()`.
+    #[must_use]
+    pub fn defer_values(mut self, defer_values: DeferValues) -> Self {
+        self.behavior.set_defer_values(defer_values);
+        self
+    }
+
+    /// Sets which elements the parser should produce. Values of excluded elements are skipped over
+    /// in the dataset without being read.
+    #[must_use]
+    pub fn tag_filter(mut self, tag_filter: TagFilter) -> Self {
+        self.behavior.set_tag_filter(tag_filter);
+        self
+    }
+
+    /// Sets which repairs the parser may make to malformed datasets in order to continue parsing.
+    /// Repairs made are recorded and available from `Parser::diagnostics()`.
+    #[must_use]
+    pub fn recovery(mut self, recovery: RecoveryPolicy) -> Self {
+        self.behavior.set_recovery(recovery);
+        self
+    }
+
     /// Sets the transfer syntax of the dataset, if known.
     #[must_use]
     pub fn dataset_ts(mut self, dataset_ts: TSRef) -> Self {
@@ -105,9 +147,116 @@ impl ParserBuilder {
             partial_vl: None,
             detected_ts: &ExplicitVRLittleEndian,
             dataset_ts: self.dataset_ts,
+            declared_ts: None,
             cs: CSRef::default(),
             current_path: Vec::new(),
             iterator_ended: false,
+            element_last_excluded: false,
+            stream_values: false,
+            pending_value: None,
+            value_source: None,
+            diagnostics: Vec::new(),
         }
     }
+
+    /// Constructs a `Parser` from this builder, over a dataset which supports seeking. Element
+    /// values selected by `defer_values()` are not read during parsing, but recorded as their
+    /// position within the dataset and loaded on demand, see `DicomElement::load_value()`.
+    ///
+    /// The dataset is shared between the parser and the elements it produces, and will remain open
+    /// until all of them are dropped.
+    ///
+    /// `dictionary` - The DICOM dictionary to use during parsing. See `build()`.
+    pub fn build_seekable<'d, R: Read + Seek + Send + 'static>(
+        &self,
+        dataset: R,
+        dictionary: &'d dyn DicomDictionary,
+    ) -> Parser<'d, SeekableDataset<R>> {
+        let dataset = SeekableDataset::new(dataset);
+        let value_source = dataset.value_source();
+        let mut parser = self.build(dataset, dictionary);
+        parser.value_source = Some(value_source);
+        parser
+    }
+
+    /// Constructs a `Parser` from this builder, over a dataset which is entirely in memory, such as
+    /// a memory-mapped file. The values of elements produced by the parser are views into the
+    /// buffer rather than copies, see `DicomElement::shared()`. Values which must be modified to
+    /// be represented, such as odd-length values which are padded, are still copied.
+    ///
+    /// The buffer is shared between the parser and the elements it produces, and will remain alive
+    /// until all of them are dropped.
+    ///
+    /// `dictionary` - The DICOM dictionary to use during parsing. See `build()`.
+    pub fn build_shared<'d, B: AsRef<[u8]> + Send + Sync + 'static>(
+        &self,
+        buffer: B,
+        dictionary: &'d dyn DicomDictionary,
+    ) -> Parser<'d, SharedDataset> {
+        let buffer: SharedBuffer = Arc::new(buffer);
+        let dataset = SharedDataset::new(buffer);
+        let value_source = dataset.value_source();
+        let mut parser = self.build(dataset, dictionary);
+        parser.value_source = Some(value_source);
+        parser
+    }
+
+    /// Constructs a `ParallelParser` from this builder, which parses many datasets in parallel
+    /// each into a `DicomRoot`. Each dataset is parsed the same as with a parser created with
+    /// `build()`.
+    ///
+    /// `dictionary` - The DICOM dictionary to use during parsing, shared by all worker threads.
+    /// See `build()`.
+    #[must_use]
+    pub fn build_parallel(
+        &self,
+        dictionary: &'static (dyn DicomDictionary + Sync),
+    ) -> ParallelParser {
+        ParallelParser::new(self.clone(), dictionary)
+    }
+
+    /// Constructs an `AsyncParser` from this builder, over a dataset read asynchronously. The
+    /// parser produces the same elements as one created with `build()`, as a `Stream`.
+    ///
+    /// `dictionary` - The DICOM dictionary to use during parsing. See `build()`.
+    #[cfg(feature = "async")]
+    pub fn build_async<'d, R: futures_io::AsyncRead + Unpin>(
+        &self,
+        dataset: R,
+        dictionary: &'d dyn DicomDictionary,
+    ) -> AsyncParser<'d, R> {
+        AsyncParser::new(dataset, self.build(AsyncBuffer::default(), dictionary))
+    }
+
+    /// Constructs an `AsyncParser` from this builder, over a dataset read with tokio. See
+    /// `build_async()`.
+    ///
+    /// `dictionary` - The DICOM dictionary to use during parsing. See `build()`.
+    #[cfg(feature = "tokio")]
+    pub fn build_tokio<'d, R: tokio::io::AsyncRead + Unpin>(
+        &self,
+        dataset: R,
+        dictionary: &'d dyn DicomDictionary,
+    ) -> AsyncParser<'d, TokioReader<R>> {
+        self.build_async(TokioReader(dataset), dictionary)
+    }
+
+    /// Constructs an `ArchiveWalker` from this builder, which produces a `Parser` for each DICOM
+    /// dataset within the given archive. Each parser is configured the same as one created with
+    /// `build()`.
+    ///
+    /// `dictionary` - The DICOM dictionary to use during parsing. See `build()`.
+    ///
+    /// # Errors
+    /// I/O errors reading the start of the archive, or if the archive is not a valid `format`
+    /// archive.
+    #[cfg(feature = "archive")]
+    pub fn build_archive<'d, R: Read + Seek>(
+        &self,
+        archive: R,
+        format: ArchiveFormat,
+        dictionary: &'d dyn DicomDictionary,
+    ) -> ParseResult<ArchiveWalker<'d, R>> {
+        ArchiveWalker::new(archive, format, self.clone(), dictionary)
+    }
 }
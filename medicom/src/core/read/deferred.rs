@@ -0,0 +1,285 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Deferred loading of element values from seekable datasets.
+//!
+//! When a `Parser` is built over a dataset which implements `Seek`, the values of large elements
+//! can be skipped over during parsing and recorded as a byte position and length within the
+//! dataset. The value is only read from the dataset when it's needed, via
+//! `DicomElement::load_value()` or `DicomElement::parse_value()`.
+
+use std::{
+    fmt,
+    io::{Error, Read, Result, Seek, SeekFrom},
+    sync::{Arc, Mutex, MutexGuard, OnceLock},
+};
+
+use crate::core::{
+    defn::{
+        constants::tags::{DOUBLE_PIXEL_DATA, FLOAT_PIXEL_DATA, PIXEL_DATA},
+        vr::{VRRef, OB, OF, OW, UN},
+    },
+    read::{shared::SharedBytes, ParseResult},
+};
+
+/// `DeferValues` specifies which element values should not be read during parsing, and instead
+/// recorded as a position within the dataset for loading later. This only has an effect when the
+/// parser is created with `ParserBuilder::build_seekable()`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum DeferValues {
+    /// All element values are read during parsing.
+    #[default]
+    Never,
+
+    /// Values whose length is greater than the given number of bytes are deferred.
+    AboveLength(u32),
+
+    /// Values of bulk data are deferred, regardless of their length. Bulk data are elements with a
+    /// VR of `OB`, `OW`, `OF`, or `UN`, the `PixelData` elements, and the fragments of
+    /// encapsulated `PixelData`.
+    BulkData,
+
+    /// Values of bulk data (see `BulkData`) and all other values whose length is greater than the
+    /// given number of bytes are deferred.
+    BulkDataOrAboveLength(u32),
+}
+
+impl DeferValues {
+    /// Evaluates whether the value of an element should be deferred.
+    ///
+    /// `tag` The tag of the element.
+    /// `vr` The VR of the element.
+    /// `length` The value length of the element, which should be explicit.
+    /// `in_pixeldata` Whether the element is a fragment within encapsulated `PixelData`.
+    #[must_use]
+    pub fn evaluate(&self, tag: u32, vr: VRRef, length: u32, in_pixeldata: bool) -> bool {
+        match self {
+            DeferValues::Never => false,
+            DeferValues::AboveLength(threshold) => length > *threshold,
+            DeferValues::BulkData => DeferValues::is_bulk_data(tag, vr, in_pixeldata),
+            DeferValues::BulkDataOrAboveLength(threshold) => {
+                length > *threshold || DeferValues::is_bulk_data(tag, vr, in_pixeldata)
+            }
+        }
+    }
+
+    fn is_bulk_data(tag: u32, vr: VRRef, in_pixeldata: bool) -> bool {
+        in_pixeldata
+            || tag == PIXEL_DATA
+            || tag == FLOAT_PIXEL_DATA
+            || tag == DOUBLE_PIXEL_DATA
+            || vr == &OB
+            || vr == &OW
+            || vr == &OF
+            || vr == &UN
+    }
+}
+
+/// A source of element values which can be read from any position. This is shared between the
+/// `Parser` and all elements it produces with deferred values.
+pub trait ValueSource: Send + Sync {
+    /// The current byte position within the source, which the parser is about to read from.
+    ///
+    /// # Errors
+    /// I/O errors querying the position of the source.
+    fn position(&self) -> Result<u64>;
+
+    /// Advances the current position of the source by the given number of bytes, without reading
+    /// them. This is used by the parser to move past a deferred value.
+    ///
+    /// # Errors
+    /// I/O errors seeking through the source.
+    fn skip(&self, length: u64) -> Result<()>;
+
+    /// Reads bytes to fill the given buffer, starting at the given byte position within the source.
+    /// The current position of the source is left unchanged.
+    ///
+    /// # Errors
+    /// I/O errors reading from the source, including if the source ends before the buffer is
+    /// filled.
+    fn read_at(&self, position: u64, buf: &mut [u8]) -> Result<()>;
+
+    /// Creates a view of bytes at the given position within the source, without copying them.
+    /// Only sources which are entirely in memory support this, the default returns `None`.
+    fn view_at(&self, _position: u64, _length: usize) -> Option<SharedBytes> {
+        None
+    }
+}
+
+impl fmt::Debug for dyn ValueSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ValueSource")
+    }
+}
+
+/// The value of an element which was not read during parsing, recorded as the position and length
+/// of the value within the dataset. The value is read from the dataset at most once, the first
+/// time it's requested.
+#[derive(Clone)]
+pub struct DeferredValue {
+    source: Arc<dyn ValueSource>,
+    position: u64,
+    length: u32,
+    loaded: OnceLock<Vec<u8>>,
+}
+
+impl fmt::Debug for DeferredValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "DeferredValue @ {}, length: {}, loaded: {}",
+            self.position,
+            self.length,
+            self.is_loaded()
+        )
+    }
+}
+
+impl DeferredValue {
+    #[must_use]
+    pub fn new(source: Arc<dyn ValueSource>, position: u64, length: u32) -> Self {
+        Self {
+            source,
+            position,
+            length,
+            loaded: OnceLock::new(),
+        }
+    }
+
+    /// The byte position of the value within the dataset.
+    #[must_use]
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// The length of the value, as encoded in the dataset.
+    #[must_use]
+    pub fn length(&self) -> u32 {
+        self.length
+    }
+
+    /// The number of bytes the loaded value occupies. Values with an odd length are padded with a
+    /// zero to be even-length, the same as values read during parsing.
+    #[must_use]
+    pub fn padded_length(&self) -> usize {
+        let length: usize = usize::try_from(self.length).unwrap_or_default();
+        length + length % 2
+    }
+
+    /// Whether the value has been loaded from the dataset.
+    #[must_use]
+    pub fn is_loaded(&self) -> bool {
+        self.loaded.get().is_some()
+    }
+
+    /// The value, if it has been loaded from the dataset.
+    #[must_use]
+    pub fn loaded(&self) -> Option<&Vec<u8>> {
+        self.loaded.get()
+    }
+
+    /// Consumes this deferred value and returns the loaded value, if it was loaded.
+    #[must_use]
+    pub fn into_loaded(self) -> Option<Vec<u8>> {
+        self.loaded.into_inner()
+    }
+
+    /// Reads the value from the dataset if not already loaded, returning the loaded value.
+    ///
+    /// # Errors
+    /// I/O errors reading the value from the dataset.
+    pub fn load(&self) -> ParseResult<&Vec<u8>> {
+        if let Some(loaded) = self.loaded.get() {
+            return Ok(loaded);
+        }
+
+        let length: usize = usize::try_from(self.length).unwrap_or_default();
+        let mut buffer: Vec<u8> = vec![0; self.padded_length()];
+        self.source
+            .read_at(self.position, &mut buffer.as_mut_slice()[0..length])?;
+        Ok(self.loaded.get_or_init(|| buffer))
+    }
+
+    /// Releases the loaded value, if loaded, so the memory can be reclaimed. It will be read from
+    /// the dataset again the next time it's requested.
+    pub fn unload(&mut self) {
+        self.loaded.take();
+    }
+}
+
+/// A dataset which implements `Seek`, shared between a `Parser` and the deferred values of the
+/// elements it produces. Created by `ParserBuilder::build_seekable()`.
+pub struct SeekableDataset<R: Read + Seek> {
+    inner: Arc<SharedSource<R>>,
+}
+
+impl<R: Read + Seek> fmt::Debug for SeekableDataset<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SeekableDataset")
+    }
+}
+
+impl<R: Read + Seek + Send + 'static> SeekableDataset<R> {
+    pub(crate) fn new(dataset: R) -> Self {
+        Self {
+            inner: Arc::new(SharedSource(Mutex::new(dataset))),
+        }
+    }
+
+    /// The handle given to elements for loading their deferred values.
+    pub(crate) fn value_source(&self) -> Arc<dyn ValueSource> {
+        self.inner.clone()
+    }
+}
+
+impl<R: Read + Seek> Read for SeekableDataset<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.inner.lock()?.read(buf)
+    }
+}
+
+struct SharedSource<R: Read + Seek>(Mutex<R>);
+
+impl<R: Read + Seek> SharedSource<R> {
+    fn lock(&self) -> Result<MutexGuard<'_, R>> {
+        self.0
+            .lock()
+            .map_err(|_e| Error::other("dataset lock poisoned"))
+    }
+}
+
+impl<R: Read + Seek + Send> ValueSource for SharedSource<R> {
+    fn position(&self) -> Result<u64> {
+        self.lock()?.stream_position()
+    }
+
+    fn skip(&self, length: u64) -> Result<()> {
+        let offset = i64::try_from(length).map_err(Error::other)?;
+        self.lock()?.seek(SeekFrom::Current(offset))?;
+        Ok(())
+    }
+
+    fn read_at(&self, position: u64, buf: &mut [u8]) -> Result<()> {
+        let mut dataset = self.lock()?;
+        let current = dataset.stream_position()?;
+        dataset.seek(SeekFrom::Start(position))?;
+        let result = dataset.read_exact(buf);
+        // Restore the position regardless of whether reading succeeded, so a parser which is still
+        // reading from this dataset is unaffected.
+        dataset.seek(SeekFrom::Start(current))?;
+        result
+    }
+}
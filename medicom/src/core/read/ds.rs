@@ -16,32 +16,98 @@
 
 //! A buffered DICOM reader
 
+/// Reads bytes which were pushed back onto the dataset, after having been read while recovering
+/// from a malformed dataset. See `Parser::resynchronize()`.
+fn read_pushback(pushback: &mut Vec<u8>, buf: &mut [u8]) -> usize {
+    let len = buf.len().min(pushback.len());
+    buf[..len].copy_from_slice(&pushback[..len]);
+    pushback.drain(..len);
+    len
+}
+
 #[cfg(feature = "compress")]
 pub(crate) mod dataset {
     use libflate::deflate::Decoder;
     use std::io::{Read, Result};
 
+    /// The source of the dataset, which may have bytes pushed back onto it prior to being read
+    /// by the decoder.
+    #[derive(Debug)]
+    struct RawDataset<R: Read> {
+        dataset: R,
+        pushback: Vec<u8>,
+    }
+
+    impl<R: Read> Read for RawDataset<R> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            if !self.pushback.is_empty() {
+                return Ok(super::read_pushback(&mut self.pushback, buf));
+            }
+            self.dataset.read(buf)
+        }
+    }
+
     #[derive(Debug)]
     pub(crate) struct Dataset<R: Read> {
-        decoder: Decoder<R>,
+        decoder: Decoder<RawDataset<R>>,
         read_deflated: bool,
+        pushback: Vec<u8>,
     }
 
     impl<R: Read> Dataset<R> {
         pub fn new(dataset: R) -> Dataset<R> {
             Dataset {
-                decoder: Decoder::new(dataset),
+                decoder: Decoder::new(RawDataset {
+                    dataset,
+                    pushback: Vec::new(),
+                }),
                 read_deflated: false,
+                pushback: Vec::new(),
             }
         }
 
         pub fn set_read_deflated(&mut self, read_deflated: bool) {
             self.read_deflated = read_deflated;
         }
+
+        #[cfg(feature = "async")]
+        pub fn get_mut(&mut self) -> &mut R {
+            &mut self.decoder.as_inner_mut().dataset
+        }
+
+        pub fn has_pushback(&self) -> bool {
+            !self.pushback.is_empty() || !self.decoder.as_inner_ref().pushback.is_empty()
+        }
+
+        pub fn unread(&mut self, mut bytes: Vec<u8>) {
+            bytes.append(&mut self.pushback);
+            self.pushback = bytes;
+        }
+
+        /// Pushes bytes back onto the dataset beneath the decoder, so they are decoded if the
+        /// dataset is read deflated. This should only be used prior to reading deflated bytes.
+        pub fn unread_raw(&mut self, mut bytes: Vec<u8>) {
+            let raw: &mut RawDataset<R> = self.decoder.as_inner_mut();
+            bytes.append(&mut raw.pushback);
+            raw.pushback = bytes;
+        }
+
+        #[cfg(feature = "async")]
+        pub fn pushback(&self) -> &[u8] {
+            &self.pushback
+        }
+
+        #[cfg(feature = "async")]
+        pub fn set_pushback(&mut self, pushback: Vec<u8>) {
+            self.pushback = pushback;
+        }
     }
 
     impl<R: Read> Read for Dataset<R> {
         fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            if !self.pushback.is_empty() {
+                return Ok(super::read_pushback(&mut self.pushback, buf));
+            }
             if self.read_deflated {
                 self.decoder.read(buf)
             } else {
@@ -58,16 +124,47 @@ pub(crate) mod dataset {
     #[derive(Debug)]
     pub(crate) struct Dataset<R: Read> {
         dataset: R,
+        pushback: Vec<u8>,
     }
 
     impl<R: Read> Dataset<R> {
         pub fn new(dataset: R) -> Dataset<R> {
-            Dataset { dataset }
+            Dataset {
+                dataset,
+                pushback: Vec::new(),
+            }
+        }
+
+        #[cfg(feature = "async")]
+        pub fn get_mut(&mut self) -> &mut R {
+            &mut self.dataset
+        }
+
+        pub fn has_pushback(&self) -> bool {
+            !self.pushback.is_empty()
+        }
+
+        pub fn unread(&mut self, mut bytes: Vec<u8>) {
+            bytes.append(&mut self.pushback);
+            self.pushback = bytes;
+        }
+
+        #[cfg(feature = "async")]
+        pub fn pushback(&self) -> &[u8] {
+            &self.pushback
+        }
+
+        #[cfg(feature = "async")]
+        pub fn set_pushback(&mut self, pushback: Vec<u8>) {
+            self.pushback = pushback;
         }
     }
 
     impl<R: Read> Read for Dataset<R> {
         fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            if !self.pushback.is_empty() {
+                return Ok(super::read_pushback(&mut self.pushback, buf));
+            }
             self.dataset.read(buf)
         }
     }
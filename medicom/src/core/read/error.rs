@@ -122,4 +122,8 @@ pub enum ParseError {
     /// A general decode error that may not be directly tied to a DICOM element.
     #[error("error decoding: {0}")]
     GeneralDecodeError(String),
+
+    /// An archive being read for DICOM datasets is malformed or uses unsupported features.
+    #[error("invalid archive: {0}")]
+    InvalidArchive(String),
 }
@@ -0,0 +1,181 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Filtering which elements are produced by the parser.
+
+use std::{fmt, ops::RangeInclusive, sync::Arc};
+
+use crate::core::defn::tag::{Tag, TagNode, TagPath};
+
+/// A predicate used by `TagFilter::Predicate`, which is given the `TagPath` of an element and
+/// returns whether the element should be produced by the parser.
+pub type TagPredicate = Arc<dyn Fn(&TagPath) -> bool + Send + Sync>;
+
+/// `TagFilter` specifies which elements the parser should produce. Elements which are excluded
+/// are still walked through by the parser, but their values are skipped over in the dataset
+/// without being read into memory, and they are not returned from the parser.
+///
+/// If a sequence is excluded then all elements within the sequence are also excluded.
+///
+/// The File Meta elements and `SpecificCharacterSet` are never excluded unless within an excluded
+/// sequence, as the parser relies on their values. Items and delimiters are excluded only if
+/// their sequence is excluded.
+#[derive(Clone, Default)]
+pub enum TagFilter {
+    /// All elements are produced.
+    #[default]
+    All,
+
+    /// Only elements selected by any of the `TagSelector`s are produced, along with the sequences
+    /// which contain them.
+    Include(Vec<TagSelector>),
+
+    /// Elements selected by any of the `TagSelector`s are excluded.
+    Exclude(Vec<TagSelector>),
+
+    /// Elements are produced only if the predicate returns `true` for their `TagPath`.
+    Predicate(TagPredicate),
+}
+
+impl fmt::Debug for TagFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TagFilter::All => write!(f, "All"),
+            TagFilter::Include(selectors) => f.debug_tuple("Include").field(selectors).finish(),
+            TagFilter::Exclude(selectors) => f.debug_tuple("Exclude").field(selectors).finish(),
+            TagFilter::Predicate(_) => write!(f, "Predicate"),
+        }
+    }
+}
+
+impl TagFilter {
+    /// Convenience for `TagFilter::Predicate`, wrapping the given function.
+    pub fn predicate<F>(predicate: F) -> TagFilter
+    where
+        F: Fn(&TagPath) -> bool + Send + Sync + 'static,
+    {
+        TagFilter::Predicate(Arc::new(predicate))
+    }
+
+    /// Whether this filter includes all elements.
+    #[must_use]
+    pub fn is_all(&self) -> bool {
+        matches!(self, TagFilter::All)
+    }
+
+    /// Evaluates whether the element at the given `TagPath` should be produced by the parser. This
+    /// does not account for whether the element's sequence was excluded.
+    #[must_use]
+    pub fn evaluate(&self, tagpath: &TagPath) -> bool {
+        match self {
+            TagFilter::All => true,
+            TagFilter::Include(selectors) => selectors
+                .iter()
+                .any(|sel| sel.selects(tagpath) || sel.leads_to(tagpath)),
+            TagFilter::Exclude(selectors) => !selectors.iter().any(|sel| sel.selects(tagpath)),
+            TagFilter::Predicate(predicate) => predicate(tagpath),
+        }
+    }
+}
+
+/// Selects elements for a `TagFilter`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TagSelector {
+    /// Selects the element at the given `TagPath` and all elements within it, if it's a sequence.
+    /// Nodes in the path which do not specify an item number match all items of the sequence.
+    TagPath(TagPath),
+
+    /// Selects all elements whose group number is within the given range, and all elements within
+    /// them.
+    Groups(RangeInclusive<u16>),
+
+    /// Selects all private elements, and all elements within them. See `Tag::is_private()`.
+    Private,
+}
+
+impl TagSelector {
+    /// Convenience for `TagSelector::TagPath`, for auto-converting the input to a `TagPath`.
+    pub fn tagpath<T>(tagpath: T) -> TagSelector
+    where
+        TagPath: From<T>,
+    {
+        TagSelector::TagPath(TagPath::from(tagpath))
+    }
+
+    /// Convenience for `TagSelector::Groups` selecting a single group.
+    #[must_use]
+    pub fn group(group: u16) -> TagSelector {
+        TagSelector::Groups(group..=group)
+    }
+
+    /// Selects the repeating Overlay groups, `(60xx,eeee)`.
+    #[must_use]
+    pub fn overlays() -> TagSelector {
+        TagSelector::Groups(0x6000..=0x60FF)
+    }
+
+    /// Selects the repeating Curve groups, `(50xx,eeee)`, which are retired.
+    #[must_use]
+    pub fn curves() -> TagSelector {
+        TagSelector::Groups(0x5000..=0x50FF)
+    }
+
+    /// Whether the element at the given `TagPath` is selected, either directly or by being within
+    /// a selected sequence.
+    #[must_use]
+    pub fn selects(&self, tagpath: &TagPath) -> bool {
+        match self {
+            TagSelector::TagPath(target) => {
+                target.nodes().len() <= tagpath.nodes().len()
+                    && TagSelector::nodes_match(target, tagpath)
+            }
+            TagSelector::Groups(groups) => tagpath.nodes().iter().any(|node| {
+                u16::try_from(node.tag() >> 16).is_ok_and(|group| groups.contains(&group))
+            }),
+            TagSelector::Private => tagpath
+                .nodes()
+                .iter()
+                .any(|node| Tag::is_private(node.tag())),
+        }
+    }
+
+    /// Whether the element at the given `TagPath` is a sequence which contains a selected element.
+    #[must_use]
+    pub fn leads_to(&self, tagpath: &TagPath) -> bool {
+        match self {
+            TagSelector::TagPath(target) => {
+                target.nodes().len() > tagpath.nodes().len()
+                    && TagSelector::nodes_match(target, tagpath)
+            }
+            TagSelector::Groups(_) | TagSelector::Private => false,
+        }
+    }
+
+    /// Whether the nodes common to both paths match.
+    fn nodes_match(target: &TagPath, current: &TagPath) -> bool {
+        target
+            .nodes()
+            .iter()
+            .zip(current.nodes())
+            .all(|(target, current): (&TagNode, &TagNode)| {
+                target.tag() == current.tag()
+                    && match (target.item(), current.item()) {
+                        (Some(target_item), Some(current_item)) => target_item == current_item,
+                        _ => true,
+                    }
+            })
+    }
+}
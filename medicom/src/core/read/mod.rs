@@ -22,9 +22,19 @@ pub use parser::ParserState;
 
 mod ds;
 
+#[cfg(feature = "archive")]
+pub mod archive;
+#[cfg(feature = "async")]
+pub mod asyncparser;
 pub mod behavior;
 pub mod builder;
+pub mod deferred;
 pub mod error;
+pub mod filter;
+pub mod parallel;
 pub mod parser;
+pub mod recovery;
+pub mod shared;
 pub mod stop;
 pub mod valdecode;
+pub mod visitor;
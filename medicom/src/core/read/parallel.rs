@@ -0,0 +1,327 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Parsing many datasets in parallel with a bounded number of worker threads.
+//!
+//! Create a `ParallelParser` with `ParserBuilder::build_parallel()`, then provide the datasets to
+//! parse as an iterator of `DatasetSource`. Each dataset is parsed into a `DicomRoot`, and the
+//! results are produced in either completion order or input order.
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, Read},
+    num::NonZeroUsize,
+    panic::{catch_unwind, AssertUnwindSafe},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread::{available_parallelism, spawn, JoinHandle},
+};
+
+use crate::core::{
+    dcmobject::DicomRoot,
+    defn::dcmdict::DicomDictionary,
+    read::{ParseError, ParseResult, ParserBuilder},
+};
+
+/// The maximum number of datasets which are queued or parsed but not yet produced, per worker.
+const QUEUED_PER_WORKER: usize = 2;
+
+/// A dataset to be parsed by a `ParallelParser`.
+pub trait DatasetSource: Send + 'static {
+    /// Parses the dataset using a parser from the given builder. This is called from a worker
+    /// thread.
+    ///
+    /// # Errors
+    /// I/O errors opening the dataset, or errors parsing the dataset.
+    fn parse(
+        &mut self,
+        builder: &ParserBuilder,
+        dictionary: &dyn DicomDictionary,
+    ) -> ParseResult<Option<DicomRoot>>;
+}
+
+/// Parses the file at the path.
+impl DatasetSource for PathBuf {
+    fn parse(
+        &mut self,
+        builder: &ParserBuilder,
+        dictionary: &dyn DicomDictionary,
+    ) -> ParseResult<Option<DicomRoot>> {
+        let file = BufReader::with_capacity(1024 * 1024, File::open(self.as_path())?);
+        DicomRoot::parse(&mut builder.build(file, dictionary))
+    }
+}
+
+/// A dataset read from a reader, such as an in-memory buffer or network stream.
+pub struct ReaderSource<R: Read + Send + 'static> {
+    reader: R,
+}
+
+impl<R: Read + Send + 'static> ReaderSource<R> {
+    #[must_use]
+    pub fn new(reader: R) -> Self {
+        Self { reader }
+    }
+
+    #[must_use]
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Read + Send + 'static> DatasetSource for ReaderSource<R> {
+    fn parse(
+        &mut self,
+        builder: &ParserBuilder,
+        dictionary: &dyn DicomDictionary,
+    ) -> ParseResult<Option<DicomRoot>> {
+        DicomRoot::parse(&mut builder.build(&mut self.reader, dictionary))
+    }
+}
+
+/// The order that a `ParallelParser` produces results in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResultOrder {
+    /// Results are produced as soon as each dataset is parsed.
+    #[default]
+    Completion,
+
+    /// Results are produced in the same order the datasets were given. A dataset which takes long
+    /// to parse will hold back the results of those after it.
+    Input,
+}
+
+/// The result of parsing one dataset with a `ParallelParser`.
+#[derive(Debug)]
+pub struct ParsedDataset<S: DatasetSource> {
+    index: usize,
+    source: S,
+    result: ParseResult<Option<DicomRoot>>,
+}
+
+impl<S: DatasetSource> ParsedDataset<S> {
+    /// The 0-based index of the dataset from the input sources.
+    #[must_use]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The source of the dataset.
+    #[must_use]
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// The result of parsing the dataset, which is `None` if it's not DICOM. See
+    /// `DicomRoot::parse()`.
+    pub fn result(&self) -> &ParseResult<Option<DicomRoot>> {
+        &self.result
+    }
+
+    /// Consumes this result, returning the source and the result of parsing it.
+    pub fn into_parts(self) -> (S, ParseResult<Option<DicomRoot>>) {
+        (self.source, self.result)
+    }
+}
+
+/// Parses many datasets in parallel, each into a `DicomRoot`. Create with
+/// `ParserBuilder::build_parallel()`.
+pub struct ParallelParser {
+    builder: ParserBuilder,
+    dictionary: &'static (dyn DicomDictionary + Sync),
+    workers: NonZeroUsize,
+    order: ResultOrder,
+}
+
+impl ParallelParser {
+    pub(crate) fn new(
+        builder: ParserBuilder,
+        dictionary: &'static (dyn DicomDictionary + Sync),
+    ) -> Self {
+        Self {
+            builder,
+            dictionary,
+            workers: available_parallelism().unwrap_or(NonZeroUsize::MIN),
+            order: ResultOrder::default(),
+        }
+    }
+
+    /// Sets the number of worker threads. The default is the available parallelism of the system.
+    #[must_use]
+    pub fn workers(mut self, workers: NonZeroUsize) -> Self {
+        self.workers = workers;
+        self
+    }
+
+    /// Sets the order results are produced in. The default is `ResultOrder::Completion`.
+    #[must_use]
+    pub fn order(mut self, order: ResultOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Starts parsing the given datasets, returning an iterator over the results.
+    ///
+    /// Sources are taken from the iterator only as results are consumed, so at most a few
+    /// datasets per worker are queued or held at a time. Dropping the returned iterator waits for
+    /// the datasets currently being parsed to finish, and skips those which are queued.
+    pub fn parse<S: DatasetSource, I: IntoIterator<Item = S>>(
+        &self,
+        sources: I,
+    ) -> ParallelResults<S, I::IntoIter> {
+        let (job_tx, job_rx) = channel::<(usize, S)>();
+        let (result_tx, result_rx) = channel::<ParsedDataset<S>>();
+        let job_rx = Arc::new(Mutex::new(job_rx));
+        let builder = Arc::new(self.builder.clone());
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        let workers: Vec<JoinHandle<()>> = (0..self.workers.get())
+            .map(|_| {
+                let job_rx = Arc::clone(&job_rx);
+                let result_tx = result_tx.clone();
+                let builder = Arc::clone(&builder);
+                let cancelled = Arc::clone(&cancelled);
+                let dictionary = self.dictionary;
+                spawn(move || {
+                    while let Some((index, mut source)) = Self::await_job(&job_rx) {
+                        if cancelled.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        let result =
+                            catch_unwind(AssertUnwindSafe(|| source.parse(&builder, dictionary)))
+                                .unwrap_or_else(|_| {
+                                    Err(ParseError::GeneralDecodeError(
+                                        "panic while parsing dataset".to_string(),
+                                    ))
+                                });
+                        let parsed = ParsedDataset {
+                            index,
+                            source,
+                            result,
+                        };
+                        if result_tx.send(parsed).is_err() {
+                            break;
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        ParallelResults {
+            sources: sources.into_iter(),
+            capacity: self.workers.get() * QUEUED_PER_WORKER,
+            order: self.order,
+            job_tx: Some(job_tx),
+            result_rx,
+            workers,
+            cancelled,
+            next_index: 0,
+            in_flight: 0,
+            next_result: 0,
+            completed: BTreeMap::new(),
+        }
+    }
+
+    /// Waits for the next job, releasing the lock once received. Returns `None` once there will be
+    /// no more jobs.
+    fn await_job<S>(job_rx: &Mutex<Receiver<(usize, S)>>) -> Option<(usize, S)> {
+        job_rx.lock().ok()?.recv().ok()
+    }
+}
+
+/// An iterator over the results of a `ParallelParser`.
+pub struct ParallelResults<S: DatasetSource, I: Iterator<Item = S>> {
+    sources: I,
+    capacity: usize,
+    order: ResultOrder,
+    job_tx: Option<Sender<(usize, S)>>,
+    result_rx: Receiver<ParsedDataset<S>>,
+    workers: Vec<JoinHandle<()>>,
+    cancelled: Arc<AtomicBool>,
+
+    /// The index of the next source taken from `sources`.
+    next_index: usize,
+    /// The number of sources given to workers whose results have not yet been received.
+    in_flight: usize,
+    /// For `ResultOrder::Input`, the index of the next result to produce.
+    next_result: usize,
+    /// For `ResultOrder::Input`, received results which are waiting on earlier results.
+    completed: BTreeMap<usize, ParsedDataset<S>>,
+}
+
+impl<S: DatasetSource, I: Iterator<Item = S>> ParallelResults<S, I> {
+    /// Gives sources to the workers until the limit of queued datasets is reached.
+    fn queue_sources(&mut self) {
+        let Some(job_tx) = &self.job_tx else {
+            return;
+        };
+        while self.in_flight + self.completed.len() < self.capacity {
+            let Some(source) = self.sources.next() else {
+                self.job_tx = None;
+                return;
+            };
+            if job_tx.send((self.next_index, source)).is_err() {
+                self.job_tx = None;
+                return;
+            }
+            self.next_index += 1;
+            self.in_flight += 1;
+        }
+    }
+}
+
+impl<S: DatasetSource, I: Iterator<Item = S>> Iterator for ParallelResults<S, I> {
+    type Item = ParsedDataset<S>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(parsed) = self.completed.remove(&self.next_result) {
+                self.next_result += 1;
+                return Some(parsed);
+            }
+
+            self.queue_sources();
+            if self.in_flight == 0 {
+                return None;
+            }
+
+            let parsed = self.result_rx.recv().ok()?;
+            self.in_flight -= 1;
+            match self.order {
+                ResultOrder::Completion => return Some(parsed),
+                ResultOrder::Input => {
+                    self.completed.insert(parsed.index, parsed);
+                }
+            }
+        }
+    }
+}
+
+impl<S: DatasetSource, I: Iterator<Item = S>> Drop for ParallelResults<S, I> {
+    fn drop(&mut self) {
+        // Workers stop after their current dataset, skipping any which are queued.
+        self.cancelled.store(true, Ordering::Relaxed);
+        self.job_tx = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
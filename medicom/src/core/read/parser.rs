@@ -14,7 +14,7 @@
    limitations under the License.
 */
 
-use std::{convert::TryFrom, io::Read, iter::once};
+use std::{convert::TryFrom, io::Read, iter::once, sync::Arc};
 
 use crate::core::{
     charset::CSRef,
@@ -28,7 +28,10 @@ use crate::core::{
         vl::ValueLength,
         vr::VRRef,
     },
-    read::{behavior::ParseBehavior, ds::dataset::Dataset, error::ParseError, stop::ParseStop},
+    read::{
+        behavior::ParseBehavior, deferred::ValueSource, ds::dataset::Dataset, error::ParseError,
+        recovery::ParseDiagnostic, stop::ParseStop,
+    },
     values::ElementWithVr,
     DICOM_PREFIX_LENGTH, FILE_PREAMBLE_LENGTH,
 };
@@ -38,7 +41,9 @@ mod dsread;
 mod elem;
 mod fme;
 mod iter;
+mod recover;
 mod util;
+mod visit;
 
 /// The `Result` type of the parser
 pub type ParseResult<T> = core::result::Result<T, ParseError>;
@@ -167,6 +172,11 @@ pub struct Parser<'d, R: Read> {
     /// then the dataset should be read using `self.detected_ts`.
     pub(super) dataset_ts: Option<TSRef>,
 
+    /// The transfer syntax declared by the File Meta `TransferSyntaxUID`, only populated if the
+    /// dataset was found to be encoded differently. In that case `self.dataset_ts` is the transfer
+    /// syntax detected from the encoding of the dataset.
+    pub(super) declared_ts: Option<TSRef>,
+
     /// The specific character set used for this dataset. This defaults to the dicom default which
     /// is `WINDOWS_1252` but is changed after having successfully parsed the specific character set
     /// element.
@@ -183,6 +193,26 @@ pub struct Parser<'d, R: Read> {
     /// to read from the dataset. This is used to track when the iterator should be considered fully
     /// consumed in those cases and prevent further attempts at reading from the dataset.
     pub(super) iterator_ended: bool,
+
+    /// Whether the last element parsed was excluded by `self.behavior.tag_filter()`, in which case
+    /// it should not be returned from the iterator.
+    pub(super) element_last_excluded: bool,
+
+    /// Whether element values should be left unread in the dataset for streaming to a visitor,
+    /// see `Parser::visit()`.
+    pub(super) stream_values: bool,
+
+    /// When streaming values, the length of the value of the last element parsed which remains
+    /// unread in the dataset.
+    pub(super) pending_value: Option<u32>,
+
+    /// The handle for reading values from the dataset at arbitrary positions, only present if the
+    /// parser was built with `ParserBuilder::build_seekable()`. When present, values selected by
+    /// `self.behavior.defer_values()` are skipped over and loaded on demand through this handle.
+    pub(super) value_source: Option<Arc<dyn ValueSource>>,
+
+    /// The repairs made to the dataset while parsing, as allowed by `self.behavior.recovery()`.
+    pub(super) diagnostics: Vec<ParseDiagnostic>,
 }
 
 impl<'d, R: Read> Parser<'d, R> {
@@ -212,6 +242,14 @@ impl<'d, R: Read> Parser<'d, R> {
         self.dataset_ts.unwrap_or(self.detected_ts)
     }
 
+    /// Get the transfer syntax declared by the File Meta `TransferSyntaxUID`, if it did not match
+    /// the encoding of the dataset. The dataset is instead parsed with the transfer syntax detected,
+    /// see `ts()`.
+    #[must_use]
+    pub fn declared_ts(&self) -> Option<TSRef> {
+        self.declared_ts
+    }
+
     /// Get the character set string values are encoded in.
     pub fn cs(&self) -> CSRef {
         self.cs
@@ -235,6 +273,19 @@ impl<'d, R: Read> Parser<'d, R> {
         &self.dicom_prefix
     }
 
+    /// Get the repairs made to the dataset so far while parsing. Repairs are only made if enabled
+    /// by `ParseBehavior::recovery()`, except for overriding the transfer syntax declared by the
+    /// File Meta, which is always recorded.
+    #[must_use]
+    pub fn diagnostics(&self) -> &[ParseDiagnostic] {
+        &self.diagnostics
+    }
+
+    /// Takes the repairs made to the dataset so far while parsing, leaving none recorded.
+    pub fn take_diagnostics(&mut self) -> Vec<ParseDiagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
     /// Checks if the stream should stop being parsed based on `self.stop`. This should be checked
     /// after parsing a tag number from the dataset.
     fn is_at_parse_stop(&self) -> bool {
@@ -307,6 +358,7 @@ impl<'d, R: Read> Parser<'d, R> {
     /// Parses the value of the given element as the transfer syntax return. If the transfer syntax
     /// cannot be resolved then this sets it to the default DICOM transfer syntax which is IVRLE.
     fn parse_transfer_syntax(&mut self, element: &DicomElement) -> ParseResult<Option<TSRef>> {
+        element.load_value()?;
         let ts_uid: String = String::try_from(&ElementWithVr::of(element))?;
         Ok(self.dictionary.get_ts_by_uid(ts_uid.as_ref()))
     }
@@ -314,17 +366,14 @@ impl<'d, R: Read> Parser<'d, R> {
     /// Parses the value of the given element as the specific character set and sets the `cs` value
     /// on this iterator to affect the parsing of further text-type element values.
     fn parse_specific_character_set(element: &DicomElement) -> ParseResult<CSRef> {
-        let new_cs: Option<String> = Vec::<String>::try_from(element)?
-            .into_iter()
-            .find(|cs_entry: &String| !cs_entry.is_empty());
+        element.load_value()?;
+        let values: Vec<String> = Vec::<String>::try_from(element)?;
 
         // TODO: There are options for what to do if we can't support the character repertoire
         //       See note on Ch 5 Part 6.1.2.3 under "Considerations on the Handling of
         //       Unsupported Character Sets"
 
-        Ok(new_cs
-            .and_then(|cs: String| CSRef::lookup_charset(&cs))
-            .unwrap_or_default())
+        Ok(CSRef::lookup_specific_character_set(&values).unwrap_or_default())
     }
 
     /// Builds a string containing debug state of parsing, for errors and spurious output while
@@ -423,7 +472,13 @@ impl<'d, R: Read> Parser<'d, R> {
                     };
                 }
                 ParserState::ReadElement => {
-                    return self.iterate_element();
+                    let element = self.iterate_element()?;
+                    // Excluded elements are walked through but not returned, continue on to the
+                    // next element.
+                    if element.is_some() && self.element_last_excluded {
+                        continue;
+                    }
+                    return Ok(element);
                 }
             }
         }
@@ -26,13 +26,20 @@
 //! the minimal bytes from the stream for detection and puts the parser state into partial-read
 //! status. This is what populates the `partial_tag`, `partial_vr`, and `partial_vl` fields so they
 //! are re-used by the other state iterations. It may also populate the file preamble.
+//!
+//! If what appears to be a file preamble is not followed by the DICOM prefix then the bytes read
+//! are sniffed as the start of a dataset without File Meta, and pushed back onto the dataset. This
+//! also detects datasets which begin at an arbitrary group, or which are deflated.
 
-use std::io::{Cursor, Read};
+use std::io::{Cursor, ErrorKind, Read};
 
 use crate::core::{
     defn::{
         constants::{
-            tags::{FILE_META_GROUP_END, FILE_META_INFORMATION_GROUP_LENGTH, SOP_INSTANCE_UID},
+            tags::{
+                FILE_META_GROUP_END, FILE_META_INFORMATION_GROUP_LENGTH, SOP_INSTANCE_UID,
+                TRANSFER_SYNTAX_UID,
+            },
             ts::{
                 ExplicitVRBigEndian, ExplicitVRLittleEndian, ImplicitVRBigEndian,
                 ImplicitVRLittleEndian,
@@ -40,16 +47,37 @@ use crate::core::{
         },
         ts::TSRef,
         vl::ValueLength,
-        vr::{VRRef, INVALID_VR},
+        vr::{VRRef, INVALID_VR, SQ, VR},
     },
-    read::parser::{
-        util::{read_tag_from_dataset, read_value_length_from_dataset, read_vr_from_dataset},
-        ParseError, ParseResult, Parser, ParserState, FILE_PREAMBLE_LENGTH,
+    read::{
+        parser::{
+            recover::{Candidate, MAX_HEADER_LENGTH, MIN_HEADER_LENGTH},
+            util::{read_tag_from_dataset, read_value_length_from_dataset, read_vr_from_dataset},
+            ParseError, ParseResult, Parser, ParserState, FILE_PREAMBLE_LENGTH,
+        },
+        recovery::RecoveryAction,
     },
+    DICOM_PREFIX, DICOM_PREFIX_LENGTH,
 };
 
+#[cfg(feature = "compress")]
+use crate::core::defn::constants::ts::DeflatedExplicitVRLittleEndian;
+
 const MAX_VALUE_LENGTH_IN_DETECT: u32 = 100;
 
+/// The number of bytes inflated when sniffing whether a dataset without File Meta is deflated.
+#[cfg(feature = "compress")]
+const SNIFFED_INFLATED_LENGTH: u64 = 64;
+
+/// The transfer syntaxes a dataset without File Meta is sniffed with, in order of preference when
+/// the dataset can be read equally well with several.
+static SNIFFED_TS: [TSRef; 4] = [
+    &ExplicitVRLittleEndian,
+    &ImplicitVRLittleEndian,
+    &ExplicitVRBigEndian,
+    &ImplicitVRBigEndian,
+];
+
 impl<R: Read> Parser<'_, R> {
     // This function is knowingly pretty lengthy. Attempting to break down into smaller functions
     // may be possible, though I have not found a way to do so without also splitting the
@@ -78,6 +106,8 @@ impl<R: Read> Parser<'_, R> {
     ///    library also uses this same value comparison.
     /// 4. Otherwise it's assumed the start of the file is proprietary file preamble. These bytes
     ///    are then skipped and detection begins again.
+    /// 5. If the assumed preamble is not followed by the DICOM prefix then the bytes are sniffed as
+    ///    a dataset without File Meta, see `iterate_preamble_or_sniff()`.
     pub(super) fn iterate_detect_state(&mut self) -> ParseResult<()> {
         // Start off assuming EVRLE, the default for File-Meta.
         let mut ts: TSRef = &ExplicitVRLittleEndian;
@@ -119,13 +149,7 @@ impl<R: Read> Parser<'_, R> {
             }
 
             // Read the remainder of the preamble and the prefix, then attempt detection again.
-            self.dataset
-                .read_exact(&mut file_preamble[bytes_read..FILE_PREAMBLE_LENGTH])?;
-            self.bytes_read += u64::try_from(file_preamble.len()).unwrap_or_default();
-            self.file_preamble = Some(file_preamble);
-            self.iterate_prefix()?;
-            self.state = ParserState::DetectTransferSyntax;
-            return Ok(());
+            return self.iterate_preamble_or_sniff(file_preamble, bytes_read);
         } else if !(FILE_META_INFORMATION_GROUP_LENGTH..=SOP_INSTANCE_UID).contains(&tag) {
             cursor.set_position(0);
             ts = &ExplicitVRBigEndian;
@@ -143,13 +167,7 @@ impl<R: Read> Parser<'_, R> {
                 }
 
                 // Read the remainder of the preamble and the prefix, then attempt detection again.
-                self.dataset
-                    .read_exact(&mut file_preamble[bytes_read..FILE_PREAMBLE_LENGTH])?;
-                self.bytes_read += u64::try_from(file_preamble.len()).unwrap_or_default();
-                self.file_preamble = Some(file_preamble);
-                self.iterate_prefix()?;
-                self.state = ParserState::DetectTransferSyntax;
-                return Ok(());
+                return self.iterate_preamble_or_sniff(file_preamble, bytes_read);
             }
         }
 
@@ -271,15 +289,203 @@ impl<R: Read> Parser<'_, R> {
         }
 
         // Garbage data so likely in preamble, finish reading preamble and prefix, restart detect.
-        self.dataset
-            .read_exact(&mut file_preamble[bytes_read..FILE_PREAMBLE_LENGTH])?;
-        self.bytes_read += u64::try_from(file_preamble.len()).unwrap_or_default();
-        self.file_preamble = Some(file_preamble);
+        self.iterate_preamble_or_sniff(file_preamble, bytes_read)
+    }
+
+    /// Reads the remainder of what is assumed to be the file preamble, of which `bytes_read` bytes
+    /// have already been read into `file_preamble`, and then the DICOM prefix following it.
+    ///
+    /// If the DICOM prefix does not follow then the bytes read are instead sniffed as the start of
+    /// a dataset which has no File Meta, which may begin at any group and may be deflated. If the
+    /// start of a dataset is found then the bytes read are pushed back onto the dataset and parsing
+    /// continues with the detected transfer syntax, otherwise the missing prefix fails parsing.
+    fn iterate_preamble_or_sniff(
+        &mut self,
+        mut file_preamble: [u8; FILE_PREAMBLE_LENGTH],
+        bytes_read: usize,
+    ) -> ParseResult<()> {
         self.partial_tag = None;
         self.partial_vr = None;
         self.partial_vl = None;
+
+        let head_length: usize = FILE_PREAMBLE_LENGTH + DICOM_PREFIX_LENGTH;
+        let mut head: Vec<u8> = Vec::with_capacity(head_length);
+        head.extend_from_slice(&file_preamble[..bytes_read]);
+        (&mut self.dataset)
+            .take(u64::try_from(head_length - bytes_read).unwrap_or_default())
+            .read_to_end(&mut head)?;
+
+        if head.len() < head_length || head[FILE_PREAMBLE_LENGTH..] != DICOM_PREFIX[..] {
+            if let Some(ts) = self.sniff_dataset(&head) {
+                self.dataset.unread(head);
+                self.detected_ts = ts;
+                self.state = ParserState::ReadElement;
+                return Ok(());
+            }
+
+            #[cfg(feature = "compress")]
+            if self.sniff_deflated_dataset(head.clone())? {
+                self.detected_ts = &DeflatedExplicitVRLittleEndian;
+                self.state = ParserState::ReadElement;
+                return Ok(());
+            }
+
+            if head.len() < head_length {
+                return Err(ParseError::IOError {
+                    source: ErrorKind::UnexpectedEof.into(),
+                });
+            }
+        }
+
+        file_preamble.copy_from_slice(&head[..FILE_PREAMBLE_LENGTH]);
+        self.bytes_read += u64::try_from(FILE_PREAMBLE_LENGTH).unwrap_or_default();
+        self.file_preamble = Some(file_preamble);
+        self.dataset.unread(head.split_off(FILE_PREAMBLE_LENGTH));
         self.iterate_prefix()?;
         self.state = ParserState::DetectTransferSyntax;
         Ok(())
     }
+
+    /// Sniffs the given bytes as the start of an uncompressed dataset, returning the transfer
+    /// syntax in which the most consecutive elements can be read.
+    fn sniff_dataset(&self, bytes: &[u8]) -> Option<TSRef> {
+        let mut detected: Option<(TSRef, usize)> = None;
+        for ts in SNIFFED_TS {
+            if let Some(count) = self.count_plausible_elements(bytes, ts) {
+                if detected.is_none_or(|(_, most)| count > most) {
+                    detected = Some((ts, count));
+                }
+            }
+        }
+        detected.map(|(ts, _)| ts)
+    }
+
+    /// Sniffs the given bytes as the start of a deflated dataset, by inflating them and checking
+    /// whether the result is the start of an `ExplicitVRLittleEndian` dataset. If so then the
+    /// inflated bytes are pushed back onto the dataset.
+    ///
+    /// The bytes are inflated by the dataset's decoder, which continues reading from the dataset
+    /// beyond them in order to complete the first deflate block. This is only attempted once no
+    /// other interpretation of the bytes remains, as it can't be undone.
+    #[cfg(feature = "compress")]
+    fn sniff_deflated_dataset(&mut self, bytes: Vec<u8>) -> ParseResult<bool> {
+        self.dataset.unread_raw(bytes);
+        self.dataset.set_read_deflated(true);
+        let mut inflated: Vec<u8> = Vec::new();
+        let result = (&mut self.dataset)
+            .take(SNIFFED_INFLATED_LENGTH)
+            .read_to_end(&mut inflated);
+        self.dataset.set_read_deflated(false);
+        match result {
+            Err(e) if e.kind() == ErrorKind::WouldBlock => return Err(e.into()),
+            Ok(_)
+                if self
+                    .count_plausible_elements(&inflated, &DeflatedExplicitVRLittleEndian)
+                    .is_some() =>
+            {
+                self.dataset.unread(inflated);
+                return Ok(true);
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    /// Counts the consecutive element headers which can be read from the start of the given bytes
+    /// using the given transfer syntax, following the value length of each element to the next.
+    /// Counting stops at the end of the bytes, or at an element whose contents can't be skipped
+    /// over such as a sequence. Returns `None` if the first element or any element following it is
+    /// not plausible. For Implicit VR the first element must be known to the dictionary, as there's
+    /// little else to go on.
+    fn count_plausible_elements(&self, bytes: &[u8], ts: TSRef) -> Option<usize> {
+        let mut offset: usize = 0;
+        let mut after_tag: u32 = 0;
+        let mut count: usize = 0;
+        while offset.saturating_add(MIN_HEADER_LENGTH) <= bytes.len() {
+            match self.evaluate_candidate(&bytes[offset..], ts, after_tag) {
+                Candidate::Header {
+                    length,
+                    tag,
+                    vr,
+                    vl,
+                } => {
+                    if count == 0 && vr.is_none() && self.lookup_vr(tag).is_none() {
+                        return None;
+                    }
+                    count += 1;
+                    after_tag = tag;
+                    let is_sq: bool = vr.or_else(|| self.lookup_vr(tag)) == Some(&SQ);
+                    match vl {
+                        ValueLength::Explicit(len) if !is_sq => {
+                            offset = offset
+                                .saturating_add(length)
+                                .saturating_add(usize::try_from(len).unwrap_or(usize::MAX));
+                        }
+                        _ => break,
+                    }
+                }
+                Candidate::Incomplete => break,
+                Candidate::Implausible => return None,
+            }
+        }
+        (count > 0).then_some(count)
+    }
+
+    /// Verifies the transfer syntax declared by the File Meta against the encoding of the first
+    /// element of the dataset, which is peeked at and pushed back onto the dataset. Datasets from
+    /// older modalities sometimes declare Explicit VR while being encoded in Implicit VR, or vice
+    /// versa. If the first element can only be read with the other VR encoding then the dataset is
+    /// parsed using that instead, and the override is recorded as a `ParseDiagnostic`.
+    pub(super) fn verify_dataset_ts(&mut self) -> ParseResult<()> {
+        let Some(declared) = self.dataset_ts else {
+            return Ok(());
+        };
+        if declared.deflated() {
+            return Ok(());
+        }
+
+        let mut window: Vec<u8> = Vec::with_capacity(MAX_HEADER_LENGTH);
+        (&mut self.dataset)
+            .take(u64::try_from(MAX_HEADER_LENGTH).unwrap_or_default())
+            .read_to_end(&mut window)?;
+
+        let mut detected: Option<TSRef> = None;
+        if window.len() >= MIN_HEADER_LENGTH && !self.is_plausible_in_ts(&window, declared) {
+            let other: TSRef = match (declared.explicit_vr(), declared.big_endian()) {
+                (true, false) => &ImplicitVRLittleEndian,
+                (false, false) => &ExplicitVRLittleEndian,
+                (true, true) => &ImplicitVRBigEndian,
+                (false, true) => &ExplicitVRBigEndian,
+            };
+            if self.is_plausible_in_ts(&window, other) {
+                detected = Some(other);
+            }
+        }
+        self.dataset.unread(window);
+
+        if let Some(detected) = detected {
+            self.declared_ts = Some(declared);
+            self.dataset_ts = Some(detected);
+            self.record_repair(
+                TRANSFER_SYNTAX_UID,
+                self.bytes_read,
+                RecoveryAction::OverrodeTransferSyntax { declared, detected },
+            );
+        }
+        Ok(())
+    }
+
+    /// Whether the given bytes are a plausible element header in the given transfer syntax. For
+    /// Implicit VR the bytes following the tag must also not be the VR the dictionary has for the
+    /// tag, which would indicate the header is actually Explicit VR.
+    fn is_plausible_in_ts(&self, window: &[u8], ts: TSRef) -> bool {
+        match self.evaluate_candidate(window, ts, 0) {
+            Candidate::Header { tag, .. } if !ts.explicit_vr() => {
+                let code: u16 = (u16::from(window[4]) << 8) + u16::from(window[5]);
+                VR::from_code(code).is_none_or(|vr| self.lookup_vr(tag) != Some(vr))
+            }
+            Candidate::Implausible => false,
+            _ => true,
+        }
+    }
 }
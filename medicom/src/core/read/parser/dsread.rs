@@ -26,7 +26,7 @@
 //! the value field, and it does not validate the structure of sequences (i.e. the proper uses of
 //! the Item, `ItemDelimitationItem`, and `SequenceDelimitationItem` elements.
 
-use std::io::{ErrorKind, Read};
+use std::io::{self, ErrorKind, Read};
 
 use crate::core::{
     charset::CSRef,
@@ -34,7 +34,7 @@ use crate::core::{
     dcmsqelem::SequenceElement,
     defn::{
         constants::{
-            tags::{DATASET_TRAILING_PADDING, ITEM},
+            tags::{DATASET_TRAILING_PADDING, ITEM, SPECIFIC_CHARACTER_SET, TRANSFER_SYNTAX_UID},
             ts::{ImplicitVRBigEndian, ImplicitVRLittleEndian},
         },
         is_non_standard_sq, is_parent_priv_sq, is_sq_delim,
@@ -44,10 +44,13 @@ use crate::core::{
         vr::{VRRef, INVALID_VR, SQ, UN},
     },
     read::{
+        deferred::DeferredValue,
         parser::{
             util::{read_tag_from_dataset, read_value_length_from_dataset, read_vr_from_dataset},
-            ParseResult, Parser,
+            ParseResult, Parser, ParserState,
         },
+        recovery::RecoveryAction,
+        shared::SharedBytes,
         ParseError,
     },
 };
@@ -67,12 +70,15 @@ impl<R: Read> Parser<'_, R> {
         Ok(tag)
     }
 
+    #[allow(clippy::too_many_lines)]
     /// Reads the remainder of the dicom element from the dataset. This assumes `self.read_tag()`
-    /// was called just prior and its result passed as the tag parameter here.
+    /// was called just prior and its result passed as the tag parameter here. If `exclude_value` is
+    /// true then the value field is skipped over rather than read, leaving the element empty.
     pub(super) fn read_dicom_element(
         &mut self,
         tag: u32,
         elem_ts: TSRef,
+        exclude_value: bool,
     ) -> ParseResult<DicomElement> {
         let is_sq_delim = is_sq_delim(tag);
         let is_parent_priv_sq = is_parent_priv_sq(&self.current_path);
@@ -86,6 +92,8 @@ impl<R: Read> Parser<'_, R> {
             elem_ts
         };
 
+        // The code of an unrecognized explicit VR, if the element is to be re-read as Implicit VR.
+        let mut implicit_retry: Option<u16> = None;
         let vr: VRRef = if ts.explicit_vr() {
             // The `partial_vr` may be populated as part of initial dataset parsing when attempting
             // to detect the transfer syntax. The UnknownExplicitVR error used here is only
@@ -97,6 +105,18 @@ impl<R: Read> Parser<'_, R> {
                 .or_else(|_e| self.read_vr());
             match vr_res {
                 Ok(vr) => vr,
+                Err(ParseError::UnknownExplicitVR(code))
+                    if self.behavior.recovery().retry_implicit_vr() =>
+                {
+                    implicit_retry = Some(code);
+                    self.lookup_vr(tag).unwrap_or(&UN)
+                }
+                Err(ParseError::UnknownExplicitVR(code))
+                    if self.behavior.recovery().resynchronize() =>
+                {
+                    // Let the caller skip ahead to the next plausible element.
+                    return Err(ParseError::UnknownExplicitVR(code));
+                }
                 Err(ParseError::UnknownExplicitVR(_code)) => &INVALID_VR,
                 Err(e) => return Err(e),
             }
@@ -110,14 +130,25 @@ impl<R: Read> Parser<'_, R> {
         };
         self.vr_last_used.replace(vr);
 
-        let vl: ValueLength = if let Some(partial_vl) = self.partial_vl {
+        let mut vl: ValueLength = if let Some(partial_vl) = self.partial_vl {
             self.partial_vl.take();
             partial_vl
+        } else if let Some(code) = implicit_retry {
+            let position: u64 = self.bytes_read.saturating_sub(6);
+            let vl: ValueLength = self.read_implicit_value_length(code, ts)?;
+            self.record_repair(tag, position, RecoveryAction::RetriedAsImplicitVR(code));
+            vl
         } else {
             self.read_value_length(ts, vr)?
         };
         self.vl_last_used.replace(vl);
 
+        let ts: TSRef = match implicit_retry {
+            Some(_) if ts.big_endian() => &ImplicitVRBigEndian,
+            Some(_) => &ImplicitVRLittleEndian,
+            None => ts,
+        };
+
         let parse_as_sq: bool = is_non_standard_sq(tag, vr, vl);
         let ts: TSRef = if parse_as_sq {
             if ts.big_endian() {
@@ -141,10 +172,31 @@ impl<R: Read> Parser<'_, R> {
 
         //eprintln!("{}", &self.current_debug_str());
 
+        let mut deferred: Option<DeferredValue> = None;
+        let mut shared: Option<SharedBytes> = None;
         let bytes: Vec<u8> = if skip_bytes {
             Vec::with_capacity(0)
+        } else if exclude_value {
+            self.skip_value_field(tag, vl, ts)?;
+            Vec::with_capacity(0)
         } else {
-            self.read_value_field(tag, vl)?
+            shared = self.share_value_field(vl, ts)?;
+            if shared.is_none() && !self.stream_value_field(tag, vl) {
+                deferred = self.defer_value_field(tag, vr, vl, ts, in_pixeldata)?;
+            }
+            if shared.is_some() || deferred.is_some() || self.pending_value.is_some() {
+                Vec::with_capacity(0)
+            } else {
+                let bytes: Vec<u8> = self.read_value_field(tag, vl)?;
+                // A value which was clamped to the end of the dataset is shorter than its value
+                // length.
+                if let ValueLength::Explicit(len) = vl {
+                    if bytes.len() < usize::try_from(len).unwrap_or_default() {
+                        vl = ValueLength::Explicit(u32::try_from(bytes.len()).unwrap_or_default());
+                    }
+                }
+                bytes
+            }
         };
 
         let ancestors: Vec<SequenceElement> = self.current_path.clone();
@@ -155,7 +207,13 @@ impl<R: Read> Parser<'_, R> {
             self.cs
         };
 
-        Ok(DicomElement::new(tag, vr, vl, ts, cs, bytes, ancestors))
+        let mut element = DicomElement::new(tag, vr, vl, ts, cs, bytes, ancestors);
+        if let Some(deferred) = deferred {
+            element.set_deferred(deferred);
+        } else if let Some(shared) = shared {
+            element.set_shared(shared);
+        }
+        Ok(element)
     }
 
     /// Reads VR from the dataset. This should only be done for `ExplicitVR` transfer syntaxes.
@@ -168,12 +226,17 @@ impl<R: Read> Parser<'_, R> {
                 self.bytes_read += u64::try_from(bytes_read).unwrap_or_default();
                 Ok(vr)
             }
+            Err(ParseError::UnknownExplicitVR(code)) => {
+                // The two bytes of the unrecognized VR were still read from the dataset.
+                self.bytes_read += 2;
+                Err(ParseError::UnknownExplicitVR(code))
+            }
             Err(e) => Err(e),
         }
     }
 
     /// Looks up the implicit VR of the given tag in the current dictionary.
-    fn lookup_vr(&self, tag: u32) -> Option<VRRef> {
+    pub(super) fn lookup_vr(&self, tag: u32) -> Option<VRRef> {
         self.dictionary
             .get_tag_by_number(tag)
             .and_then(|read_tag: &Tag| read_tag.implicit_vr())
@@ -192,6 +255,145 @@ impl<R: Read> Parser<'_, R> {
         }
     }
 
+    /// Skips over the value field of the dicom element without reading it into memory. The dataset
+    /// is seeked past the value if the parser supports it, otherwise the value is read and
+    /// discarded in chunks.
+    fn skip_value_field(&mut self, tag: u32, vl: ValueLength, ts: TSRef) -> ParseResult<()> {
+        let ValueLength::Explicit(length) = vl else {
+            return Ok(());
+        };
+        let length: u64 = u64::from(length);
+        if length == 0 {
+            return Ok(());
+        }
+
+        // Deflated datasets have no correlation between positions in the dataset and positions in
+        // the decoded stream.
+        // Bytes pushed back onto the dataset are not accounted for by the position of the source.
+        if !ts.deflated() && !self.dataset.has_pushback() {
+            if let Some(source) = self.value_source.clone() {
+                source.skip(length)?;
+                self.bytes_read += length;
+                return Ok(());
+            }
+        }
+
+        let skipped: u64 = io::copy(&mut (&mut self.dataset).take(length), &mut io::sink())?;
+        self.bytes_read += skipped;
+        if skipped < length {
+            // See `read_value_field()` regarding datasets ending in trailing padding.
+            return if tag == 0 || tag == DATASET_TRAILING_PADDING {
+                Err(ParseError::ExpectedEOF)
+            } else if self.behavior.recovery().clamp_value_lengths() {
+                self.record_repair(
+                    tag,
+                    self.bytes_read - skipped,
+                    RecoveryAction::ClampedValueLength {
+                        declared: u32::try_from(length).unwrap_or_default(),
+                        actual: u32::try_from(skipped).unwrap_or_default(),
+                    },
+                );
+                Ok(())
+            } else {
+                Err(ParseError::IOError {
+                    source: ErrorKind::UnexpectedEof.into(),
+                })
+            };
+        }
+        Ok(())
+    }
+
+    /// Leaves the value field of the dicom element unread in the dataset if the parser is streaming
+    /// values to a visitor, recording its length in `self.pending_value`. The values of File Meta
+    /// elements and `SpecificCharacterSet` are always read as the parser relies on them.
+    fn stream_value_field(&mut self, tag: u32, vl: ValueLength) -> bool {
+        if !self.stream_values
+            || self.state != ParserState::ReadElement
+            || tag == SPECIFIC_CHARACTER_SET
+            || tag == TRANSFER_SYNTAX_UID
+        {
+            return false;
+        }
+        match vl {
+            ValueLength::Explicit(length) if length > 0 => {
+                self.pending_value = Some(length);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Skips over the value field of the dicom element if the dataset is an in-memory buffer,
+    /// returning a view of the value within the buffer rather than copying it. Values with an odd
+    /// length are not shared as they must be padded to be even-length, see `read_value_field()`.
+    fn share_value_field(
+        &mut self,
+        vl: ValueLength,
+        ts: TSRef,
+    ) -> ParseResult<Option<SharedBytes>> {
+        if ts.deflated() || self.dataset.has_pushback() {
+            return Ok(None);
+        }
+        let ValueLength::Explicit(length) = vl else {
+            return Ok(None);
+        };
+        if length == 0 || length % 2 != 0 {
+            return Ok(None);
+        }
+
+        let Some(source) = self.value_source.clone() else {
+            return Ok(None);
+        };
+        let position: u64 = source.position()?;
+        let length_usize: usize = usize::try_from(length).unwrap_or_default();
+        // If the value runs past the end of the buffer then read it normally, which reports the
+        // error appropriately.
+        let Some(shared) = source.view_at(position, length_usize) else {
+            return Ok(None);
+        };
+        source.skip(u64::from(length))?;
+        self.bytes_read += u64::from(length);
+        Ok(Some(shared))
+    }
+
+    /// Skips over the value field of the dicom element if the parser supports deferring values and
+    /// `ParseBehavior::defer_values()` selects this element, returning the recorded position of the
+    /// value within the dataset. Returns `None` if the value should instead be read.
+    fn defer_value_field(
+        &mut self,
+        tag: u32,
+        vr: VRRef,
+        vl: ValueLength,
+        ts: TSRef,
+        in_pixeldata: bool,
+    ) -> ParseResult<Option<DeferredValue>> {
+        // Deflated datasets have no correlation between positions in the dataset and positions in
+        // the decoded stream. Bytes pushed back onto the dataset are not accounted for by the
+        // position of the source.
+        if ts.deflated() || self.dataset.has_pushback() {
+            return Ok(None);
+        }
+        let ValueLength::Explicit(length) = vl else {
+            return Ok(None);
+        };
+        if length == 0
+            || !self
+                .behavior
+                .defer_values()
+                .evaluate(tag, vr, length, in_pixeldata)
+        {
+            return Ok(None);
+        }
+
+        let Some(source) = self.value_source.clone() else {
+            return Ok(None);
+        };
+        let position: u64 = source.position()?;
+        source.skip(u64::from(length))?;
+        self.bytes_read += u64::from(length);
+        Ok(Some(DeferredValue::new(source, position, length)))
+    }
+
     /// Reads the value field of the dicom element into a byte array. If the `ValueLength` is
     /// undefined then this returns an empty array as elements with undefined length should have
     /// their contents parsed as dicom elements.
@@ -200,6 +402,13 @@ impl<R: Read> Parser<'_, R> {
             // Undefined length means that the contents of the element are other dicom elements to
             // be parsed. Don't read data from the dataset in this case.
             ValueLength::Explicit(0) | ValueLength::UndefinedLength => Ok(Vec::with_capacity(0)),
+            ValueLength::Explicit(value_length)
+                if self.behavior.recovery().clamp_value_lengths()
+                    && tag != 0
+                    && tag != DATASET_TRAILING_PADDING =>
+            {
+                self.read_clamped_value_field(tag, value_length)
+            }
             ValueLength::Explicit(value_length) => {
                 // If length is odd we only read that exact bytes from the dataset but the bytes
                 // we should return from this should be padded with a zero in order to always
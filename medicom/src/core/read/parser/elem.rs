@@ -19,7 +19,7 @@
 //!
 //! The byte-level logic for reading an element is contained within the `dsread` module.
 
-use std::io::Read;
+use std::{io::Read, iter::once};
 
 use crate::core::{
     charset::CSRef,
@@ -33,10 +33,15 @@ use crate::core::{
             },
             ts::ImplicitVRLittleEndian,
         },
+        is_sq_delim,
+        tag::{TagNode, TagPath},
         ts::TSRef,
         vl::ValueLength,
     },
-    read::parser::{ParseResult, Parser},
+    read::{
+        parser::{ParseResult, Parser},
+        ParseError,
+    },
 };
 
 impl<'d, R: Read> Parser<'d, R> {
@@ -51,6 +56,7 @@ impl<'d, R: Read> Parser<'d, R> {
             self.dataset.set_read_deflated(ts.deflated());
         }
 
+        let previous_tag: u32 = self.tag_last_read;
         let tag: u32 = self.read_tag(ts)?;
         if self.is_at_parse_stop() {
             return Ok(None);
@@ -77,7 +83,24 @@ impl<'d, R: Read> Parser<'d, R> {
             }
         }
 
-        let element: DicomElement = self.read_dicom_element(tag, ts)?;
+        let excluded: bool = self.is_excluded(tag);
+        self.element_last_excluded = excluded;
+
+        let element: DicomElement = match self.read_dicom_element(tag, ts, excluded) {
+            // Only returned if allowed to resynchronize, skip ahead to the next plausible element
+            // and parse it instead. Following a delimiter any element may be next.
+            Err(ParseError::UnknownExplicitVR(_)) => {
+                self.partial_tag.take();
+                let after_tag: u32 = if previous_tag >> 16 == 0xFFFE {
+                    0
+                } else {
+                    previous_tag
+                };
+                self.resynchronize(ts, tag, after_tag)?;
+                return self.iterate_element();
+            }
+            result => result?,
+        };
 
         // if the file-meta state was skipped due to the initial detection we may still need to
         // switch transfer syntax -- only do this if the element is at the root of the dataset
@@ -85,7 +108,8 @@ impl<'d, R: Read> Parser<'d, R> {
             self.dataset_ts = self
                 .parse_transfer_syntax(&element)?
                 .or(Some(&ImplicitVRLittleEndian));
-        } else if element.tag() == SPECIFIC_CHARACTER_SET {
+        } else if element.tag() == SPECIFIC_CHARACTER_SET && !excluded {
+            // The value of SpecificCharacterSet is only skipped if within an excluded sequence.
             let cs: CSRef = Parser::<'d, R>::parse_specific_character_set(&element)?;
             if element.sq_path().is_empty() {
                 self.cs = cs;
@@ -114,7 +138,9 @@ impl<'d, R: Read> Parser<'d, R> {
 
         self.pop_sequence_items_based_on_byte_pos();
 
-        if element.is_sq_like() || tag == ITEM {
+        // Fragments of encapsulated pixel data have a value rather than containing elements, so
+        // they're not part of the path of the elements which follow.
+        if element.is_sq_like() || (tag == ITEM && !element.is_within_pixel_data()) {
             let seq_end_pos: Option<u64> = if let ValueLength::Explicit(len) = element.vl() {
                 Some(self.bytes_read + u64::from(len))
             } else {
@@ -127,15 +153,54 @@ impl<'d, R: Read> Parser<'d, R> {
                 self.cs
             };
 
-            self.current_path.push(SequenceElement::new(
-                tag,
-                seq_end_pos,
-                element.vr(),
-                element.vl(),
-                sq_cs,
-            ));
+            let mut sq_elem =
+                SequenceElement::new(tag, seq_end_pos, element.vr(), element.vl(), sq_cs);
+            sq_elem.set_excluded(excluded);
+            self.current_path.push(sq_elem);
+        } else if element.vl() == ValueLength::UndefinedLength
+            && !is_sq_delim(tag)
+            && self.behavior.recovery().resynchronize()
+        {
+            // Only sequences and encapsulated values may have undefined length, the length of this
+            // element's value is unknown so skip ahead to the next plausible element.
+            self.resynchronize(ts, tag, tag)?;
         }
 
         Ok(Some(element))
     }
+
+    /// Evaluates whether the element with the given tag, about to be read, is excluded by
+    /// `ParseBehavior::tag_filter()`. Elements within an excluded sequence are always excluded.
+    fn is_excluded(&self, tag: u32) -> bool {
+        if self
+            .current_path
+            .last()
+            .is_some_and(SequenceElement::is_excluded)
+        {
+            return true;
+        }
+
+        let tag_filter = self.behavior.tag_filter();
+        // Items and delimiters are only excluded along with their sequence, and the parser relies
+        // on the values of File Meta elements and SpecificCharacterSet.
+        if tag_filter.is_all()
+            || tag == ITEM
+            || tag == ITEM_DELIMITATION_ITEM
+            || tag == SEQUENCE_DELIMITATION_ITEM
+            || tag == SPECIFIC_CHARACTER_SET
+            || tag >> 16 == 0x0002
+        {
+            return false;
+        }
+
+        let tagpath: TagPath = self
+            .current_path
+            .iter()
+            .filter(|sq| sq.sq_tag() != ITEM)
+            .map(|sq| sq.node().clone())
+            .chain(once(tag.into()))
+            .collect::<Vec<TagNode>>()
+            .into();
+        !tag_filter.evaluate(&tagpath)
+    }
 }
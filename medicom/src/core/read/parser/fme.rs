@@ -30,7 +30,10 @@ use crate::core::{
         },
         ts::TSRef,
     },
-    read::parser::{ParseError, ParseResult, Parser, ParserState},
+    read::{
+        parser::{ParseError, ParseResult, Parser, ParserState},
+        recovery::RecoveryAction,
+    },
     values::ElementWithVr,
     DICOM_PREFIX, DICOM_PREFIX_LENGTH, FILE_PREAMBLE_LENGTH,
 };
@@ -80,7 +83,8 @@ impl<R: Read> Parser<'_, R> {
             return Ok(None);
         }
 
-        let grouplength: DicomElement = self.read_dicom_element(tag, ts)?;
+        let grouplength: DicomElement = self.read_dicom_element(tag, ts, false)?;
+        grouplength.load_value()?;
         self.fmi_grouplength = u32::try_from(&ElementWithVr::of(&grouplength))?;
         self.fmi_start = self.bytes_read;
         self.state = ParserState::ReadFileMeta;
@@ -92,18 +96,17 @@ impl<R: Read> Parser<'_, R> {
 
     /// Performs the `ParserState::FileMeta` iteration
     pub(super) fn iterate_file_meta(&mut self) -> ParseResult<Option<DicomElement>> {
+        // If the group length is only a hint then the end of file meta is found by reading the
+        // first element outside of it, see `end_file_meta_at_tag()`.
+        let group_length_as_hint: bool = self.behavior.recovery().group_length_as_hint();
+
         // check if we're about to read an element which is outside the file meta section, if so
         // then change states outside of this one.
-        if self.fmi_grouplength > 0
+        if !group_length_as_hint
+            && self.fmi_grouplength > 0
             && (self.bytes_read >= self.fmi_start + u64::from(self.fmi_grouplength))
         {
-            // if we never read a transfer syntax in the file-meta then jump back to detecting the
-            // transfer syntax of the main dataset.
-            self.state = if self.dataset_ts.is_some() {
-                ParserState::ReadElement
-            } else {
-                ParserState::DetectTransferSyntax
-            };
+            self.end_file_meta()?;
             return Ok(None);
         }
 
@@ -112,11 +115,15 @@ impl<R: Read> Parser<'_, R> {
         let ts: TSRef = self.detected_ts;
 
         let tag: u32 = self.read_tag(ts)?;
+        if group_length_as_hint && tag > FILE_META_GROUP_END {
+            self.end_file_meta_at_tag(tag, ts)?;
+            return Ok(None);
+        }
         if self.is_at_parse_stop() {
             return Ok(None);
         }
 
-        let element: DicomElement = self.read_dicom_element(tag, ts)?;
+        let element: DicomElement = self.read_dicom_element(tag, ts, false)?;
         if element.tag() == TRANSFER_SYNTAX_UID {
             match self.parse_transfer_syntax(&element) {
                 Ok(Some(ts)) => {
@@ -128,18 +135,12 @@ impl<R: Read> Parser<'_, R> {
         }
 
         // if group length was read use the byte position to determine if we're out of file-meta
-        if (self.fmi_grouplength > 0
+        if (!group_length_as_hint
+            && self.fmi_grouplength > 0
             && (self.bytes_read >= self.fmi_start + u64::from(self.fmi_grouplength)))
             || tag > FILE_META_GROUP_END
         {
-            // if we exit file-meta without having parsed transfer-syntax or if the ts is unknown to
-            // the dictionary used for parsing then flip to DetectState so the implicit vs. explicit
-            // can be detected since it's likely to change after file-meta.
-            self.state = if self.dataset_ts.is_some() {
-                ParserState::ReadElement
-            } else {
-                ParserState::DetectTransferSyntax
-            };
+            self.end_file_meta()?;
         }
 
         // reset partial_tag to None
@@ -147,4 +148,50 @@ impl<R: Read> Parser<'_, R> {
 
         Ok(Some(element))
     }
+
+    /// Ends the File Meta group after having read the tag of the first element outside of it, when
+    /// the File Meta group length is only considered a hint. The tag is pushed back onto the
+    /// dataset so it's read again in the transfer syntax of the dataset. If the group length does
+    /// not match the actual length of the group then the discrepancy is recorded.
+    fn end_file_meta_at_tag(&mut self, tag: u32, ts: TSRef) -> ParseResult<()> {
+        let group: u16 = u16::try_from(tag >> 16).unwrap_or_default();
+        let elem: u16 = u16::try_from(tag & 0x0000_FFFF).unwrap_or_default();
+        let tag_bytes: Vec<u8> = if ts.big_endian() {
+            [group.to_be_bytes(), elem.to_be_bytes()].concat()
+        } else {
+            [group.to_le_bytes(), elem.to_le_bytes()].concat()
+        };
+        self.dataset.unread(tag_bytes);
+        self.bytes_read -= 4;
+        self.partial_tag.take();
+
+        let actual: u64 = self.bytes_read - self.fmi_start;
+        if self.fmi_grouplength > 0 && actual != u64::from(self.fmi_grouplength) {
+            self.record_repair(
+                FILE_META_INFORMATION_GROUP_LENGTH,
+                self.fmi_start,
+                RecoveryAction::IgnoredGroupLength {
+                    declared: self.fmi_grouplength,
+                    actual,
+                },
+            );
+        }
+
+        self.end_file_meta()
+    }
+
+    /// Changes state after the File Meta group has been read. If the File Meta declared a transfer
+    /// syntax then it's verified against the encoding of the dataset, see `verify_dataset_ts()`.
+    fn end_file_meta(&mut self) -> ParseResult<()> {
+        // if we exit file-meta without having parsed transfer-syntax or if the ts is unknown to
+        // the dictionary used for parsing then flip to DetectState so the implicit vs. explicit
+        // can be detected since it's likely to change after file-meta.
+        if self.dataset_ts.is_some() {
+            self.state = ParserState::ReadElement;
+            self.verify_dataset_ts()
+        } else {
+            self.state = ParserState::DetectTransferSyntax;
+            Ok(())
+        }
+    }
 }
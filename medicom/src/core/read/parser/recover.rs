@@ -0,0 +1,289 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! This module contains the logic for repairing malformed datasets while parsing, as allowed by
+//! `ParseBehavior::recovery()`. Each repair is recorded as a `ParseDiagnostic`.
+
+use std::io::{ErrorKind, Read};
+
+use crate::core::{
+    defn::{
+        constants::tags::ITEM,
+        is_sq_delim,
+        tag::{Tag, TagNode, TagPath},
+        ts::TSRef,
+        vl::ValueLength,
+        vr::{VRRef, OB, OW, SQ, UN, VR},
+    },
+    read::{
+        parser::{ParseResult, Parser},
+        recovery::{ParseDiagnostic, RecoveryAction},
+    },
+};
+
+/// The lowest group number of elements which may be resynchronized on. Groups below this are
+/// either File Meta or command groups, which would not appear in the middle of a dataset.
+const MIN_RESYNC_GROUP: u32 = 0x0008;
+
+/// The length of the longest element header, an explicit VR with 2-byte padding.
+pub(super) const MAX_HEADER_LENGTH: usize = 12;
+
+/// The length of the shortest element header.
+pub(super) const MIN_HEADER_LENGTH: usize = 8;
+
+/// The result of checking whether bytes could be the header of an element.
+pub(super) enum Candidate {
+    /// The bytes are a plausible header of the given length.
+    Header {
+        length: usize,
+        tag: u32,
+        vr: Option<VRRef>,
+        vl: ValueLength,
+    },
+
+    /// More bytes are needed to determine whether the header is plausible.
+    Incomplete,
+
+    /// The bytes are not a plausible header.
+    Implausible,
+}
+
+impl<R: Read> Parser<'_, R> {
+    /// Records a repair made to the element with the given tag, which is within the current path.
+    pub(super) fn record_repair(&mut self, tag: u32, position: u64, action: RecoveryAction) {
+        let mut tagpath: TagPath = TagPath::from(self.current_path.as_slice());
+        tagpath.nodes_mut().push(TagNode::from(tag));
+        self.diagnostics
+            .push(ParseDiagnostic::new(tagpath, position, action));
+    }
+
+    /// Reads the value length of an element whose explicit VR was not recognized, as an Implicit
+    /// VR value length. The two bytes already read as the VR, given as `code`, are the first half of
+    /// the 4-byte value length.
+    pub(super) fn read_implicit_value_length(
+        &mut self,
+        code: u16,
+        ts: TSRef,
+    ) -> ParseResult<ValueLength> {
+        let mut buf: [u8; 4] = [0; 4];
+        buf[0..2].copy_from_slice(&code.to_be_bytes());
+        self.dataset.read_exact(&mut buf[2..4])?;
+        self.bytes_read += 2;
+        let value_length: u32 = if ts.big_endian() {
+            u32::from_be_bytes(buf)
+        } else {
+            u32::from_le_bytes(buf)
+        };
+        Ok(ValueLength::from(value_length))
+    }
+
+    /// Reads the value field of an element, truncating the value if its length runs past the end of
+    /// the dataset. As with `read_value_field()` odd-length values are padded to be even-length.
+    pub(super) fn read_clamped_value_field(
+        &mut self,
+        tag: u32,
+        value_length: u32,
+    ) -> ParseResult<Vec<u8>> {
+        let position: u64 = self.bytes_read;
+        let length: usize = usize::try_from(value_length).unwrap_or_default();
+        // The length may be wildly incorrect, avoid allocating for it up-front.
+        let mut buffer: Vec<u8> = Vec::with_capacity(length.min(1024 * 1024) + 1);
+        (&mut self.dataset)
+            .take(u64::from(value_length))
+            .read_to_end(&mut buffer)?;
+        self.bytes_read += u64::try_from(buffer.len()).unwrap_or_default();
+
+        if buffer.len() < length {
+            let actual: u32 = u32::try_from(buffer.len()).unwrap_or_default();
+            self.record_repair(
+                tag,
+                position,
+                RecoveryAction::ClampedValueLength {
+                    declared: value_length,
+                    actual,
+                },
+            );
+        }
+        if !buffer.len().is_multiple_of(2) {
+            buffer.push(0);
+        }
+        Ok(buffer)
+    }
+
+    /// Skips forward through the dataset to the next plausible element header, after the element
+    /// with the given tag could not be parsed. The header found is read into the `self.partial_`
+    /// fields so parsing resumes with that element. Any bytes read beyond the header are pushed
+    /// back onto the dataset.
+    ///
+    /// A header is plausible if its tag is a sequence delimiter while within a sequence, or if its
+    /// tag is greater than `after_tag` and its VR and value length are valid. For Implicit VR
+    /// transfer syntaxes the tag must also be known to the dictionary, or be private.
+    ///
+    /// If the dataset ends before a plausible header is found then parsing will end with the next
+    /// element.
+    pub(super) fn resynchronize(&mut self, ts: TSRef, tag: u32, after_tag: u32) -> ParseResult<()> {
+        let position: u64 = self.bytes_read;
+        let mut window: Vec<u8> = Vec::with_capacity(MAX_HEADER_LENGTH);
+        let mut skipped: u64 = 0;
+        loop {
+            if !self.fill_window(&mut window, MIN_HEADER_LENGTH)? {
+                skipped += u64::try_from(window.len()).unwrap_or_default();
+                break;
+            }
+
+            let mut candidate = self.evaluate_candidate(&window, ts, after_tag);
+            if matches!(candidate, Candidate::Incomplete) {
+                candidate = if self.fill_window(&mut window, MAX_HEADER_LENGTH)? {
+                    self.evaluate_candidate(&window, ts, after_tag)
+                } else {
+                    Candidate::Implausible
+                };
+            }
+
+            if let Candidate::Header {
+                length,
+                tag: found_tag,
+                vr,
+                vl,
+            } = candidate
+            {
+                self.dataset.unread(window.split_off(length));
+                self.bytes_read += skipped + u64::try_from(length).unwrap_or_default();
+                self.partial_tag = Some(found_tag);
+                self.partial_vr = vr;
+                self.partial_vl = Some(vl);
+                self.record_repair(tag, position, RecoveryAction::Resynchronized { skipped });
+                return Ok(());
+            }
+
+            // Elements are always even-length so a header can only begin on an even byte.
+            window.drain(..2);
+            skipped += 2;
+        }
+
+        self.bytes_read += skipped;
+        self.record_repair(tag, position, RecoveryAction::Resynchronized { skipped });
+        Ok(())
+    }
+
+    /// Reads from the dataset until the window holds at least `length` bytes. Returns `false` if
+    /// the dataset ends first.
+    fn fill_window(&mut self, window: &mut Vec<u8>, length: usize) -> ParseResult<bool> {
+        let mut buf: [u8; MAX_HEADER_LENGTH] = [0; MAX_HEADER_LENGTH];
+        while window.len() < length {
+            let needed: usize = length - window.len();
+            match self.dataset.read(&mut buf[..needed]) {
+                Ok(0) => return Ok(false),
+                Ok(n) => window.extend_from_slice(&buf[..n]),
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(true)
+    }
+
+    /// Evaluates whether the start of the window is a plausible element header.
+    pub(super) fn evaluate_candidate(&self, window: &[u8], ts: TSRef, after_tag: u32) -> Candidate {
+        let read_u16 = |bytes: &[u8]| -> u16 {
+            let bytes: [u8; 2] = [bytes[0], bytes[1]];
+            if ts.big_endian() {
+                u16::from_be_bytes(bytes)
+            } else {
+                u16::from_le_bytes(bytes)
+            }
+        };
+        let read_u32 = |bytes: &[u8]| -> u32 {
+            let bytes: [u8; 4] = [bytes[0], bytes[1], bytes[2], bytes[3]];
+            if ts.big_endian() {
+                u32::from_be_bytes(bytes)
+            } else {
+                u32::from_le_bytes(bytes)
+            }
+        };
+
+        let tag: u32 =
+            (u32::from(read_u16(&window[0..2])) << 16) | u32::from(read_u16(&window[2..4]));
+
+        // Delimiters are always encoded as Implicit VR, and only appear within sequences.
+        if is_sq_delim(tag) {
+            let vl: ValueLength = ValueLength::from(read_u32(&window[4..8]));
+            let plausible: bool = !self.current_path.is_empty()
+                && match vl {
+                    ValueLength::Explicit(len) => {
+                        len.is_multiple_of(2) && (tag == ITEM || len == 0)
+                    }
+                    ValueLength::UndefinedLength => tag == ITEM,
+                };
+            return if plausible {
+                Candidate::Header {
+                    length: MIN_HEADER_LENGTH,
+                    tag,
+                    vr: None,
+                    vl,
+                }
+            } else {
+                Candidate::Implausible
+            };
+        }
+
+        if tag >> 16 < MIN_RESYNC_GROUP || tag >> 16 == 0xFFFF || tag <= after_tag {
+            return Candidate::Implausible;
+        }
+
+        let (length, vr, vl): (usize, Option<VRRef>, ValueLength) = if ts.explicit_vr() {
+            let code: u16 = (u16::from(window[4]) << 8) + u16::from(window[5]);
+            let Some(vr) = VR::from_code(code) else {
+                return Candidate::Implausible;
+            };
+            if vr.has_explicit_2byte_pad {
+                if window.len() < MAX_HEADER_LENGTH {
+                    return Candidate::Incomplete;
+                }
+                if window[6] != 0 || window[7] != 0 {
+                    return Candidate::Implausible;
+                }
+                let vl: ValueLength = ValueLength::from(read_u32(&window[8..12]));
+                (MAX_HEADER_LENGTH, Some(vr), vl)
+            } else {
+                let vl: ValueLength = ValueLength::Explicit(u32::from(read_u16(&window[6..8])));
+                (MIN_HEADER_LENGTH, Some(vr), vl)
+            }
+        } else {
+            if !Tag::is_private(tag) && self.lookup_vr(tag).is_none() {
+                return Candidate::Implausible;
+            }
+            let vl: ValueLength = ValueLength::from(read_u32(&window[4..8]));
+            (MIN_HEADER_LENGTH, None, vl)
+        };
+
+        let plausible: bool = match vl {
+            ValueLength::Explicit(len) => len.is_multiple_of(2),
+            ValueLength::UndefinedLength => {
+                vr.is_none_or(|vr| vr == &SQ || vr == &UN || vr == &OB || vr == &OW)
+            }
+        };
+        if plausible {
+            Candidate::Header {
+                length,
+                tag,
+                vr,
+                vl,
+            }
+        } else {
+            Candidate::Implausible
+        }
+    }
+}
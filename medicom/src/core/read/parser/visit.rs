@@ -0,0 +1,181 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! This module contains the logic for driving a `DicomVisitor` from the parser, translating the
+//! elements parsed into structural events and streaming values in chunks.
+
+use std::io::{ErrorKind, Read};
+
+use crate::core::{
+    dcmelement::DicomElement,
+    defn::constants::tags::{
+        DATASET_TRAILING_PADDING, ITEM, ITEM_DELIMITATION_ITEM, SEQUENCE_DELIMITATION_ITEM,
+    },
+    read::{
+        parser::{ParseResult, Parser},
+        visitor::DicomVisitor,
+        ParseError,
+    },
+};
+
+/// The kinds of elements which contain other elements, or fragments, and are ended by a
+/// delimiter or by reaching the end of their value length.
+enum Container {
+    Sequence,
+    Item,
+    EncapsulatedPixelData,
+}
+
+impl<R: Read> Parser<'_, R> {
+    /// Parses the dataset, reporting its structure to the given visitor and streaming element
+    /// values to it in chunks of at most `DicomVisitor::chunk_size()` bytes. Element values are
+    /// read from the dataset as they're streamed, and are never held entirely in memory. The
+    /// exceptions are `SpecificCharacterSet` and the File Meta elements, which the parser relies
+    /// on, and the values of parsers created with `ParserBuilder::build_shared()`.
+    ///
+    /// # Errors
+    /// Errors parsing the dataset, or errors returned by the visitor.
+    pub fn visit<V: DicomVisitor + ?Sized>(&mut self, visitor: &mut V) -> ParseResult<()> {
+        self.stream_values = true;
+        let result = self.visit_elements(visitor);
+        self.stream_values = false;
+        self.pending_value = None;
+        result
+    }
+
+    fn visit_elements<V: DicomVisitor + ?Sized>(&mut self, visitor: &mut V) -> ParseResult<()> {
+        let mut buffer: Vec<u8> = vec![0u8; visitor.chunk_size().max(1)];
+        let mut open: Vec<(DicomElement, Container)> = Vec::new();
+        let mut fragment_index: usize = 0;
+
+        while let Some(element) = self.next() {
+            let element = element?;
+            let tag = element.tag();
+
+            // Delimiters end the container they're within, otherwise containers are ended when an
+            // element is encountered outside of them.
+            let depth = Parser::<R>::structural_depth(&element);
+            let depth = if tag == ITEM_DELIMITATION_ITEM || tag == SEQUENCE_DELIMITATION_ITEM {
+                depth.saturating_sub(1)
+            } else {
+                depth
+            };
+            while open.len() > depth {
+                Parser::<R>::end_container(visitor, open.pop())?;
+            }
+
+            if tag == ITEM_DELIMITATION_ITEM || tag == SEQUENCE_DELIMITATION_ITEM {
+                continue;
+            }
+
+            if tag == ITEM && element.is_within_pixel_data() {
+                visitor.fragment(&element, fragment_index)?;
+                fragment_index += 1;
+                self.stream_value(visitor, &element, &mut buffer)?;
+                visitor.end_element(&element)?;
+            } else if tag == ITEM {
+                visitor.start_item(&element)?;
+                open.push((element, Container::Item));
+            } else if element.is_sq_like() && element.is_pixel_data() {
+                visitor.start_element(&element)?;
+                fragment_index = 0;
+                open.push((element, Container::EncapsulatedPixelData));
+            } else if element.is_sq_like() {
+                visitor.start_sequence(&element)?;
+                open.push((element, Container::Sequence));
+            } else {
+                visitor.start_element(&element)?;
+                let streamed = self.stream_value(visitor, &element, &mut buffer);
+                // See `read_value_field()` regarding datasets ending in trailing padding.
+                let ended = matches!(streamed, Err(ParseError::ExpectedEOF));
+                if !ended {
+                    streamed?;
+                }
+                visitor.end_element(&element)?;
+                if ended {
+                    break;
+                }
+            }
+        }
+
+        while !open.is_empty() {
+            Parser::<R>::end_container(visitor, open.pop())?;
+        }
+        Ok(())
+    }
+
+    /// Gives the value of the element to the visitor in chunks. If the value was left unread in
+    /// the dataset it's read from the dataset in chunks, otherwise the element's value is chunked.
+    fn stream_value<V: DicomVisitor + ?Sized>(
+        &mut self,
+        visitor: &mut V,
+        element: &DicomElement,
+        buffer: &mut [u8],
+    ) -> ParseResult<()> {
+        let Some(length) = self.pending_value.take() else {
+            for chunk in element.load_value()?.chunks(buffer.len()) {
+                visitor.value_chunk(element, chunk)?;
+            }
+            return Ok(());
+        };
+
+        let mut remaining: usize = usize::try_from(length).unwrap_or_default();
+        while remaining > 0 {
+            let chunk_len = remaining.min(buffer.len());
+            let chunk = &mut buffer[..chunk_len];
+            self.dataset.read_exact(chunk).map_err(|e| {
+                if e.kind() == ErrorKind::UnexpectedEof
+                    && (element.tag() == 0 || element.tag() == DATASET_TRAILING_PADDING)
+                {
+                    ParseError::ExpectedEOF
+                } else {
+                    ParseError::IOError { source: e }
+                }
+            })?;
+            self.bytes_read += u64::try_from(chunk_len).unwrap_or_default();
+            remaining -= chunk_len;
+            visitor.value_chunk(element, chunk)?;
+        }
+        Ok(())
+    }
+
+    fn end_container<V: DicomVisitor + ?Sized>(
+        visitor: &mut V,
+        container: Option<(DicomElement, Container)>,
+    ) -> ParseResult<()> {
+        match container {
+            Some((element, Container::Sequence)) => visitor.end_sequence(&element),
+            Some((element, Container::Item)) => visitor.end_item(&element),
+            Some((element, Container::EncapsulatedPixelData)) => visitor.end_element(&element),
+            None => Ok(()),
+        }
+    }
+
+    /// The number of sequences and items the element is within. Fragments of encapsulated
+    /// `PixelData` are not counted as items, as they contain no elements.
+    fn structural_depth(element: &DicomElement) -> usize {
+        let mut depth: usize = 0;
+        let mut in_pixeldata: bool = false;
+        for sq in element.sq_path() {
+            if in_pixeldata && sq.sq_tag() == ITEM {
+                continue;
+            }
+            in_pixeldata = sq.is_pixel_data();
+            depth += 1;
+        }
+        depth
+    }
+}
@@ -0,0 +1,236 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Recovering from malformed datasets.
+//!
+//! By default the parser fails on the first malformed element it encounters. A lenient
+//! `RecoveryPolicy` instead allows the parser to repair the dataset as it's parsed, recording
+//! each repair as a `ParseDiagnostic`, see `Parser::diagnostics()`.
+
+use std::fmt;
+
+use crate::core::defn::{tag::TagPath, ts::TSRef};
+
+/// `RecoveryPolicy` specifies which repairs the parser may make to malformed datasets in order to
+/// continue parsing. Each repair made is recorded as a `ParseDiagnostic`.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RecoveryPolicy {
+    /// Specifies whether an element in an Explicit VR transfer syntax whose VR is not recognized
+    /// should be re-read as Implicit VR, where the bytes read as the VR are instead the first half
+    /// of a 4-byte value length. Elements from Implicit VR datasets which were copied into Explicit
+    /// VR datasets without conversion are a common source of this.
+    ///
+    /// Default: `false`.
+    retry_implicit_vr: bool,
+
+    /// Specifies whether an element value whose length runs past the end of the dataset should be
+    /// truncated to the bytes remaining in the dataset.
+    ///
+    /// Default: `false`.
+    clamp_value_lengths: bool,
+
+    /// Specifies whether the parser should skip forward to the next plausible element after
+    /// encountering an element which can't be parsed, such as one with an unrecognized VR or a
+    /// non-sequence element with undefined length.
+    ///
+    /// Default: `false`.
+    resynchronize: bool,
+
+    /// Specifies whether the File Meta group length should only be considered a hint. The end of
+    /// the File Meta group is instead determined by reading an element outside of group `0002`.
+    ///
+    /// Default: `false`.
+    group_length_as_hint: bool,
+}
+
+impl RecoveryPolicy {
+    /// A policy which makes no repairs, the parser fails on malformed datasets.
+    #[must_use]
+    pub fn strict() -> RecoveryPolicy {
+        RecoveryPolicy::default()
+    }
+
+    /// A policy which makes all repairs.
+    #[must_use]
+    pub fn lenient() -> RecoveryPolicy {
+        RecoveryPolicy {
+            retry_implicit_vr: true,
+            clamp_value_lengths: true,
+            resynchronize: true,
+            group_length_as_hint: true,
+        }
+    }
+
+    /// Whether this policy makes no repairs.
+    #[must_use]
+    pub fn is_strict(&self) -> bool {
+        *self == RecoveryPolicy::strict()
+    }
+
+    /// Specifies whether an element in an Explicit VR transfer syntax whose VR is not recognized
+    /// should be re-read as Implicit VR.
+    ///
+    /// Default: `false`.
+    #[must_use]
+    pub fn retry_implicit_vr(&self) -> bool {
+        self.retry_implicit_vr
+    }
+
+    /// Specifies whether an element value whose length runs past the end of the dataset should be
+    /// truncated to the bytes remaining in the dataset.
+    ///
+    /// Default: `false`.
+    #[must_use]
+    pub fn clamp_value_lengths(&self) -> bool {
+        self.clamp_value_lengths
+    }
+
+    /// Specifies whether the parser should skip forward to the next plausible element after
+    /// encountering an element which can't be parsed.
+    ///
+    /// Default: `false`.
+    #[must_use]
+    pub fn resynchronize(&self) -> bool {
+        self.resynchronize
+    }
+
+    /// Specifies whether the File Meta group length should only be considered a hint.
+    ///
+    /// Default: `false`.
+    #[must_use]
+    pub fn group_length_as_hint(&self) -> bool {
+        self.group_length_as_hint
+    }
+
+    /// Specify whether an element whose VR is not recognized should be re-read as Implicit VR.
+    pub fn set_retry_implicit_vr(&mut self, retry_implicit_vr: bool) {
+        self.retry_implicit_vr = retry_implicit_vr;
+    }
+
+    /// Specify whether an element value whose length runs past the end of the dataset should be
+    /// truncated.
+    pub fn set_clamp_value_lengths(&mut self, clamp_value_lengths: bool) {
+        self.clamp_value_lengths = clamp_value_lengths;
+    }
+
+    /// Specify whether the parser should skip forward to the next plausible element after
+    /// encountering an element which can't be parsed.
+    pub fn set_resynchronize(&mut self, resynchronize: bool) {
+        self.resynchronize = resynchronize;
+    }
+
+    /// Specify whether the File Meta group length should only be considered a hint.
+    pub fn set_group_length_as_hint(&mut self, group_length_as_hint: bool) {
+        self.group_length_as_hint = group_length_as_hint;
+    }
+}
+
+/// A repair made by the parser to a malformed dataset.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RecoveryAction {
+    /// An unrecognized explicit VR, the given code, was re-read as Implicit VR.
+    RetriedAsImplicitVR(u16),
+
+    /// A value length which runs past the end of the dataset was truncated to the bytes
+    /// remaining.
+    ClampedValueLength { declared: u32, actual: u32 },
+
+    /// Bytes were skipped over to reach the next plausible element.
+    Resynchronized { skipped: u64 },
+
+    /// The File Meta group length did not match the actual length of the File Meta group.
+    IgnoredGroupLength { declared: u32, actual: u64 },
+
+    /// The transfer syntax declared by the File Meta `TransferSyntaxUID` did not match the
+    /// encoding of the dataset, which was instead parsed with the detected transfer syntax.
+    OverrodeTransferSyntax { declared: TSRef, detected: TSRef },
+}
+
+impl fmt::Display for RecoveryAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecoveryAction::RetriedAsImplicitVR(code) => {
+                write!(f, "unknown explicit vr {code:#06X} re-read as implicit vr")
+            }
+            RecoveryAction::ClampedValueLength { declared, actual } => write!(
+                f,
+                "value length {declared} runs past end of dataset, truncated to {actual}"
+            ),
+            RecoveryAction::Resynchronized { skipped } => {
+                write!(f, "skipped {skipped} bytes to the next plausible element")
+            }
+            RecoveryAction::IgnoredGroupLength { declared, actual } => write!(
+                f,
+                "file meta group length {declared} does not match actual length {actual}"
+            ),
+            RecoveryAction::OverrodeTransferSyntax { declared, detected } => write!(
+                f,
+                "declared transfer syntax {} does not match encoding, detected {}",
+                declared.uid().ident(),
+                detected.uid().ident()
+            ),
+        }
+    }
+}
+
+/// A record of a repair made by the parser to a malformed dataset.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseDiagnostic {
+    tagpath: TagPath,
+    position: u64,
+    action: RecoveryAction,
+}
+
+impl ParseDiagnostic {
+    pub(crate) fn new(tagpath: TagPath, position: u64, action: RecoveryAction) -> Self {
+        Self {
+            tagpath,
+            position,
+            action,
+        }
+    }
+
+    /// The path of the element which was repaired.
+    #[must_use]
+    pub fn tagpath(&self) -> &TagPath {
+        &self.tagpath
+    }
+
+    /// The byte position within the dataset at which the repair was made.
+    #[must_use]
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// The repair which was made.
+    #[must_use]
+    pub fn action(&self) -> &RecoveryAction {
+        &self.action
+    }
+}
+
+impl fmt::Display for ParseDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} @ byte pos {:#X}: {}",
+            TagPath::format_tagpath_to_display(&self.tagpath, None),
+            self.position,
+            self.action
+        )
+    }
+}
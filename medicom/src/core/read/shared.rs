@@ -0,0 +1,167 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Zero-copy parsing of datasets which are entirely in memory.
+//!
+//! When a `Parser` is built over an in-memory buffer, such as a memory-mapped file, the values of
+//! the elements it produces are views into that buffer rather than copies of it. The buffer is
+//! reference-counted and remains alive as long as the parser or any element referencing it.
+
+use std::{
+    fmt,
+    io::{Error, ErrorKind, Read, Result},
+    ops::Range,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use crate::core::read::deferred::ValueSource;
+
+/// A reference-counted buffer which element values can be views into.
+pub type SharedBuffer = Arc<dyn AsRef<[u8]> + Send + Sync>;
+
+/// A view of a range of bytes within a `SharedBuffer`.
+#[derive(Clone)]
+pub struct SharedBytes {
+    buffer: SharedBuffer,
+    range: Range<usize>,
+}
+
+impl fmt::Debug for SharedBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SharedBytes {:?}", self.range)
+    }
+}
+
+impl SharedBytes {
+    /// Creates a view of the given range of the buffer. Returns `None` if the range is not within
+    /// the bounds of the buffer.
+    #[must_use]
+    pub fn new(buffer: SharedBuffer, range: Range<usize>) -> Option<Self> {
+        if range.start > range.end || range.end > (*buffer).as_ref().len() {
+            return None;
+        }
+        Some(Self { buffer, range })
+    }
+
+    /// The byte position of the view within the buffer.
+    #[must_use]
+    pub fn position(&self) -> usize {
+        self.range.start
+    }
+
+    /// The viewed bytes.
+    #[must_use]
+    pub fn as_slice(&self) -> &[u8] {
+        &(*self.buffer).as_ref()[self.range.clone()]
+    }
+
+    /// The number of viewed bytes.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.range.len()
+    }
+
+    /// Whether the view is of zero bytes.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.range.is_empty()
+    }
+}
+
+/// A dataset over an in-memory buffer, shared between a `Parser` and the elements it produces.
+/// Created by `ParserBuilder::build_shared()`.
+pub struct SharedDataset {
+    inner: Arc<SharedSource>,
+}
+
+impl fmt::Debug for SharedDataset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "SharedDataset @ {}",
+            self.inner.pos.load(Ordering::Relaxed)
+        )
+    }
+}
+
+impl SharedDataset {
+    pub(crate) fn new(buffer: SharedBuffer) -> Self {
+        Self {
+            inner: Arc::new(SharedSource {
+                buffer,
+                pos: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// The handle given to the parser for creating views of element values.
+    pub(crate) fn value_source(&self) -> Arc<dyn ValueSource> {
+        self.inner.clone()
+    }
+}
+
+impl Read for SharedDataset {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let bytes: &[u8] = (*self.inner.buffer).as_ref();
+        let pos: usize = self.inner.pos.load(Ordering::Acquire).min(bytes.len());
+        let count: usize = buf.len().min(bytes.len() - pos);
+        buf[..count].copy_from_slice(&bytes[pos..pos + count]);
+        self.inner.pos.store(pos + count, Ordering::Release);
+        Ok(count)
+    }
+}
+
+struct SharedSource {
+    buffer: SharedBuffer,
+    pos: AtomicUsize,
+}
+
+impl SharedSource {
+    fn range(&self, position: u64, length: usize) -> Result<Range<usize>> {
+        let start: usize = usize::try_from(position).map_err(Error::other)?;
+        let end: usize = start
+            .checked_add(length)
+            .filter(|end| *end <= (*self.buffer).as_ref().len())
+            .ok_or_else(|| Error::from(ErrorKind::UnexpectedEof))?;
+        Ok(start..end)
+    }
+}
+
+impl ValueSource for SharedSource {
+    fn position(&self) -> Result<u64> {
+        u64::try_from(self.pos.load(Ordering::Acquire)).map_err(Error::other)
+    }
+
+    fn skip(&self, length: u64) -> Result<()> {
+        let length: usize = usize::try_from(length).map_err(Error::other)?;
+        self.pos.fetch_add(length, Ordering::AcqRel);
+        Ok(())
+    }
+
+    fn read_at(&self, position: u64, buf: &mut [u8]) -> Result<()> {
+        let range = self.range(position, buf.len())?;
+        buf.copy_from_slice(&(*self.buffer).as_ref()[range]);
+        Ok(())
+    }
+
+    fn view_at(&self, position: u64, length: usize) -> Option<SharedBytes> {
+        let range = self.range(position, length).ok()?;
+        SharedBytes::new(self.buffer.clone(), range)
+    }
+}
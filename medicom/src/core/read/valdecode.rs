@@ -24,7 +24,7 @@ use crate::core::{
     defn::{
         tag::{Tag, TagNode},
         vr::{
-            VRRef, AT, CS_SEPARATOR, DS, FD, FL, IS, NULL_PADDING, OD, OF, OL, OV, OW, SL,
+            VRRef, AT, CS_SEPARATOR, DS, FD, FL, IS, NULL_PADDING, OD, OF, OL, OV, OW, PN, SL,
             SPACE_PADDING, SS, SV, UI, UL, UN, US, UV, VR,
         },
     },
@@ -202,12 +202,8 @@ impl TryFrom<&ElementWithVr<'_>> for String {
     /// All character string VR's -- subsequent interpretation of String is necessary based on VR
     /// AE, AS, CS, DA, DS, DT, IS, LO, LT, PN, SH, ST, TM, UC, UI, UR, UT
     fn try_from(value: &ElementWithVr<'_>) -> ParseResult<Self> {
-        let element: &DicomElement = value.0;
         let data: &[u8] = BytesWithoutPadding::from(value).0;
-        element
-            .cs()
-            .decode(data)
-            .map_err(|e| ParseError::CharsetError { source: e })
+        decode_text(value.0, value.1, data)
     }
 }
 
@@ -236,21 +232,29 @@ impl TryFrom<&ElementWithVr<'_>> for Vec<String> {
         let element: &DicomElement = value.0;
         let vr: VRRef = value.1;
         let data: &[u8] = BytesWithoutPadding::from(value).0;
-        element
-            .cs()
-            .decode(data)
-            .map_err(|e| ParseError::CharsetError { source: e })
-            .map(|multivalue: String| {
-                if vr.allows_backslash_text_value {
-                    vec![multivalue]
-                } else {
-                    multivalue
-                        .split(CS_SEPARATOR)
-                        .map(str::to_owned)
-                        .collect::<Vec<String>>()
-                }
-            })
+        decode_text(element, vr, data).map(|multivalue: String| {
+            if vr.allows_backslash_text_value {
+                vec![multivalue]
+            } else {
+                multivalue
+                    .split(CS_SEPARATOR)
+                    .map(str::to_owned)
+                    .collect::<Vec<String>>()
+            }
+        })
+    }
+}
+
+/// Decodes the text of a value using the element's character set, handling the delimiters of
+/// person names for PN.
+fn decode_text(element: &DicomElement, vr: VRRef, data: &[u8]) -> ParseResult<String> {
+    let cs = element.cs();
+    if vr == &PN {
+        cs.decode_person_name(data)
+    } else {
+        cs.decode(data)
     }
+    .map_err(|e| ParseError::CharsetError { source: e })
 }
 
 impl<'e> From<&ElementWithVr<'e>> for BytesWithoutPadding<'e> {
@@ -261,7 +265,7 @@ impl<'e> From<&ElementWithVr<'e>> for BytesWithoutPadding<'e> {
         // grab the position to start reading bytes from prior to computing the new bytes_read
         let mut left_index: usize = 0;
 
-        let data: &[u8] = value.0.data().as_slice();
+        let data: &[u8] = value.0.data();
         if data.is_empty() {
             return BytesWithoutPadding(data);
         }
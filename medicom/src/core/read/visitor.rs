@@ -0,0 +1,117 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Streaming, event-based parsing of a dataset.
+//!
+//! As an alternative to iterating over the `DicomElement`s of a `Parser`, which holds each
+//! element's entire value in memory, `Parser::visit()` reports the structure of the dataset to a
+//! `DicomVisitor` and streams element values to it in bounded chunks. This allows processing
+//! datasets with very large values, such as `PixelData`, in constant memory.
+
+use crate::core::{dcmelement::DicomElement, read::ParseResult};
+
+/// The default maximum number of bytes of a value given to `DicomVisitor::value_chunk()`.
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Receives events while parsing a dataset with `Parser::visit()`. All methods have a default
+/// implementation which does nothing, so implementations only need to handle the events they're
+/// interested in.
+///
+/// The `DicomElement`s given to these methods have no value loaded. Values are given to
+/// `value_chunk()` as they're read from the dataset. Elements excluded by the parser's
+/// `TagFilter` produce no events.
+///
+/// Any error returned from these methods stops parsing and is returned from `Parser::visit()`.
+pub trait DicomVisitor {
+    /// The maximum number of bytes to give to each call to `value_chunk()`.
+    fn chunk_size(&self) -> usize {
+        DEFAULT_CHUNK_SIZE
+    }
+
+    /// The start of an element which has a value. This is followed by zero or more calls to
+    /// `value_chunk()` and then `end_element()`.
+    ///
+    /// This is also the start of encapsulated `PixelData`, which is followed by calls to
+    /// `fragment()` for each of its fragments and then `end_element()`.
+    ///
+    /// # Errors
+    /// Errors from the visitor, which stop parsing.
+    fn start_element(&mut self, _element: &DicomElement) -> ParseResult<()> {
+        Ok(())
+    }
+
+    /// A chunk of the value of the element most recently started with `start_element()` or
+    /// `fragment()`. The chunks are the bytes of the value as encoded in the dataset, which is not
+    /// padded to be even-length.
+    ///
+    /// # Errors
+    /// Errors from the visitor, which stop parsing.
+    fn value_chunk(&mut self, _element: &DicomElement, _chunk: &[u8]) -> ParseResult<()> {
+        Ok(())
+    }
+
+    /// The end of an element started with `start_element()` or `fragment()`.
+    ///
+    /// # Errors
+    /// Errors from the visitor, which stop parsing.
+    fn end_element(&mut self, _element: &DicomElement) -> ParseResult<()> {
+        Ok(())
+    }
+
+    /// The start of a sequence element. This is followed by calls to `start_item()` for each item
+    /// in the sequence and then `end_sequence()`.
+    ///
+    /// # Errors
+    /// Errors from the visitor, which stop parsing.
+    fn start_sequence(&mut self, _element: &DicomElement) -> ParseResult<()> {
+        Ok(())
+    }
+
+    /// The end of a sequence element, given the element which started the sequence.
+    ///
+    /// # Errors
+    /// Errors from the visitor, which stop parsing.
+    fn end_sequence(&mut self, _element: &DicomElement) -> ParseResult<()> {
+        Ok(())
+    }
+
+    /// The start of an item within a sequence. This is followed by events for the elements within
+    /// the item and then `end_item()`.
+    ///
+    /// # Errors
+    /// Errors from the visitor, which stop parsing.
+    fn start_item(&mut self, _element: &DicomElement) -> ParseResult<()> {
+        Ok(())
+    }
+
+    /// The end of an item within a sequence, given the element which started the item.
+    ///
+    /// # Errors
+    /// Errors from the visitor, which stop parsing.
+    fn end_item(&mut self, _element: &DicomElement) -> ParseResult<()> {
+        Ok(())
+    }
+
+    /// The start of a fragment of encapsulated `PixelData`, where `index` is the 0-based index of
+    /// the fragment. The first fragment is the Basic Offset Table. This is followed by zero or more
+    /// calls to `value_chunk()` and then `end_element()`.
+    ///
+    /// # Errors
+    /// Errors from the visitor, which stop parsing.
+    fn fragment(&mut self, _element: &DicomElement, _index: usize) -> ParseResult<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,363 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Serde support for the object model, enabled with the `serde` feature.
+//!
+//! `DicomRoot`, `DicomObject`, `DicomElement`, and `SequenceElement` serialize the tag, VR, value
+//! length, transfer syntax, character set, and value bytes of every element so that a dataset
+//! deserializes to the same structure it was serialized from. References to definitions are
+//! serialized by identifier and looked up when deserializing:
+//! - A VR by its two-letter identifier, e.g. `"PN"`.
+//! - A transfer syntax by its UID. Only the transfer syntaxes defined in `defn::constants::ts`
+//!   can be resolved, along with those of the standard dictionary if the `stddicom` feature is
+//!   enabled.
+//! - A character set by the name of its encoding, e.g. `"windows-1252"`.
+//!
+//! Values which are deferred are loaded when serialized, and values which are views of a shared
+//! buffer are copied when deserialized. The modules `vr`, `ts`, and `cs` can be used with
+//! `#[serde(with = "...")]` to serialize these references within other structures.
+
+use std::{borrow::Cow, collections::BTreeMap};
+
+use serde::{ser::Error as SerError, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::core::{
+    charset::CSRef,
+    dcmelement::DicomElement,
+    dcmobject::{DicomObject, DicomRoot},
+    dcmsqelem::SequenceElement,
+    defn::{tag::TagNode, ts::TSRef, vl::ValueLength, vr::VRRef},
+};
+
+/// Serializes a `VRRef` by its two-letter identifier.
+pub mod vr {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    use crate::core::defn::vr::{VRRef, INVALID_VR, VR};
+
+    /// # Errors
+    /// Errors from the serializer.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn serialize<S: Serializer>(vr: &VRRef, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(vr.ident)
+    }
+
+    /// # Errors
+    /// If the identifier is not a known VR.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<VRRef, D::Error> {
+        let ident: String = String::deserialize(deserializer)?;
+        VR::from_ident(&ident)
+            .or_else(|| (ident == INVALID_VR.ident).then_some(&INVALID_VR))
+            .ok_or_else(|| D::Error::custom(format!("unknown VR: {ident}")))
+    }
+}
+
+/// Serializes a `TSRef` by its UID.
+pub mod ts {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    use crate::core::defn::{
+        constants::ts::{
+            DeflatedExplicitVRLittleEndian, ExplicitVRBigEndian, ExplicitVRLittleEndian,
+            ImplicitVRBigEndian, ImplicitVRLittleEndian,
+        },
+        ts::TSRef,
+    };
+
+    /// The transfer syntaxes which can be resolved without a dictionary.
+    static KNOWN_TS: [TSRef; 5] = [
+        &ImplicitVRLittleEndian,
+        &ExplicitVRLittleEndian,
+        &ExplicitVRBigEndian,
+        &DeflatedExplicitVRLittleEndian,
+        &ImplicitVRBigEndian,
+    ];
+
+    /// The identifier a transfer syntax is serialized as. Transfer syntaxes which only exist for
+    /// completeness, such as Implicit VR Big Endian, have no UID and use their name instead.
+    fn key(ts: TSRef) -> &'static str {
+        if ts.uid().uid().is_empty() {
+            ts.uid().ident()
+        } else {
+            ts.uid().uid()
+        }
+    }
+
+    /// # Errors
+    /// Errors from the serializer.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn serialize<S: Serializer>(ts: &TSRef, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(key(ts))
+    }
+
+    /// # Errors
+    /// If the UID is not a transfer syntax which can be resolved.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<TSRef, D::Error> {
+        let uid: String = String::deserialize(deserializer)?;
+        let known: Option<TSRef> = KNOWN_TS.iter().copied().find(|ts| key(ts) == uid);
+        #[cfg(feature = "stddicom")]
+        let known: Option<TSRef> = known.or_else(|| {
+            use crate::{
+                core::defn::dcmdict::DicomDictionary, dict::stdlookup::STANDARD_DICOM_DICTIONARY,
+            };
+            STANDARD_DICOM_DICTIONARY.get_ts_by_uid(&uid)
+        });
+        known.ok_or_else(|| D::Error::custom(format!("unknown transfer syntax: {uid}")))
+    }
+}
+
+/// Serializes a `CSRef` by the name of its encoding, or by the values of `SpecificCharacterSet`
+/// separated by backslash for character sets using code extensions.
+pub mod cs {
+    use encoding_rs::Encoding;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    use crate::core::charset::CSRef;
+
+    /// # Errors
+    /// Errors from the serializer.
+    pub fn serialize<S: Serializer>(cs: &CSRef, serializer: S) -> Result<S::Ok, S::Error> {
+        let code_extensions: Vec<&str> = cs.code_extensions();
+        if code_extensions.is_empty() {
+            serializer.serialize_str(cs.name())
+        } else {
+            serializer.serialize_str(&code_extensions.join("\\"))
+        }
+    }
+
+    /// # Errors
+    /// If the name is not a known encoding.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<CSRef, D::Error> {
+        let name: String = String::deserialize(deserializer)?;
+        Encoding::for_label(name.as_bytes())
+            .map(CSRef::of)
+            .or_else(|| {
+                let values: Vec<&str> = name.split('\\').collect();
+                CSRef::lookup_specific_character_set(&values)
+            })
+            .ok_or_else(|| D::Error::custom(format!("unknown character set: {name}")))
+    }
+}
+
+/// The serialized form of a `DicomElement`.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "DicomElement")]
+struct ElementRepr<'a> {
+    tag: u32,
+    #[serde(with = "vr")]
+    vr: VRRef,
+    vl: ValueLength,
+    #[serde(with = "ts")]
+    ts: TSRef,
+    #[serde(with = "cs")]
+    cs: CSRef,
+    sq_path: Cow<'a, [SequenceElement]>,
+    #[serde(
+        serialize_with = "serde_bytes::serialize",
+        deserialize_with = "deserialize_bytes"
+    )]
+    data: Cow<'a, [u8]>,
+}
+
+impl Serialize for DicomElement {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let data: &[u8] = self.load_value().map_err(S::Error::custom)?;
+        ElementRepr {
+            tag: self.tag(),
+            vr: self.vr(),
+            vl: self.vl(),
+            ts: self.ts(),
+            cs: self.cs(),
+            sq_path: Cow::Borrowed(self.sq_path()),
+            data: Cow::Borrowed(data),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DicomElement {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr: ElementRepr = ElementRepr::deserialize(deserializer)?;
+        Ok(DicomElement::new(
+            repr.tag,
+            repr.vr,
+            repr.vl,
+            repr.ts,
+            repr.cs,
+            repr.data.into_owned(),
+            repr.sq_path.into_owned(),
+        ))
+    }
+}
+
+/// The serialized form of a `SequenceElement`.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "SequenceElement")]
+struct SequenceElementRepr<'a> {
+    node: Cow<'a, TagNode>,
+    sq_end_pos: Option<u64>,
+    #[serde(with = "vr")]
+    vr: VRRef,
+    vl: ValueLength,
+    #[serde(with = "cs")]
+    cs: CSRef,
+    excluded: bool,
+}
+
+impl Serialize for SequenceElement {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SequenceElementRepr {
+            node: Cow::Borrowed(self.node()),
+            sq_end_pos: self.sq_end_pos(),
+            vr: self.vr(),
+            vl: self.vl(),
+            cs: self.cs(),
+            excluded: self.is_excluded(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SequenceElement {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr: SequenceElementRepr = SequenceElementRepr::deserialize(deserializer)?;
+        let mut sq_elem =
+            SequenceElement::new(repr.node.tag(), repr.sq_end_pos, repr.vr, repr.vl, repr.cs);
+        sq_elem.set_item(repr.node.item());
+        sq_elem.set_excluded(repr.excluded);
+        Ok(sq_elem)
+    }
+}
+
+/// Serializes the child nodes of an object as a sequence, in tag order. Each is keyed by the tag of
+/// its element so the tags are not serialized separately.
+struct ChildNodes<'a>(&'a DicomObject);
+
+impl Serialize for ChildNodes<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter_child_nodes().map(|(_tag, child)| child))
+    }
+}
+
+/// Serializes the items of an object as a sequence.
+struct Items<'a>(&'a DicomObject);
+
+impl Serialize for Items<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter_items())
+    }
+}
+
+/// The serialized form of a `DicomObject`.
+#[derive(Serialize)]
+#[serde(rename = "DicomObject")]
+struct ObjectRef<'a> {
+    element: &'a DicomElement,
+    children: ChildNodes<'a>,
+    items: Items<'a>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "DicomObject")]
+struct ObjectRepr {
+    element: DicomElement,
+    children: Vec<DicomObject>,
+    items: Vec<DicomObject>,
+}
+
+impl Serialize for DicomObject {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ObjectRef {
+            element: self.element(),
+            children: ChildNodes(self),
+            items: Items(self),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DicomObject {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr: ObjectRepr = ObjectRepr::deserialize(deserializer)?;
+        Ok(DicomObject::new_with_children(
+            repr.element,
+            to_child_nodes(repr.children),
+            repr.items,
+        ))
+    }
+}
+
+/// The serialized form of a `DicomRoot`. The root object does not represent an element, so only
+/// its child nodes and items are serialized.
+#[derive(Serialize)]
+#[serde(rename = "DicomRoot")]
+struct RootRef<'a> {
+    #[serde(with = "ts")]
+    ts: TSRef,
+    #[serde(with = "cs")]
+    cs: CSRef,
+    children: ChildNodes<'a>,
+    items: Items<'a>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "DicomRoot")]
+struct RootRepr {
+    #[serde(with = "ts")]
+    ts: TSRef,
+    #[serde(with = "cs")]
+    cs: CSRef,
+    children: Vec<DicomObject>,
+    items: Vec<DicomObject>,
+}
+
+impl Serialize for DicomRoot {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RootRef {
+            ts: self.ts(),
+            cs: self.cs(),
+            children: ChildNodes(self.as_obj()),
+            items: Items(self.as_obj()),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DicomRoot {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr: RootRepr = RootRepr::deserialize(deserializer)?;
+        Ok(DicomRoot::new(
+            repr.ts,
+            repr.cs,
+            to_child_nodes(repr.children),
+            repr.items,
+        ))
+    }
+}
+
+fn to_child_nodes(children: Vec<DicomObject>) -> BTreeMap<u32, DicomObject> {
+    children
+        .into_iter()
+        .map(|child| (child.element().tag(), child))
+        .collect()
+}
+
+/// Deserializes bytes into an owned buffer, as element values never borrow from the input.
+fn deserialize_bytes<'de, 'a, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Cow<'a, [u8]>, D::Error> {
+    serde_bytes::ByteBuf::deserialize(deserializer).map(|bytes| Cow::Owned(bytes.into_vec()))
+}
@@ -14,7 +14,22 @@
    limitations under the License.
 */
 
-use crate::{core::dcmelement::DicomElement, core::defn::vr::VRRef};
+pub mod age;
+pub mod datetime;
+pub mod pn;
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    core::dcmelement::DicomElement,
+    core::defn::vr::VRRef,
+    core::read::ParseError,
+    core::values::{
+        age::DicomAge,
+        datetime::{DicomDate, DicomDateTime, DicomRange, DicomTime},
+        pn::PersonName,
+    },
+};
 
 /// Wrapper around `&[u8]` for getting a slice of the element value without the padding values.
 #[derive(Debug)]
@@ -33,10 +48,12 @@ impl<'e> ElementWithVr<'e> {
 
 /// Wrapper around `u32` for parsing DICOM Attributes.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Attribute(pub u32);
 
 /// Wrapper around an element's value parsed into a native/raw type.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RawValue<'e> {
     Attributes(Vec<Attribute>),
     Uid(String),
@@ -49,12 +66,12 @@ pub enum RawValue<'e> {
     ULongs(Vec<u64>),
     Floats(Vec<f32>),
     Doubles(Vec<f64>),
-    Bytes(Vec<u8>),
+    Bytes(#[cfg_attr(feature = "serde", serde(with = "serde_bytes"))] Vec<u8>),
     Words(Vec<u16>),
     DWords(Vec<u32>),
     QWords(Vec<u64>),
 
-    BytesView(&'e Vec<u8>),
+    BytesView(#[cfg_attr(feature = "serde", serde(borrow, with = "serde_bytes"))] &'e [u8]),
 }
 
 impl<'e> RawValue<'e> {
@@ -160,6 +177,13 @@ impl<'e> RawValue<'e> {
         RawValue::QWords(vec![qword])
     }
 
+    /// Convenience for `RawValue::Strings` of multiple typed values, such as `DicomDate` or
+    /// `PersonName`, each in its encoded form.
+    #[must_use]
+    pub fn of_typed<T: Display>(values: &[T]) -> RawValue<'e> {
+        RawValue::Strings(values.iter().map(ToString::to_string).collect())
+    }
+
     #[must_use]
     pub fn attr(&self) -> Option<&Attribute> {
         match self {
@@ -240,4 +264,156 @@ impl<'e> RawValue<'e> {
             _ => None,
         }
     }
+
+    /// Parses the first value as a date, for a VR of DA.
+    #[must_use]
+    pub fn date(&self) -> Option<DicomDate> {
+        self.parse_first()
+    }
+
+    /// Parses all values as dates, for a VR of DA. Returns `None` if any value is invalid.
+    #[must_use]
+    pub fn dates(&self) -> Option<Vec<DicomDate>> {
+        self.parse_all()
+    }
+
+    /// Parses the first value as a time, for a VR of TM.
+    #[must_use]
+    pub fn time(&self) -> Option<DicomTime> {
+        self.parse_first()
+    }
+
+    /// Parses all values as times, for a VR of TM. Returns `None` if any value is invalid.
+    #[must_use]
+    pub fn times(&self) -> Option<Vec<DicomTime>> {
+        self.parse_all()
+    }
+
+    /// Parses the first value as a date time, for a VR of DT.
+    #[must_use]
+    pub fn datetime(&self) -> Option<DicomDateTime> {
+        self.parse_first()
+    }
+
+    /// Parses all values as date times, for a VR of DT. Returns `None` if any value is invalid.
+    #[must_use]
+    pub fn datetimes(&self) -> Option<Vec<DicomDateTime>> {
+        self.parse_all()
+    }
+
+    /// Parses the first value as a range of dates or times, as used in query keys for a VR of DA,
+    /// TM, or DT.
+    #[must_use]
+    pub fn range<T>(&self) -> Option<DicomRange<T>>
+    where
+        T: FromStr<Err = ParseError> + Clone,
+    {
+        self.parse_first()
+    }
+
+    /// Parses the first value as an age, for a VR of AS.
+    #[must_use]
+    pub fn age(&self) -> Option<DicomAge> {
+        self.parse_first()
+    }
+
+    /// Parses the first value as a person name, for a VR of PN.
+    #[must_use]
+    pub fn person_name(&self) -> Option<PersonName> {
+        self.parse_first()
+    }
+
+    /// Parses all values as person names, for a VR of PN. Returns `None` if any value is invalid.
+    #[must_use]
+    pub fn person_names(&self) -> Option<Vec<PersonName>> {
+        self.parse_all()
+    }
+
+    /// The values as decimals, for a VR of DS. Integer values, such as those of IS, and unparsed
+    /// strings are also converted.
+    #[must_use]
+    pub fn decimals(&self) -> Option<Vec<f64>> {
+        match self {
+            RawValue::Doubles(doubles) => Some(doubles.clone()),
+            RawValue::Floats(floats) => Some(floats.iter().map(|f| f64::from(*f)).collect()),
+            RawValue::Ints(ints) => Some(ints.iter().map(|i| f64::from(*i)).collect()),
+            RawValue::Strings(_) => self.parse_all(),
+            _ => None,
+        }
+    }
+
+    /// The values as integers, for a VR of IS. Decimal values are only converted if they have no
+    /// fractional part, and unparsed strings are also converted.
+    #[must_use]
+    pub fn integers(&self) -> Option<Vec<i64>> {
+        match self {
+            RawValue::Ints(ints) => Some(ints.iter().map(|i| i64::from(*i)).collect()),
+            RawValue::Longs(longs) => Some(longs.clone()),
+            RawValue::Shorts(shorts) => Some(shorts.iter().map(|s| i64::from(*s)).collect()),
+            RawValue::UShorts(ushorts) => Some(ushorts.iter().map(|s| i64::from(*s)).collect()),
+            RawValue::Doubles(doubles) => doubles
+                .iter()
+                .map(|d| {
+                    // IS values are limited to 12 characters, so any integral value is exact.
+                    #[allow(clippy::cast_possible_truncation)]
+                    (d.fract() == 0.0 && d.abs() < 1e15).then_some(*d as i64)
+                })
+                .collect(),
+            RawValue::Strings(_) => self.parse_all(),
+            _ => None,
+        }
+    }
+
+    /// Parses the first string value as `T`.
+    fn parse_first<T: FromStr>(&self) -> Option<T> {
+        self.string()
+            .and_then(|value| value.trim().parse::<T>().ok())
+    }
+
+    /// Parses all string values as `T`, failing if any are invalid.
+    fn parse_all<T: FromStr>(&self) -> Option<Vec<T>> {
+        match self {
+            RawValue::Strings(strings) => strings
+                .iter()
+                .map(|value| value.trim().parse::<T>().ok())
+                .collect(),
+            _ => None,
+        }
+    }
+}
+
+impl From<DicomDate> for RawValue<'_> {
+    fn from(value: DicomDate) -> Self {
+        RawValue::Strings(vec![value.to_string()])
+    }
+}
+
+impl From<DicomTime> for RawValue<'_> {
+    fn from(value: DicomTime) -> Self {
+        RawValue::Strings(vec![value.to_string()])
+    }
+}
+
+impl From<DicomDateTime> for RawValue<'_> {
+    fn from(value: DicomDateTime) -> Self {
+        RawValue::Strings(vec![value.to_string()])
+    }
+}
+
+impl From<DicomAge> for RawValue<'_> {
+    fn from(value: DicomAge) -> Self {
+        RawValue::Strings(vec![value.to_string()])
+    }
+}
+
+impl From<PersonName> for RawValue<'_> {
+    fn from(value: PersonName) -> Self {
+        RawValue::Strings(vec![value.to_string()])
+    }
+}
+
+impl<T: Display + PartialEq> From<DicomRange<T>> for RawValue<'_> {
+    fn from(value: DicomRange<T>) -> Self {
+        RawValue::Strings(vec![value.to_string()])
+    }
 }
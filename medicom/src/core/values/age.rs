@@ -0,0 +1,126 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Typed value for the Age String (AS) value representation: `nnnU`, a number of days, weeks,
+//! months, or years. Parsing is lenient of lowercase units and numbers with fewer than three
+//! digits, which are zero-padded when displayed.
+
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use crate::core::{
+    read::{ParseError, ParseResult},
+    values::datetime::{invalid, parse_digits, trim_value},
+};
+
+/// The unit of a `DicomAge`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AgeUnit {
+    Days,
+    Weeks,
+    Months,
+    Years,
+}
+
+impl AgeUnit {
+    /// The character which represents the unit in an encoded value.
+    #[must_use]
+    pub fn code(&self) -> char {
+        match self {
+            AgeUnit::Days => 'D',
+            AgeUnit::Weeks => 'W',
+            AgeUnit::Months => 'M',
+            AgeUnit::Years => 'Y',
+        }
+    }
+}
+
+/// An age, as encoded by the AS value representation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DicomAge {
+    value: u16,
+    unit: AgeUnit,
+}
+
+impl DicomAge {
+    /// Creates an age.
+    ///
+    /// # Errors
+    /// `ParseError::GeneralDecodeError` if the value does not fit in three digits.
+    pub fn new(value: u16, unit: AgeUnit) -> ParseResult<DicomAge> {
+        if value > 999 {
+            return Err(invalid("age", value));
+        }
+        Ok(DicomAge { value, unit })
+    }
+
+    #[must_use]
+    pub fn value(&self) -> u16 {
+        self.value
+    }
+
+    #[must_use]
+    pub fn unit(&self) -> AgeUnit {
+        self.unit
+    }
+}
+
+impl FromStr for DicomAge {
+    type Err = ParseError;
+
+    /// Parses an age in the format `nnnU`. The unit may be lowercase and the number may have fewer
+    /// than three digits.
+    fn from_str(value: &str) -> ParseResult<Self> {
+        let value: &str = trim_value(value);
+        let Some(code) = value.chars().last() else {
+            return Err(invalid("age", value));
+        };
+        let unit: AgeUnit = match code.to_ascii_uppercase() {
+            'D' => AgeUnit::Days,
+            'W' => AgeUnit::Weeks,
+            'M' => AgeUnit::Months,
+            'Y' => AgeUnit::Years,
+            _ => return Err(invalid("age unit", value)),
+        };
+        let number: &str = &value[..value.len() - code.len_utf8()];
+        DicomAge::new(parse_digits(number.trim(), "age")?, unit)
+    }
+}
+
+impl Display for DicomAge {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:03}{}", self.value, self.unit.code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AgeUnit, DicomAge};
+
+    #[test]
+    fn test_parse_ages() {
+        let age: DicomAge = "042Y".parse().expect("age");
+        assert_eq!((42, AgeUnit::Years), (age.value(), age.unit()));
+        assert_eq!("042Y", age.to_string());
+        assert_eq!("006M", "6m".parse::<DicomAge>().unwrap().to_string());
+        assert_eq!("010D", "10 D".parse::<DicomAge>().unwrap().to_string());
+        assert!("1000Y".parse::<DicomAge>().is_err());
+        assert!("042".parse::<DicomAge>().is_err());
+        assert!("Y".parse::<DicomAge>().is_err());
+    }
+}
@@ -0,0 +1,907 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Typed values for the Date (DA), Time (TM), and Date Time (DT) value representations, along with
+//! ranges of them as used in queries. Refer to Part 5, Section 6.2 and Part 4, Section C.2.2.2.5.
+//!
+//! Values may have partial precision, such as a date with only a year, or a time with only hours.
+//! Parsing is lenient of some common malformations, such as the separators used by ACR-NEMA
+//! (`YYYY.MM.DD` and `HH:MM:SS`), surrounding whitespace, and fractional seconds with more than six
+//! digits. Values are always displayed in the standard encoding.
+
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use crate::core::read::{ParseError, ParseResult};
+
+/// The maximum number of digits of fractional seconds.
+const FRACTION_DIGITS: u8 = 6;
+
+/// A value which may have partial precision, and so represents a span of time.
+pub trait PartialPrecision: Ord + Sized {
+    /// The earliest complete value within the span of this value.
+    #[must_use]
+    fn earliest(&self) -> Self;
+
+    /// The latest complete value within the span of this value.
+    #[must_use]
+    fn latest(&self) -> Self;
+}
+
+/// A date, as encoded by the DA value representation: `YYYYMMDD`, where the month and day may be
+/// omitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DicomDate {
+    year: u16,
+    month: Option<u8>,
+    day: Option<u8>,
+}
+
+impl DicomDate {
+    /// Creates a date, which may omit the day or both the month and day.
+    ///
+    /// # Errors
+    /// `ParseError::GeneralDecodeError` if any component is out of range, or a day is given
+    /// without a month.
+    pub fn new(year: u16, month: Option<u8>, day: Option<u8>) -> ParseResult<DicomDate> {
+        if year > 9999 {
+            return Err(invalid("year", year));
+        }
+        if let Some(month) = month {
+            if !(1..=12).contains(&month) {
+                return Err(invalid("month", month));
+            }
+        }
+        match (month, day) {
+            (None, Some(_)) => return Err(invalid("date without month", year)),
+            (Some(month), Some(day)) if day < 1 || day > days_in_month(year, month) => {
+                return Err(invalid("day", day));
+            }
+            _ => {}
+        }
+        Ok(DicomDate { year, month, day })
+    }
+
+    /// Creates a complete date.
+    ///
+    /// # Errors
+    /// `ParseError::GeneralDecodeError` if any component is out of range.
+    pub fn from_ymd(year: u16, month: u8, day: u8) -> ParseResult<DicomDate> {
+        DicomDate::new(year, Some(month), Some(day))
+    }
+
+    #[must_use]
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    #[must_use]
+    pub fn month(&self) -> Option<u8> {
+        self.month
+    }
+
+    #[must_use]
+    pub fn day(&self) -> Option<u8> {
+        self.day
+    }
+
+    /// Whether the date has a year, month, and day.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.day.is_some()
+    }
+}
+
+impl PartialPrecision for DicomDate {
+    fn earliest(&self) -> Self {
+        DicomDate {
+            year: self.year,
+            month: Some(self.month.unwrap_or(1)),
+            day: Some(self.day.unwrap_or(1)),
+        }
+    }
+
+    fn latest(&self) -> Self {
+        let month: u8 = self.month.unwrap_or(12);
+        DicomDate {
+            year: self.year,
+            month: Some(month),
+            day: Some(self.day.unwrap_or_else(|| days_in_month(self.year, month))),
+        }
+    }
+}
+
+impl FromStr for DicomDate {
+    type Err = ParseError;
+
+    /// Parses a date in the format `YYYYMMDD`, `YYYYMM`, or `YYYY`. The separated format
+    /// `YYYY.MM.DD` from ACR-NEMA is also accepted, as are `-` and `/` separators.
+    fn from_str(value: &str) -> ParseResult<Self> {
+        let value: &str = trim_value(value);
+        if !value.is_ascii() {
+            return Err(invalid("date", value));
+        }
+        let bytes: &[u8] = value.as_bytes();
+        let compact: String = if bytes.len() == 10
+            && matches!(bytes[4], b'.' | b'-' | b'/')
+            && bytes[4] == bytes[7]
+        {
+            [&value[0..4], &value[5..7], &value[8..10]].concat()
+        } else {
+            value.to_string()
+        };
+
+        match compact.len() {
+            4 | 6 | 8 => {}
+            _ => return Err(invalid("date", value)),
+        }
+        let year: u16 = parse_digits(&compact[0..4], "year")?;
+        let month: Option<u8> = compact
+            .get(4..6)
+            .map(|month| parse_digits(month, "month"))
+            .transpose()?;
+        let day: Option<u8> = compact
+            .get(6..8)
+            .map(|day| parse_digits(day, "day"))
+            .transpose()?;
+        DicomDate::new(year, month, day)
+    }
+}
+
+impl Display for DicomDate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}", self.year)?;
+        if let Some(month) = self.month {
+            write!(f, "{month:02}")?;
+        }
+        if let Some(day) = self.day {
+            write!(f, "{day:02}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A time of day, as encoded by the TM value representation: `HHMMSS.FFFFFF`, where the minutes,
+/// seconds, and fractional seconds may be omitted. Fractional seconds may have fewer than six
+/// digits, which is retained when displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DicomTime {
+    hour: u8,
+    minute: Option<u8>,
+    second: Option<u8>,
+    micros: Option<u32>,
+    fraction_digits: u8,
+}
+
+impl DicomTime {
+    /// Creates a time, which may omit the trailing components. A second of 60 is allowed for leap
+    /// seconds.
+    ///
+    /// # Errors
+    /// `ParseError::GeneralDecodeError` if any component is out of range, or a component is given
+    /// without the preceding component.
+    pub fn new(
+        hour: u8,
+        minute: Option<u8>,
+        second: Option<u8>,
+        micros: Option<u32>,
+    ) -> ParseResult<DicomTime> {
+        let fraction_digits: u8 = if micros.is_some() { FRACTION_DIGITS } else { 0 };
+        DicomTime::with_fraction_digits(hour, minute, second, micros, fraction_digits)
+    }
+
+    fn with_fraction_digits(
+        hour: u8,
+        minute: Option<u8>,
+        second: Option<u8>,
+        micros: Option<u32>,
+        fraction_digits: u8,
+    ) -> ParseResult<DicomTime> {
+        if hour > 23 {
+            return Err(invalid("hour", hour));
+        }
+        if minute.is_some_and(|minute| minute > 59) {
+            return Err(invalid("minute", minute.unwrap_or_default()));
+        }
+        if second.is_some_and(|second| second > 60) {
+            return Err(invalid("second", second.unwrap_or_default()));
+        }
+        if micros.is_some_and(|micros| micros > 999_999) {
+            return Err(invalid("fractional seconds", micros.unwrap_or_default()));
+        }
+        if (minute.is_none() && second.is_some()) || (second.is_none() && micros.is_some()) {
+            return Err(invalid("time missing components", hour));
+        }
+        Ok(DicomTime {
+            hour,
+            minute,
+            second,
+            micros,
+            fraction_digits,
+        })
+    }
+
+    #[must_use]
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    #[must_use]
+    pub fn minute(&self) -> Option<u8> {
+        self.minute
+    }
+
+    #[must_use]
+    pub fn second(&self) -> Option<u8> {
+        self.second
+    }
+
+    /// The fractional seconds, in microseconds.
+    #[must_use]
+    pub fn micros(&self) -> Option<u32> {
+        self.micros
+    }
+
+    /// The number of digits of fractional seconds, up to six.
+    #[must_use]
+    pub fn fraction_digits(&self) -> u8 {
+        self.fraction_digits
+    }
+}
+
+impl PartialPrecision for DicomTime {
+    fn earliest(&self) -> Self {
+        DicomTime {
+            hour: self.hour,
+            minute: Some(self.minute.unwrap_or(0)),
+            second: Some(self.second.unwrap_or(0)),
+            micros: Some(self.micros.unwrap_or(0)),
+            fraction_digits: FRACTION_DIGITS,
+        }
+    }
+
+    fn latest(&self) -> Self {
+        // A value with fewer fractional digits spans all values with more digits.
+        let micros: u32 = match self.micros {
+            Some(micros) => {
+                micros + 10u32.pow(u32::from(FRACTION_DIGITS - self.fraction_digits)) - 1
+            }
+            None => 999_999,
+        };
+        DicomTime {
+            hour: self.hour,
+            minute: Some(self.minute.unwrap_or(59)),
+            second: Some(self.second.unwrap_or(59)),
+            micros: Some(micros),
+            fraction_digits: FRACTION_DIGITS,
+        }
+    }
+}
+
+impl FromStr for DicomTime {
+    type Err = ParseError;
+
+    /// Parses a time in the format `HHMMSS.FFFFFF`, where the trailing components may be omitted.
+    /// The separated format `HH:MM:SS.FFFFFF` from ACR-NEMA is also accepted. Fractional seconds
+    /// with more than six digits are truncated.
+    fn from_str(value: &str) -> ParseResult<Self> {
+        let value: &str = trim_value(value);
+        if !value.is_ascii() {
+            return Err(invalid("time", value));
+        }
+        let (whole, fraction) = match value.split_once('.') {
+            Some((whole, fraction)) => (whole, Some(fraction)),
+            None => (value, None),
+        };
+        let whole: String = if whole.len() > 2 && whole.as_bytes()[2] == b':' {
+            whole.replace(':', "")
+        } else {
+            whole.to_string()
+        };
+
+        match whole.len() {
+            2 | 4 | 6 => {}
+            _ => return Err(invalid("time", value)),
+        }
+        let hour: u8 = parse_digits(&whole[0..2], "hour")?;
+        let minute: Option<u8> = whole
+            .get(2..4)
+            .map(|minute| parse_digits(minute, "minute"))
+            .transpose()?;
+        let second: Option<u8> = whole
+            .get(4..6)
+            .map(|second| parse_digits(second, "second"))
+            .transpose()?;
+
+        let (micros, fraction_digits) = match fraction {
+            None => (None, 0),
+            Some("") => return Err(invalid("fractional seconds", value)),
+            Some(fraction) => {
+                let digits: &str = fraction
+                    .get(..usize::from(FRACTION_DIGITS))
+                    .unwrap_or(fraction);
+                let num_digits: u8 = u8::try_from(digits.len()).unwrap_or(FRACTION_DIGITS);
+                let parsed: u32 = parse_digits(digits, "fractional seconds")?;
+                let micros: u32 = parsed * 10u32.pow(u32::from(FRACTION_DIGITS - num_digits));
+                (Some(micros), num_digits)
+            }
+        };
+        DicomTime::with_fraction_digits(hour, minute, second, micros, fraction_digits)
+    }
+}
+
+impl Display for DicomTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}", self.hour)?;
+        if let Some(minute) = self.minute {
+            write!(f, "{minute:02}")?;
+        }
+        if let Some(second) = self.second {
+            write!(f, "{second:02}")?;
+        }
+        if let Some(micros) = self.micros {
+            let digits: usize = usize::from(self.fraction_digits);
+            let fraction: u32 =
+                micros / 10u32.pow(u32::from(FRACTION_DIGITS - self.fraction_digits));
+            write!(f, ".{fraction:0digits$}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A date and time, as encoded by the DT value representation: `YYYYMMDDHHMMSS.FFFFFF&ZZXX`, where
+/// trailing components may be omitted and `&ZZXX` is an optional offset from UTC.
+///
+/// Ordering compares the components as written, without adjusting for the UTC offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DicomDateTime {
+    date: DicomDate,
+    time: Option<DicomTime>,
+    offset_minutes: Option<i16>,
+}
+
+impl DicomDateTime {
+    /// Creates a date time. A time may only be given with a complete date.
+    ///
+    /// `offset_minutes`: The offset from UTC, in minutes.
+    ///
+    /// # Errors
+    /// `ParseError::GeneralDecodeError` if a time is given with a partial date, or the offset is
+    /// out of range.
+    pub fn new(
+        date: DicomDate,
+        time: Option<DicomTime>,
+        offset_minutes: Option<i16>,
+    ) -> ParseResult<DicomDateTime> {
+        if time.is_some() && !date.is_complete() {
+            return Err(invalid("time with partial date", date));
+        }
+        if offset_minutes.is_some_and(|offset| !(-12 * 60..=14 * 60).contains(&offset)) {
+            return Err(invalid("UTC offset", offset_minutes.unwrap_or_default()));
+        }
+        Ok(DicomDateTime {
+            date,
+            time,
+            offset_minutes,
+        })
+    }
+
+    #[must_use]
+    pub fn date(&self) -> &DicomDate {
+        &self.date
+    }
+
+    #[must_use]
+    pub fn time(&self) -> Option<&DicomTime> {
+        self.time.as_ref()
+    }
+
+    /// The offset from UTC, in minutes.
+    #[must_use]
+    pub fn offset_minutes(&self) -> Option<i16> {
+        self.offset_minutes
+    }
+}
+
+impl PartialPrecision for DicomDateTime {
+    fn earliest(&self) -> Self {
+        let midnight = DicomTime {
+            hour: 0,
+            minute: None,
+            second: None,
+            micros: None,
+            fraction_digits: 0,
+        };
+        DicomDateTime {
+            date: self.date.earliest(),
+            time: Some(self.time.unwrap_or(midnight).earliest()),
+            offset_minutes: self.offset_minutes,
+        }
+    }
+
+    fn latest(&self) -> Self {
+        let end_of_day = DicomTime {
+            hour: 23,
+            minute: None,
+            second: None,
+            micros: None,
+            fraction_digits: 0,
+        };
+        DicomDateTime {
+            date: self.date.latest(),
+            time: Some(self.time.unwrap_or(end_of_day).latest()),
+            offset_minutes: self.offset_minutes,
+        }
+    }
+}
+
+impl FromStr for DicomDateTime {
+    type Err = ParseError;
+
+    /// Parses a date time in the format `YYYYMMDDHHMMSS.FFFFFF&ZZXX`, where trailing components of
+    /// the date and time may be omitted. The date and time may also be separated by `T` or a space,
+    /// in which case each is parsed as with `DicomDate` and `DicomTime`.
+    fn from_str(value: &str) -> ParseResult<Self> {
+        let value: &str = trim_value(value);
+        // Components are split by byte position, which are only character boundaries for ASCII.
+        if !value.is_ascii() {
+            return Err(invalid("date time", value));
+        }
+
+        let mut rest: &str = value;
+        let mut offset_minutes: Option<i16> = None;
+        if let Some(sign_pos) = value.len().checked_sub(5) {
+            let (datetime, offset) = value.split_at(sign_pos);
+            let sign: Option<i16> = match offset.as_bytes()[0] {
+                b'+' => Some(1),
+                b'-' => Some(-1),
+                _ => None,
+            };
+            if let Some(sign) = sign {
+                let hours: i16 = parse_digits(&offset[1..3], "UTC offset")?;
+                let minutes: i16 = parse_digits(&offset[3..5], "UTC offset")?;
+                if minutes > 59 {
+                    return Err(invalid("UTC offset", offset));
+                }
+                offset_minutes = Some(sign * (hours * 60 + minutes));
+                rest = datetime;
+            }
+        }
+
+        let (date, time) = if let Some((date, time)) = rest.split_once(['T', ' ']) {
+            (date, Some(time))
+        } else if rest.len() > 8 {
+            let (date, time) = rest.split_at(8);
+            (date, Some(time))
+        } else {
+            (rest, None)
+        };
+        let date: DicomDate = date.parse()?;
+        let time: Option<DicomTime> = time.map(str::parse).transpose()?;
+        DicomDateTime::new(date, time, offset_minutes)
+    }
+}
+
+impl Display for DicomDateTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.date)?;
+        if let Some(time) = &self.time {
+            write!(f, "{time}")?;
+        }
+        if let Some(offset) = self.offset_minutes {
+            let sign: char = if offset < 0 { '-' } else { '+' };
+            let offset: u16 = offset.unsigned_abs();
+            write!(f, "{sign}{:02}{:02}", offset / 60, offset % 60)?;
+        }
+        Ok(())
+    }
+}
+
+/// A range of dates or times, as used for matching in queries: `start-end`, where either the start
+/// or end may be omitted to leave the range open. A single value is a range which starts and ends
+/// with that value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DicomRange<T> {
+    start: Option<T>,
+    end: Option<T>,
+}
+
+impl<T> DicomRange<T> {
+    /// Creates a range, which may be open at either end.
+    #[must_use]
+    pub fn new(start: Option<T>, end: Option<T>) -> DicomRange<T> {
+        DicomRange { start, end }
+    }
+
+    #[must_use]
+    pub fn start(&self) -> Option<&T> {
+        self.start.as_ref()
+    }
+
+    #[must_use]
+    pub fn end(&self) -> Option<&T> {
+        self.end.as_ref()
+    }
+}
+
+impl<T: Clone> DicomRange<T> {
+    /// Creates a range of a single value.
+    #[must_use]
+    pub fn single(value: T) -> DicomRange<T> {
+        DicomRange {
+            start: Some(value.clone()),
+            end: Some(value),
+        }
+    }
+}
+
+impl<T: PartialPrecision> DicomRange<T> {
+    /// Whether the value is within this range. Values with partial precision match if any part of
+    /// their span is within the range, and the range includes all of the span of its start and end.
+    #[must_use]
+    pub fn contains(&self, value: &T) -> bool {
+        let after_start: bool = self
+            .start
+            .as_ref()
+            .is_none_or(|start| value.latest().cmp(&start.earliest()) != Ordering::Less);
+        let before_end: bool = self
+            .end
+            .as_ref()
+            .is_none_or(|end| value.earliest().cmp(&end.latest()) != Ordering::Greater);
+        after_start && before_end
+    }
+}
+
+impl<T: FromStr<Err = ParseError> + Clone> FromStr for DicomRange<T> {
+    type Err = ParseError;
+
+    /// Parses a range, or a single value. As `-` may also be part of a value, such as the UTC
+    /// offset of a date time, the value is first parsed as a single value, then split at each `-`
+    /// until both sides parse.
+    fn from_str(value: &str) -> ParseResult<Self> {
+        let value: &str = trim_value(value);
+        if let Ok(single) = value.parse::<T>() {
+            return Ok(DicomRange::single(single));
+        }
+
+        for (pos, _) in value.match_indices('-') {
+            let (start, end) = (&value[..pos], &value[pos + 1..]);
+            if start.is_empty() && end.is_empty() {
+                break;
+            }
+            let start: Option<ParseResult<T>> = (!start.is_empty()).then(|| start.parse());
+            let end: Option<ParseResult<T>> = (!end.is_empty()).then(|| end.parse());
+            if let (Ok(start), Ok(end)) = (start.transpose(), end.transpose()) {
+                return Ok(DicomRange { start, end });
+            }
+        }
+        Err(invalid("range", value))
+    }
+}
+
+impl<T: Display + PartialEq> Display for DicomRange<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match (&self.start, &self.end) {
+            (Some(start), Some(end)) if start == end => write!(f, "{start}"),
+            (start, end) => {
+                if let Some(start) = start {
+                    write!(f, "{start}")?;
+                }
+                write!(f, "-")?;
+                if let Some(end) = end {
+                    write!(f, "{end}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Removes the whitespace and null padding which may surround a value.
+pub(crate) fn trim_value(value: &str) -> &str {
+    value.trim_matches(|c: char| c.is_whitespace() || c == '\0')
+}
+
+/// Parses a component of a value which must consist only of ASCII digits.
+pub(crate) fn parse_digits<T: FromStr>(digits: &str, component: &str) -> ParseResult<T> {
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid(component, digits));
+    }
+    digits.parse::<T>().map_err(|_| invalid(component, digits))
+}
+
+pub(crate) fn invalid<V: Display>(component: &str, value: V) -> ParseError {
+    ParseError::GeneralDecodeError(format!("invalid {component}: {value}"))
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Conversions to and from the date and time types of the `chrono` crate.
+#[cfg(feature = "chrono")]
+mod chrono_conv {
+    use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+    use crate::core::{
+        read::{ParseError, ParseResult},
+        values::datetime::{invalid, DicomDate, DicomDateTime, DicomTime, PartialPrecision},
+    };
+
+    impl TryFrom<&DicomDate> for NaiveDate {
+        type Error = ParseError;
+
+        /// Converts a date, which must be complete.
+        fn try_from(value: &DicomDate) -> ParseResult<Self> {
+            match (value.month, value.day) {
+                (Some(month), Some(day)) => {
+                    NaiveDate::from_ymd_opt(i32::from(value.year), u32::from(month), u32::from(day))
+                        .ok_or_else(|| invalid("date", value))
+                }
+                _ => Err(invalid("partial date", value)),
+            }
+        }
+    }
+
+    impl TryFrom<NaiveDate> for DicomDate {
+        type Error = ParseError;
+
+        /// Converts a date, which must be within the years 0 to 9999.
+        fn try_from(value: NaiveDate) -> ParseResult<Self> {
+            let year: u16 = u16::try_from(value.year()).map_err(|_| invalid("year", value))?;
+            DicomDate::from_ymd(
+                year,
+                u8::try_from(value.month()).unwrap_or_default(),
+                u8::try_from(value.day()).unwrap_or_default(),
+            )
+        }
+    }
+
+    impl TryFrom<&DicomTime> for NaiveTime {
+        type Error = ParseError;
+
+        /// Converts a time, where omitted components are zero. Leap seconds are represented as
+        /// chrono does, as the 59th second with over one second of fractional seconds.
+        fn try_from(value: &DicomTime) -> ParseResult<Self> {
+            let time: DicomTime = value.earliest();
+            let second: u32 = u32::from(time.second.unwrap_or_default());
+            let mut micros: u32 = time.micros.unwrap_or_default();
+            if second == 60 {
+                micros += 1_000_000;
+            }
+            NaiveTime::from_hms_micro_opt(
+                u32::from(time.hour),
+                u32::from(time.minute.unwrap_or_default()),
+                second.min(59),
+                micros,
+            )
+            .ok_or_else(|| invalid("time", value))
+        }
+    }
+
+    impl From<NaiveTime> for DicomTime {
+        fn from(value: NaiveTime) -> Self {
+            let (second, micros) = if value.nanosecond() >= 1_000_000_000 {
+                (60, (value.nanosecond() - 1_000_000_000) / 1_000)
+            } else {
+                (value.second(), value.nanosecond() / 1_000)
+            };
+            DicomTime {
+                hour: u8::try_from(value.hour()).unwrap_or_default(),
+                minute: u8::try_from(value.minute()).ok(),
+                second: u8::try_from(second).ok(),
+                micros: Some(micros),
+                fraction_digits: super::FRACTION_DIGITS,
+            }
+        }
+    }
+
+    impl TryFrom<&DicomDateTime> for NaiveDateTime {
+        type Error = ParseError;
+
+        /// Converts a date time, which must have a complete date, ignoring any UTC offset. An
+        /// omitted time is midnight.
+        fn try_from(value: &DicomDateTime) -> ParseResult<Self> {
+            let date = NaiveDate::try_from(&value.date)?;
+            let time: NaiveTime = value
+                .time
+                .as_ref()
+                .map_or(Ok(NaiveTime::MIN), NaiveTime::try_from)?;
+            Ok(NaiveDateTime::new(date, time))
+        }
+    }
+
+    impl TryFrom<&DicomDateTime> for DateTime<FixedOffset> {
+        type Error = ParseError;
+
+        /// Converts a date time, which must have a complete date and a UTC offset.
+        fn try_from(value: &DicomDateTime) -> ParseResult<Self> {
+            let offset: FixedOffset = value
+                .offset_minutes
+                .and_then(|minutes| FixedOffset::east_opt(i32::from(minutes) * 60))
+                .ok_or_else(|| invalid("date time without UTC offset", value))?;
+            NaiveDateTime::try_from(value)?
+                .and_local_timezone(offset)
+                .single()
+                .ok_or_else(|| invalid("date time", value))
+        }
+    }
+
+    impl TryFrom<NaiveDateTime> for DicomDateTime {
+        type Error = ParseError;
+
+        fn try_from(value: NaiveDateTime) -> ParseResult<Self> {
+            DicomDateTime::new(
+                DicomDate::try_from(value.date())?,
+                Some(DicomTime::from(value.time())),
+                None,
+            )
+        }
+    }
+
+    impl TryFrom<DateTime<FixedOffset>> for DicomDateTime {
+        type Error = ParseError;
+
+        fn try_from(value: DateTime<FixedOffset>) -> ParseResult<Self> {
+            let offset: i16 = i16::try_from(value.offset().local_minus_utc() / 60)
+                .map_err(|_| invalid("UTC offset", value))?;
+            DicomDateTime::new(
+                DicomDate::try_from(value.date_naive())?,
+                Some(DicomTime::from(value.time())),
+                Some(offset),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DicomDate, DicomDateTime, DicomRange, DicomTime};
+
+    #[test]
+    fn test_parse_dates() {
+        let date: DicomDate = "20240229".parse().expect("leap day");
+        assert_eq!(
+            (2024, Some(2), Some(29)),
+            (date.year(), date.month(), date.day())
+        );
+        assert!("20230229".parse::<DicomDate>().is_err());
+        assert!("202413".parse::<DicomDate>().is_err());
+
+        assert_eq!("2024", "2024".parse::<DicomDate>().unwrap().to_string());
+        assert_eq!(
+            "202402",
+            "202402 ".parse::<DicomDate>().unwrap().to_string()
+        );
+        assert_eq!(
+            "20240131",
+            "2024.01.31".parse::<DicomDate>().unwrap().to_string()
+        );
+        assert_eq!(
+            "20240131",
+            "2024-01-31".parse::<DicomDate>().unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_times() {
+        let time: DicomTime = "070907.0705".parse().expect("time");
+        assert_eq!(
+            (7, Some(9), Some(7), Some(70500)),
+            (time.hour(), time.minute(), time.second(), time.micros())
+        );
+        assert_eq!("070907.0705", time.to_string());
+        assert_eq!("1010", "1010".parse::<DicomTime>().unwrap().to_string());
+        assert_eq!(
+            "101005",
+            "10:10:05".parse::<DicomTime>().unwrap().to_string()
+        );
+        assert_eq!(
+            "101005.123456",
+            "101005.1234567".parse::<DicomTime>().unwrap().to_string()
+        );
+        assert!("2400".parse::<DicomTime>().is_err());
+        assert!("10106".parse::<DicomTime>().is_err());
+    }
+
+    #[test]
+    fn test_parse_datetimes() {
+        let datetime: DicomDateTime = "20240102030405.5-0500".parse().expect("date time");
+        assert_eq!(Some(-300), datetime.offset_minutes());
+        assert_eq!("030405.5", datetime.time().unwrap().to_string());
+        assert_eq!("20240102030405.5-0500", datetime.to_string());
+
+        assert_eq!("2024", "2024".parse::<DicomDateTime>().unwrap().to_string());
+        assert_eq!(
+            "202401021030+0100",
+            "2024-01-02T10:30+0100"
+                .parse::<DicomDateTime>()
+                .unwrap()
+                .to_string()
+        );
+        assert!("202401021030+1500".parse::<DicomDateTime>().is_err());
+    }
+
+    /// Non-ASCII values are rejected rather than split within a character.
+    #[test]
+    fn test_parse_non_ascii() {
+        assert!("aaaéb".parse::<DicomDate>().is_err());
+        assert!("2024é1".parse::<DicomDate>().is_err());
+        assert!("aéb".parse::<DicomTime>().is_err());
+        assert!("10é5".parse::<DicomTime>().is_err());
+        assert!("ééé".parse::<DicomDateTime>().is_err());
+        assert!("20200101é1234".parse::<DicomDateTime>().is_err());
+        assert!("2020010112é+0100".parse::<DicomDateTime>().is_err());
+        assert!("2020é-2021".parse::<DicomRange<DicomDate>>().is_err());
+    }
+
+    #[test]
+    fn test_parse_ranges() {
+        let range: DicomRange<DicomDate> = "20200101-20201231".parse().expect("range");
+        assert!(range.contains(&"20200615".parse().unwrap()));
+        assert!(range.contains(&"2020".parse().unwrap()));
+        assert!(!range.contains(&"20210101".parse().unwrap()));
+        assert_eq!("20200101-20201231", range.to_string());
+
+        let open: DicomRange<DicomDate> = "-2019".parse().expect("open start");
+        assert!(open.start().is_none());
+        assert!(open.contains(&"20191231".parse().unwrap()));
+        assert!(!open.contains(&"20200101".parse().unwrap()));
+
+        let single: DicomRange<DicomDate> = "202006".parse().expect("single");
+        assert!(single.contains(&"20200630".parse().unwrap()));
+        assert_eq!("202006", single.to_string());
+
+        let times: DicomRange<DicomTime> = "0800-120000.5".parse().expect("time range");
+        assert!(times.contains(&"1200".parse().unwrap()));
+        assert!(times.contains(&"120000.59".parse().unwrap()));
+        assert!(!times.contains(&"120000.6".parse().unwrap()));
+
+        let datetimes: DicomRange<DicomDateTime> =
+            "20200101-0500-20200102".parse().expect("date time range");
+        assert_eq!(Some(-300), datetimes.start().unwrap().offset_minutes());
+        assert_eq!("20200102", datetimes.end().unwrap().to_string());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono_conversions() {
+        use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime};
+
+        let datetime: DicomDateTime = "20240102030405.25+0130".parse().expect("date time");
+        let converted = DateTime::<FixedOffset>::try_from(&datetime).expect("convert");
+        assert_eq!("2024-01-02 03:04:05.250 +01:30", converted.to_string());
+        assert_eq!(
+            "20240102030405.250000+0130",
+            DicomDateTime::try_from(converted).unwrap().to_string()
+        );
+
+        let partial: DicomDate = "202401".parse().expect("partial");
+        assert!(NaiveDate::try_from(&partial).is_err());
+        let naive = NaiveDateTime::try_from(&"20240102".parse::<DicomDateTime>().unwrap());
+        assert_eq!("2024-01-02 00:00:00", naive.unwrap().to_string());
+    }
+}
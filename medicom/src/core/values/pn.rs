@@ -0,0 +1,282 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Typed value for the Person Name (PN) value representation. Refer to Part 5, Section 6.2.1.
+//!
+//! A name consists of up to three component groups separated by `=`: alphabetic, ideographic, and
+//! phonetic. Each group consists of up to five components separated by `^`: family name, given
+//! name, middle name, prefix, and suffix.
+
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use crate::core::{
+    read::{ParseError, ParseResult},
+    values::datetime::{invalid, trim_value},
+};
+
+/// The separator between component groups.
+const GROUP_SEPARATOR: char = '=';
+
+/// The separator between components within a group.
+const COMPONENT_SEPARATOR: char = '^';
+
+/// One representation of a person's name, the components of which may be empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct PersonNameGroup {
+    family: String,
+    given: String,
+    middle: String,
+    prefix: String,
+    suffix: String,
+}
+
+impl PersonNameGroup {
+    #[must_use]
+    pub fn family(&self) -> &str {
+        &self.family
+    }
+
+    #[must_use]
+    pub fn given(&self) -> &str {
+        &self.given
+    }
+
+    #[must_use]
+    pub fn middle(&self) -> &str {
+        &self.middle
+    }
+
+    #[must_use]
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    #[must_use]
+    pub fn suffix(&self) -> &str {
+        &self.suffix
+    }
+
+    #[must_use]
+    pub fn with_family<S: Into<String>>(mut self, family: S) -> Self {
+        self.family = family.into();
+        self
+    }
+
+    #[must_use]
+    pub fn with_given<S: Into<String>>(mut self, given: S) -> Self {
+        self.given = given.into();
+        self
+    }
+
+    #[must_use]
+    pub fn with_middle<S: Into<String>>(mut self, middle: S) -> Self {
+        self.middle = middle.into();
+        self
+    }
+
+    #[must_use]
+    pub fn with_prefix<S: Into<String>>(mut self, prefix: S) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    #[must_use]
+    pub fn with_suffix<S: Into<String>>(mut self, suffix: S) -> Self {
+        self.suffix = suffix.into();
+        self
+    }
+
+    /// Whether all components are empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.components().iter().all(|c| c.is_empty())
+    }
+
+    fn components(&self) -> [&str; 5] {
+        [
+            &self.family,
+            &self.given,
+            &self.middle,
+            &self.prefix,
+            &self.suffix,
+        ]
+    }
+
+    fn parse_group(group: &str) -> ParseResult<PersonNameGroup> {
+        let mut components = group
+            .split(COMPONENT_SEPARATOR)
+            .map(|c| c.trim().to_string());
+        let parsed = PersonNameGroup {
+            family: components.next().unwrap_or_default(),
+            given: components.next().unwrap_or_default(),
+            middle: components.next().unwrap_or_default(),
+            prefix: components.next().unwrap_or_default(),
+            suffix: components.next().unwrap_or_default(),
+        };
+        if components.next().is_some() {
+            return Err(invalid("person name components", group));
+        }
+        Ok(parsed)
+    }
+}
+
+impl Display for PersonNameGroup {
+    /// Displays the components separated by `^`, omitting trailing empty components.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let components: [&str; 5] = self.components();
+        let len: usize = components
+            .iter()
+            .rposition(|c| !c.is_empty())
+            .map_or(0, |pos| pos + 1);
+        write!(f, "{}", components[..len].join("^"))
+    }
+}
+
+/// A person's name, as encoded by the PN value representation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct PersonName {
+    alphabetic: PersonNameGroup,
+    ideographic: PersonNameGroup,
+    phonetic: PersonNameGroup,
+}
+
+impl PersonName {
+    /// Creates a name with only the alphabetic group.
+    #[must_use]
+    pub fn alphabetic_only(alphabetic: PersonNameGroup) -> PersonName {
+        PersonName {
+            alphabetic,
+            ..Default::default()
+        }
+    }
+
+    #[must_use]
+    pub fn alphabetic(&self) -> &PersonNameGroup {
+        &self.alphabetic
+    }
+
+    #[must_use]
+    pub fn ideographic(&self) -> &PersonNameGroup {
+        &self.ideographic
+    }
+
+    #[must_use]
+    pub fn phonetic(&self) -> &PersonNameGroup {
+        &self.phonetic
+    }
+
+    #[must_use]
+    pub fn with_alphabetic(mut self, alphabetic: PersonNameGroup) -> Self {
+        self.alphabetic = alphabetic;
+        self
+    }
+
+    #[must_use]
+    pub fn with_ideographic(mut self, ideographic: PersonNameGroup) -> Self {
+        self.ideographic = ideographic;
+        self
+    }
+
+    #[must_use]
+    pub fn with_phonetic(mut self, phonetic: PersonNameGroup) -> Self {
+        self.phonetic = phonetic;
+        self
+    }
+}
+
+impl FromStr for PersonName {
+    type Err = ParseError;
+
+    /// Parses a name of up to three component groups, each of up to five components. Whitespace
+    /// surrounding each component is removed.
+    fn from_str(value: &str) -> ParseResult<Self> {
+        let value: &str = trim_value(value);
+        let mut groups = value.split(GROUP_SEPARATOR);
+        let name = PersonName {
+            alphabetic: PersonNameGroup::parse_group(groups.next().unwrap_or_default())?,
+            ideographic: PersonNameGroup::parse_group(groups.next().unwrap_or_default())?,
+            phonetic: PersonNameGroup::parse_group(groups.next().unwrap_or_default())?,
+        };
+        if groups.next().is_some() {
+            return Err(invalid("person name groups", value));
+        }
+        Ok(name)
+    }
+}
+
+impl Display for PersonName {
+    /// Displays the component groups separated by `=`, omitting trailing empty groups.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let groups: [&PersonNameGroup; 3] = [&self.alphabetic, &self.ideographic, &self.phonetic];
+        let len: usize = groups
+            .iter()
+            .rposition(|g| !g.is_empty())
+            .map_or(0, |pos| pos + 1);
+        for (i, group) in groups[..len].iter().enumerate() {
+            if i > 0 {
+                write!(f, "{GROUP_SEPARATOR}")?;
+            }
+            write!(f, "{group}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PersonName, PersonNameGroup};
+
+    #[test]
+    fn test_parse_person_names() {
+        let name: PersonName = "DOE^JOHN^Q^DR^JR".parse().expect("name");
+        let alphabetic: &PersonNameGroup = name.alphabetic();
+        assert_eq!(
+            ["DOE", "JOHN", "Q", "DR", "JR"],
+            [
+                alphabetic.family(),
+                alphabetic.given(),
+                alphabetic.middle(),
+                alphabetic.prefix(),
+                alphabetic.suffix()
+            ]
+        );
+        assert!(name.ideographic().is_empty());
+        assert_eq!("DOE^JOHN^Q^DR^JR", name.to_string());
+
+        let groups: PersonName = "Yamada^Tarou=山田^太郎=やまだ^たろう"
+            .parse()
+            .expect("groups");
+        assert_eq!("山田", groups.ideographic().family());
+        assert_eq!("たろう", groups.phonetic().given());
+        assert_eq!("Yamada^Tarou=山田^太郎=やまだ^たろう", groups.to_string());
+
+        let padded: PersonName = "DOE^JOHN^^^ ".parse().expect("padded");
+        assert_eq!("DOE^JOHN", padded.to_string());
+        assert!("A^B^C^D^E^F".parse::<PersonName>().is_err());
+        assert!("A=B=C=D".parse::<PersonName>().is_err());
+    }
+
+    #[test]
+    fn test_build_person_name() {
+        let name = PersonName::default()
+            .with_alphabetic(PersonNameGroup::default().with_family("DOE"))
+            .with_phonetic(PersonNameGroup::default().with_given("JON"));
+        assert_eq!("DOE==^JON", name.to_string());
+    }
+}
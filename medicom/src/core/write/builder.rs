@@ -21,13 +21,14 @@ use crate::core::{
     defn::{constants::ts, ts::TSRef},
     write::{
         ds::dataset::Dataset,
+        sqlength::{SequenceLength, SequenceLengths},
         writer::{Writer, WriterState},
     },
     FILE_PREAMBLE_LENGTH,
 };
 
 /// A builder for constructing a `Writer`.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct WriterBuilder {
     /// Initial writer state. Defaults to `WriterState::Preamble`.
     state: Option<WriterState>,
@@ -40,6 +41,15 @@ pub struct WriterBuilder {
     /// If a file preamble is specified then the `"DICM"` prefix will be written immediately
     /// after the file preamble is written.
     file_preamble: Option<[u8; FILE_PREAMBLE_LENGTH]>,
+
+    /// Whether to generate the File Meta group. Defaults to false.
+    generate_file_meta: bool,
+
+    /// The AE Title to write as `SourceApplicationEntityTitle` in generated File Meta.
+    source_ae_title: Option<String>,
+
+    /// How the value lengths of sequences and items are encoded.
+    sequence_lengths: SequenceLengths,
 }
 
 impl WriterBuilder {
@@ -79,6 +89,44 @@ impl WriterBuilder {
         self
     }
 
+    /// Sets whether to generate a complete File Meta group when writing, in place of relying on
+    /// the File Meta elements given to the `Writer`. The `MediaStorageSOPClassUID` and
+    /// `MediaStorageSOPInstanceUID` are taken from the dataset's `SOPClassUID` and
+    /// `SOPInstanceUID`, and the `TransferSyntaxUID` from the writer's transfer syntax. Other File
+    /// Meta elements given to the `Writer` are kept.
+    #[must_use]
+    pub fn generate_file_meta(mut self, generate_file_meta: bool) -> Self {
+        self.generate_file_meta = generate_file_meta;
+        self
+    }
+
+    /// Sets the AE Title to write as `SourceApplicationEntityTitle` in generated File Meta.
+    #[must_use]
+    pub fn source_ae_title<S: Into<String>>(mut self, source_ae_title: S) -> Self {
+        self.source_ae_title = Some(source_ae_title.into());
+        self
+    }
+
+    /// Sets how the value lengths of all sequences and their items are encoded when writing a
+    /// `DicomRoot`. Defaults to `SequenceLength::Preserve`.
+    #[must_use]
+    pub fn sequence_length(mut self, sequence_length: SequenceLength) -> Self {
+        self.sequence_lengths.set_default(sequence_length);
+        self
+    }
+
+    /// Sets how the value lengths of the sequence with the given tag and its items are encoded
+    /// when writing a `DicomRoot`, in place of the setting for all sequences.
+    #[must_use]
+    pub fn sequence_length_for<T>(mut self, tag: T, sequence_length: SequenceLength) -> Self
+    where
+        u32: From<T>,
+    {
+        self.sequence_lengths
+            .set_for_tag(u32::from(tag), sequence_length);
+        self
+    }
+
     /// Constructs a `Writer` from this builder.
     pub fn build<DatasetType: Write>(&self, dataset: DatasetType) -> Writer<DatasetType> {
         Writer {
@@ -88,6 +136,9 @@ impl WriterBuilder {
             ts: self.ts.unwrap_or(&ts::ExplicitVRLittleEndian),
             cs: self.cs.unwrap_or_default(),
             file_preamble: self.file_preamble,
+            generate_file_meta: self.generate_file_meta,
+            source_ae_title: self.source_ae_title.clone(),
+            sequence_lengths: self.sequence_lengths.clone(),
         }
     }
 }
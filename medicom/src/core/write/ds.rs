@@ -19,12 +19,15 @@
 #[cfg(feature = "compress")]
 pub(crate) mod dataset {
     use libflate::deflate::Encoder;
-    use std::io::{Result, Write};
+    use std::io::{Error, ErrorKind, Result, Seek, SeekFrom, Write};
 
     #[derive(Debug)]
     pub(crate) struct Dataset<W: Write> {
         encoder: Encoder<W>,
         write_deflated: bool,
+        /// Whether any bytes have been written deflated, in which case the deflated stream needs
+        /// to be completed.
+        deflate_started: bool,
     }
 
     impl<W: Write> Dataset<W> {
@@ -32,16 +35,41 @@ pub(crate) mod dataset {
             Dataset {
                 encoder: Encoder::new(dataset),
                 write_deflated: false,
+                deflate_started: false,
             }
         }
 
         pub fn set_write_deflated(&mut self, write_deflated: bool) {
             self.write_deflated = write_deflated;
+            self.deflate_started |= write_deflated;
         }
 
         pub fn into_inner(self) -> W {
             self.encoder.into_inner()
         }
+
+        /// Completes the deflated stream, if any bytes were written deflated.
+        pub fn finish(self) -> Result<W> {
+            if self.deflate_started {
+                self.encoder.finish().into_result()
+            } else {
+                Ok(self.encoder.into_inner())
+            }
+        }
+    }
+
+    impl<W: Write + Seek> Dataset<W> {
+        /// Seeks within the wrapped dataset. Positions within a deflated stream do not correspond
+        /// to the bytes written, so this fails once writing deflated has started.
+        pub fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+            if self.deflate_started {
+                return Err(Error::new(
+                    ErrorKind::Unsupported,
+                    "cannot seek within a deflated dataset",
+                ));
+            }
+            self.encoder.as_inner_mut().seek(pos)
+        }
     }
 
     impl<W: Write> Write for Dataset<W> {
@@ -65,7 +93,7 @@ pub(crate) mod dataset {
 
 #[cfg(not(feature = "compress"))]
 pub(crate) mod dataset {
-    use std::io::{Result, Write};
+    use std::io::{Result, Seek, SeekFrom, Write};
 
     #[derive(Debug)]
     pub(crate) struct Dataset<W: Write> {
@@ -80,6 +108,16 @@ pub(crate) mod dataset {
         pub fn into_inner(self) -> W {
             self.dataset
         }
+
+        pub fn finish(self) -> Result<W> {
+            Ok(self.dataset)
+        }
+    }
+
+    impl<W: Write + Seek> Dataset<W> {
+        pub fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+            self.dataset.seek(pos)
+        }
     }
 
     impl<W: Write> Write for Dataset<W> {
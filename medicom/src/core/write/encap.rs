@@ -0,0 +1,279 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Streaming encapsulated Pixel Data, one fragment at a time, without holding all of the frames in
+//! memory. Refer to Part 5, Annex A.4.
+//!
+//! The offset table needs the offset of each frame, which isn't known until the frames have been
+//! written. Space for the table is reserved when writing begins and the entries are filled in by
+//! seeking back once all frames have been written.
+
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use crate::core::{
+    defn::{
+        constants::tags::{
+            EXTENDED_OFFSET_TABLE, EXTENDED_OFFSET_TABLE_LENGTHS, ITEM, PIXEL_DATA,
+            SEQUENCE_DELIMITATION_ITEM,
+        },
+        vl::ValueLength,
+        vr::{VRRef, OB, OV, UN},
+    },
+    write::{
+        error::WriteError,
+        writer::{WriteResult, Writer},
+    },
+};
+
+/// Length of the Item tag and value length which precede the value of each fragment.
+const ITEM_HEADER_LENGTH: u64 = 8;
+
+/// The offset table generated for the frames of encapsulated Pixel Data.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OffsetTable {
+    /// An empty Basic Offset Table. Frames are not tracked.
+    #[default]
+    Empty,
+
+    /// A Basic Offset Table with the offset of each frame. Offsets are limited to 32 bits, so the
+    /// fragments of all but the last frame must total less than 4GB.
+    Basic,
+
+    /// The `ExtendedOffsetTable` and `ExtendedOffsetTableLengths` elements, which are written
+    /// immediately before `PixelData`, along with an empty Basic Offset Table.
+    Extended,
+}
+
+impl<W: Write + Seek> Writer<W> {
+    /// Begins writing encapsulated `PixelData`, returning an `EncapsulatedWriter` for writing its
+    /// fragments. All elements preceding `PixelData`, or `ExtendedOffsetTable` if used, must have
+    /// been written.
+    ///
+    /// `num_frames`: The number of frames which will be written, for reserving space in the offset
+    /// table. This is ignored for `OffsetTable::Empty`.
+    ///
+    /// # Errors
+    /// - `WriteError::DeflatedOffsetTable` if an offset table other than `OffsetTable::Empty` is
+    ///   requested for a deflated transfer syntax, as the table can't be filled in afterwards.
+    /// - Errors from `write_element_header()`, or writing to the dataset.
+    pub fn begin_encapsulated(
+        &mut self,
+        offset_table: OffsetTable,
+        num_frames: usize,
+    ) -> WriteResult<EncapsulatedWriter<'_, W>> {
+        if self.ts.deflated() && offset_table != OffsetTable::Empty {
+            return Err(WriteError::DeflatedOffsetTable(
+                self.ts.uid().name().to_string(),
+            ));
+        }
+        let num_frames: usize = if offset_table == OffsetTable::Empty {
+            0
+        } else {
+            num_frames
+        };
+
+        let mut eot_pos: Option<(u64, u64)> = None;
+        if offset_table == OffsetTable::Extended {
+            let offsets_pos: u64 = self.reserve_table(EXTENDED_OFFSET_TABLE, &OV, num_frames, 8)?;
+            let lengths_pos: u64 =
+                self.reserve_table(EXTENDED_OFFSET_TABLE_LENGTHS, &OV, num_frames, 8)?;
+            eot_pos = Some((offsets_pos, lengths_pos));
+        }
+
+        self.write_element_header(PIXEL_DATA, &OB, ValueLength::UndefinedLength)?;
+        let bot_frames: usize = if offset_table == OffsetTable::Basic {
+            num_frames
+        } else {
+            0
+        };
+        let bot_pos: u64 = self.reserve_table(ITEM, &UN, bot_frames, 4)?;
+
+        Ok(EncapsulatedWriter {
+            writer: self,
+            offset_table,
+            num_frames,
+            bot_pos,
+            eot_pos,
+            offsets: Vec::with_capacity(num_frames),
+            lengths: Vec::with_capacity(num_frames),
+            fragments_size: 0,
+        })
+    }
+
+    /// Writes an element whose value is `num_entries` zeroed entries of `entry_size` bytes, to be
+    /// filled in later. Returns the position of the value within the dataset, which is only
+    /// determined for non-empty tables so that empty tables never seek.
+    fn reserve_table(
+        &mut self,
+        tag: u32,
+        vr: VRRef,
+        num_entries: usize,
+        entry_size: usize,
+    ) -> WriteResult<u64> {
+        let length: u32 = u32::try_from(num_entries.saturating_mul(entry_size))
+            .map_err(|_| WriteError::InvalidValueLength)?;
+        self.write_element_header(tag, vr, ValueLength::Explicit(length))?;
+        let pos: u64 = if length == 0 {
+            0
+        } else {
+            self.dataset.seek(SeekFrom::Current(0))?
+        };
+        self.write_value_from(io::repeat(0), u64::from(length))?;
+        Ok(pos)
+    }
+}
+
+/// Writes the fragments of encapsulated `PixelData`, created by `Writer::begin_encapsulated()`.
+/// Each frame is made of one or more fragments. `finish()` must be called once all fragments have
+/// been written, which completes `PixelData` and fills in the offset table.
+#[derive(Debug)]
+pub struct EncapsulatedWriter<'w, W: Write + Seek> {
+    writer: &'w mut Writer<W>,
+    offset_table: OffsetTable,
+    num_frames: usize,
+
+    /// Position of the Basic Offset Table value within the dataset.
+    bot_pos: u64,
+
+    /// Positions of the Extended Offset Table and Extended Offset Table Lengths values.
+    eot_pos: Option<(u64, u64)>,
+
+    /// Offset of each frame's first fragment, relative to the first fragment.
+    offsets: Vec<u64>,
+
+    /// The length of each frame, excluding padding of its fragments.
+    lengths: Vec<u64>,
+
+    /// The total encoded size of all fragments written so far.
+    fragments_size: u64,
+}
+
+impl<W: Write + Seek> EncapsulatedWriter<'_, W> {
+    /// Begins a new frame. Following fragments are part of this frame until the next is begun.
+    ///
+    /// # Errors
+    /// - `WriteError::FrameCountMismatch` if this would exceed the number of frames reserved.
+    /// - `WriteError::BasicOffsetTableOverflow` if the frame's offset is too large.
+    pub fn begin_frame(&mut self) -> WriteResult<()> {
+        if self.offset_table == OffsetTable::Empty {
+            return Ok(());
+        }
+        if self.offsets.len() == self.num_frames {
+            return Err(WriteError::FrameCountMismatch(
+                self.num_frames,
+                self.offsets.len() + 1,
+            ));
+        }
+        if self.offset_table == OffsetTable::Basic && u32::try_from(self.fragments_size).is_err() {
+            return Err(WriteError::BasicOffsetTableOverflow(self.fragments_size));
+        }
+        self.offsets.push(self.fragments_size);
+        self.lengths.push(0);
+        Ok(())
+    }
+
+    /// Writes a frame which consists of a single fragment.
+    ///
+    /// # Errors
+    /// Errors from `begin_frame()` and `write_fragment()`.
+    pub fn write_frame(&mut self, data: &[u8]) -> WriteResult<()> {
+        self.begin_frame()?;
+        self.write_fragment(data)
+    }
+
+    /// Writes a fragment of the current frame, beginning the first frame if needed.
+    ///
+    /// # Errors
+    /// Errors from `write_fragment_from()`.
+    pub fn write_fragment(&mut self, data: &[u8]) -> WriteResult<()> {
+        let length: u32 = u32::try_from(data.len()).map_err(|_| WriteError::InvalidValueLength)?;
+        self.write_fragment_from(data, length)
+    }
+
+    /// Writes a fragment of the current frame whose `length` bytes are read from the reader,
+    /// beginning the first frame if needed. Odd lengths are padded to even length.
+    ///
+    /// # Errors
+    /// Errors from `begin_frame()` and `Writer::write_value_from()`, or writing to the dataset.
+    pub fn write_fragment_from<R: Read>(&mut self, reader: R, length: u32) -> WriteResult<()> {
+        if self.offsets.is_empty() {
+            self.begin_frame()?;
+        }
+        let padded: u32 = length.saturating_add(length % 2);
+        self.writer
+            .write_element_header(ITEM, &UN, ValueLength::Explicit(padded))?;
+        self.writer.write_value_from(reader, u64::from(length))?;
+        if padded > length {
+            self.writer.write_value_from(io::repeat(0), 1)?;
+        }
+        self.fragments_size += ITEM_HEADER_LENGTH + u64::from(padded);
+        if let Some(frame_length) = self.lengths.last_mut() {
+            *frame_length += u64::from(length);
+        }
+        Ok(())
+    }
+
+    /// Completes `PixelData` with the Sequence Delimitation Item and fills in the offset table.
+    /// Afterwards the dataset is positioned after `PixelData`.
+    ///
+    /// # Errors
+    /// - `WriteError::FrameCountMismatch` if the number of frames written differs from the number
+    ///   reserved.
+    /// - Errors writing to the dataset.
+    pub fn finish(self) -> WriteResult<()> {
+        self.writer.write_element_header(
+            SEQUENCE_DELIMITATION_ITEM,
+            &UN,
+            ValueLength::Explicit(0),
+        )?;
+        if self.offsets.len() != self.num_frames {
+            return Err(WriteError::FrameCountMismatch(
+                self.num_frames,
+                self.offsets.len(),
+            ));
+        }
+
+        let mut tables: Vec<(u64, Vec<u8>)> = Vec::new();
+        if let Some((offsets_pos, lengths_pos)) = self.eot_pos {
+            tables.push((offsets_pos, to_le_bytes(&self.offsets, u64::to_le_bytes)));
+            tables.push((lengths_pos, to_le_bytes(&self.lengths, u64::to_le_bytes)));
+        } else if self.offset_table == OffsetTable::Basic {
+            let offsets: Vec<u32> = self
+                .offsets
+                .iter()
+                .map(|offset| u32::try_from(*offset).unwrap_or(u32::MAX))
+                .collect();
+            tables.push((self.bot_pos, to_le_bytes(&offsets, u32::to_le_bytes)));
+        }
+        if tables.is_empty() {
+            return Ok(());
+        }
+
+        let dataset = &mut self.writer.dataset;
+        let end: u64 = dataset.seek(SeekFrom::Current(0))?;
+        for (pos, bytes) in tables {
+            dataset.seek(SeekFrom::Start(pos))?;
+            dataset.write_all(&bytes)?;
+        }
+        dataset.seek(SeekFrom::Start(end))?;
+        Ok(())
+    }
+}
+
+/// Encodes the values of an offset table. Encapsulated Pixel Data is always little endian.
+fn to_le_bytes<T: Copy, const N: usize>(values: &[T], encode: fn(T) -> [u8; N]) -> Vec<u8> {
+    values.iter().flat_map(|value| encode(*value)).collect()
+}
@@ -27,6 +27,33 @@ pub enum WriteError {
     #[error("value length of undefined cannot be used with implicit VR")]
     InvalidValueLength,
 
+    /// The File Meta group could not be generated as required values were not available.
+    #[error("unable to generate file meta: {0}")]
+    IncompleteFileMeta(String),
+
+    /// Transcoding between the transfer syntaxes would require compressing or decompressing pixel
+    /// data.
+    #[error("unable to transcode from {0} to {1}")]
+    UnsupportedTranscode(String, String),
+
+    /// The number of frames written as encapsulated pixel data differs from the number of entries
+    /// reserved in the offset table.
+    #[error("offset table has {0} frames but {1} were written")]
+    FrameCountMismatch(usize, usize),
+
+    /// A frame's offset is too large for the 32-bit entries of the Basic Offset Table.
+    #[error("frame offset {0} is too large for the basic offset table")]
+    BasicOffsetTableOverflow(u64),
+
+    /// The offset table of encapsulated pixel data cannot be filled in once written, as the
+    /// dataset is deflated.
+    #[error("offset table cannot be used with deflated transfer syntax {0}")]
+    DeflatedOffsetTable(String),
+
+    /// The written dataset could not be parsed back.
+    #[error("written dataset failed verification: {0}")]
+    VerificationFailed(String),
+
     /// Wrapper around `std::io::Error`.
     #[error("i/o error writing to stream")]
     IOError {
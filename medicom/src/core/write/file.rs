@@ -0,0 +1,209 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Writing a `DicomRoot` to a file such that the file is either completely written or not present.
+//!
+//! The dataset is written to a temporary file in the same directory, synced to disk, then renamed
+//! to the destination path. If the process is interrupted the destination is never left partially
+//! written, though the temporary file may remain.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::core::{
+    dcmobject::DicomRoot,
+    defn::{dcmdict::DicomDictionary, ts::TSRef},
+    read::ParserBuilder,
+    write::{
+        builder::WriterBuilder,
+        error::WriteError,
+        writer::{WriteResult, Writer},
+    },
+};
+
+/// The extension of temporary files, which should not be mistaken for DICOM files.
+const TEMP_EXTENSION: &str = "tmp";
+
+/// Distinguishes temporary files created by this process.
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Options for `write_dcmroot_to_path()`.
+#[derive(Default, Clone)]
+pub struct WriteFileOptions<'d> {
+    /// The builder for the `Writer`. Defaults to `WriterBuilder::for_file()` with the transfer
+    /// syntax and character set of the `DicomRoot`.
+    writer: Option<WriterBuilder>,
+
+    /// If set, the written file is parsed using this dictionary before being moved into place.
+    verify_with: Option<&'d dyn DicomDictionary>,
+}
+
+impl<'d> WriteFileOptions<'d> {
+    /// Sets the builder used to create the `Writer`.
+    #[must_use]
+    pub fn writer(mut self, writer: WriterBuilder) -> Self {
+        self.writer = Some(writer);
+        self
+    }
+
+    /// Sets the dictionary to verify the written file with, by parsing the file through to its end
+    /// before moving it into place. The dictionary must be able to resolve the file's transfer
+    /// syntax.
+    #[must_use]
+    pub fn verify_with(mut self, dictionary: &'d dyn DicomDictionary) -> Self {
+        self.verify_with = Some(dictionary);
+        self
+    }
+}
+
+/// Writes the `DicomRoot` to the file at the given path, replacing any existing file. The dataset
+/// is written to a temporary file in the same directory which is synced to disk and, if
+/// configured, verified, before being renamed to the path. Returns the size of the written file,
+/// which for a deflated transfer syntax is the size after compression.
+///
+/// # Errors
+/// - `WriteError::VerificationFailed` if the written file could not be parsed.
+/// - Errors writing the dataset, or I/O errors creating, syncing, or renaming the file. The
+///   temporary file is removed on error.
+pub fn write_dcmroot_to_path<P: AsRef<Path>>(
+    dcmroot: &DicomRoot,
+    path: P,
+    options: &WriteFileOptions<'_>,
+) -> WriteResult<u64> {
+    let path: &Path = path.as_ref();
+    let (temp, file) = TempFile::create(path)?;
+
+    let builder: WriterBuilder = options
+        .writer
+        .clone()
+        .unwrap_or_else(|| WriterBuilder::for_file().ts(dcmroot.ts()).cs(dcmroot.cs()));
+    let mut writer: Writer<BufWriter<File>> = builder.build(BufWriter::new(file));
+    writer.write_dcmroot(dcmroot)?;
+    let ts: TSRef = writer.ts();
+    let file: File = writer
+        .finish()?
+        .into_inner()
+        .map_err(std::io::IntoInnerError::into_error)?;
+    file.sync_all()?;
+    let file_len: u64 = file.metadata()?.len();
+    // The file is closed before being renamed, which some platforms require.
+    drop(file);
+
+    if let Some(dictionary) = options.verify_with {
+        verify(&temp.path, ts, dictionary)?;
+    }
+
+    temp.persist(path)?;
+    Ok(file_len)
+}
+
+/// Parses every element of the file, checking the dataset is complete.
+fn verify(path: &Path, ts: TSRef, dictionary: &dyn DicomDictionary) -> WriteResult<()> {
+    let mut parser = ParserBuilder::default().build(BufReader::new(File::open(path)?), dictionary);
+    let mut num_elements: usize = 0;
+    for element in &mut parser {
+        element.map_err(|e| WriteError::VerificationFailed(e.to_string()))?;
+        num_elements += 1;
+    }
+    if num_elements == 0 {
+        return Err(WriteError::VerificationFailed(
+            "no elements could be parsed".to_string(),
+        ));
+    }
+    if parser.ts() != ts {
+        return Err(WriteError::VerificationFailed(format!(
+            "parsed as {} instead of {}",
+            parser.ts().uid().name(),
+            ts.uid().name()
+        )));
+    }
+    Ok(())
+}
+
+/// The path of a temporary file next to its destination, which is removed when dropped unless
+/// persisted.
+struct TempFile {
+    path: PathBuf,
+    persisted: bool,
+}
+
+impl TempFile {
+    /// Creates a new temporary file in the same directory as the destination path, named after
+    /// the destination so it can be associated with it.
+    fn create(dest: &Path) -> WriteResult<(TempFile, File)> {
+        let file_name: String = dest
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .ok_or_else(|| WriteError::DetailedIOError {
+                source: std::io::ErrorKind::InvalidInput.into(),
+                detail: format!("not a file path: {}", dest.display()),
+            })?;
+        let counter: u64 = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let path: PathBuf = dest.with_file_name(format!(
+            ".{file_name}.{}.{counter}.{TEMP_EXTENSION}",
+            process::id()
+        ));
+        let file: File = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|source| WriteError::DetailedIOError {
+                source,
+                detail: format!("creating {}", path.display()),
+            })?;
+        Ok((
+            TempFile {
+                path,
+                persisted: false,
+            },
+            file,
+        ))
+    }
+
+    /// Renames the temporary file to the destination, then syncs the directory so the rename is
+    /// durable.
+    fn persist(mut self, dest: &Path) -> WriteResult<()> {
+        fs::rename(&self.path, dest).map_err(|source| WriteError::DetailedIOError {
+            source,
+            detail: format!("renaming to {}", dest.display()),
+        })?;
+        self.persisted = true;
+
+        // Directories can only be opened and synced this way on unix.
+        #[cfg(unix)]
+        {
+            let dir: &Path = dest
+                .parent()
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .unwrap_or(Path::new("."));
+            File::open(dir)?.sync_all()?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.persisted {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Generating the File Meta group from the dataset being written and the `Writer`'s settings.
+
+use std::collections::BTreeMap;
+
+use crate::core::{
+    charset::CSRef,
+    dcmelement::DicomElement,
+    defn::{
+        constants::{
+            tags::{
+                FILE_META_INFORMATION_VERSION, IMPLEMENTATION_CLASS_UID,
+                IMPLEMENTATION_VERSION_NAME, MEDIA_STORAGE_SOP_CLASS_UID,
+                MEDIA_STORAGE_SOP_INSTANCE_UID, SOP_CLASS_UID, SOP_INSTANCE_UID,
+                SOURCE_APPLICATION_ENTITY_TITLE, TRANSFER_SYNTAX_UID,
+            },
+            ts::ExplicitVRLittleEndian,
+        },
+        ts::TSRef,
+        vl::ValueLength,
+        vr::{VRRef, AE, OB, SH, UI},
+    },
+    values::RawValue,
+    write::{error::WriteError, writer::WriteResult},
+    MEDICOM_IMPLEMENTATION_CLASS_UID, MEDICOM_IMPLEMENTATION_VERSION_NAME,
+};
+
+/// The version of the File Meta Information header, Part 10 Section 7.1.
+const FILE_META_VERSION: [u8; 2] = [0x00, 0x01];
+
+/// Generates a complete File Meta group, excluding `FileMetaInformationGroupLength`, which the
+/// `Writer` computes when writing. Elements are returned in tag order.
+///
+/// - `supplied`: File Meta elements given to the `Writer`, which are kept unless generated below.
+/// - `dataset`: Elements of the dataset which follow File Meta. Only root-level elements up to and
+///   including `SOPInstanceUID` are needed.
+///
+/// The `MediaStorageSOPClassUID` and `MediaStorageSOPInstanceUID` are taken from the dataset's
+/// `SOPClassUID` and `SOPInstanceUID`, falling back to any supplied values. The
+/// `TransferSyntaxUID` and `ImplementationClassUID`/`ImplementationVersionName` always reflect the
+/// `Writer`, and `SourceApplicationEntityTitle` is set if given.
+///
+/// # Errors
+/// `WriteError::IncompleteFileMeta` if the SOP Class or SOP Instance UIDs can't be determined, or
+/// errors encoding values.
+pub(crate) fn generate_file_meta(
+    supplied: &[&DicomElement],
+    dataset: &[&DicomElement],
+    ts: TSRef,
+    source_ae_title: Option<&str>,
+) -> WriteResult<Vec<DicomElement>> {
+    let mut fm_elements: BTreeMap<u32, DicomElement> =
+        supplied.iter().map(|e| (e.tag(), (*e).clone())).collect();
+
+    let mut version =
+        DicomElement::new_empty(FILE_META_INFORMATION_VERSION, &OB, &ExplicitVRLittleEndian);
+    version.encode_val(RawValue::Bytes(FILE_META_VERSION.to_vec()))?;
+    fm_elements.insert(FILE_META_INFORMATION_VERSION, version);
+
+    for (fm_tag, ds_tag) in [
+        (MEDIA_STORAGE_SOP_CLASS_UID, SOP_CLASS_UID),
+        (MEDIA_STORAGE_SOP_INSTANCE_UID, SOP_INSTANCE_UID),
+    ] {
+        let ds_elem = dataset
+            .iter()
+            .find(|e| e.tag() == ds_tag && e.sq_path().is_empty());
+        if let Some(ds_elem) = ds_elem {
+            // UIDs are encoded the same regardless of transfer syntax.
+            let data: Vec<u8> = ds_elem.load_value()?.to_vec();
+            let fm_elem = DicomElement::new(
+                fm_tag,
+                &UI,
+                ValueLength::Explicit(u32::try_from(data.len()).unwrap_or_default()),
+                &ExplicitVRLittleEndian,
+                CSRef::default(),
+                data,
+                Vec::with_capacity(0),
+            );
+            fm_elements.insert(fm_tag, fm_elem);
+        }
+        if fm_elements.get(&fm_tag).is_none_or(DicomElement::is_empty) {
+            return Err(WriteError::IncompleteFileMeta(format!(
+                "no value for ({:04X},{:04X})",
+                ds_tag >> 16,
+                ds_tag & 0xFFFF
+            )));
+        }
+    }
+
+    let mut generated: Vec<(u32, VRRef, &str)> = vec![
+        (TRANSFER_SYNTAX_UID, &UI, ts.uid().uid()),
+        (
+            IMPLEMENTATION_CLASS_UID,
+            &UI,
+            MEDICOM_IMPLEMENTATION_CLASS_UID,
+        ),
+        (
+            IMPLEMENTATION_VERSION_NAME,
+            &SH,
+            MEDICOM_IMPLEMENTATION_VERSION_NAME,
+        ),
+    ];
+    if let Some(source_ae_title) = source_ae_title {
+        generated.push((SOURCE_APPLICATION_ENTITY_TITLE, &AE, source_ae_title));
+    }
+    for (tag, vr, value) in generated {
+        let mut element = DicomElement::new_empty(tag, vr, &ExplicitVRLittleEndian);
+        if vr == &UI {
+            element.encode_val(RawValue::of_uid(value))?;
+        } else {
+            element.encode_val(RawValue::of_string(value))?;
+        }
+        fm_elements.insert(tag, element);
+    }
+
+    Ok(fm_elements.into_values().collect())
+}
@@ -15,8 +15,14 @@
 */
 
 mod ds;
+pub(crate) mod filemeta;
 
 pub mod builder;
+pub mod encap;
 pub mod error;
+pub mod file;
+pub mod recode;
+pub mod sqlength;
+pub mod transcode;
 pub mod valencode;
 pub mod writer;
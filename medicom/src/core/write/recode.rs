@@ -0,0 +1,258 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Re-encoding the text values of a dataset into UTF-8.
+//!
+//! Values of the VRs whose text is decoded with `SpecificCharacterSet`, i.e. those for which
+//! `VR::get_proper_cs()` returns the dataset's character set, are decoded with the character set
+//! in effect for them and encoded again as `ISO_IR 192`. Any values which can't be decoded
+//! losslessly are reported, with the malformed bytes replaced with U+FFFD in the re-encoded value.
+
+use std::collections::{btree_map::Entry, BTreeMap};
+
+use encoding_rs::UTF_8;
+
+use crate::core::{
+    charset::CSRef,
+    dcmelement::DicomElement,
+    dcmobject::{DicomObject, DicomRoot},
+    dcmsqelem::SequenceElement,
+    defn::{
+        constants::tags::{FILE_META_GROUP_END, SPECIFIC_CHARACTER_SET},
+        tag::{Tag, TagPath},
+        ts::TSRef,
+        vl::ValueLength,
+        vr::{CS, PN},
+    },
+    values::RawValue,
+    write::{sqlength::update_lengths, writer::WriteResult},
+};
+
+/// The `SpecificCharacterSet` Defined Term for UTF-8.
+const ISO_IR_192: &str = "ISO_IR 192";
+
+/// A text value which could not be decoded losslessly from its original character set.
+#[derive(Debug, Clone)]
+pub struct LossyValue {
+    path: TagPath,
+    cs: CSRef,
+    text: String,
+}
+
+impl LossyValue {
+    /// The path to the element within the dataset.
+    #[must_use]
+    pub fn path(&self) -> &TagPath {
+        &self.path
+    }
+
+    /// The character set the value was decoded with.
+    #[must_use]
+    pub fn cs(&self) -> CSRef {
+        self.cs
+    }
+
+    /// The decoded text, as re-encoded, with U+FFFD in place of anything which couldn't be
+    /// decoded.
+    #[must_use]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+/// A dataset re-encoded into UTF-8, along with the values which could not be decoded losslessly.
+pub struct Utf8Recoding {
+    dcmroot: DicomRoot,
+    lossy_values: Vec<LossyValue>,
+}
+
+impl Utf8Recoding {
+    /// The re-encoded dataset.
+    #[must_use]
+    pub fn dcmroot(&self) -> &DicomRoot {
+        &self.dcmroot
+    }
+
+    /// Takes the re-encoded dataset.
+    #[must_use]
+    pub fn into_dcmroot(self) -> DicomRoot {
+        self.dcmroot
+    }
+
+    /// The values which could not be decoded losslessly, in the order they appear in the dataset.
+    #[must_use]
+    pub fn lossy_values(&self) -> &[LossyValue] {
+        &self.lossy_values
+    }
+
+    /// Whether all values were decoded losslessly.
+    #[must_use]
+    pub fn is_lossless(&self) -> bool {
+        self.lossy_values.is_empty()
+    }
+}
+
+/// Re-encodes all text values of the dataset into UTF-8, returning the new dataset along with any
+/// values which could not be decoded losslessly.
+///
+/// - Values are decoded with the character set in effect for them, which accounts for items with
+///   their own `SpecificCharacterSet`. Trailing padding is removed and the re-encoded value is
+///   padded to even length with a space.
+/// - `SpecificCharacterSet` is set to `ISO_IR 192` in the dataset, being added if not present, and
+///   in any items which specify their own.
+/// - Explicit lengths of sequences and items are updated to the lengths of their re-encoded
+///   contents, and group length elements outside of File Meta are removed.
+/// - File Meta elements are not changed.
+///
+/// # Errors
+/// Errors loading deferred values or encoding `SpecificCharacterSet`.
+pub fn recode_to_utf8(root: &DicomRoot) -> WriteResult<Utf8Recoding> {
+    let ts: TSRef = root.ts();
+    let mut recoder = Recoder {
+        ts,
+        lossy_values: Vec::new(),
+    };
+    let (mut child_nodes, items) = recoder.children(root.as_obj())?;
+    if let Entry::Vacant(entry) = child_nodes.entry(SPECIFIC_CHARACTER_SET) {
+        entry.insert(DicomObject::new(specific_character_set(ts, Vec::new())?));
+    }
+    for child in child_nodes.values_mut() {
+        update_lengths(child, ts);
+    }
+
+    Ok(Utf8Recoding {
+        dcmroot: DicomRoot::new(ts, CSRef::of(UTF_8), child_nodes, items),
+        lossy_values: recoder.lossy_values,
+    })
+}
+
+/// Creates a `SpecificCharacterSet` element with the value `ISO_IR 192`.
+fn specific_character_set(ts: TSRef, sq_path: Vec<SequenceElement>) -> WriteResult<DicomElement> {
+    let mut element = DicomElement::new_empty(SPECIFIC_CHARACTER_SET, &CS, ts);
+    element.encode_val(RawValue::of_string(ISO_IR_192))?;
+    element.set_sq_path(sq_path);
+    Ok(element)
+}
+
+struct Recoder {
+    ts: TSRef,
+    lossy_values: Vec<LossyValue>,
+}
+
+impl Recoder {
+    /// Re-encodes the child nodes and items of the given object.
+    fn children(
+        &mut self,
+        obj: &DicomObject,
+    ) -> WriteResult<(BTreeMap<u32, DicomObject>, Vec<DicomObject>)> {
+        let mut child_nodes: BTreeMap<u32, DicomObject> = BTreeMap::new();
+        for (tag, child) in obj.iter_child_nodes() {
+            if let Some(child) = self.object(child)? {
+                child_nodes.insert(*tag, child);
+            }
+        }
+        let mut items: Vec<DicomObject> = Vec::with_capacity(obj.item_count());
+        for item in obj.iter_items() {
+            if let Some(item) = self.object(item)? {
+                items.push(item);
+            }
+        }
+        Ok((child_nodes, items))
+    }
+
+    /// Re-encodes the given object and its children, returning `None` if it should be removed.
+    fn object(&mut self, obj: &DicomObject) -> WriteResult<Option<DicomObject>> {
+        let element: &DicomElement = obj.element();
+        if element.tag() <= FILE_META_GROUP_END {
+            return Ok(Some(DicomObject::new(element.clone())));
+        }
+        if Tag::is_group_length(element.tag()) {
+            return Ok(None);
+        }
+
+        // The contents of sequences are all re-encoded, so the character set tracked for each
+        // sequence and item is also UTF-8.
+        let sq_path: Vec<SequenceElement> = element
+            .sq_path()
+            .iter()
+            .cloned()
+            .map(|mut sq| {
+                sq.set_cs(CSRef::of(UTF_8));
+                sq
+            })
+            .collect();
+
+        let recoded: DicomElement = if element.tag() == SPECIFIC_CHARACTER_SET {
+            specific_character_set(self.ts, sq_path)?
+        } else if element.vr().decode_text_with_replaced_cs {
+            self.text(element, sq_path)?
+        } else {
+            DicomElement::new(
+                element.tag(),
+                element.vr(),
+                element.vl(),
+                element.ts(),
+                CSRef::of(UTF_8),
+                element.load_value()?.to_vec(),
+                sq_path,
+            )
+        };
+
+        let (child_nodes, items) = self.children(obj)?;
+        Ok(Some(DicomObject::new_with_children(
+            recoded,
+            child_nodes,
+            items,
+        )))
+    }
+
+    /// Decodes the text value of the element with its character set and encodes it as UTF-8,
+    /// recording the value if it could not be decoded losslessly.
+    fn text(
+        &mut self,
+        element: &DicomElement,
+        sq_path: Vec<SequenceElement>,
+    ) -> WriteResult<DicomElement> {
+        let data: &[u8] = element.load_value()?;
+        let (text, had_errors) = element
+            .cs()
+            .decode_with_replacement(data, element.vr() == &PN);
+        let text: &str = text.trim_end_matches([' ', '\0']);
+        if had_errors {
+            self.lossy_values.push(LossyValue {
+                path: element.create_tagpath(),
+                cs: element.cs(),
+                text: text.to_string(),
+            });
+        }
+
+        let mut data: Vec<u8> = text.as_bytes().to_vec();
+        if !data.len().is_multiple_of(2) {
+            data.push(b' ');
+        }
+        let vl: ValueLength =
+            u32::try_from(data.len()).map_or(ValueLength::UndefinedLength, ValueLength::Explicit);
+        Ok(DicomElement::new(
+            element.tag(),
+            element.vr(),
+            vl,
+            element.ts(),
+            CSRef::of(UTF_8),
+            data,
+            sq_path,
+        ))
+    }
+}
@@ -0,0 +1,285 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Encoding the value lengths of sequences and items when writing a `DicomRoot`.
+//!
+//! Sequences and items may be encoded with an explicit length, or with undefined length followed
+//! by a delimitation item. Explicit lengths are computed from the contents of the `DicomRoot` as
+//! it's written, so they remain correct after the `DicomRoot` has been edited.
+
+use std::{borrow::Cow, collections::HashMap};
+
+use crate::core::{
+    charset::CSRef,
+    dcmelement::DicomElement,
+    dcmobject::{DicomObject, DicomRoot},
+    dcmsqelem::SequenceElement,
+    defn::{
+        constants::{
+            tags::{
+                FILE_META_GROUP_END, FILE_META_INFORMATION_GROUP_LENGTH, ITEM,
+                ITEM_DELIMITATION_ITEM, SEQUENCE_DELIMITATION_ITEM,
+            },
+            ts::ExplicitVRLittleEndian,
+        },
+        is_parent_priv_sq, is_sq_delim,
+        ts::TSRef,
+        vl::ValueLength,
+        vr::{SQ, UN},
+    },
+    DICOM_PREFIX_LENGTH, FILE_PREAMBLE_LENGTH,
+};
+
+/// The encoded size of a delimitation item, which has no value.
+const DELIMITER_SIZE: u64 = 8;
+
+/// The encoded size of `FileMetaInformationGroupLength`, which the `Writer` always writes.
+const FILE_META_GROUP_LENGTH_SIZE: u64 = 12;
+
+/// How the value length of sequences and their items are encoded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SequenceLength {
+    /// Keep whether each sequence and item has an explicit or undefined length. Explicit lengths
+    /// are recomputed from their contents.
+    #[default]
+    Preserve,
+
+    /// Encode with explicit lengths computed from their contents.
+    Explicit,
+
+    /// Encode with undefined lengths, followed by a delimitation item.
+    Undefined,
+}
+
+/// The `SequenceLength` to use for all sequences, along with any to use for specific sequences.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SequenceLengths {
+    default: SequenceLength,
+    by_tag: HashMap<u32, SequenceLength>,
+}
+
+impl SequenceLengths {
+    pub(crate) fn set_default(&mut self, sequence_length: SequenceLength) {
+        self.default = sequence_length;
+    }
+
+    pub(crate) fn set_for_tag(&mut self, tag: u32, sequence_length: SequenceLength) {
+        self.by_tag.insert(tag, sequence_length);
+    }
+
+    fn for_tag(&self, tag: u32) -> SequenceLength {
+        self.by_tag.get(&tag).copied().unwrap_or(self.default)
+    }
+}
+
+/// Creates a delimitation item for the end of a sequence or item. The `sq_path` should be that of
+/// the other contents of the sequence or item.
+pub(crate) fn new_delimiter(tag: u32, ts: TSRef, sq_path: Vec<SequenceElement>) -> DicomElement {
+    DicomElement::new(
+        tag,
+        &UN,
+        ValueLength::Explicit(0),
+        ts,
+        CSRef::default(),
+        Vec::with_capacity(0),
+        sq_path,
+    )
+}
+
+/// Flattens the `DicomRoot` into the elements to write, like `DicomRoot::flatten()`, but with the
+/// value lengths of sequences and items encoded according to the given `SequenceLengths` and
+/// computed for the given transfer syntax. Delimitation items are added or removed as needed.
+/// Elements which are unchanged are borrowed from the `DicomRoot`.
+pub(crate) fn flatten_with_lengths<'a>(
+    dcmroot: &'a DicomRoot,
+    ts: TSRef,
+    sequence_lengths: &SequenceLengths,
+) -> Vec<Cow<'a, DicomElement>> {
+    let mut encoder = LengthEncoder {
+        ts,
+        sequence_lengths,
+        elements: Vec::new(),
+    };
+    for item in dcmroot.as_obj().iter_items() {
+        encoder.object(item, None);
+    }
+    for (_tag, child) in dcmroot.as_obj().iter_child_nodes() {
+        encoder.object(child, None);
+    }
+    encoder.elements
+}
+
+struct LengthEncoder<'a, 's> {
+    ts: TSRef,
+    sequence_lengths: &'s SequenceLengths,
+    elements: Vec<Cow<'a, DicomElement>>,
+}
+
+impl<'a> LengthEncoder<'a, '_> {
+    /// Adds the elements of the object and its contents, returning its total encoded size.
+    ///
+    /// `parent_length`: For items, the `SequenceLength` of the sequence they're within.
+    fn object(&mut self, obj: &'a DicomObject, parent_length: Option<SequenceLength>) -> u64 {
+        let element: &DicomElement = obj.element();
+        let header_size: u64 = self.header_size(element);
+
+        let is_item: bool = element.tag() == ITEM && !element.is_within_pixel_data();
+        let is_sq: bool = element.vr() == &SQ;
+        let has_contents: bool = obj.item_count() > 0 || obj.child_count() > 0;
+        if !is_item && !is_sq && !has_contents {
+            self.elements.push(Cow::Borrowed(element));
+            return header_size + element.value_size() as u64;
+        }
+
+        // Sequences which aren't SQ, such as encapsulated pixel data or private sequences with a
+        // VR of UN, are only able to be encoded with undefined length.
+        let sequence_length: SequenceLength = if is_item {
+            parent_length.unwrap_or_default()
+        } else if is_sq {
+            self.sequence_lengths.for_tag(element.tag())
+        } else {
+            SequenceLength::Undefined
+        };
+
+        let index: usize = self.elements.len();
+        self.elements.push(Cow::Borrowed(element));
+
+        let mut contents_size: u64 = 0;
+        for item in obj.iter_items() {
+            contents_size += self.object(item, Some(sequence_length));
+        }
+        let mut delimiter: Option<&'a DicomElement> = None;
+        for (tag, child) in obj.iter_child_nodes() {
+            if *tag == SEQUENCE_DELIMITATION_ITEM || *tag == ITEM_DELIMITATION_ITEM {
+                delimiter = Some(child.element());
+                continue;
+            }
+            contents_size += self.object(child, None);
+        }
+
+        let explicit_length: Option<u32> = match sequence_length {
+            SequenceLength::Preserve if element.vl() == ValueLength::UndefinedLength => None,
+            SequenceLength::Preserve | SequenceLength::Explicit => {
+                u32::try_from(contents_size).ok()
+            }
+            SequenceLength::Undefined => None,
+        };
+
+        let vl: ValueLength = if let Some(length) = explicit_length {
+            ValueLength::Explicit(length)
+        } else {
+            let delimiter: Cow<'a, DicomElement> = if let Some(delimiter) = delimiter {
+                Cow::Borrowed(delimiter)
+            } else {
+                // The delimiter is within the sequence/item, sharing the path of its other
+                // contents, if any.
+                let sq_path: Vec<SequenceElement> = obj
+                    .iter_items()
+                    .chain(obj.iter_child_nodes().map(|(_tag, child)| child))
+                    .next()
+                    .map_or_else(Vec::new, |c| c.element().sq_path().clone());
+                let delim_tag: u32 = if is_item {
+                    ITEM_DELIMITATION_ITEM
+                } else {
+                    SEQUENCE_DELIMITATION_ITEM
+                };
+                Cow::Owned(new_delimiter(delim_tag, self.ts, sq_path))
+            };
+            self.elements.push(delimiter);
+            contents_size += DELIMITER_SIZE;
+            ValueLength::UndefinedLength
+        };
+
+        if vl != element.vl() {
+            self.elements[index] = Cow::Owned(DicomElement::new(
+                element.tag(),
+                element.vr(),
+                vl,
+                element.ts(),
+                element.cs(),
+                element.data().to_vec(),
+                element.sq_path().clone(),
+            ));
+        }
+
+        header_size + contents_size
+    }
+
+    fn header_size(&self, element: &DicomElement) -> u64 {
+        header_size(self.ts, element)
+    }
+}
+
+/// Flattens the `DicomRoot` into the elements to write as a file, like `flatten_with_lengths()`
+/// with the default `SequenceLengths`, along with the byte offset from the start of the file at
+/// which each element is written. This assumes the `Writer` writes the file preamble and File Meta
+/// as given in the `DicomRoot`.
+pub(crate) fn flatten_with_file_offsets(dcmroot: &DicomRoot) -> Vec<(u64, Cow<'_, DicomElement>)> {
+    let ts: TSRef = dcmroot.ts();
+    let mut offset: u64 =
+        (FILE_PREAMBLE_LENGTH + DICOM_PREFIX_LENGTH) as u64 + FILE_META_GROUP_LENGTH_SIZE;
+    let mut offsets: Vec<(u64, Cow<'_, DicomElement>)> = Vec::new();
+    for element in flatten_with_lengths(dcmroot, ts, &SequenceLengths::default()) {
+        if element.tag() == FILE_META_INFORMATION_GROUP_LENGTH {
+            continue;
+        }
+        let element_ts: TSRef = if element.tag() <= FILE_META_GROUP_END {
+            &ExplicitVRLittleEndian
+        } else {
+            ts
+        };
+        let size: u64 = header_size(element_ts, &element) + element.value_size() as u64;
+        offsets.push((offset, element));
+        offset += size;
+    }
+    offsets
+}
+
+/// Recomputes the explicit value lengths of the sequences and items within the object from their
+/// contents, such as after it has been edited. Undefined lengths are left as-is. Returns the total
+/// encoded size of the object in the given transfer syntax.
+pub(crate) fn update_lengths(obj: &mut DicomObject, ts: TSRef) -> u64 {
+    let header_size: u64 = header_size(ts, obj.element());
+    if obj.item_count() == 0 && obj.child_count() == 0 {
+        return header_size + obj.element().value_size() as u64;
+    }
+
+    let mut contents_size: u64 = 0;
+    for item in obj.iter_items_mut() {
+        contents_size += update_lengths(item, ts);
+    }
+    for (_tag, child) in obj.iter_child_nodes_mut() {
+        contents_size += update_lengths(child, ts);
+    }
+    if obj.element().vl() != ValueLength::UndefinedLength {
+        if let Ok(length) = u32::try_from(contents_size) {
+            obj.element_mut().set_vl(ValueLength::Explicit(length));
+        }
+    }
+    header_size + contents_size
+}
+
+/// The encoded size of the element's tag, VR, and value length, following the same rules as the
+/// `Writer` for which elements are written as Implicit VR.
+fn header_size(ts: TSRef, element: &DicomElement) -> u64 {
+    let implicit_vr: bool =
+        !ts.explicit_vr() || is_sq_delim(element.tag()) || is_parent_priv_sq(element.sq_path());
+    if !implicit_vr && element.vr().has_explicit_2byte_pad {
+        12
+    } else {
+        8
+    }
+}
@@ -0,0 +1,279 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Re-encoding a dataset from one transfer syntax into another.
+//!
+//! Only conversions which do not require compressing or decompressing pixel data are supported,
+//! i.e. between Implicit and Explicit VR, Little and Big Endian, and deflated datasets.
+
+use std::collections::BTreeMap;
+
+use crate::core::{
+    dcmelement::DicomElement,
+    dcmobject::{DicomObject, DicomRoot},
+    dcmsqelem::SequenceElement,
+    defn::{
+        constants::{
+            tags::{
+                FILE_META_GROUP_END, ITEM, ITEM_DELIMITATION_ITEM, PIXEL_DATA,
+                SEQUENCE_DELIMITATION_ITEM, TRANSFER_SYNTAX_UID,
+            },
+            ts::ExplicitVRLittleEndian,
+        },
+        dcmdict::DicomDictionary,
+        is_parent_priv_sq, is_sq_delim,
+        tag::Tag,
+        ts::TSRef,
+        vl::ValueLength,
+        vr::{VRRef, AT, FD, FL, OD, OF, OL, OV, OW, SL, SQ, SS, SV, UI, UL, UN, US, UV},
+    },
+    values::RawValue,
+    write::{error::WriteError, sqlength::new_delimiter, writer::WriteResult},
+};
+
+/// The largest value which can be encoded with a 16-bit value length, as used by most VRs in
+/// Explicit VR transfer syntaxes.
+const MAX_SHORT_VALUE_LENGTH: usize = 0xFFFF;
+
+/// Re-encodes the dataset into the target transfer syntax, returning the new dataset.
+///
+/// - Values of VRs with multi-byte numbers are byte-swapped when the byte order changes.
+/// - When converting from Implicit VR, elements with a VR of `UN` are resolved to their VR from
+///   the dictionary, and `PixelData` is given the VR `OW`. Values too long for the 16-bit value
+///   length of their VR in Explicit VR are changed to `UN`.
+/// - Sequences and items with an explicit length are changed to undefined length when the change
+///   between Implicit and Explicit VR would alter their encoded length.
+/// - Group length elements outside of File Meta are removed when their lengths would change.
+/// - The `TransferSyntaxUID` in File Meta, if present, is updated to the target.
+///
+/// # Errors
+/// `WriteError::UnsupportedTranscode` if either transfer syntax has encapsulated pixel data and
+/// they differ, or errors loading deferred values.
+pub fn transcode(
+    root: &DicomRoot,
+    target: TSRef,
+    dictionary: &dyn DicomDictionary,
+) -> WriteResult<DicomRoot> {
+    let source: TSRef = root.ts();
+    if source != target && (source.encapsulated() || target.encapsulated()) {
+        return Err(WriteError::UnsupportedTranscode(
+            source.uid().name().to_string(),
+            target.uid().name().to_string(),
+        ));
+    }
+
+    let transcoder = Transcoder { target, dictionary };
+    let (child_nodes, items) = transcoder.children(root.as_obj())?;
+    Ok(DicomRoot::new(target, root.cs(), child_nodes, items))
+}
+
+/// Re-encodes an object and its contents, such as an element or item taken from another dataset,
+/// into the target transfer syntax following the same rules as `transcode`. Returns `None` if the
+/// object is a group length which would not be kept. The `sq_path`s and lengths of the result are
+/// not updated for where it's placed in the target dataset.
+///
+/// # Errors
+/// `WriteError::UnsupportedTranscode` if the object is pixel data and either transfer syntax has
+/// encapsulated pixel data and they differ, or errors loading deferred values.
+pub(crate) fn transcode_object(
+    obj: &DicomObject,
+    target: TSRef,
+    dictionary: &dyn DicomDictionary,
+) -> WriteResult<Option<DicomObject>> {
+    let source: TSRef = obj.element().ts();
+    if obj.element().is_pixel_data()
+        && source != target
+        && (source.encapsulated() || target.encapsulated())
+    {
+        return Err(WriteError::UnsupportedTranscode(
+            source.uid().name().to_string(),
+            target.uid().name().to_string(),
+        ));
+    }
+
+    Transcoder { target, dictionary }.object(obj)
+}
+
+/// Re-encodes a single element into the given transfer syntax with the given VR and value length.
+/// The value is byte-swapped according to the VR if the byte order changes. The value length is
+/// not adjusted, so this should not be used for sequences whose encoded length changes.
+///
+/// # Errors
+/// Errors loading a deferred value.
+pub(crate) fn transcode_element(
+    element: &DicomElement,
+    vr: VRRef,
+    vl: ValueLength,
+    ts: TSRef,
+) -> WriteResult<DicomElement> {
+    let data: &[u8] = element.load_value()?;
+    // Fragments of encapsulated pixel data are not encoded according to byte order.
+    let data: Vec<u8> =
+        if element.ts().big_endian() == ts.big_endian() || element.is_within_pixel_data() {
+            data.to_vec()
+        } else {
+            swap_byte_order(vr, data)
+        };
+    Ok(DicomElement::new(
+        element.tag(),
+        vr,
+        vl,
+        ts,
+        element.cs(),
+        data,
+        element.sq_path().clone(),
+    ))
+}
+
+/// Swaps the byte order of each number within a value of the given VR. Values of VRs which are not
+/// numbers, or are single bytes, are returned as-is.
+fn swap_byte_order(vr: VRRef, data: &[u8]) -> Vec<u8> {
+    let mut data: Vec<u8> = data.to_vec();
+    let width: usize = byte_order_width(vr);
+    if width > 1 {
+        for number in data.chunks_exact_mut(width) {
+            number.reverse();
+        }
+    }
+    data
+}
+
+/// The number of bytes of each number within values of the given VR which are affected by byte
+/// order. Tags (`AT`) are encoded as two 16-bit numbers.
+fn byte_order_width(vr: VRRef) -> usize {
+    if vr == &AT || vr == &OW || vr == &SS || vr == &US {
+        2
+    } else if vr == &FL || vr == &OF || vr == &OL || vr == &SL || vr == &UL {
+        4
+    } else if vr == &FD || vr == &OD || vr == &OV || vr == &SV || vr == &UV {
+        8
+    } else {
+        1
+    }
+}
+
+struct Transcoder<'d> {
+    target: TSRef,
+    dictionary: &'d dyn DicomDictionary,
+}
+
+impl Transcoder<'_> {
+    /// Transcodes the child nodes and items of the given object.
+    fn children(
+        &self,
+        obj: &DicomObject,
+    ) -> WriteResult<(BTreeMap<u32, DicomObject>, Vec<DicomObject>)> {
+        let mut child_nodes: BTreeMap<u32, DicomObject> = BTreeMap::new();
+        for (tag, child) in obj.iter_child_nodes() {
+            if let Some(child) = self.object(child)? {
+                child_nodes.insert(*tag, child);
+            }
+        }
+        let mut items: Vec<DicomObject> = Vec::with_capacity(obj.item_count());
+        for item in obj.iter_items() {
+            if let Some(item) = self.object(item)? {
+                items.push(item);
+            }
+        }
+        Ok((child_nodes, items))
+    }
+
+    /// Transcodes the given object and its children, returning `None` if it should be removed.
+    fn object(&self, obj: &DicomObject) -> WriteResult<Option<DicomObject>> {
+        let element: &DicomElement = obj.element();
+        if element.tag() <= FILE_META_GROUP_END {
+            return self.file_meta(element).map(|e| Some(DicomObject::new(e)));
+        }
+
+        // Sequences within private sequences of undefined length are always encoded as Implicit
+        // VR Little Endian so their lengths are not affected.
+        let resized: bool = element.ts().explicit_vr() != self.target.explicit_vr()
+            && !is_parent_priv_sq(element.sq_path());
+        if resized && Tag::is_group_length(element.tag()) {
+            return Ok(None);
+        }
+
+        let vr: VRRef = self.resolve_vr(element);
+        let undefine: bool = resized
+            && (vr == &SQ || (element.tag() == ITEM && !element.is_within_pixel_data()))
+            && matches!(element.vl(), ValueLength::Explicit(len) if len > 0);
+        let vl: ValueLength = if undefine {
+            ValueLength::UndefinedLength
+        } else {
+            element.vl()
+        };
+
+        let transcoded: DicomElement = transcode_element(element, vr, vl, self.target)?;
+        let (mut child_nodes, items) = self.children(obj)?;
+        if undefine {
+            // The delimiter is within the sequence/item, sharing the path of its other contents.
+            let (delim_tag, sq_path) = if element.tag() == ITEM {
+                let sq_path = child_nodes.values().next().map(|c| c.element().sq_path());
+                (ITEM_DELIMITATION_ITEM, sq_path)
+            } else {
+                let sq_path = items.first().map(|i| i.element().sq_path());
+                (SEQUENCE_DELIMITATION_ITEM, sq_path)
+            };
+            let delimiter = new_delimiter(
+                delim_tag,
+                self.target,
+                sq_path.map_or_else(Vec::new, |p: &Vec<SequenceElement>| p.clone()),
+            );
+            child_nodes.insert(delim_tag, DicomObject::new(delimiter));
+        }
+
+        Ok(Some(DicomObject::new_with_children(
+            transcoded,
+            child_nodes,
+            items,
+        )))
+    }
+
+    /// File Meta elements are always encoded as Explicit VR Little Endian and are not changed,
+    /// except for updating the `TransferSyntaxUID` to the target.
+    fn file_meta(&self, element: &DicomElement) -> WriteResult<DicomElement> {
+        if element.tag() != TRANSFER_SYNTAX_UID {
+            return Ok(element.clone());
+        }
+        let mut ts_uid = DicomElement::new_empty(TRANSFER_SYNTAX_UID, &UI, &ExplicitVRLittleEndian);
+        ts_uid.encode_val(RawValue::of_uid(self.target.uid().uid()))?;
+        Ok(ts_uid)
+    }
+
+    /// Determines the VR the element should be encoded with in the target transfer syntax.
+    fn resolve_vr(&self, element: &DicomElement) -> VRRef {
+        let mut vr: VRRef = element.vr();
+        if !element.ts().explicit_vr() {
+            if element.tag() == PIXEL_DATA {
+                // Part 5, Section A.1: Pixel Data is always OW when encoded with Implicit VR.
+                vr = &OW;
+            } else if vr == &UN && !is_sq_delim(element.tag()) {
+                vr = self
+                    .dictionary
+                    .get_tag_by_number(element.tag())
+                    .and_then(Tag::implicit_vr)
+                    .unwrap_or(vr);
+            }
+        }
+        if self.target.explicit_vr()
+            && !vr.has_explicit_2byte_pad
+            && element.value_size() > MAX_SHORT_VALUE_LENGTH
+        {
+            vr = &UN;
+        }
+        vr
+    }
+}
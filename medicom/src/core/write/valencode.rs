@@ -21,7 +21,7 @@ use std::{iter::once, mem::size_of};
 
 use crate::core::{
     dcmelement::DicomElement,
-    defn::vr::CS_SEPARATOR_BYTE,
+    defn::vr::{CS_SEPARATOR_BYTE, PN},
     read::{ParseError, ParseResult},
     values::{Attribute, RawValue},
 };
@@ -53,7 +53,7 @@ impl<'e> TryFrom<ElemAndRawValue<'e>> for Vec<u8> {
             RawValue::DWords(dwords) => ElemAndDoubleWords(elem, dwords).into(),
             RawValue::QWords(qwords) => ElemAndQuadWords(elem, qwords).into(),
 
-            RawValue::BytesView(bytes) => bytes.clone(),
+            RawValue::BytesView(bytes) => bytes.to_vec(),
         };
 
         // All fields are required to be of even length, with padding added as necessary. Note
@@ -114,15 +114,18 @@ impl<'e> TryFrom<ElemAndStrings<'e>> for Vec<u8> {
         let (values, errs): (MaybeBytes, MaybeBytes) = strings
             .iter()
             .map(|s| {
-                elem.cs()
-                    .encode(s)
-                    // Add the separator after each encoded value. Below the last separator
-                    // will be popped off.
-                    .map(|mut v| {
-                        v.push(CS_SEPARATOR_BYTE);
-                        v
-                    })
-                    .map_err(|e| ParseError::CharsetError { source: e })
+                if elem.vr() == &PN {
+                    elem.cs().encode_person_name(s)
+                } else {
+                    elem.cs().encode(s)
+                }
+                // Add the separator after each encoded value. Below the last separator
+                // will be popped off.
+                .map(|mut v| {
+                    v.push(CS_SEPARATOR_BYTE);
+                    v
+                })
+                .map_err(|e| ParseError::CharsetError { source: e })
             })
             .partition(ParseResult::is_ok);
 
@@ -14,7 +14,10 @@
    limitations under the License.
 */
 
-use std::io::Write;
+use std::{
+    borrow::Borrow,
+    io::{self, ErrorKind, Read, Write},
+};
 
 use crate::core::{
     charset::CSRef,
@@ -22,17 +25,23 @@ use crate::core::{
     dcmobject::DicomRoot,
     defn::{
         constants::{
-            tags::{FILE_META_GROUP_END, FILE_META_INFORMATION_GROUP_LENGTH},
+            tags::{FILE_META_GROUP_END, FILE_META_INFORMATION_GROUP_LENGTH, SOP_INSTANCE_UID},
             ts::{ExplicitVRLittleEndian, ImplicitVRBigEndian, ImplicitVRLittleEndian},
         },
         is_parent_priv_sq, is_sq_delim,
         ts::TSRef,
         vl::{ValueLength, UNDEFINED_LENGTH},
-        vr::{self, VRRef, OB},
+        vr::{self, VRRef},
     },
     read::ParseError,
     values::RawValue,
-    write::{ds::dataset::Dataset, error::WriteError},
+    write::{
+        ds::dataset::Dataset,
+        error::WriteError,
+        filemeta::generate_file_meta,
+        sqlength::{flatten_with_lengths, SequenceLengths},
+        transcode::transcode_element,
+    },
     DICOM_PREFIX, FILE_PREAMBLE_LENGTH,
 };
 
@@ -62,6 +71,16 @@ pub struct Writer<W: Write> {
     /// If a file preamble is specified then the `"DICM"` prefix will be written immediately
     /// after the file preamble is written.
     pub(crate) file_preamble: Option<[u8; FILE_PREAMBLE_LENGTH]>,
+
+    /// Whether to generate the File Meta group from the dataset and these settings, rather than
+    /// only writing the File Meta elements given.
+    pub(crate) generate_file_meta: bool,
+
+    /// The AE Title to write as `SourceApplicationEntityTitle` in generated File Meta.
+    pub(crate) source_ae_title: Option<String>,
+
+    /// How the value lengths of sequences and items are encoded by `write_dcmroot()`.
+    pub(crate) sequence_lengths: SequenceLengths,
 }
 
 impl<W: Write> Writer<W> {
@@ -99,19 +118,31 @@ impl<W: Write> Writer<W> {
         Ok(e)
     }
 
-    /// Consumes self and return the wrapped dataset field.
+    /// Consumes self and return the wrapped dataset field. If the transfer syntax is deflated then
+    /// use `finish()` instead, which completes the deflated stream.
     pub fn into_dataset(self) -> W {
         self.dataset.into_inner()
     }
 
+    /// Completes writing the dataset, consuming self and returning the wrapped dataset field. For
+    /// deflated transfer syntaxes this writes out the remaining deflated bytes.
+    ///
+    /// # Errors
+    /// Errors may occur writing the remaining bytes to the dataset.
+    pub fn finish(self) -> WriteResult<W> {
+        Ok(self.dataset.finish()?)
+    }
+
     /// Flattens the given `DicomRoot` elements into a stream of `DicomElement` and writes the
-    /// resulting elements into the dataset.
+    /// resulting elements into the dataset. The value lengths of sequences and items are encoded
+    /// according to the writer's `SequenceLength` settings, with explicit lengths computed from
+    /// the contents of the `DicomRoot`.
     ///
     /// # Errors
     /// Errors may occur writing to the dataset.
     pub fn write_dcmroot(&mut self, dcmroot: &DicomRoot) -> WriteResult<usize> {
-        let elements = dcmroot.flatten();
-        self.write_elements(elements.into_iter())
+        let elements = flatten_with_lengths(dcmroot, self.ts, &self.sequence_lengths);
+        self.write_any_elements(elements.into_iter())
     }
 
     /// Write the iterator of `DicomElement` to the dataset. If the `WriteState` is set to any
@@ -122,6 +153,29 @@ impl<W: Write> Writer<W> {
     pub fn write_elements<'a, I>(&mut self, elements: I) -> WriteResult<usize>
     where
         I: Iterator<Item = &'a DicomElement>,
+    {
+        self.write_any_elements(elements)
+    }
+
+    /// Write the iterator of `DicomElement` to the dataset. If the `WriteState` is set to any
+    /// valid state for file media, this will handle appropriate encoding for file meta group.
+    ///
+    /// # Notes
+    /// This is the same as `write_elements()` but takes an iterator over owned elements.
+    ///
+    /// # Errors
+    /// Errors may occur writing to the dataset.
+    pub fn write_owned_elements<I>(&mut self, elements: I) -> WriteResult<usize>
+    where
+        I: Iterator<Item = DicomElement>,
+    {
+        self.write_any_elements(elements)
+    }
+
+    fn write_any_elements<E, I>(&mut self, elements: I) -> WriteResult<usize>
+    where
+        E: Borrow<DicomElement>,
+        I: Iterator<Item = E>,
     {
         let mut bytes_written: usize = 0;
 
@@ -135,20 +189,31 @@ impl<W: Write> Writer<W> {
             self.state = WriterState::WriteFileMeta;
         }
 
-        let mut fm_elements: Vec<&DicomElement> = Vec::new();
+        let mut fm_elements: Vec<E> = Vec::new();
+        // When generating FileMeta, the elements following FileMeta are held until the
+        // SOPInstanceUID has been seen, as FileMeta values are taken from them.
+        let mut held_elements: Vec<E> = Vec::new();
         for element in elements {
             // Collect all the FileMeta elements to write them in one go, as their total byte
             // length is needed for the first element, FileMetaInformationGroupLength.
             if self.state == WriterState::WriteFileMeta {
-                if element.tag() <= FILE_META_GROUP_END {
+                let elem: &DicomElement = element.borrow();
+                if elem.tag() <= FILE_META_GROUP_END {
                     // Ignore FileMetaInformationGroupLength in place of one made below.
-                    if element.tag() != FILE_META_INFORMATION_GROUP_LENGTH {
+                    if elem.tag() != FILE_META_INFORMATION_GROUP_LENGTH {
                         fm_elements.push(element);
                     }
                     continue;
                 }
 
-                bytes_written += self.write_fm_elements(fm_elements.as_slice())?;
+                if self.generate_file_meta
+                    && (!elem.sq_path().is_empty() || elem.tag() <= SOP_INSTANCE_UID)
+                {
+                    held_elements.push(element);
+                    continue;
+                }
+
+                bytes_written += self.write_file_meta(&fm_elements, &held_elements)?;
                 // The list of FileMeta elements are no longer needed.
                 fm_elements.clear();
 
@@ -156,81 +221,151 @@ impl<W: Write> Writer<W> {
                 // getting to this state the `element` value is non-FileMeta and hasn't been
                 // written out yet.
                 self.state = WriterState::WriteElement;
+                for held in held_elements.drain(..) {
+                    bytes_written +=
+                        Writer::write_element(&mut self.dataset, self.ts, held.borrow())?;
+                }
             }
 
-            bytes_written += Writer::write_element(&mut self.dataset, self.ts, element)?;
+            bytes_written += Writer::write_element(&mut self.dataset, self.ts, element.borrow())?;
         }
 
         // If the input elements only consist of FileMeta elements then the above loop will never
         // result in writing any elements as they're being collected into `fm_elements`.
-        if self.state == WriterState::WriteFileMeta && !fm_elements.is_empty() {
-            bytes_written += self.write_fm_elements(fm_elements.as_slice())?;
+        if self.state == WriterState::WriteFileMeta
+            && (!fm_elements.is_empty() || !held_elements.is_empty())
+        {
+            bytes_written += self.write_file_meta(&fm_elements, &held_elements)?;
+            // File Meta has been written so any elements given later are not part of it.
+            self.state = WriterState::WriteElement;
+            for held in held_elements.drain(..) {
+                bytes_written += Writer::write_element(&mut self.dataset, self.ts, held.borrow())?;
+            }
         }
 
         self.bytes_written += u64::try_from(bytes_written).unwrap_or_default();
         Ok(bytes_written)
     }
 
-    /// Write the iterator of `DicomElement` to the dataset. If the `WriteState` is set to any
-    /// valid state for file media, this will handle appropriate encoding for file meta group.
+    /// Writes the header of an element, its tag, VR, and value length, without its value. This
+    /// allows writing values too large to hold in memory. For an explicit length the value should
+    /// then be written with `write_value_from()`. For undefined length the items and delimitation
+    /// item should follow, which can also be written with this function.
     ///
-    /// # Notes
-    /// This is the same as `write_elements()` but takes an iterator over owned elements.
-    ///
-    /// This is an unfortunate copy/paste.
+    /// Any File Meta must already have been written, and the preamble is written if needed.
     ///
     /// # Errors
-    /// Errors may occur writing to the dataset.
-    pub fn write_owned_elements<I>(&mut self, elements: I) -> WriteResult<usize>
+    /// - `WriteError::IncompleteFileMeta` if File Meta is to be generated but has not been written.
+    /// - `WriteError::InvalidValueLength` if the length is undefined and the VR does not allow it.
+    /// - Errors writing to the dataset.
+    pub fn write_element_header<T>(
+        &mut self,
+        tag: T,
+        vr: VRRef,
+        vl: ValueLength,
+    ) -> WriteResult<usize>
     where
-        I: Iterator<Item = DicomElement>,
+        u32: From<T>,
     {
-        let mut bytes_written: usize = 0;
+        self.begin_streaming()?;
+        let header = DicomElement::new(
+            tag,
+            vr,
+            vl,
+            self.ts,
+            self.cs,
+            Vec::with_capacity(0),
+            Vec::with_capacity(0),
+        );
+        let bytes_written = Writer::write_element(&mut self.dataset, self.ts, &header)?;
+        self.bytes_written += u64::try_from(bytes_written).unwrap_or_default();
+        Ok(bytes_written)
+    }
 
-        if self.state == WriterState::WritePreamble {
-            if let Some(preamble) = self.file_preamble {
-                self.dataset.write_all(&preamble)?;
-                bytes_written += preamble.len();
-                self.dataset.write_all(DICOM_PREFIX)?;
-                bytes_written += DICOM_PREFIX.len();
-            }
-            self.state = WriterState::WriteFileMeta;
+    /// Writes exactly `length` bytes read from the reader as the value of the element whose header
+    /// was just written with `write_element_header()`. The bytes must already be encoded for the
+    /// writer's transfer syntax, as they're written as-is.
+    ///
+    /// # Errors
+    /// - `WriteError::DetailedIOError` if the reader ends before `length` bytes were read.
+    /// - Errors reading from the reader or writing to the dataset.
+    pub fn write_value_from<R: Read>(&mut self, reader: R, length: u64) -> WriteResult<u64> {
+        let copied: u64 = io::copy(&mut reader.take(length), &mut self.dataset)?;
+        self.bytes_written += copied;
+        if copied < length {
+            return Err(WriteError::DetailedIOError {
+                source: ErrorKind::UnexpectedEof.into(),
+                detail: format!("expected {length} bytes of value but read {copied}"),
+            });
         }
+        Ok(copied)
+    }
 
-        let mut fm_elements: Vec<DicomElement> = Vec::new();
-        for element in elements {
-            // Collect all the FileMeta elements to write them in one go, as their total byte
-            // length is needed for the first element, FileMetaInformationGroupLength.
-            if self.state == WriterState::WriteFileMeta {
-                if element.tag() <= FILE_META_GROUP_END {
-                    // Ignore FileMetaInformationGroupLength in place of one made below.
-                    if element.tag() != FILE_META_INFORMATION_GROUP_LENGTH {
-                        fm_elements.push(element);
-                    }
-                    continue;
-                }
-
-                bytes_written += self.write_owned_fm_elements(fm_elements.as_slice())?;
-                // The list of FileMeta elements are no longer needed.
-                fm_elements.clear();
+    /// Writes an element whose value of the given length is read from the reader, without holding
+    /// the value in memory. If the length is odd then the value is padded to even length using
+    /// the VR's padding. The bytes must already be encoded for the writer's transfer syntax.
+    ///
+    /// # Errors
+    /// Errors from `write_element_header()` and `write_value_from()`.
+    pub fn write_element_from<T, R>(
+        &mut self,
+        tag: T,
+        vr: VRRef,
+        length: u32,
+        reader: R,
+    ) -> WriteResult<u64>
+    where
+        u32: From<T>,
+        R: Read,
+    {
+        let padded: u32 = length.saturating_add(length % 2);
+        let mut bytes_written: u64 =
+            self.write_element_header(tag, vr, ValueLength::Explicit(padded))? as u64;
+        bytes_written += self.write_value_from(reader, u64::from(length))?;
+        if padded > length {
+            self.dataset.write_all(&[vr.padding])?;
+            self.bytes_written += 1;
+            bytes_written += 1;
+        }
+        Ok(bytes_written)
+    }
 
-                // Flip state to write standard elements, and fall-through. In the condition for
-                // getting to this state the `element` value is non-FileMeta and hasn't been
-                // written out yet.
-                self.state = WriterState::WriteElement;
+    /// Prepares for writing individual elements, writing the preamble if it hasn't been written.
+    fn begin_streaming(&mut self) -> WriteResult<()> {
+        if self.state == WriterState::WritePreamble {
+            self.write_any_elements(std::iter::empty::<&DicomElement>())?;
+        }
+        if self.state != WriterState::WriteElement {
+            if self.generate_file_meta {
+                return Err(WriteError::IncompleteFileMeta(
+                    "elements up to SOPInstanceUID must be written first".to_string(),
+                ));
             }
-
-            bytes_written += Writer::write_element(&mut self.dataset, self.ts, &element)?;
+            self.state = WriterState::WriteElement;
         }
+        Ok(())
+    }
 
-        // If the input elements only consist of FileMeta elements then the above loop will never
-        // result in writing any elements as they're being collected into `fm_elements`.
-        if self.state == WriterState::WriteFileMeta && !fm_elements.is_empty() {
-            bytes_written += self.write_owned_fm_elements(fm_elements.as_slice())?;
+    /// Writes the `FileMeta` group, either the given elements or if configured, a group generated
+    /// from them and the elements which follow `FileMeta`.
+    fn write_file_meta<E: Borrow<DicomElement>>(
+        &mut self,
+        fm_elements: &[E],
+        following: &[E],
+    ) -> WriteResult<usize> {
+        let fm_elements: Vec<&DicomElement> = fm_elements.iter().map(Borrow::borrow).collect();
+        if !self.generate_file_meta {
+            return self.write_fm_elements(&fm_elements);
         }
 
-        self.bytes_written += u64::try_from(bytes_written).unwrap_or_default();
-        Ok(bytes_written)
+        let following: Vec<&DicomElement> = following.iter().map(Borrow::borrow).collect();
+        let generated: Vec<DicomElement> = generate_file_meta(
+            &fm_elements,
+            &following,
+            self.ts,
+            self.source_ae_title.as_deref(),
+        )?;
+        self.write_fm_elements(&generated.iter().collect::<Vec<&DicomElement>>())
     }
 
     /// Writes all the given `FileMeta` elements to an in-memory buffer, computes the length of the
@@ -263,20 +398,6 @@ impl<W: Write> Writer<W> {
         Ok(bytes_written)
     }
 
-    /// Writes all the given `FileMeta` elements to an in-memory buffer, computes the length of the
-    /// resulting bytes, and generates a `FileMetaInformationGroupLength` element, writes it to the
-    /// dataset, then writes the in-memory buffer to the dataset as well.
-    ///
-    /// `fm_elements`: Slice of `&DicomElement`s which should all be elements with tag numbers in
-    /// the range for `FileMeta`, and SHOULD NOT include a `FileMetaInformationGroupLength` element.
-    ///
-    /// # Notes
-    /// Same as `write_fm_elements` but takes `&[DicomElement]`.
-    fn write_owned_fm_elements(&mut self, fm_elements: &[DicomElement]) -> WriteResult<usize> {
-        let fm_elem_refs = fm_elements.iter().collect::<Vec<&DicomElement>>();
-        self.write_fm_elements(fm_elem_refs.as_slice())
-    }
-
     fn new_fme(tag: u32, vr: VRRef, value: RawValue) -> WriteResult<DicomElement> {
         let mut element = DicomElement::new_empty(tag, vr, &ExplicitVRLittleEndian);
 
@@ -294,6 +415,9 @@ impl<W: Write> Writer<W> {
     ) -> WriteResult<usize> {
         let mut bytes_written: usize = 0;
 
+        // Values which were deferred during parsing need to be loaded in order to be written.
+        element.load_value()?;
+
         // If the element is a sequence-delimiter, or is within a private sequence, then the
         // elements should be written with Implicit VR.
         let ts = if is_sq_delim(element.tag()) || is_parent_priv_sq(element.sq_path()) {
@@ -306,44 +430,22 @@ impl<W: Write> Writer<W> {
             dataset_ts
         };
 
-        // TODO: Make this behavior a preference on the writer.
-        // TODO: Performance improvements:
-        //       - Some VRs are encoded with limited characters that will always be single-byte
-        //         when using the default character set, and should not require re-encoding when
-        //         the byte-endian changes.
-
-        // Non-PixelData elements are only affected by change to byte-endian, e.g. a change
-        // from ExplicitVRLittleEndian to JPEGBaselineProcess1 would only result in a change to
-        // how PixelData tags are encoded but not any other elements.
-        let non_pd_same_endian =
-            !element.is_pixel_data() && element.ts().big_endian() == ts.big_endian();
-        // Values without bytes and values encoded as plain bytes are not affected by change to
-        // byte-endian.
-        let non_endian_affected = element.is_empty() || element.vr() == &OB;
-
+        // Values are re-encoded if the transfer syntax differs, which only affects values whose
+        // byte order changes.
         let changed = if element.ts() == ts {
             None
-        } else if non_pd_same_endian || non_endian_affected {
-            let data = element.data().clone();
-            let updated_elem = DicomElement::new(
-                element.tag(),
-                element.vr(),
-                element.vl(),
-                ts,
-                element.cs(),
-                data,
-                element.sq_path().clone(),
-            );
-            Some(updated_elem)
         } else {
-            // Re-encode the value to the new transfer syntax.
-            let mut re_enc = DicomElement::new_empty(element.tag(), element.vr(), ts);
-            let value = element.parse_value()?;
-            re_enc.encode_val(value)?;
-            Some(re_enc)
+            Some(transcode_element(element, element.vr(), element.vl(), ts)?)
         };
         let element = changed.as_ref().unwrap_or(element);
 
+        // The entire element is deflated, including delimiters and elements within private
+        // sequences which are written as Implicit VR.
+        #[cfg(feature = "compress")]
+        {
+            dataset.set_write_deflated(dataset_ts.deflated());
+        }
+
         bytes_written += Writer::write_tag(dataset, element)?;
         bytes_written += Writer::write_vr(dataset, element)?;
         bytes_written += Writer::write_vl(dataset, element)?;
@@ -451,13 +553,8 @@ impl<W: Write> Writer<W> {
             return Ok(0);
         }
 
-        #[cfg(feature = "compress")]
-        {
-            dataset.set_write_deflated(element.ts().deflated());
-        }
-
         let mut bytes_written: usize = 0;
-        dataset.write_all(element.data().as_slice())?;
+        dataset.write_all(element.data())?;
         bytes_written += element.data().len();
         Ok(bytes_written)
     }
@@ -0,0 +1,626 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! Conversion between `DicomRoot` and the Native DICOM Model XML, enabled with the `xml` feature.
+//! Refer to Part 19, Annex A.
+//!
+//! The document's `NativeDicomModel` element contains a `DicomAttribute` for each attribute, with
+//! its `tag` as eight uppercase hex digits and its `vr`. The attribute's values are each a `Value`
+//! or `PersonName`, its sequence items are each an `Item` containing further attributes, and binary
+//! values are either the base64-encoded `InlineBinary` or a `BulkData` element whose `uri` refers
+//! to the value stored elsewhere.
+//!
+//! As with the JSON model, binary values are always little endian. When reading, the transfer
+//! syntax of the resulting `DicomRoot` is taken from the Transfer Syntax UID attribute if present,
+//! otherwise Explicit VR Little Endian is used.
+
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, Write},
+};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use quick_xml::{
+    events::{BytesDecl, BytesStart, BytesText, Event},
+    Reader, Writer,
+};
+use thiserror::Error;
+
+pub use crate::core::interchange::{BulkDataResolverFn, BulkDataUriFn};
+
+use crate::core::{
+    dcmobject::{DicomObject, DicomRoot},
+    defn::{
+        constants::{
+            lookup::MINIMAL_DICOM_DICTIONARY,
+            tags::{SPECIFIC_CHARACTER_SET, TRANSFER_SYNTAX_UID},
+        },
+        dcmdict::DicomDictionary,
+        tag::{Tag, TagRef},
+        vr::{VRRef, SQ, VR},
+    },
+    interchange::{
+        bulk_data_uri, model_attributes, model_value, model_vr, parse_tag, resolve_cs, resolve_ts,
+        values_from_strings, ModelBuilder, ModelValue,
+    },
+    read::ParseError,
+    values::{
+        pn::{PersonName, PersonNameGroup},
+        Attribute, RawValue,
+    },
+};
+
+/// The namespace of the Native DICOM Model.
+pub const NATIVE_DICOM_NAMESPACE: &str = "http://dicom.nema.org/PS3.19/models/NativeDICOM";
+
+/// The names of the component groups of a person name, in order.
+const PN_GROUPS: [&str; 3] = ["Alphabetic", "Ideographic", "Phonetic"];
+
+/// The names of the components of a person name group, in order.
+const PN_COMPONENTS: [&str; 5] = [
+    "FamilyName",
+    "GivenName",
+    "MiddleName",
+    "NamePrefix",
+    "NameSuffix",
+];
+
+/// Errors that can occur converting to or from the Native DICOM Model.
+#[derive(Error, Debug)]
+pub enum XmlError {
+    /// The XML could not be parsed or written.
+    #[error("invalid xml: {0}")]
+    InvalidXml(#[from] quick_xml::Error),
+
+    /// The XML document does not follow the Native DICOM Model.
+    #[error("invalid document: {0}")]
+    InvalidDocument(String),
+
+    /// An attribute does not follow the Native DICOM Model.
+    #[error("invalid attribute {tag}: {detail}")]
+    InvalidAttribute { tag: String, detail: String },
+
+    /// An attribute's value is `BulkData` which could not be resolved.
+    #[error("unable to resolve bulk data for {tag}: {uri}")]
+    UnresolvedBulkData { tag: String, uri: String },
+
+    /// A value could not be decoded from, or encoded into, an element.
+    #[error("error converting element value: {0}")]
+    ValueError(#[from] ParseError),
+}
+
+pub type XmlResult<T> = Result<T, XmlError>;
+
+/// Options for writing a `DicomRoot` as XML.
+#[derive(Default, Clone, Copy)]
+pub struct XmlWriteOptions<'a> {
+    /// Binary values larger than this many bytes are referred to by `BulkData`.
+    bulk_data_threshold: Option<usize>,
+
+    /// Creates the `BulkData` URI for values over the threshold.
+    bulk_data_uri: Option<BulkDataUriFn<'a>>,
+
+    /// The dictionary for the `keyword` of each attribute.
+    dictionary: Option<&'a dyn DicomDictionary>,
+
+    /// Whether the written XML is indented.
+    pretty: bool,
+}
+
+impl<'a> XmlWriteOptions<'a> {
+    /// Refers to binary values larger than `threshold` bytes by the URI created for the element,
+    /// rather than including them as `InlineBinary`. The caller is responsible for storing the
+    /// value such that it can be retrieved from the URI. If no URI is created the value is included
+    /// inline.
+    #[must_use]
+    pub fn bulk_data(mut self, threshold: usize, uri: BulkDataUriFn<'a>) -> Self {
+        self.bulk_data_threshold = Some(threshold);
+        self.bulk_data_uri = Some(uri);
+        self
+    }
+
+    /// Sets the dictionary used to include the `keyword` of each attribute. Private attributes are
+    /// looked up by their private creator. Without one, keywords are omitted.
+    #[must_use]
+    pub fn dictionary(mut self, dictionary: &'a dyn DicomDictionary) -> Self {
+        self.dictionary = Some(dictionary);
+        self
+    }
+
+    /// Sets whether the written XML is indented.
+    #[must_use]
+    pub fn pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+}
+
+/// Options for reading XML as a `DicomRoot`.
+#[derive(Default, Clone, Copy)]
+pub struct XmlReadOptions<'a> {
+    /// Resolves `BulkData` URIs.
+    bulk_data_resolver: Option<BulkDataResolverFn<'a>>,
+
+    /// The dictionary for resolving the Transfer Syntax UID.
+    dictionary: Option<&'a dyn DicomDictionary>,
+}
+
+impl<'a> XmlReadOptions<'a> {
+    /// Sets the function for retrieving the values of attributes given as `BulkData`. Without one,
+    /// reading XML which has bulk data fails.
+    #[must_use]
+    pub fn bulk_data_resolver(mut self, resolver: BulkDataResolverFn<'a>) -> Self {
+        self.bulk_data_resolver = Some(resolver);
+        self
+    }
+
+    /// Sets the dictionary for resolving the Transfer Syntax UID. By default only the uncompressed
+    /// transfer syntaxes can be resolved.
+    #[must_use]
+    pub fn dictionary(mut self, dictionary: &'a dyn DicomDictionary) -> Self {
+        self.dictionary = Some(dictionary);
+        self
+    }
+}
+
+/// Writes the `DicomRoot` as a Native DICOM Model XML document.
+///
+/// # Errors
+/// - `XmlError::ValueError` if a value could not be decoded, or a deferred value loaded.
+/// - `XmlError::InvalidXml` if writing fails.
+pub fn write_xml<W: Write>(
+    dcmroot: &DicomRoot,
+    writer: W,
+    options: &XmlWriteOptions<'_>,
+) -> XmlResult<()> {
+    let mut xml: Writer<W> = if options.pretty {
+        Writer::new_with_indent(writer, b' ', 2)
+    } else {
+        Writer::new(writer)
+    };
+    xml.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+    xml.create_element("NativeDicomModel")
+        .with_attribute(("xmlns", NATIVE_DICOM_NAMESPACE))
+        .with_attribute(("xml:space", "preserve"))
+        .write_inner_content(|xml| write_dataset(xml, dcmroot.as_obj(), options))?;
+    Ok(())
+}
+
+/// Reads a Native DICOM Model XML document as a `DicomRoot`.
+///
+/// # Errors
+/// - `XmlError::InvalidXml` if the XML could not be parsed.
+/// - `XmlError::InvalidDocument` if the document has no `NativeDicomModel`.
+/// - `XmlError::InvalidAttribute` if an attribute is invalid.
+/// - `XmlError::UnresolvedBulkData` if `BulkData` could not be resolved.
+/// - `XmlError::ValueError` if a value could not be encoded for its VR.
+pub fn read_xml<R: BufRead>(reader: R, options: &XmlReadOptions<'_>) -> XmlResult<DicomRoot> {
+    let document: XmlNode = XmlNode::parse(reader)?;
+    let dataset: &XmlNode = document
+        .children_named("NativeDicomModel")
+        .next()
+        .ok_or_else(|| XmlError::InvalidDocument("missing NativeDicomModel".to_string()))?;
+
+    let dictionary: &dyn DicomDictionary = options.dictionary.unwrap_or(&MINIMAL_DICOM_DICTIONARY);
+    let reader = DatasetReader {
+        builder: ModelBuilder::new(
+            resolve_ts(first_value(dataset, TRANSFER_SYNTAX_UID), dictionary),
+            resolve_cs(&string_values(dataset, SPECIFIC_CHARACTER_SET)?),
+        ),
+        options,
+    };
+    let children: BTreeMap<u32, DicomObject> = reader.dataset(dataset)?;
+    Ok(reader.builder.build(children))
+}
+
+/// Writes a `DicomAttribute` for each of the child nodes of the object.
+fn write_dataset<W: Write>(
+    xml: &mut Writer<W>,
+    obj: &DicomObject,
+    options: &XmlWriteOptions<'_>,
+) -> XmlResult<()> {
+    for (tag, child) in model_attributes(obj) {
+        write_attribute(xml, obj, tag, child, options)?;
+    }
+    Ok(())
+}
+
+/// Writes the `DicomAttribute` for the object, with its VR and value.
+///
+/// `parent`: The object containing the attribute, for finding the private creator of private
+/// attributes.
+fn write_attribute<W: Write>(
+    xml: &mut Writer<W>,
+    parent: &DicomObject,
+    tag: u32,
+    obj: &DicomObject,
+    options: &XmlWriteOptions<'_>,
+) -> XmlResult<()> {
+    let vr: VRRef = model_vr(obj);
+    let tag_attr: String = format!("{tag:08X}");
+    let mut start: BytesStart = BytesStart::new("DicomAttribute");
+    start.push_attribute(("tag", tag_attr.as_str()));
+    start.push_attribute(("vr", vr.ident));
+    let creator: Option<String> = parent.get_private_creator(tag);
+    let known: Option<TagRef> = options.dictionary.and_then(|dict| match &creator {
+        Some(creator) => dict.get_private_tag(creator, tag),
+        None if Tag::is_private(tag) => None,
+        None => dict.get_tag_by_number(tag),
+    });
+    if let Some(known) = known {
+        start.push_attribute(("keyword", known.ident()));
+    }
+    if let Some(creator) = &creator {
+        start.push_attribute(("privateCreator", creator.as_str()));
+    }
+
+    let value: ModelValue = model_value(obj)?;
+    if matches!(value, ModelValue::Empty) {
+        xml.write_event(Event::Empty(start))?;
+        return Ok(());
+    }
+
+    xml.write_event(Event::Start(start.borrow()))?;
+    match value {
+        ModelValue::Empty => {}
+        ModelValue::Sequence => {
+            for (index, item) in obj.iter_items().enumerate() {
+                xml.create_element("Item")
+                    .with_attribute(("number", (index + 1).to_string().as_str()))
+                    .write_inner_content(|xml| write_dataset(xml, item, options))?;
+            }
+        }
+        ModelValue::Binary(bytes) => {
+            let uri: Option<String> = bulk_data_uri(
+                obj.element(),
+                &bytes,
+                options.bulk_data_threshold,
+                options.bulk_data_uri,
+            );
+            if let Some(uri) = uri {
+                xml.create_element("BulkData")
+                    .with_attribute(("uri", uri.as_str()))
+                    .write_empty()?;
+            } else {
+                xml.create_element("InlineBinary")
+                    .write_text_content(BytesText::new(&BASE64.encode(&bytes)))?;
+            }
+        }
+        ModelValue::Values(value) => {
+            for (index, value) in write_values(value).iter().enumerate() {
+                let number: String = (index + 1).to_string();
+                if vr.ident == "PN" {
+                    write_person_name(xml, &number, value)?;
+                } else {
+                    xml.create_element("Value")
+                        .with_attribute(("number", number.as_str()))
+                        .write_text_content(BytesText::new(value))?;
+                }
+            }
+        }
+    }
+    xml.write_event(Event::End(start.to_end()))?;
+    Ok(())
+}
+
+/// Writes a `PersonName` with an element for each non-empty component. A value which can't be
+/// parsed as a person name is written as the family name.
+fn write_person_name<W: Write>(xml: &mut Writer<W>, number: &str, value: &str) -> XmlResult<()> {
+    let name: PersonName = value.parse::<PersonName>().unwrap_or_else(|_| {
+        PersonName::alphabetic_only(PersonNameGroup::default().with_family(value.trim()))
+    });
+    xml.create_element("PersonName")
+        .with_attribute(("number", number))
+        .write_inner_content(|xml| {
+            let groups: [&PersonNameGroup; 3] =
+                [name.alphabetic(), name.ideographic(), name.phonetic()];
+            for (group_name, group) in PN_GROUPS.iter().zip(groups) {
+                if group.is_empty() {
+                    continue;
+                }
+                xml.create_element(*group_name).write_inner_content(|xml| {
+                    let components: [&str; 5] = [
+                        group.family(),
+                        group.given(),
+                        group.middle(),
+                        group.prefix(),
+                        group.suffix(),
+                    ];
+                    for (component_name, component) in PN_COMPONENTS.iter().zip(components) {
+                        if !component.is_empty() {
+                            xml.create_element(*component_name)
+                                .write_text_content(BytesText::new(component))?;
+                        }
+                    }
+                    Ok::<(), XmlError>(())
+                })?;
+            }
+            Ok::<(), XmlError>(())
+        })?;
+    Ok(())
+}
+
+/// Converts the decoded values of an element to their text in the model.
+fn write_values(value: RawValue<'_>) -> Vec<String> {
+    match value {
+        RawValue::Strings(strings) => strings,
+        RawValue::Uid(uid) => vec![uid],
+        RawValue::Attributes(attrs) => attrs
+            .into_iter()
+            .map(|Attribute(tag)| format!("{tag:08X}"))
+            .collect(),
+        RawValue::Shorts(shorts) => shorts.iter().map(ToString::to_string).collect(),
+        RawValue::UShorts(ushorts) => ushorts.iter().map(ToString::to_string).collect(),
+        RawValue::Ints(ints) => ints.iter().map(ToString::to_string).collect(),
+        RawValue::UInts(uints) => uints.iter().map(ToString::to_string).collect(),
+        RawValue::Longs(longs) => longs.iter().map(ToString::to_string).collect(),
+        RawValue::ULongs(ulongs) => ulongs.iter().map(ToString::to_string).collect(),
+        RawValue::Floats(floats) => floats.iter().map(ToString::to_string).collect(),
+        RawValue::Doubles(doubles) => doubles.iter().map(ToString::to_string).collect(),
+        RawValue::Bytes(_)
+        | RawValue::Words(_)
+        | RawValue::DWords(_)
+        | RawValue::QWords(_)
+        | RawValue::BytesView(_) => Vec::new(),
+    }
+}
+
+/// The first value of the attribute with the given tag.
+fn first_value(dataset: &XmlNode, tag: u32) -> Option<&str> {
+    find_attribute(dataset, tag)
+        .and_then(|attribute| attribute.children_named("Value").next())
+        .map(|value| value.text.as_str())
+}
+
+/// The values of an attribute of the dataset, placed by their number, with missing values as
+/// empty strings.
+fn string_values(dataset: &XmlNode, tag: u32) -> XmlResult<Vec<&str>> {
+    let Some(attribute) = find_attribute(dataset, tag) else {
+        return Ok(Vec::new());
+    };
+    Ok(numbered(attribute, "Value", &format!("{tag:08X}"))?
+        .into_iter()
+        .map(|value| value.map_or("", |value| value.text.as_str()))
+        .collect())
+}
+
+fn find_attribute(dataset: &XmlNode, tag: u32) -> Option<&XmlNode> {
+    dataset
+        .children_named("DicomAttribute")
+        .find(|attribute| attribute.attribute("tag").and_then(parse_tag) == Some(tag))
+}
+
+/// Converts the `DicomAttribute`s of XML datasets into `DicomObject`s.
+struct DatasetReader<'o, 'a> {
+    builder: ModelBuilder,
+    options: &'o XmlReadOptions<'a>,
+}
+
+impl DatasetReader<'_, '_> {
+    /// Converts the attributes within a `NativeDicomModel` or `Item` into the child nodes of an
+    /// object.
+    fn dataset(&self, dataset: &XmlNode) -> XmlResult<BTreeMap<u32, DicomObject>> {
+        let mut children: BTreeMap<u32, DicomObject> = BTreeMap::new();
+        for attribute in dataset.children_named("DicomAttribute") {
+            let key: &str = attribute.attribute("tag").unwrap_or_default();
+            let tag: u32 = parse_tag(key).ok_or_else(|| invalid(key, "tag is not valid"))?;
+            children.insert(tag, self.attribute(key, tag, attribute)?);
+        }
+        Ok(children)
+    }
+
+    /// Converts a `DicomAttribute` into an object.
+    fn attribute(&self, key: &str, tag: u32, attribute: &XmlNode) -> XmlResult<DicomObject> {
+        let vr: VRRef = attribute
+            .attribute("vr")
+            .and_then(VR::from_ident)
+            .ok_or_else(|| invalid(key, "missing or unknown vr"))?;
+
+        if vr == &SQ {
+            let items: Vec<BTreeMap<u32, DicomObject>> = numbered(attribute, "Item", key)?
+                .into_iter()
+                .map(|item| item.map_or_else(|| Ok(BTreeMap::new()), |item| self.dataset(item)))
+                .collect::<XmlResult<Vec<BTreeMap<u32, DicomObject>>>>()?;
+            return Ok(self.builder.sequence(tag, items));
+        }
+
+        if let Some(inline) = attribute.children_named("InlineBinary").next() {
+            let encoded: String = inline.text.split_whitespace().collect();
+            let bytes: Vec<u8> = BASE64
+                .decode(encoded)
+                .map_err(|_| invalid(key, "InlineBinary is not base64"))?;
+            return Ok(self.builder.binary(tag, vr, bytes));
+        }
+        if let Some(bulk_data) = attribute.children_named("BulkData").next() {
+            let uri: &str = bulk_data
+                .attribute("uri")
+                .ok_or_else(|| invalid(key, "BulkData has no uri"))?;
+            let unresolved = || XmlError::UnresolvedBulkData {
+                tag: key.to_string(),
+                uri: uri.to_string(),
+            };
+            let resolver: BulkDataResolverFn<'_> =
+                self.options.bulk_data_resolver.ok_or_else(unresolved)?;
+            let bytes: Vec<u8> = resolver(uri).map_err(|_| unresolved())?;
+            return Ok(self.builder.binary(tag, vr, bytes));
+        }
+
+        let strings: Vec<String> = if vr.ident == "PN" {
+            numbered(attribute, "PersonName", key)?
+                .into_iter()
+                .map(|name| name.map(read_person_name).unwrap_or_default())
+                .collect()
+        } else {
+            numbered(attribute, "Value", key)?
+                .into_iter()
+                .map(|value| value.map(|value| value.text.clone()).unwrap_or_default())
+                .collect()
+        };
+        let value: Option<RawValue> = if strings.is_empty() {
+            None
+        } else {
+            Some(values_from_strings(vr, strings).map_err(|detail| invalid(key, &detail))?)
+        };
+        Ok(self.builder.element(tag, vr, value)?)
+    }
+}
+
+/// Converts a `PersonName` into the string encoding of the PN VR.
+fn read_person_name(name: &XmlNode) -> String {
+    let group = |group_name: &str| {
+        let Some(group) = name.children_named(group_name).next() else {
+            return PersonNameGroup::default();
+        };
+        let component = |component_name: &str| {
+            group
+                .children_named(component_name)
+                .next()
+                .map(|component| component.text.clone())
+                .unwrap_or_default()
+        };
+        PersonNameGroup::default()
+            .with_family(component(PN_COMPONENTS[0]))
+            .with_given(component(PN_COMPONENTS[1]))
+            .with_middle(component(PN_COMPONENTS[2]))
+            .with_prefix(component(PN_COMPONENTS[3]))
+            .with_suffix(component(PN_COMPONENTS[4]))
+    };
+    PersonName::default()
+        .with_alphabetic(group(PN_GROUPS[0]))
+        .with_ideographic(group(PN_GROUPS[1]))
+        .with_phonetic(group(PN_GROUPS[2]))
+        .to_string()
+}
+
+/// The children with the given name placed by their `number` attribute, which starts at 1. Any
+/// numbers which are skipped are `None`. Children without a number follow in document order.
+///
+/// # Errors
+/// `XmlError::InvalidAttribute` if a number is zero or greater than the count of children with
+/// the given name, as the number would otherwise size the result.
+fn numbered<'n>(
+    parent: &'n XmlNode,
+    name: &'n str,
+    tag: &str,
+) -> XmlResult<Vec<Option<&'n XmlNode>>> {
+    let count: usize = parent.children_named(name).count();
+    let mut placed: Vec<Option<&XmlNode>> = Vec::new();
+    let mut unnumbered: Vec<&XmlNode> = Vec::new();
+    for child in parent.children_named(name) {
+        match child
+            .attribute("number")
+            .and_then(|number| number.trim().parse::<usize>().ok())
+        {
+            Some(number) if number == 0 || number > count => {
+                return Err(invalid(
+                    tag,
+                    &format!("{name} number {number} is out of range"),
+                ));
+            }
+            Some(number) => {
+                if placed.len() < number {
+                    placed.resize(number, None);
+                }
+                placed[number - 1] = Some(child);
+            }
+            None => unnumbered.push(child),
+        }
+    }
+    placed.extend(unnumbered.into_iter().map(Some));
+    Ok(placed)
+}
+
+fn invalid(tag: &str, detail: &str) -> XmlError {
+    XmlError::InvalidAttribute {
+        tag: tag.to_string(),
+        detail: detail.to_string(),
+    }
+}
+
+/// An element of the XML document, read in full before conversion as the Native DICOM Model is
+/// navigated by element name and attribute.
+#[derive(Debug, Default)]
+struct XmlNode {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<XmlNode>,
+    text: String,
+}
+
+impl XmlNode {
+    /// Parses the document, returning a node whose children are the document's root elements.
+    fn parse<R: BufRead>(reader: R) -> XmlResult<XmlNode> {
+        let mut reader: Reader<R> = Reader::from_reader(reader);
+        reader.config_mut().expand_empty_elements = true;
+
+        let mut stack: Vec<XmlNode> = vec![XmlNode::default()];
+        let mut buf: Vec<u8> = Vec::new();
+        loop {
+            match reader.read_event_into(&mut buf)? {
+                Event::Start(start) => {
+                    let mut node = XmlNode {
+                        name: String::from_utf8_lossy(start.local_name().as_ref()).to_string(),
+                        ..XmlNode::default()
+                    };
+                    for attr in start.attributes() {
+                        let attr = attr.map_err(quick_xml::Error::from)?;
+                        node.attributes.push((
+                            String::from_utf8_lossy(attr.key.local_name().as_ref()).to_string(),
+                            attr.unescape_value()?.to_string(),
+                        ));
+                    }
+                    stack.push(node);
+                }
+                Event::End(_) => {
+                    let node: XmlNode = stack.pop().unwrap_or_default();
+                    let Some(parent) = stack.last_mut() else {
+                        return Err(XmlError::InvalidDocument("unbalanced elements".to_string()));
+                    };
+                    parent.children.push(node);
+                }
+                Event::Text(text) => {
+                    if let Some(node) = stack.last_mut() {
+                        node.text.push_str(&text.unescape()?);
+                    }
+                }
+                Event::CData(cdata) => {
+                    if let Some(node) = stack.last_mut() {
+                        node.text.push_str(&String::from_utf8_lossy(&cdata));
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        match (stack.pop(), stack.is_empty()) {
+            (Some(document), true) => Ok(document),
+            _ => Err(XmlError::InvalidDocument("unclosed elements".to_string())),
+        }
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _value)| key == name)
+            .map(|(_key, value)| value.as_str())
+    }
+
+    fn children_named<'n>(&'n self, name: &'n str) -> impl Iterator<Item = &'n XmlNode> + 'n {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+}
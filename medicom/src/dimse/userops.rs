@@ -284,18 +284,19 @@ impl StoreUserOp {
 
         // Pull out all necessary early tags for processing the remaining DICOM elements of this
         // SOP.
-        let mut spec_char_set: Option<String> = None;
+        let mut spec_char_set: Vec<String> = Vec::new();
         let mut sop_class_uid: Option<String> = None;
         let mut sop_inst_uid: Option<String> = None;
         let mut header_elems: Vec<DicomElement> = Vec::new();
         for elem in parser.by_ref() {
             let tag = elem.tag();
             if tag == SpecificCharacterSet.tag() {
-                spec_char_set = elem
+                let value: RawValue = elem
                     .parse_value()
-                    .map_err(|e| AssocError::ab_failure(DimseError::ParseError(e)))?
-                    .string()
-                    .cloned();
+                    .map_err(|e| AssocError::ab_failure(DimseError::ParseError(e)))?;
+                if let RawValue::Strings(values) = value {
+                    spec_char_set = values;
+                }
             } else if tag == SOPClassUID.tag() {
                 sop_class_uid = elem
                     .parse_value()
@@ -318,9 +319,8 @@ impl StoreUserOp {
         // Chain the header elements parsed above with the parser to reconstitute the DICOM SOP.
         let stitched_elems = header_elems.into_iter().chain(parser);
 
-        let spec_char_set = spec_char_set
-            .and_then(|s| CSRef::lookup_charset(&s))
-            .unwrap_or_default();
+        let spec_char_set =
+            CSRef::lookup_specific_character_set(&spec_char_set).unwrap_or_default();
         let sop_class_uid = sop_class_uid
             .and_then(|s| STANDARD_DICOM_DICTIONARY.get_uid_by_uid(&s))
             .ok_or_else(|| {
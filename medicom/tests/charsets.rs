@@ -23,13 +23,23 @@ mod charsets_tests {
     use medicom::{
         core::{
             charset::CSRef,
+            dcmelement::DicomElement,
             dcmobject::DicomRoot,
-            read::{ParseResult, ParserBuilder},
-            values::ElementWithVr,
+            defn::{
+                constants::ts::ExplicitVRLittleEndian,
+                tag::Tag,
+                vl::ValueLength,
+                vr::{VRRef, LO, LT, PN},
+            },
+            read::{ParseResult, ParserBuilder, ParserState},
+            values::{ElementWithVr, RawValue},
         },
         dict::{
             stdlookup::STANDARD_DICOM_DICTIONARY,
-            tags::{DirectoryRecordSequence, PatientsName, SpecificCharacterSet},
+            tags::{
+                DirectoryRecordSequence, OtherPatientIDs, PatientComments, PatientsName,
+                SpecificCharacterSet,
+            },
         },
     };
 
@@ -103,9 +113,9 @@ mod charsets_tests {
         test_nested_charset(
             &dcmroot,
             33,
-            CSRef::of(encoding_rs::WINDOWS_1252),
+            CSRef::of(encoding_rs::EUC_KR),
             "ISO 2022 IR 149",
-            "Hong^Gildong=\u{1b}$)Cûó^\u{1b}$)CÑÎÔ×=\u{1b}$)CÈ«^\u{1b}$)C±æµ¿",
+            "Hong^Gildong=洪^吉洞=홍^길동",
         )?;
 
         Ok(())
@@ -208,14 +218,13 @@ mod charsets_tests {
 
     /// This uses multiple charsets, ISO-IR-13 and ISO-IR-87.
     #[test]
-    #[ignore]
     fn test_scs_h32() -> ParseResult<()> {
         test_scs_file(
             true,
             "dclunie/charsettests/SCSH32",
-            CSRef::of(encoding_rs::SHIFT_JIS),
+            CSRef::of(encoding_rs::ISO_2022_JP),
             "ISO 2022 IR 13",
-            "",
+            "ﾔﾏﾀﾞ^ﾀﾛｳ=山田^太郎=やまだ^たろう",
         )
     }
 
@@ -235,9 +244,9 @@ mod charsets_tests {
         test_scs_file(
             true,
             "dclunie/charsettests/SCSI2",
-            CSRef::of(encoding_rs::WINDOWS_1252),
+            CSRef::of(encoding_rs::EUC_KR),
             "ISO 2022 IR 149",
-            "Hong^Gildong=\u{1b}$)Cûó^\u{1b}$)CÑÎÔ×=\u{1b}$)CÈ«^\u{1b}$)C±æµ¿",
+            "Hong^Gildong=洪^吉洞=홍^길동",
         )
     }
 
@@ -307,4 +316,148 @@ mod charsets_tests {
 
         Ok(())
     }
+
+    /// Decodes the bytes as a value of an element with the character set of the values of
+    /// `SpecificCharacterSet`, and encodes the text back into the same bytes.
+    fn test_code_extensions(
+        scs: &[&str],
+        tag: &Tag,
+        vr: VRRef,
+        text: &str,
+        bytes: &[u8],
+    ) -> ParseResult<()> {
+        let cs: CSRef =
+            CSRef::lookup_specific_character_set(scs).expect("Should resolve character set");
+        assert_eq!(scs, cs.code_extensions().as_slice());
+
+        let mut data: Vec<u8> = bytes.to_vec();
+        if data.len() % 2 == 1 {
+            data.push(b' ');
+        }
+        let vl = ValueLength::Explicit(u32::try_from(data.len()).expect("Value length"));
+        let mut element = DicomElement::new(
+            tag.tag(),
+            vr,
+            vl,
+            &ExplicitVRLittleEndian,
+            cs,
+            data.clone(),
+            Vec::new(),
+        );
+        assert_eq!(vec![text.to_string()], Vec::<String>::try_from(&element)?);
+
+        element.encode_val(RawValue::of_string(text))?;
+        assert_eq!(data, element.data());
+        Ok(())
+    }
+
+    /// Part 5, Annex H.3.1, Japanese person name using ISO 2022 IR 87.
+    #[test]
+    fn test_code_extensions_japanese_ir87() -> ParseResult<()> {
+        test_code_extensions(
+            &["", "ISO 2022 IR 87"],
+            &PatientsName,
+            &PN,
+            "Yamada^Tarou=山田^太郎=やまだ^たろう",
+            b"Yamada^Tarou=\x1b$B;3ED\x1b(B^\x1b$BB@O:\x1b(B=\x1b$B$d$^$@\x1b(B^\x1b$B$?$m$&\x1b(B",
+        )
+    }
+
+    /// Part 5, Annex H.3.2, Japanese person name using ISO 2022 IR 13 and ISO 2022 IR 87.
+    #[test]
+    fn test_code_extensions_japanese_ir13_ir87() -> ParseResult<()> {
+        test_code_extensions(
+            &["ISO 2022 IR 13", "ISO 2022 IR 87"],
+            &PatientsName,
+            &PN,
+            "ﾔﾏﾀﾞ^ﾀﾛｳ=山田^太郎=やまだ^たろう",
+            b"\xd4\xcf\xc0\xde^\xc0\xdb\xb3=\x1b$B;3ED\x1b(J^\x1b$BB@O:\x1b(J=\x1b$B$d$^$@\x1b(J^\x1b$B$?$m$&\x1b(J",
+        )
+    }
+
+    /// Part 5, Annex I.2, Korean person name using ISO 2022 IR 149, where the character set is
+    /// designated again for each component.
+    #[test]
+    fn test_code_extensions_korean_ir149() -> ParseResult<()> {
+        test_code_extensions(
+            &["", "ISO 2022 IR 149"],
+            &PatientsName,
+            &PN,
+            "Hong^Gildong=洪^吉洞=홍^길동",
+            b"Hong^Gildong=\x1b$)C\xfb\xf3^\x1b$)C\xd1\xce\xd4\xd7=\x1b$)C\xc8\xab^\x1b$)C\xb1\xe6\xb5\xbf",
+        )
+    }
+
+    /// Part 5, Annex J.3, Chinese person name using ISO 2022 IR 58.
+    #[test]
+    fn test_code_extensions_chinese_ir58() -> ParseResult<()> {
+        test_code_extensions(
+            &["", "ISO 2022 IR 58"],
+            &PatientsName,
+            &PN,
+            "Zhang^XiaoDong=张^小东=",
+            b"Zhang^XiaoDong=\x1b$)A\xd5\xc5^\x1b$)A\xd0\xa1\xb6\xab=",
+        )
+    }
+
+    /// The character set is designated again after each line of text.
+    #[test]
+    fn test_code_extensions_multiline_text() -> ParseResult<()> {
+        test_code_extensions(
+            &["", "ISO 2022 IR 149"],
+            &PatientComments,
+            &LT,
+            "가\r\n나",
+            b"\x1b$)C\xb0\xa1\r\n\x1b$)C\xb3\xaa",
+        )
+    }
+
+    /// Each value of a multi-valued element returns to the default repertoire before the value
+    /// delimiter, which is not mistaken for part of a multi-byte character.
+    #[test]
+    fn test_code_extensions_multiple_values() -> ParseResult<()> {
+        let cs = CSRef::lookup_specific_character_set(&["", "ISO 2022 IR 87"])
+            .expect("Should resolve character set");
+        let mut element = DicomElement::new(
+            OtherPatientIDs.tag(),
+            &LO,
+            ValueLength::Explicit(0),
+            &ExplicitVRLittleEndian,
+            cs,
+            Vec::new(),
+            Vec::new(),
+        );
+        let values: Vec<String> = vec!["山田".to_string(), "Tarou".to_string()];
+        element.encode_val(RawValue::Strings(values.clone()))?;
+        assert_eq!(b"\x1b$B;3ED\x1b(B\\Tarou", element.data());
+        assert_eq!(values, Vec::<String>::try_from(&element)?);
+        Ok(())
+    }
+
+    /// The parser resolves all values of `SpecificCharacterSet` for the values which follow it.
+    #[test]
+    fn test_parse_code_extensions() -> ParseResult<()> {
+        let scs: &[u8] = b"\\ISO 2022 IR 87 ";
+        let pn: &[u8] =
+            b"Yamada^Tarou=\x1b$B;3ED\x1b(B^\x1b$BB@O:\x1b(B=\x1b$B$d$^$@\x1b(B^\x1b$B$?$m$&\x1b(B ";
+        let mut dataset: Vec<u8> = vec![0x08, 0x00, 0x05, 0x00, b'C', b'S'];
+        dataset.extend_from_slice(&u16::try_from(scs.len()).expect("length").to_le_bytes());
+        dataset.extend_from_slice(scs);
+        dataset.extend_from_slice(&[0x10, 0x00, 0x10, 0x00, b'P', b'N']);
+        dataset.extend_from_slice(&u16::try_from(pn.len()).expect("length").to_le_bytes());
+        dataset.extend_from_slice(pn);
+
+        let mut parser = ParserBuilder::default()
+            .state(ParserState::ReadElement)
+            .dataset_ts(&ExplicitVRLittleEndian)
+            .build(dataset.as_slice(), &STANDARD_DICOM_DICTIONARY);
+        let dcmroot: DicomRoot = DicomRoot::parse(&mut parser)?.expect("Parse into DicomRoot");
+        assert_eq!(encoding_rs::ISO_2022_JP.name(), dcmroot.cs().name());
+        assert_eq!(vec!["", "ISO 2022 IR 87"], dcmroot.cs().code_extensions());
+        assert_eq!(
+            Some(RawValue::of_string("Yamada^Tarou=山田^太郎=やまだ^たろう")),
+            dcmroot.get_value_by_tag(&PatientsName)
+        );
+        Ok(())
+    }
 }
@@ -82,3 +82,130 @@ pub const NULL_ELEMENT: &[u8] = &[
     // INVALID              VR: INVALID VL: 0
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
 ];
+
+#[rustfmt::skip]
+pub const STUDY_DESCRIPTION_ELEMENT: &[u8] = &[
+    // StudyDescription     VR: LO      VL: 14
+    0x08, 0x00, 0x30, 0x10, 0x4C, 0x4F, 0x0E, 0x00,
+    0x44, 0x45, 0x46, 0x45, 0x52, 0x52, 0x45, 0x44, 0x20, 0x53, 0x54, 0x55, 0x44, 0x59,
+];
+
+#[rustfmt::skip]
+pub const PIXEL_DATA_ELEMENT: &[u8] = &[
+    // PixelData            VR: OW      VL: 8
+    0xE0, 0x7F, 0x10, 0x00, 0x4F, 0x57, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00,
+    0x01, 0x00, 0x02, 0x00, 0x03, 0x00, 0x04, 0x00,
+];
+
+#[rustfmt::skip]
+pub const REFERENCED_STUDY_SEQUENCE_ELEMENT: &[u8] = &[
+    // ReferencedStudySequence  VR: SQ      VL: Undefined
+    0x08, 0x00, 0x10, 0x11, 0x53, 0x51, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF,
+
+    // Item                     VL: Undefined
+    0xFE, 0xFF, 0x00, 0xE0, 0xFF, 0xFF, 0xFF, 0xFF,
+
+    // ReferencedSOPClassUID    VR: UI      VL: 6
+    0x08, 0x00, 0x50, 0x11, 0x55, 0x49, 0x06, 0x00,
+    0x31, 0x2E, 0x32, 0x2E, 0x33, 0x00,
+
+    // ReferencedSOPInstanceUID VR: UI      VL: 6
+    0x08, 0x00, 0x55, 0x11, 0x55, 0x49, 0x06, 0x00,
+    0x31, 0x2E, 0x32, 0x2E, 0x34, 0x00,
+
+    // ItemDelimitationItem     VL: 0
+    0xFE, 0xFF, 0x0D, 0xE0, 0x00, 0x00, 0x00, 0x00,
+
+    // Item                     VL: Undefined
+    0xFE, 0xFF, 0x00, 0xE0, 0xFF, 0xFF, 0xFF, 0xFF,
+
+    // ReferencedSOPInstanceUID VR: UI      VL: 6
+    0x08, 0x00, 0x55, 0x11, 0x55, 0x49, 0x06, 0x00,
+    0x31, 0x2E, 0x32, 0x2E, 0x35, 0x00,
+
+    // ItemDelimitationItem     VL: 0
+    0xFE, 0xFF, 0x0D, 0xE0, 0x00, 0x00, 0x00, 0x00,
+
+    // SequenceDelimitationItem VL: 0
+    0xFE, 0xFF, 0xDD, 0xE0, 0x00, 0x00, 0x00, 0x00,
+];
+
+#[rustfmt::skip]
+pub const PRIVATE_ELEMENTS: &[u8] = &[
+    // (0009,0010) Private Creator  VR: LO  VL: 8
+    0x09, 0x00, 0x10, 0x00, 0x4C, 0x4F, 0x08, 0x00,
+    0x41, 0x43, 0x4D, 0x45, 0x20, 0x31, 0x2E, 0x31,
+
+    // (0009,1001) Private Element  VR: LO  VL: 6
+    0x09, 0x00, 0x01, 0x10, 0x4C, 0x4F, 0x06, 0x00,
+    0x53, 0x45, 0x43, 0x52, 0x45, 0x54,
+];
+
+#[rustfmt::skip]
+pub const ENCAPSULATED_PIXEL_DATA_ELEMENT: &[u8] = &[
+    // PixelData            VR: OB      VL: Undefined
+    0xE0, 0x7F, 0x10, 0x00, 0x4F, 0x42, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF,
+
+    // Item, Basic Offset Table         VL: 0
+    0xFE, 0xFF, 0x00, 0xE0, 0x00, 0x00, 0x00, 0x00,
+
+    // Item, Fragment                   VL: 6
+    0xFE, 0xFF, 0x00, 0xE0, 0x06, 0x00, 0x00, 0x00,
+    0x01, 0x02, 0x03, 0x04, 0x05, 0x06,
+
+    // Item, Fragment                   VL: 2
+    0xFE, 0xFF, 0x00, 0xE0, 0x02, 0x00, 0x00, 0x00,
+    0x07, 0x08,
+
+    // SequenceDelimitationItem         VL: 0
+    0xFE, 0xFF, 0xDD, 0xE0, 0x00, 0x00, 0x00, 0x00,
+];
+
+#[rustfmt::skip]
+pub const IMPLICIT_VR_ELEMENT: &[u8] = &[
+    // StudyDate            VR: None    VL: 8, encoded as Implicit VR within Explicit VR
+    0x08, 0x00, 0x20, 0x00, 0x08, 0x00, 0x00, 0x00,
+    0x32, 0x30, 0x32, 0x34, 0x30, 0x31, 0x30, 0x31,
+];
+
+#[rustfmt::skip]
+pub const UNDEFINED_LENGTH_ELEMENT: &[u8] = &[
+    // LongCodeValue        VR: UC      VL: Undefined
+    0x08, 0x00, 0x19, 0x01, 0x55, 0x43, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF,
+    0x47, 0x41, 0x52, 0x42, 0x41, 0x47, 0x45, 0x21,
+];
+
+#[rustfmt::skip]
+pub const PATIENT_ELEMENTS: &[u8] = &[
+    // PatientsName         VR: PN      VL: 8
+    0x10, 0x00, 0x10, 0x00, 0x50, 0x4E, 0x08, 0x00,
+    0x44, 0x4F, 0x45, 0x5E, 0x4A, 0x4F, 0x48, 0x4E,
+
+    // PatientID            VR: LO      VL: 4
+    0x10, 0x00, 0x20, 0x00, 0x4C, 0x4F, 0x04, 0x00,
+    0x31, 0x32, 0x33, 0x34,
+];
+
+#[rustfmt::skip]
+pub const IMPLICIT_PATIENT_ELEMENTS: &[u8] = &[
+    // PatientsName         VR: None    VL: 8
+    0x10, 0x00, 0x10, 0x00, 0x08, 0x00, 0x00, 0x00,
+    0x44, 0x4F, 0x45, 0x5E, 0x4A, 0x4F, 0x48, 0x4E,
+
+    // PatientID            VR: None    VL: 4
+    0x10, 0x00, 0x20, 0x00, 0x04, 0x00, 0x00, 0x00,
+    0x31, 0x32, 0x33, 0x34,
+];
+
+#[rustfmt::skip]
+pub const IMPLICIT_DEFINED_LENGTH_SEQUENCE: &[u8] = &[
+    // ReferencedStudySequence  VR: None    VL: 22
+    0x08, 0x00, 0x10, 0x11, 0x16, 0x00, 0x00, 0x00,
+
+    // Item                     VL: 14
+    0xFE, 0xFF, 0x00, 0xE0, 0x0E, 0x00, 0x00, 0x00,
+
+    // ReferencedSOPClassUID    VR: None    VL: 6
+    0x08, 0x00, 0x50, 0x11, 0x06, 0x00, 0x00, 0x00,
+    0x31, 0x2E, 0x32, 0x2E, 0x33, 0x00,
+];
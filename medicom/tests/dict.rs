@@ -18,7 +18,21 @@
 mod dict_tests {
     use medicom::{
         self,
-        core::defn::{tag::Tag, ts::TransferSyntax, uid::UIDRef},
+        core::{
+            dcmobject::DicomRoot,
+            defn::{
+                dcmdict::{DicomDictionary, MultiDicomDictionary},
+                rtdict::{RuntimeDicomDictionary, RuntimeDictError, RuntimeDictFormat},
+                tag::Tag,
+                ts::TransferSyntax,
+                uid::UIDRef,
+                vm::VM,
+                vr::{CS, ST, US},
+            },
+            inspect::{FormattedElement, FormattedTagType, FormattedTagValue, PrivateCreators},
+            read::{ParserBuilder, ParserState},
+        },
+        dict::stdlookup::STANDARD_DICOM_DICTIONARY,
         dict::{
             lookup::{
                 TAG_BY_IDENT, TAG_BY_VALUE, TS_BY_IDENT, TS_BY_UID, UID_BY_IDENT, UID_BY_UID,
@@ -206,4 +220,225 @@ mod dict_tests {
             }
         }
     }
+
+    const DCMTK_DICTIONARY: &str = "\
+# Private tags
+(0029,\"SIEMENS CSA HEADER\",08)\tCS\tCSAImageHeaderType\t1\tPrivateTag
+(0019,\"SIEMENS MR HEADER\",0c)\tUS\tBValue\t1\tPrivateTag
+
+# Public tags, where ranges are skipped
+(6000-60ff,3000)\tox\tOverlayData\t1\tDICOM
+(0018,9999)\tLO\tVendorNote\t1-n\tPrivateTag
+";
+
+    const CSV_DICTIONARY: &str = "\
+tag,creator,vr,keyword,vm,name
+0029xx08,SIEMENS CSA HEADER,CS,CSAImageHeaderType,1,CSA Image Header Type
+\"(0019,xx0C)\",SIEMENS MR HEADER,US,BValue,1,\"B Value, in s/mm2\"
+00189999,,LO,VendorNote,1-n,
+";
+
+    /// Private tags are found by their private creator and the lower byte of their element
+    /// number, in any block, while public tags are found by number and keyword.
+    fn assert_runtime_dictionary(dict: &RuntimeDicomDictionary) {
+        assert_eq!(3, dict.len());
+
+        let header_type = dict
+            .get_private_tag("SIEMENS CSA HEADER", 0x0029_1008)
+            .expect("CSAImageHeaderType");
+        assert_eq!("CSAImageHeaderType", header_type.ident());
+        assert_eq!(Some(&CS), header_type.implicit_vr());
+        assert_eq!(0x0029_1008, header_type.tag());
+        assert_eq!(
+            Some(header_type),
+            dict.get_private_tag("SIEMENS CSA HEADER ", 0x0029_1208)
+        );
+        assert!(dict
+            .get_private_tag("SIEMENS MR HEADER", 0x0029_1008)
+            .is_none());
+        assert!(dict
+            .get_private_tag("SIEMENS CSA HEADER", 0x0029_1009)
+            .is_none());
+        assert!(dict.get_tag_by_number(0x0029_1008).is_none());
+        assert!(dict.get_tag_by_name("CSAImageHeaderType").is_none());
+
+        let b_value = dict
+            .get_private_tag("SIEMENS MR HEADER", 0x0019_100C)
+            .expect("BValue");
+        assert_eq!(Some(&US), b_value.implicit_vr());
+
+        let note = dict.get_tag_by_number(0x0018_9999).expect("VendorNote");
+        assert_eq!(&VM::AtLeast(1), note.vm());
+        assert_eq!(Some(note), dict.get_tag_by_name("vendornote"));
+    }
+
+    #[test]
+    fn test_runtime_dictionary_dcmtk() -> Result<(), RuntimeDictError> {
+        let dict = RuntimeDicomDictionary::parse(DCMTK_DICTIONARY, RuntimeDictFormat::Dcmtk)?;
+        assert_runtime_dictionary(&dict);
+        Ok(())
+    }
+
+    #[test]
+    fn test_runtime_dictionary_csv() -> Result<(), RuntimeDictError> {
+        let dict = RuntimeDicomDictionary::parse(CSV_DICTIONARY, RuntimeDictFormat::Csv)?;
+        assert_runtime_dictionary(&dict);
+        let b_value = dict
+            .get_private_tag("SIEMENS MR HEADER", 0x0019_100C)
+            .expect("BValue");
+        assert_eq!("B Value, in s/mm2", b_value.desc());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_runtime_dictionary_json() -> Result<(), RuntimeDictError> {
+        let json = r#"[
+            {"tag": "0029xx08", "creator": "SIEMENS CSA HEADER", "vr": "CS", "keyword": "CSAImageHeaderType"},
+            {"tag": "(0019,xx0C)", "creator": "SIEMENS MR HEADER", "vr": "US", "keyword": "BValue"},
+            {"tag": "00189999", "vr": "LO", "keyword": "VendorNote", "vm": "1-n"}
+        ]"#;
+        let dict = RuntimeDicomDictionary::parse(json, RuntimeDictFormat::Json)?;
+        assert_runtime_dictionary(&dict);
+        Ok(())
+    }
+
+    /// Loading the same definitions again reuses the definitions already loaded, and later
+    /// definitions of the same tag replace earlier ones.
+    #[test]
+    fn test_runtime_dictionary_reload() -> Result<(), RuntimeDictError> {
+        let first = RuntimeDicomDictionary::parse(DCMTK_DICTIONARY, RuntimeDictFormat::Dcmtk)?;
+        let second = RuntimeDicomDictionary::parse(DCMTK_DICTIONARY, RuntimeDictFormat::Dcmtk)?;
+        let note = first.get_tag_by_number(0x0018_9999).expect("VendorNote");
+        assert!(std::ptr::eq(
+            note,
+            second.get_tag_by_number(0x0018_9999).expect("VendorNote")
+        ));
+        let b_value = first
+            .get_private_tag("SIEMENS MR HEADER", 0x0019_100C)
+            .expect("BValue");
+        assert!(std::ptr::eq(
+            b_value,
+            second
+                .get_private_tag("SIEMENS MR HEADER", 0x0019_100C)
+                .expect("BValue")
+        ));
+
+        let replaced = "tag,vr,keyword\n00189999,LO,VendorNote\n00189999,ST,VendorNote\n";
+        let dict = RuntimeDicomDictionary::parse(replaced, RuntimeDictFormat::Csv)?;
+        assert_eq!(1, dict.len());
+        let note = dict.get_tag_by_number(0x0018_9999).expect("VendorNote");
+        assert_eq!(Some(&ST), note.implicit_vr());
+        assert_eq!(Some(note), dict.get_tag_by_name("VendorNote"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_runtime_dictionary_invalid() {
+        let invalid = "tag,vr,keyword\n00189999,QQ,VendorNote\n";
+        assert!(matches!(
+            RuntimeDicomDictionary::parse(invalid, RuntimeDictFormat::Csv),
+            Err(RuntimeDictError::InvalidEntry { entry: 2, .. })
+        ));
+
+        let missing_keyword = "tag,vr\n00189999,LO\n";
+        assert!(matches!(
+            RuntimeDicomDictionary::parse(missing_keyword, RuntimeDictFormat::Csv),
+            Err(RuntimeDictError::InvalidEntry { entry: 1, .. })
+        ));
+
+        assert!(matches!(
+            RuntimeDicomDictionary::from_path(std::path::Path::new("private.txt")),
+            Err(RuntimeDictError::UnknownFormat(_))
+        ));
+    }
+
+    /// Private tags are resolved through their private creator when formatting elements, both
+    /// within a `DicomRoot` and when parsed individually, and their VR is resolved for Implicit VR.
+    #[test]
+    fn test_runtime_dictionary_formats_private_tags() -> Result<(), RuntimeDictError> {
+        let runtime = RuntimeDicomDictionary::parse(DCMTK_DICTIONARY, RuntimeDictFormat::Dcmtk)?;
+
+        #[rustfmt::skip]
+        let dataset: &[u8] = &[
+            // (0029,0011) Private Creator          VL: 18      "SIEMENS CSA HEADER"
+            0x29, 0x00, 0x11, 0x00, 0x12, 0x00, 0x00, 0x00,
+            0x53, 0x49, 0x45, 0x4D, 0x45, 0x4E, 0x53, 0x20, 0x43,
+            0x53, 0x41, 0x20, 0x48, 0x45, 0x41, 0x44, 0x45, 0x52,
+            // (0029,1108) CSAImageHeaderType       VL: 12      "IMAGE NUM 4 "
+            0x29, 0x00, 0x08, 0x11, 0x0C, 0x00, 0x00, 0x00,
+            0x49, 0x4D, 0x41, 0x47, 0x45, 0x20, 0x4E, 0x55, 0x4D, 0x20, 0x34, 0x20,
+        ];
+        let parser = ParserBuilder::default()
+            .state(ParserState::ReadElement)
+            .dataset_ts(&ImplicitVRLittleEndian)
+            .build(dataset, &STANDARD_DICOM_DICTIONARY);
+
+        let mut creators = PrivateCreators::default();
+        let mut elements = Vec::new();
+        for element in parser {
+            let element = element.expect("Parse element");
+            creators.record(&element);
+            elements.push(element);
+        }
+        let header_type = &elements[1];
+        assert_eq!(Some("SIEMENS CSA HEADER"), creators.creator_of(header_type));
+
+        let formatted = FormattedElement::new(header_type)
+            .with_dict(MultiDicomDictionary::new(vec![
+                &runtime,
+                &STANDARD_DICOM_DICTIONARY,
+            ]))
+            .with_private_creator(creators.creator_of(header_type).map(str::to_string));
+        assert!(matches!(
+            formatted.get_tag_type(),
+            FormattedTagType::Known(0x0029_1108, ident) if ident == "CSAImageHeaderType"
+        ));
+        assert!(matches!(
+            formatted.get_tag_value(),
+            FormattedTagValue::Stringified(value) if value == "IMAGE NUM 4"
+        ));
+        assert!(formatted.to_string().contains("CS CSAImageHeaderType"));
+
+        let unresolved =
+            FormattedElement::new(header_type).with_dict(MultiDicomDictionary::new(vec![
+                &runtime,
+                &STANDARD_DICOM_DICTIONARY,
+            ]));
+        assert!(matches!(
+            unresolved.get_tag_type(),
+            FormattedTagType::Private(_)
+        ));
+
+        let mut parser = ParserBuilder::default()
+            .state(ParserState::ReadElement)
+            .dataset_ts(&ImplicitVRLittleEndian)
+            .build(dataset, &STANDARD_DICOM_DICTIONARY);
+        let dcmroot = DicomRoot::parse(&mut parser)
+            .expect("Parse into DicomRoot")
+            .expect("DicomRoot");
+        assert_eq!(
+            Some("SIEMENS CSA HEADER".to_string()),
+            dcmroot.as_obj().get_private_creator(0x0029_1108)
+        );
+
+        #[cfg(feature = "xml")]
+        {
+            use medicom::core::xml::{write_xml, XmlWriteOptions};
+
+            let dict = MultiDicomDictionary::new(vec![&runtime, &STANDARD_DICOM_DICTIONARY]);
+            let mut xml: Vec<u8> = Vec::new();
+            write_xml(
+                &dcmroot,
+                &mut xml,
+                &XmlWriteOptions::default().dictionary(&dict),
+            )
+            .expect("write xml");
+            assert!(String::from_utf8_lossy(&xml).contains(
+                r#"<DicomAttribute tag="00291108" vr="UN" keyword="CSAImageHeaderType" privateCreator="SIEMENS CSA HEADER">"#
+            ));
+        }
+
+        Ok(())
+    }
 }
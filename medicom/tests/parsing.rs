@@ -21,6 +21,7 @@ mod parsing_tests {
     use std::{
         convert::TryFrom,
         io::{Cursor, ErrorKind},
+        num::NonZeroUsize,
     };
 
     use medicom::{
@@ -30,37 +31,63 @@ mod parsing_tests {
             defn::{
                 constants::lookup::MINIMAL_DICOM_DICTIONARY,
                 dcmdict::DicomDictionary,
-                tag::{Tag, TagPath},
+                tag::{Tag, TagNode, TagPath},
                 vl::ValueLength,
                 vr,
             },
-            read::{stop::ParseStop, ParseError, ParseResult, Parser, ParserBuilder, ParserState},
+            pixeldata::{
+                pdencap::{EncapsulatedPixelData, Fragment},
+                pdinfo::PixelDataSliceInfo,
+                PixelDataError,
+            },
+            read::{
+                deferred::{DeferValues, DeferredValue, SeekableDataset},
+                filter::{TagFilter, TagSelector},
+                parallel::{ParsedDataset, ReaderSource, ResultOrder},
+                recovery::{ParseDiagnostic, RecoveryAction, RecoveryPolicy},
+                stop::ParseStop,
+                visitor::DicomVisitor,
+                ParseError, ParseResult, Parser, ParserBuilder, ParserState,
+            },
             values::{ElementWithVr, RawValue},
         },
         dict::{
             stdlookup::STANDARD_DICOM_DICTIONARY,
             tags::{
                 ContourData, ContourImageSequence, ContourSequence, FileMetaInformationGroupLength,
-                FrameofReferenceUID, Item, ItemDelimitationItem, PatientComments, PatientsName,
-                PatientsWeight, PixelData, ROIContourSequence, RTReferencedSeriesSequence,
-                RTReferencedStudySequence, ReferencedFrameofReferenceSequence,
-                ReferencedImageSequence, ReferencedSOPClassUID, ReferencedSOPInstanceUID,
-                ReferencedStudySequence, SOPClassUID, SequenceDelimitationItem, SeriesInstanceUID,
-                SharedFunctionalGroupsSequence, SourceImageSequence, SpecificCharacterSet,
-                StructureSetROISequence, StructureSetTime, StudyDescription,
+                FrameofReferenceUID, Item, ItemDelimitationItem, LongCodeValue, PatientComments,
+                PatientID, PatientsName, PatientsWeight, PixelData, ROIContourSequence,
+                RTReferencedSeriesSequence, RTReferencedStudySequence,
+                ReferencedFrameofReferenceSequence, ReferencedImageSequence, ReferencedSOPClassUID,
+                ReferencedSOPInstanceUID, ReferencedStudySequence, SOPClassUID,
+                SequenceDelimitationItem, SeriesInstanceUID, SharedFunctionalGroupsSequence,
+                SourceImageSequence, SpecificCharacterSet, StructureSetROISequence,
+                StructureSetTime, StudyDate, StudyDescription, TransferSyntaxUID,
+            },
+            transfer_syntaxes::{
+                ExplicitVRBigEndian, ExplicitVRLittleEndian, ImplicitVRLittleEndian, RLELossless,
             },
-            transfer_syntaxes::{ExplicitVRBigEndian, ImplicitVRLittleEndian},
             uids::{CTImageStorage, EnhancedMRImageStorage, MRImageStorage},
         },
     };
 
+    #[cfg(feature = "archive")]
+    use medicom::core::read::archive::ArchiveFormat;
+    #[cfg(feature = "compress")]
+    use medicom::dict::transfer_syntaxes::DeflatedExplicitVRLittleEndian;
+
     use crate::common::{
         common_stddicom::{
             fixture, is_standard_dcm_file, parse_all_dcmroot_values, parse_all_dicom_files,
             parse_file,
         },
         mock::MockDicomDataset,
-        mockdata::{INVALID_VR_ELEMENT, NULL_ELEMENT, STANDARD_HEADER},
+        mockdata::{
+            ENCAPSULATED_PIXEL_DATA_ELEMENT, IMPLICIT_PATIENT_ELEMENTS, IMPLICIT_VR_ELEMENT,
+            INVALID_VR_ELEMENT, NULL_ELEMENT, PATIENT_ELEMENTS, PIXEL_DATA_ELEMENT,
+            PRIVATE_ELEMENTS, REFERENCED_STUDY_SEQUENCE_ELEMENT, STANDARD_HEADER,
+            STUDY_DESCRIPTION_ELEMENT, UNDEFINED_LENGTH_ELEMENT,
+        },
     };
 
     #[test]
@@ -169,6 +196,1271 @@ mod parsing_tests {
         assert!(first_non_fme.is_none());
     }
 
+    fn parse_with_recovery(
+        dataset: &[&[u8]],
+        recovery: RecoveryPolicy,
+    ) -> ParseResult<(Vec<DicomElement>, Vec<ParseDiagnostic>)> {
+        let mut parser = ParserBuilder::default()
+            .recovery(recovery)
+            .build(Cursor::new(dataset.concat()), &STANDARD_DICOM_DICTIONARY);
+        let elements = parser
+            .by_ref()
+            .collect::<ParseResult<Vec<DicomElement>>>()?;
+        Ok((elements, parser.take_diagnostics()))
+    }
+
+    #[test]
+    fn test_recovery_retry_implicit_vr() -> ParseResult<()> {
+        let (elements, diagnostics) = parse_with_recovery(
+            &[
+                STANDARD_HEADER,
+                IMPLICIT_VR_ELEMENT,
+                STUDY_DESCRIPTION_ELEMENT,
+            ],
+            RecoveryPolicy::lenient(),
+        )?;
+
+        let study_date = elements
+            .iter()
+            .find(|e| e.tag() == StudyDate.tag())
+            .expect("StudyDate");
+        assert_eq!(&vr::DA, study_date.vr());
+        assert_eq!(ValueLength::Explicit(8), study_date.vl());
+        assert_eq!(b"20240101", study_date.data());
+        assert!(elements.iter().any(|e| e.tag() == StudyDescription.tag()));
+
+        assert_eq!(1, diagnostics.len());
+        assert_eq!(
+            &RecoveryAction::RetriedAsImplicitVR(0x0800),
+            diagnostics[0].action()
+        );
+        assert_eq!(&TagPath::from(&StudyDate), diagnostics[0].tagpath());
+        assert_eq!(STANDARD_HEADER.len() as u64, diagnostics[0].position());
+        Ok(())
+    }
+
+    #[test]
+    fn test_recovery_resynchronize_unknown_vr() -> ParseResult<()> {
+        let mut recovery = RecoveryPolicy::strict();
+        recovery.set_resynchronize(true);
+        let (elements, diagnostics) = parse_with_recovery(
+            &[
+                STANDARD_HEADER,
+                INVALID_VR_ELEMENT,
+                STUDY_DESCRIPTION_ELEMENT,
+            ],
+            recovery,
+        )?;
+
+        // The element with the invalid VR is skipped over entirely.
+        let tags: Vec<u32> = elements.iter().skip(8).map(DicomElement::tag).collect();
+        assert_eq!(
+            vec![SpecificCharacterSet.tag(), StudyDescription.tag()],
+            tags
+        );
+        assert_eq!(
+            Some(&"DEFERRED STUDY".to_string()),
+            elements[9].parse_value()?.string()
+        );
+
+        assert_eq!(1, diagnostics.len());
+        assert_eq!(
+            &RecoveryAction::Resynchronized { skipped: 4 },
+            diagnostics[0].action()
+        );
+        assert_eq!(&TagPath::from(&SOPClassUID), diagnostics[0].tagpath());
+        Ok(())
+    }
+
+    #[test]
+    fn test_recovery_resynchronize_undefined_length() -> ParseResult<()> {
+        let dataset: &[&[u8]] = &[
+            STANDARD_HEADER,
+            UNDEFINED_LENGTH_ELEMENT,
+            STUDY_DESCRIPTION_ELEMENT,
+            PIXEL_DATA_ELEMENT,
+        ];
+        let (elements, diagnostics) = parse_with_recovery(dataset, RecoveryPolicy::lenient())?;
+
+        let tags: Vec<u32> = elements.iter().skip(9).map(DicomElement::tag).collect();
+        assert_eq!(
+            vec![LongCodeValue.tag(), StudyDescription.tag(), PixelData.tag()],
+            tags
+        );
+        assert!(elements[10].sq_path().is_empty());
+        assert!(elements[11].sq_path().is_empty());
+
+        assert_eq!(1, diagnostics.len());
+        assert_eq!(
+            &RecoveryAction::Resynchronized { skipped: 8 },
+            diagnostics[0].action()
+        );
+
+        // Without recovery the value of the element is parsed as elements.
+        let (elements, diagnostics) =
+            parse_with_recovery(dataset, RecoveryPolicy::strict()).unwrap_or_default();
+        assert!(diagnostics.is_empty());
+        assert!(!elements.iter().any(|e| e.tag() == PixelData.tag()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_recovery_clamp_value_length() -> ParseResult<()> {
+        let truncated = &STUDY_DESCRIPTION_ELEMENT[..STUDY_DESCRIPTION_ELEMENT.len() - 4];
+        let (elements, diagnostics) =
+            parse_with_recovery(&[STANDARD_HEADER, truncated], RecoveryPolicy::lenient())?;
+
+        let study_desc = elements.last().expect("StudyDescription");
+        assert_eq!(StudyDescription.tag(), study_desc.tag());
+        assert_eq!(ValueLength::Explicit(10), study_desc.vl());
+        assert_eq!(b"DEFERRED S", study_desc.data());
+
+        assert_eq!(1, diagnostics.len());
+        assert_eq!(
+            &RecoveryAction::ClampedValueLength {
+                declared: 14,
+                actual: 10
+            },
+            diagnostics[0].action()
+        );
+
+        let strict = parse_with_recovery(&[STANDARD_HEADER, truncated], RecoveryPolicy::strict());
+        assert!(strict.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_recovery_group_length_as_hint() -> ParseResult<()> {
+        // The group length is at the end of the first element, after the preamble and prefix.
+        let mut header: Vec<u8> = STANDARD_HEADER.to_vec();
+        header[140..144].copy_from_slice(&16u32.to_le_bytes());
+        let (elements, diagnostics) = parse_with_recovery(
+            &[&header, STUDY_DESCRIPTION_ELEMENT],
+            RecoveryPolicy::lenient(),
+        )?;
+
+        let tags: Vec<u32> = elements.iter().map(DicomElement::tag).collect();
+        assert_eq!(10, tags.len());
+        assert!(tags[..8].iter().all(|tag| tag >> 16 == 0x0002));
+        assert_eq!(StudyDescription.tag(), tags[9]);
+
+        assert_eq!(1, diagnostics.len());
+        assert_eq!(
+            &RecoveryAction::IgnoredGroupLength {
+                declared: 16,
+                actual: 0xE2
+            },
+            diagnostics[0].action()
+        );
+        assert_eq!(
+            &TagPath::from(&FileMetaInformationGroupLength),
+            diagnostics[0].tagpath()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_dataset_at_arbitrary_group() -> ParseResult<()> {
+        for (dataset, ts) in [
+            (PATIENT_ELEMENTS, &ExplicitVRLittleEndian),
+            (IMPLICIT_PATIENT_ELEMENTS, &ImplicitVRLittleEndian),
+        ] {
+            let mut parser = ParserBuilder::default()
+                .build(Cursor::new(dataset.to_vec()), &STANDARD_DICOM_DICTIONARY);
+            let elements = parser
+                .by_ref()
+                .collect::<ParseResult<Vec<DicomElement>>>()?;
+
+            assert_eq!(ts, parser.ts());
+            assert!(parser.file_preamble().is_none());
+            assert_eq!(2, elements.len());
+            assert_eq!(PatientsName.tag(), elements[0].tag());
+            assert_eq!(b"DOE^JOHN", elements[0].data());
+            assert_eq!(PatientID.tag(), elements[1].tag());
+            assert_eq!(b"1234", elements[1].data());
+        }
+        Ok(())
+    }
+
+    /// Builds a dataset without File Meta, encoded as `ExplicitVRLittleEndian`, which is large
+    /// enough that detection only sees a portion of it when deflated.
+    #[cfg(feature = "compress")]
+    fn build_patient_dataset() -> Vec<u8> {
+        let mut seed: u32 = 1;
+        let comments: Vec<u8> = (0..400)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                b'A' + u8::try_from((seed >> 16) % 26).unwrap_or_default()
+            })
+            .collect();
+        let mut dataset: Vec<u8> = PATIENT_ELEMENTS.to_vec();
+        // PatientComments      VR: LT      VL: 400
+        dataset.extend_from_slice(&[0x10, 0x00, 0x00, 0x40, 0x4C, 0x54, 0x90, 0x01]);
+        dataset.extend_from_slice(&comments);
+        dataset
+    }
+
+    #[cfg(feature = "compress")]
+    #[test]
+    fn test_detect_deflated_dataset() -> ParseResult<()> {
+        use std::io::Write;
+
+        let dataset: Vec<u8> = build_patient_dataset();
+        let mut encoder = libflate::deflate::Encoder::new(Vec::new());
+        encoder.write_all(&dataset)?;
+        let deflated: Vec<u8> = encoder.finish().into_result()?;
+        assert!(deflated.len() > 132);
+
+        let mut parser = ParserBuilder::default()
+            .build(Cursor::new(deflated.clone()), &STANDARD_DICOM_DICTIONARY);
+        let elements = parser
+            .by_ref()
+            .collect::<ParseResult<Vec<DicomElement>>>()?;
+
+        assert_eq!(&DeflatedExplicitVRLittleEndian, parser.ts());
+        assert_eq!(3, elements.len());
+        assert_eq!(PatientsName.tag(), elements[0].tag());
+        assert_eq!(PatientComments.tag(), elements[2].tag());
+        assert_eq!(&dataset[dataset.len() - 400..], elements[2].data());
+
+        #[cfg(feature = "async")]
+        for chunk_size in [1, 16, 4096] {
+            let actual = parse_async(TrickleDataset {
+                data: deflated.clone(),
+                pos: 0,
+                chunk_size,
+                ready: false,
+            })?;
+            assert_same_elements(&elements, &actual);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_declared_ts_mismatch() -> ParseResult<()> {
+        // The File Meta declares RLE Lossless, encoded as Explicit VR Little Endian, however the
+        // dataset following it is encoded as Implicit VR Little Endian.
+        let header: &[u8] = &STANDARD_HEADER[..STANDARD_HEADER.len() - 18];
+        let mut parser = ParserBuilder::default().build(
+            Cursor::new([header, IMPLICIT_VR_ELEMENT, IMPLICIT_PATIENT_ELEMENTS].concat()),
+            &STANDARD_DICOM_DICTIONARY,
+        );
+        let elements = parser
+            .by_ref()
+            .collect::<ParseResult<Vec<DicomElement>>>()?;
+
+        assert_eq!(&ImplicitVRLittleEndian, parser.ts());
+        assert_eq!(Some(&RLELossless), parser.declared_ts());
+
+        let tags: Vec<u32> = elements.iter().map(DicomElement::tag).collect();
+        assert_eq!(
+            vec![StudyDate.tag(), PatientsName.tag(), PatientID.tag()],
+            tags[8..]
+        );
+        assert_eq!(b"20240101", elements[8].data());
+
+        let diagnostics = parser.diagnostics();
+        assert_eq!(1, diagnostics.len());
+        assert_eq!(
+            &RecoveryAction::OverrodeTransferSyntax {
+                declared: &RLELossless,
+                detected: &ImplicitVRLittleEndian,
+            },
+            diagnostics[0].action()
+        );
+        assert_eq!(&TagPath::from(&TransferSyntaxUID), diagnostics[0].tagpath());
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_declared_ts_match() -> ParseResult<()> {
+        let mut parser = ParserBuilder::default().build(
+            Cursor::new([STANDARD_HEADER, STUDY_DESCRIPTION_ELEMENT].concat()),
+            &STANDARD_DICOM_DICTIONARY,
+        );
+        let elements = parser
+            .by_ref()
+            .collect::<ParseResult<Vec<DicomElement>>>()?;
+
+        assert_eq!(10, elements.len());
+        assert_eq!(&RLELossless, parser.ts());
+        assert!(parser.declared_ts().is_none());
+        assert!(parser.diagnostics().is_empty());
+        Ok(())
+    }
+
+    /// Builds a tar archive of the given entries. Entries with names ending in `/` are directories,
+    /// and names longer than 100 bytes are written with a GNU long name entry.
+    #[cfg(feature = "archive")]
+    fn build_tar(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        fn push_entry(tar: &mut Vec<u8>, name: &[u8], typeflag: u8, data: &[u8]) {
+            let mut header = [0u8; 512];
+            header[..name.len().min(100)].copy_from_slice(&name[..name.len().min(100)]);
+            header[100..107].copy_from_slice(b"0000644");
+            header[124..135].copy_from_slice(format!("{:011o}", data.len()).as_bytes());
+            header[136..147].copy_from_slice(b"00000000000");
+            header[156] = typeflag;
+            header[257..263].copy_from_slice(b"ustar\0");
+            header[263..265].copy_from_slice(b"00");
+            header[148..156].copy_from_slice(b"        ");
+            let checksum: u32 = header.iter().map(|b| u32::from(*b)).sum();
+            header[148..155].copy_from_slice(format!("{checksum:06o}\0").as_bytes());
+            tar.extend_from_slice(&header);
+            tar.extend_from_slice(data);
+            tar.resize(tar.len().div_ceil(512) * 512, 0);
+        }
+
+        let mut tar: Vec<u8> = Vec::new();
+        for (name, data) in entries {
+            if name.len() > 100 {
+                push_entry(&mut tar, b"././@LongLink", b'L', name.as_bytes());
+            }
+            let typeflag = if name.ends_with('/') { b'5' } else { b'0' };
+            push_entry(&mut tar, name.as_bytes(), typeflag, data);
+        }
+        tar.resize(tar.len() + 1024, 0);
+        tar
+    }
+
+    /// Builds a zip archive of the given entries, with each entry either stored or deflated.
+    #[cfg(feature = "archive")]
+    fn build_zip(entries: &[(&str, &[u8])], deflate: bool) -> ParseResult<Vec<u8>> {
+        use std::io::Write;
+
+        let mut zip: Vec<u8> = Vec::new();
+        let mut central: Vec<u8> = Vec::new();
+        for (name, data) in entries {
+            let (method, compressed): (u16, Vec<u8>) = if deflate {
+                let mut encoder = libflate::deflate::Encoder::new(Vec::new());
+                encoder.write_all(data)?;
+                (8, encoder.finish().into_result()?)
+            } else {
+                (0, data.to_vec())
+            };
+            let offset = u32::try_from(zip.len()).unwrap_or_default();
+            let csize = u32::try_from(compressed.len()).unwrap_or_default();
+            let usize = u32::try_from(data.len()).unwrap_or_default();
+            let name_len = u16::try_from(name.len()).unwrap_or_default();
+
+            // Version needed, flags, method, modification time and date, CRC.
+            let mut common: Vec<u8> = [20u16, 0, method, 0, 0]
+                .iter()
+                .flat_map(|v| v.to_le_bytes())
+                .collect();
+            common.extend_from_slice(&0u32.to_le_bytes());
+            common.extend_from_slice(&csize.to_le_bytes());
+            common.extend_from_slice(&usize.to_le_bytes());
+            common.extend_from_slice(&name_len.to_le_bytes());
+            // Extra field length.
+            common.extend_from_slice(&0u16.to_le_bytes());
+
+            zip.extend_from_slice(b"PK\x03\x04");
+            zip.extend_from_slice(&common);
+            zip.extend_from_slice(name.as_bytes());
+            zip.extend_from_slice(&compressed);
+
+            central.extend_from_slice(b"PK\x01\x02");
+            // Version made by.
+            central.extend_from_slice(&20u16.to_le_bytes());
+            central.extend_from_slice(&common);
+            // Comment length, disk number, internal and external attributes.
+            central.extend_from_slice(&[0u8; 10]);
+            central.extend_from_slice(&offset.to_le_bytes());
+            central.extend_from_slice(name.as_bytes());
+        }
+        let num_entries = u16::try_from(entries.len()).unwrap_or_default();
+        let cd_size = u32::try_from(central.len()).unwrap_or_default();
+        let cd_offset = u32::try_from(zip.len()).unwrap_or_default();
+        zip.extend_from_slice(&central);
+        zip.extend_from_slice(b"PK\x05\x06");
+        zip.extend_from_slice(&[0u8; 4]);
+        zip.extend_from_slice(&num_entries.to_le_bytes());
+        zip.extend_from_slice(&num_entries.to_le_bytes());
+        zip.extend_from_slice(&cd_size.to_le_bytes());
+        zip.extend_from_slice(&cd_offset.to_le_bytes());
+        zip.extend_from_slice(&0u16.to_le_bytes());
+        Ok(zip)
+    }
+
+    /// Parses all DICOM datasets within the archive, returning the entry path and the number of
+    /// elements parsed for each.
+    #[cfg(feature = "archive")]
+    fn parse_archive(archive: Vec<u8>, format: ArchiveFormat) -> ParseResult<Vec<(String, usize)>> {
+        let mut parsed: Vec<(String, usize)> = Vec::new();
+        for entry in ParserBuilder::default().build_archive(
+            Cursor::new(archive),
+            format,
+            &STANDARD_DICOM_DICTIONARY,
+        )? {
+            let (path, parser) = entry?;
+            let elements = parser.collect::<ParseResult<Vec<DicomElement>>>()?;
+            parsed.push((path, elements.len()));
+        }
+        Ok(parsed)
+    }
+
+    #[cfg(feature = "archive")]
+    fn archive_entries() -> Vec<(String, Vec<u8>)> {
+        vec![
+            ("study/".to_string(), Vec::new()),
+            (
+                "study/1.dcm".to_string(),
+                [STANDARD_HEADER, STUDY_DESCRIPTION_ELEMENT].concat(),
+            ),
+            ("study/readme.txt".to_string(), b"not a dicom file".to_vec()),
+            (
+                format!("study/{}/2", "series".repeat(20)),
+                build_patient_dataset(),
+            ),
+        ]
+    }
+
+    #[cfg(feature = "archive")]
+    #[test]
+    fn test_archive_tar() -> ParseResult<()> {
+        use std::io::Write;
+
+        let entries = archive_entries();
+        let entries: Vec<(&str, &[u8])> = entries
+            .iter()
+            .map(|(name, data)| (name.as_str(), data.as_slice()))
+            .collect();
+        let tar: Vec<u8> = build_tar(&entries);
+        let expected = vec![
+            (entries[1].0.to_string(), 10),
+            (entries[3].0.to_string(), 3),
+        ];
+
+        assert_eq!(Some(ArchiveFormat::Tar), ArchiveFormat::sniff(&tar));
+        assert_eq!(expected, parse_archive(tar.clone(), ArchiveFormat::Tar)?);
+
+        let mut encoder = libflate::gzip::Encoder::new(Vec::new())?;
+        encoder.write_all(&tar)?;
+        let tar_gz: Vec<u8> = encoder.finish().into_result()?;
+        assert_eq!(Some(ArchiveFormat::TarGz), ArchiveFormat::sniff(&tar_gz));
+        assert_eq!(expected, parse_archive(tar_gz, ArchiveFormat::TarGz)?);
+
+        // A truncated archive produces the entries before the truncation, then ends with an error.
+        let truncated: Vec<u8> = tar[..tar.len() - 1200].to_vec();
+        let mut walker = ParserBuilder::default().build_archive(
+            Cursor::new(truncated),
+            ArchiveFormat::Tar,
+            &STANDARD_DICOM_DICTIONARY,
+        )?;
+        assert_eq!(
+            entries[1].0,
+            walker.next().transpose()?.map(|e| e.0).unwrap_or_default()
+        );
+        assert!(matches!(
+            walker.next(),
+            Some(Err(ParseError::IOError { .. }))
+        ));
+        assert!(walker.next().is_none());
+        Ok(())
+    }
+
+    #[cfg(feature = "archive")]
+    #[test]
+    fn test_archive_zip() -> ParseResult<()> {
+        let entries = archive_entries();
+        let entries: Vec<(&str, &[u8])> = entries
+            .iter()
+            .map(|(name, data)| (name.as_str(), data.as_slice()))
+            .collect();
+        let expected = vec![
+            (entries[1].0.to_string(), 10),
+            (entries[3].0.to_string(), 3),
+        ];
+
+        for deflate in [false, true] {
+            let zip: Vec<u8> = build_zip(&entries, deflate)?;
+            assert_eq!(Some(ArchiveFormat::Zip), ArchiveFormat::sniff(&zip));
+            assert_eq!(expected, parse_archive(zip, ArchiveFormat::Zip)?);
+        }
+
+        let not_zip: Vec<u8> = build_tar(&entries);
+        assert!(matches!(
+            ParserBuilder::default().build_archive(
+                Cursor::new(not_zip),
+                ArchiveFormat::Zip,
+                &STANDARD_DICOM_DICTIONARY,
+            ),
+            Err(ParseError::InvalidArchive(_))
+        ));
+        Ok(())
+    }
+
+    /// Entries are detected as DICOM using the walker's parser options, such as its recovery.
+    #[cfg(feature = "archive")]
+    #[test]
+    fn test_archive_detects_with_builder() -> ParseResult<()> {
+        // A dataset without a preamble whose value runs past the end of the entry.
+        let truncated: &[u8] = &STUDY_DESCRIPTION_ELEMENT[..STUDY_DESCRIPTION_ELEMENT.len() - 4];
+        let tar: Vec<u8> = build_tar(&[("truncated", truncated)]);
+        let count_dicom = |builder: ParserBuilder| -> ParseResult<usize> {
+            Ok(builder
+                .build_archive(
+                    Cursor::new(tar.clone()),
+                    ArchiveFormat::Tar,
+                    &STANDARD_DICOM_DICTIONARY,
+                )?
+                .count())
+        };
+
+        assert_eq!(0, count_dicom(ParserBuilder::default())?);
+        assert_eq!(
+            1,
+            count_dicom(ParserBuilder::default().recovery(RecoveryPolicy::lenient()))?
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "archive")]
+    #[test]
+    fn test_archive_format_from_path() {
+        use std::path::Path;
+
+        assert_eq!(
+            Some(ArchiveFormat::Zip),
+            ArchiveFormat::from_path(Path::new("/data/STUDY.ZIP"))
+        );
+        assert_eq!(
+            Some(ArchiveFormat::Tar),
+            ArchiveFormat::from_path(Path::new("study.tar"))
+        );
+        assert_eq!(
+            Some(ArchiveFormat::TarGz),
+            ArchiveFormat::from_path(Path::new("study.tar.gz"))
+        );
+        assert_eq!(
+            Some(ArchiveFormat::TarGz),
+            ArchiveFormat::from_path(Path::new("study.tgz"))
+        );
+        assert_eq!(None, ArchiveFormat::from_path(Path::new("study.dcm")));
+        assert_eq!(None, ArchiveFormat::from_path(Path::new("study.gz")));
+    }
+
+    #[test]
+    fn test_parallel_parse() {
+        let datasets: Vec<Vec<u8>> = (0..20)
+            .map(|i| match i % 5 {
+                // Not DICOM.
+                3 => b"not a dicom dataset".to_vec(),
+                // Truncated within an element value.
+                4 => [STANDARD_HEADER, &STUDY_DESCRIPTION_ELEMENT[..12]].concat(),
+                _ => [STANDARD_HEADER, STUDY_DESCRIPTION_ELEMENT].concat(),
+            })
+            .collect();
+        let sources = || {
+            datasets
+                .clone()
+                .into_iter()
+                .map(|dataset| ReaderSource::new(Cursor::new(dataset)))
+        };
+        let parallel = ParserBuilder::default()
+            .build_parallel(&STANDARD_DICOM_DICTIONARY)
+            .workers(NonZeroUsize::new(3).unwrap_or(NonZeroUsize::MIN));
+
+        let check = |parsed: &ParsedDataset<ReaderSource<Cursor<Vec<u8>>>>| match (
+            parsed.index() % 5,
+            parsed.result(),
+        ) {
+            (3, Ok(None)) | (4, Err(_)) => {}
+            (0..=2, Ok(Some(dcmroot))) => {
+                assert_eq!(10, dcmroot.get_child_count());
+            }
+            (index, result) => panic!("unexpected result for {index}: {result:?}"),
+        };
+
+        let mut indices: Vec<usize> = Vec::new();
+        for parsed in parallel.parse(sources()) {
+            check(&parsed);
+            indices.push(parsed.index());
+        }
+        indices.sort_unstable();
+        assert_eq!((0..20).collect::<Vec<usize>>(), indices);
+
+        let parallel = parallel.order(ResultOrder::Input);
+        let mut indices: Vec<usize> = Vec::new();
+        for parsed in parallel.parse(sources()) {
+            check(&parsed);
+            indices.push(parsed.index());
+        }
+        assert_eq!((0..20).collect::<Vec<usize>>(), indices);
+
+        // Dropping the results before all are consumed stops the workers.
+        let mut results = parallel.parse(sources());
+        assert!(results.next().is_some());
+        drop(results);
+
+        let missing = std::env::temp_dir().join("medicom-parallel-missing.dcm");
+        let results: Vec<_> = parallel.parse(vec![missing.clone()]).collect();
+        assert_eq!(1, results.len());
+        assert_eq!(&missing, results[0].source());
+        assert!(matches!(
+            results[0].result(),
+            Err(ParseError::IOError { .. })
+        ));
+    }
+
+    fn build_deferring_parser(
+        defer_values: DeferValues,
+    ) -> Parser<'static, SeekableDataset<Cursor<Vec<u8>>>> {
+        let dataset = [
+            STANDARD_HEADER,
+            STUDY_DESCRIPTION_ELEMENT,
+            PIXEL_DATA_ELEMENT,
+        ]
+        .concat();
+        ParserBuilder::default()
+            .defer_values(defer_values)
+            .build_seekable(Cursor::new(dataset), &STANDARD_DICOM_DICTIONARY)
+    }
+
+    #[test]
+    fn test_deferred_bulk_data() -> ParseResult<()> {
+        let mut parser = build_deferring_parser(DeferValues::BulkData);
+
+        let study_desc = parser
+            .find(|e| e.as_ref().is_ok_and(|e| e.tag() == StudyDescription.tag()))
+            .expect("StudyDescription")?;
+        assert!(!study_desc.is_deferred());
+        assert_eq!(
+            Some(&"DEFERRED STUDY".to_string()),
+            study_desc.parse_value()?.string()
+        );
+
+        let pixdata = parser.next().expect("PixelData")?;
+        assert_eq!(PixelData.tag(), pixdata.tag());
+        assert!(pixdata.is_deferred());
+        assert!(pixdata.data().is_empty());
+        assert!(!pixdata.is_empty());
+        assert_eq!(8, pixdata.value_size());
+
+        let deferred = pixdata.deferred().expect("deferred value");
+        let expected_pos = STANDARD_HEADER.len() + STUDY_DESCRIPTION_ELEMENT.len() + 12;
+        assert_eq!(
+            u64::try_from(expected_pos).unwrap_or_default(),
+            deferred.position()
+        );
+        assert_eq!(8, deferred.length());
+        assert!(!deferred.is_loaded());
+
+        assert_eq!(
+            RawValue::Words(vec![1, 2, 3, 4]),
+            pixdata.parse_value()?,
+            "deferred value should load on demand"
+        );
+        assert_eq!(&PIXEL_DATA_ELEMENT[12..], pixdata.data());
+
+        assert!(parser.next().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_deferred_above_length() -> ParseResult<()> {
+        let parser = build_deferring_parser(DeferValues::AboveLength(10));
+
+        let mut num_deferred = 0;
+        for elem in parser {
+            let elem = elem?;
+            if elem.tag() == StudyDescription.tag() {
+                assert!(elem.is_deferred());
+                assert_eq!(
+                    Some(&"DEFERRED STUDY".to_string()),
+                    elem.parse_value()?.string()
+                );
+            }
+            if elem.is_deferred() {
+                num_deferred += 1;
+                assert!(matches!(elem.vl(), ValueLength::Explicit(len) if len > 10));
+            }
+        }
+        // Four of the File Meta elements, StudyDescription, but not PixelData.
+        assert_eq!(5, num_deferred);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deferred_dcmroot() -> ParseResult<()> {
+        let mut parser = build_deferring_parser(DeferValues::BulkData);
+        let mut dcmroot = DicomRoot::parse(&mut parser)?.expect("DicomRoot");
+        // The parser can be dropped while elements still reference the dataset.
+        drop(parser);
+
+        let pixdata = dcmroot
+            .get_child_by_tag_mut(&PixelData)
+            .expect("PixelData")
+            .element_mut();
+        assert!(pixdata.is_deferred());
+        assert_eq!(8, pixdata.load_value()?.len());
+        assert!(pixdata.deferred().is_some_and(DeferredValue::is_loaded));
+
+        pixdata.unload_value();
+        assert!(pixdata.deferred().is_some_and(|d| !d.is_loaded()));
+        assert!(pixdata.data().is_empty());
+
+        assert_eq!(
+            Some(RawValue::Words(vec![1, 2, 3, 4])),
+            dcmroot.get_value_by_tag(&PixelData)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_shared_values() -> ParseResult<()> {
+        let dataset = [
+            STANDARD_HEADER,
+            STUDY_DESCRIPTION_ELEMENT,
+            PIXEL_DATA_ELEMENT,
+        ]
+        .concat();
+        let mut parser = ParserBuilder::default()
+            // Shared values take precedence over deferring values.
+            .defer_values(DeferValues::BulkData)
+            .build_shared(dataset, &STANDARD_DICOM_DICTIONARY);
+        let mut dcmroot = DicomRoot::parse(&mut parser)?.expect("DicomRoot");
+        drop(parser);
+
+        let study_desc = dcmroot
+            .get_child_by_tag(&StudyDescription)
+            .expect("StudyDescription")
+            .element();
+        assert!(study_desc.is_shared());
+        assert!(!study_desc.is_deferred());
+        assert_eq!(
+            Some(&"DEFERRED STUDY".to_string()),
+            study_desc.parse_value()?.string()
+        );
+
+        let pixdata = dcmroot
+            .get_child_by_tag_mut(&PixelData)
+            .expect("PixelData")
+            .element_mut();
+        let shared = pixdata.shared().expect("shared value");
+        let expected_pos = STANDARD_HEADER.len() + STUDY_DESCRIPTION_ELEMENT.len() + 12;
+        assert_eq!(expected_pos, shared.position());
+        assert_eq!(&PIXEL_DATA_ELEMENT[12..], pixdata.data());
+        assert_eq!(8, pixdata.value_size());
+
+        // Modifying the value copies it out of the shared buffer.
+        pixdata.mut_data()[0] = 5;
+        assert!(!pixdata.is_shared());
+        assert_eq!(
+            Some(RawValue::Words(vec![5, 2, 3, 4])),
+            dcmroot.get_value_by_tag(&PixelData)
+        );
+        Ok(())
+    }
+
+    fn parse_filtered(tag_filter: TagFilter) -> ParseResult<DicomRoot> {
+        let dataset = [
+            STANDARD_HEADER,
+            STUDY_DESCRIPTION_ELEMENT,
+            REFERENCED_STUDY_SEQUENCE_ELEMENT,
+            PRIVATE_ELEMENTS,
+            PIXEL_DATA_ELEMENT,
+        ]
+        .concat();
+        let mut parser = ParserBuilder::default()
+            .tag_filter(tag_filter)
+            .build(Cursor::new(dataset), &STANDARD_DICOM_DICTIONARY);
+        let dcmroot = DicomRoot::parse(&mut parser)?.expect("DicomRoot");
+        assert!(parser.next().is_none(), "entire dataset should be parsed");
+        Ok(dcmroot)
+    }
+
+    #[test]
+    fn test_tag_filter_all() -> ParseResult<()> {
+        let dcmroot = parse_filtered(TagFilter::All)?;
+        // Eight File Meta elements, SpecificCharacterSet, StudyDescription, ReferencedStudySequence,
+        // two private elements, and PixelData.
+        assert_eq!(14, dcmroot.get_child_count());
+        Ok(())
+    }
+
+    #[test]
+    fn test_tag_filter_exclude() -> ParseResult<()> {
+        let dcmroot = parse_filtered(TagFilter::Exclude(vec![
+            TagSelector::Private,
+            TagSelector::group(0x7FE0),
+            TagSelector::tagpath(&StudyDescription),
+        ]))?;
+
+        assert_eq!(10, dcmroot.get_child_count());
+        assert!(dcmroot.get_child_by_tag(&SpecificCharacterSet).is_some());
+        let ref_study_seq = dcmroot
+            .get_child_by_tag(&ReferencedStudySequence)
+            .expect("ReferencedStudySequence");
+        assert_eq!(2, ref_study_seq.item_count());
+        assert_eq!(
+            Some(RawValue::Uid("1.2.5".to_string())),
+            ref_study_seq
+                .get_item_by_index(2)
+                .and_then(|item| item.get_value_by_tag(&ReferencedSOPInstanceUID))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_tag_filter_include() -> ParseResult<()> {
+        // Include the ReferencedSOPInstanceUID from all items of the sequence.
+        let mut all_items_path =
+            TagPath::from(vec![&ReferencedStudySequence, &ReferencedSOPInstanceUID]);
+        all_items_path.nodes_mut()[0].item_mut().take();
+        let dcmroot = parse_filtered(TagFilter::Include(vec![
+            TagSelector::TagPath(all_items_path),
+            TagSelector::tagpath(&StudyDescription),
+        ]))?;
+
+        // File Meta elements and SpecificCharacterSet are always included.
+        assert_eq!(11, dcmroot.get_child_count());
+        assert_eq!(
+            Some(RawValue::Strings(vec!["DEFERRED STUDY".to_string()])),
+            dcmroot.get_value_by_tag(&StudyDescription)
+        );
+        let ref_study_seq = dcmroot
+            .get_child_by_tag(&ReferencedStudySequence)
+            .expect("ReferencedStudySequence");
+        assert_eq!(2, ref_study_seq.item_count());
+        for item in ref_study_seq.iter_items() {
+            assert!(item.get_child_by_tag(&ReferencedSOPClassUID).is_none());
+            assert!(item.get_child_by_tag(&ReferencedSOPInstanceUID).is_some());
+        }
+
+        // Nodes within the path default to the first item.
+        let dcmroot = parse_filtered(TagFilter::Include(vec![TagSelector::tagpath(vec![
+            &ReferencedStudySequence,
+            &ReferencedSOPInstanceUID,
+        ])]))?;
+        let ref_study_seq = dcmroot
+            .get_child_by_tag(&ReferencedStudySequence)
+            .expect("ReferencedStudySequence");
+        assert_eq!(2, ref_study_seq.item_count());
+        assert_eq!(
+            Some(RawValue::Uid("1.2.4".to_string())),
+            ref_study_seq
+                .get_item_by_index(1)
+                .and_then(|item| item.get_value_by_tag(&ReferencedSOPInstanceUID))
+        );
+        assert!(ref_study_seq
+            .get_item_by_index(2)
+            .is_some_and(|item| item.get_child_by_tag(&ReferencedSOPInstanceUID).is_none()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_tag_filter_shared() -> ParseResult<()> {
+        // Excluded values are skipped by position rather than read.
+        let dataset = [STANDARD_HEADER, PRIVATE_ELEMENTS, PIXEL_DATA_ELEMENT].concat();
+        let mut parser = ParserBuilder::default()
+            .tag_filter(TagFilter::Exclude(vec![TagSelector::Private]))
+            .build_shared(dataset, &STANDARD_DICOM_DICTIONARY);
+        let dcmroot = DicomRoot::parse(&mut parser)?.expect("DicomRoot");
+        assert_eq!(10, dcmroot.get_child_count());
+        assert_eq!(
+            Some(RawValue::Words(vec![1, 2, 3, 4])),
+            dcmroot.get_value_by_tag(&PixelData)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_tag_filter_predicate() -> ParseResult<()> {
+        // Exclude the contents of all sequences.
+        let dcmroot = parse_filtered(TagFilter::predicate(|tagpath: &TagPath| {
+            tagpath.nodes().len() == 1
+        }))?;
+        assert_eq!(14, dcmroot.get_child_count());
+        let ref_study_seq = dcmroot
+            .get_child_by_tag(&ReferencedStudySequence)
+            .expect("ReferencedStudySequence");
+        assert!(ref_study_seq.iter_items().all(|item| {
+            item.get_child_by_tag(&ReferencedSOPClassUID).is_none()
+                && item.get_child_by_tag(&ReferencedSOPInstanceUID).is_none()
+        }));
+
+        // Excluding a sequence excludes its contents, without consulting the predicate.
+        let dcmroot = parse_filtered(TagFilter::predicate(|tagpath: &TagPath| {
+            tagpath.nodes().first().map(TagNode::tag) != Some(ReferencedStudySequence.tag())
+        }))?;
+        assert_eq!(13, dcmroot.get_child_count());
+        assert!(dcmroot.get_child_by_tag(&ReferencedStudySequence).is_none());
+        Ok(())
+    }
+
+    /// Records the events of visiting a dataset, for verifying against.
+    #[derive(Default)]
+    struct RecordingVisitor {
+        events: Vec<String>,
+        values: Vec<Vec<u8>>,
+        largest_chunk: usize,
+    }
+
+    impl DicomVisitor for RecordingVisitor {
+        fn chunk_size(&self) -> usize {
+            4
+        }
+
+        fn start_element(&mut self, element: &DicomElement) -> ParseResult<()> {
+            // The parser relies on the values of File Meta elements and SpecificCharacterSet.
+            let is_read =
+                element.tag() >> 16 == 0x0002 || element.tag() == SpecificCharacterSet.tag();
+            assert!(is_read || element.data().is_empty());
+            self.events.push(format!(
+                "start {}",
+                Tag::format_tag_to_display(element.tag())
+            ));
+            self.values.push(Vec::new());
+            Ok(())
+        }
+
+        fn value_chunk(&mut self, _element: &DicomElement, chunk: &[u8]) -> ParseResult<()> {
+            self.largest_chunk = self.largest_chunk.max(chunk.len());
+            if let Some(value) = self.values.last_mut() {
+                value.extend_from_slice(chunk);
+            }
+            Ok(())
+        }
+
+        fn end_element(&mut self, element: &DicomElement) -> ParseResult<()> {
+            self.events
+                .push(format!("end {}", Tag::format_tag_to_display(element.tag())));
+            Ok(())
+        }
+
+        fn start_sequence(&mut self, element: &DicomElement) -> ParseResult<()> {
+            self.events.push(format!(
+                "start sq {}",
+                Tag::format_tag_to_display(element.tag())
+            ));
+            Ok(())
+        }
+
+        fn end_sequence(&mut self, element: &DicomElement) -> ParseResult<()> {
+            self.events.push(format!(
+                "end sq {}",
+                Tag::format_tag_to_display(element.tag())
+            ));
+            Ok(())
+        }
+
+        fn start_item(&mut self, _element: &DicomElement) -> ParseResult<()> {
+            self.events.push("start item".to_string());
+            Ok(())
+        }
+
+        fn end_item(&mut self, _element: &DicomElement) -> ParseResult<()> {
+            self.events.push("end item".to_string());
+            Ok(())
+        }
+
+        fn fragment(&mut self, _element: &DicomElement, index: usize) -> ParseResult<()> {
+            self.events.push(format!("fragment {index}"));
+            self.values.push(Vec::new());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_visitor_events() -> ParseResult<()> {
+        let mut parser = MockDicomDataset::build_mock_parser(&[
+            STANDARD_HEADER,
+            STUDY_DESCRIPTION_ELEMENT,
+            REFERENCED_STUDY_SEQUENCE_ELEMENT,
+            PIXEL_DATA_ELEMENT,
+        ]);
+        let mut visitor = RecordingVisitor::default();
+        parser.visit(&mut visitor)?;
+
+        // Skip the File Meta elements and SpecificCharacterSet.
+        let events: Vec<&str> = visitor.events.iter().skip(18).map(String::as_str).collect();
+        assert_eq!(
+            vec![
+                "start (0008,1030)",
+                "end (0008,1030)",
+                "start sq (0008,1110)",
+                "start item",
+                "start (0008,1150)",
+                "end (0008,1150)",
+                "start (0008,1155)",
+                "end (0008,1155)",
+                "end item",
+                "start item",
+                "start (0008,1155)",
+                "end (0008,1155)",
+                "end item",
+                "end sq (0008,1110)",
+                "start (7FE0,0010)",
+                "end (7FE0,0010)",
+            ],
+            events
+        );
+
+        let values: Vec<&[u8]> = visitor.values.iter().skip(9).map(Vec::as_slice).collect();
+        assert_eq!(
+            vec![
+                "DEFERRED STUDY".as_bytes(),
+                b"1.2.3\0",
+                b"1.2.4\0",
+                b"1.2.5\0",
+                &PIXEL_DATA_ELEMENT[12..],
+            ],
+            values
+        );
+        assert_eq!(4, visitor.largest_chunk);
+        Ok(())
+    }
+
+    #[test]
+    fn test_visitor_fragments() -> ParseResult<()> {
+        let mut parser = MockDicomDataset::build_mock_parser(&[
+            STANDARD_HEADER,
+            ENCAPSULATED_PIXEL_DATA_ELEMENT,
+        ]);
+        let mut visitor = RecordingVisitor::default();
+        parser.visit(&mut visitor)?;
+
+        let events: Vec<&str> = visitor.events.iter().skip(18).map(String::as_str).collect();
+        assert_eq!(
+            vec![
+                "start (7FE0,0010)",
+                "fragment 0",
+                "end (FFFE,E000)",
+                "fragment 1",
+                "end (FFFE,E000)",
+                "fragment 2",
+                "end (FFFE,E000)",
+                "end (7FE0,0010)",
+            ],
+            events
+        );
+
+        let values: Vec<&[u8]> = visitor.values.iter().skip(10).map(Vec::as_slice).collect();
+        assert_eq!(vec![&[][..], &[1, 2, 3, 4, 5, 6][..], &[7, 8][..]], values);
+        Ok(())
+    }
+
+    #[test]
+    fn test_encapsulated_pixel_data() -> Result<(), PixelDataError> {
+        let mut parser = MockDicomDataset::build_mock_parser(&[
+            STANDARD_HEADER,
+            ENCAPSULATED_PIXEL_DATA_ELEMENT,
+        ]);
+        let dcmroot = DicomRoot::parse(&mut parser)?.expect("parse mock dataset");
+        let encap = EncapsulatedPixelData::from_dcmroot(&dcmroot)?.expect("encapsulated");
+        assert!(encap.basic_offset_table().is_empty());
+        let offsets: Vec<u64> = encap.fragments().iter().map(Fragment::offset).collect();
+        assert_eq!(vec![0, 14], offsets);
+        assert_eq!(1, encap.num_frames());
+        assert_eq!(&[1, 2, 3, 4, 5, 6, 7, 8][..], &*encap.frame(0)?);
+
+        let parser = MockDicomDataset::build_mock_parser(&[
+            STANDARD_HEADER,
+            ENCAPSULATED_PIXEL_DATA_ELEMENT,
+        ]);
+        let mut pixdata_info = PixelDataSliceInfo::process_dcm_parser(parser)?;
+        assert!(pixdata_info.bytes().is_empty());
+        assert_eq!(Some(&encap), pixdata_info.encapsulated());
+        assert!(matches!(
+            pixdata_info.validate(),
+            Err(PixelDataError::EncapsulatedPixelData)
+        ));
+        Ok(())
+    }
+
+    /// Items of encapsulated pixel data with an explicit length, such as a Basic Offset Table with
+    /// entries for several frames, are fragments rather than items containing elements. The
+    /// fragments which follow, and elements after the pixel data, are not nested within them.
+    #[test]
+    fn test_encapsulated_pixel_data_sq_path() -> Result<(), PixelDataError> {
+        use medicom::dict::tags::DataSetTrailingPadding;
+
+        #[rustfmt::skip]
+        let dataset: &[u8] = &[
+            // PixelData            VR: OB      VL: Undefined
+            0xE0, 0x7F, 0x10, 0x00, 0x4F, 0x42, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF,
+            // Item, Basic Offset Table         VL: 8
+            0xFE, 0xFF, 0x00, 0xE0, 0x08, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x0C, 0x00, 0x00, 0x00,
+            // Item, Fragment                   VL: 4
+            0xFE, 0xFF, 0x00, 0xE0, 0x04, 0x00, 0x00, 0x00,
+            0x01, 0x02, 0x03, 0x04,
+            // Item, Fragment                   VL: 2
+            0xFE, 0xFF, 0x00, 0xE0, 0x02, 0x00, 0x00, 0x00,
+            0x05, 0x06,
+            // SequenceDelimitationItem         VL: 0
+            0xFE, 0xFF, 0xDD, 0xE0, 0x00, 0x00, 0x00, 0x00,
+            // DataSetTrailingPadding   VR: OB  VL: 2
+            0xFC, 0xFF, 0xFC, 0xFF, 0x4F, 0x42, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00,
+            0x00, 0x00,
+        ];
+        let build_parser = || {
+            ParserBuilder::default()
+                .state(ParserState::ReadElement)
+                .dataset_ts(&ExplicitVRLittleEndian)
+                .build(dataset, &STANDARD_DICOM_DICTIONARY)
+        };
+
+        let elements: Vec<DicomElement> = build_parser().collect::<ParseResult<_>>()?;
+        let paths: Vec<(u32, Vec<TagNode>)> = elements
+            .iter()
+            .map(|e| (e.tag(), e.sq_path().iter().map(TagNode::from).collect()))
+            .collect();
+        let fragment = |item: usize| vec![TagNode::new(&PixelData, Some(item))];
+        assert_eq!(
+            vec![
+                (PixelData.tag(), Vec::new()),
+                (Item.tag(), fragment(1)),
+                (Item.tag(), fragment(2)),
+                (Item.tag(), fragment(3)),
+                (SequenceDelimitationItem.tag(), fragment(3)),
+                (DataSetTrailingPadding.tag(), Vec::new()),
+            ],
+            paths
+        );
+
+        let dcmroot = DicomRoot::parse(&mut build_parser())?.expect("parse dataset");
+        assert!(dcmroot.get_child_by_tag(&DataSetTrailingPadding).is_some());
+        let encap = EncapsulatedPixelData::from_dcmroot(&dcmroot)?.expect("encapsulated");
+        assert_eq!(&[0, 12], encap.basic_offset_table());
+        assert_eq!(2, encap.fragments().len());
+        assert_eq!(&[1, 2, 3, 4][..], &*encap.frame(0)?);
+        assert_eq!(&[5, 6][..], &*encap.frame(1)?);
+        Ok(())
+    }
+
+    /// An asynchronous dataset which gives at most `chunk_size` bytes per read, and is pending
+    /// before every read.
+    #[cfg(feature = "async")]
+    struct TrickleDataset {
+        data: Vec<u8>,
+        pos: usize,
+        chunk_size: usize,
+        ready: bool,
+    }
+
+    #[cfg(feature = "async")]
+    impl futures::io::AsyncRead for TrickleDataset {
+        fn poll_read(
+            self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+            buf: &mut [u8],
+        ) -> std::task::Poll<std::io::Result<usize>> {
+            let this = self.get_mut();
+            if !this.ready {
+                this.ready = true;
+                cx.waker().wake_by_ref();
+                return std::task::Poll::Pending;
+            }
+            this.ready = false;
+            let len = buf
+                .len()
+                .min(this.chunk_size)
+                .min(this.data.len() - this.pos);
+            buf[..len].copy_from_slice(&this.data[this.pos..this.pos + len]);
+            this.pos += len;
+            std::task::Poll::Ready(Ok(len))
+        }
+    }
+
+    #[cfg(feature = "async")]
+    fn parse_async<R: futures::io::AsyncRead + Unpin>(
+        dataset: R,
+    ) -> ParseResult<Vec<DicomElement>> {
+        use futures::StreamExt;
+
+        let parser = ParserBuilder::default().build_async(dataset, &STANDARD_DICOM_DICTIONARY);
+        futures::executor::block_on(parser.collect::<Vec<ParseResult<DicomElement>>>())
+            .into_iter()
+            .collect()
+    }
+
+    #[cfg(feature = "async")]
+    fn assert_same_elements(expected: &[DicomElement], actual: &[DicomElement]) {
+        assert_eq!(expected.len(), actual.len());
+        for (expected, actual) in expected.iter().zip(actual) {
+            assert_eq!(expected.tag(), actual.tag());
+            assert_eq!(expected.vr(), actual.vr());
+            assert_eq!(expected.vl(), actual.vl());
+            assert_eq!(expected.ts(), actual.ts());
+            assert_eq!(expected.data(), actual.data());
+            assert_eq!(expected.create_tagpath(), actual.create_tagpath());
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_async_parser() -> ParseResult<()> {
+        let dataset = [
+            STANDARD_HEADER,
+            STUDY_DESCRIPTION_ELEMENT,
+            REFERENCED_STUDY_SEQUENCE_ELEMENT,
+            PRIVATE_ELEMENTS,
+            ENCAPSULATED_PIXEL_DATA_ELEMENT,
+        ]
+        .concat();
+        let expected = ParserBuilder::default()
+            .build(Cursor::new(dataset.clone()), &STANDARD_DICOM_DICTIONARY)
+            .collect::<ParseResult<Vec<DicomElement>>>()?;
+
+        // Reading in small chunks forces elements to be re-parsed as more bytes become available.
+        for chunk_size in [1, 3, 16, 4096] {
+            let actual = parse_async(TrickleDataset {
+                data: dataset.clone(),
+                pos: 0,
+                chunk_size,
+                ready: false,
+            })?;
+            assert_same_elements(&expected, &actual);
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_async_parser_truncated() {
+        let dataset = [STANDARD_HEADER, STUDY_DESCRIPTION_ELEMENT].concat();
+        let truncated = dataset[..dataset.len() - 4].to_vec();
+        let result = parse_async(TrickleDataset {
+            data: truncated,
+            pos: 0,
+            chunk_size: 16,
+            ready: false,
+        });
+        let Err(ParseError::DetailedError { source, .. }) = result else {
+            panic!("expected error parsing truncated dataset");
+        };
+        let ParseError::IOError { source } = *source else {
+            panic!("expected IOError");
+        };
+        assert_eq!(ErrorKind::UnexpectedEof, source.kind());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_tokio_parser() -> ParseResult<()> {
+        use futures::StreamExt;
+
+        let dataset = [
+            STANDARD_HEADER,
+            REFERENCED_STUDY_SEQUENCE_ELEMENT,
+            PIXEL_DATA_ELEMENT,
+        ]
+        .concat();
+        let expected = ParserBuilder::default()
+            .build(Cursor::new(dataset.clone()), &STANDARD_DICOM_DICTIONARY)
+            .collect::<ParseResult<Vec<DicomElement>>>()?;
+
+        let parser =
+            ParserBuilder::default().build_tokio(dataset.as_slice(), &STANDARD_DICOM_DICTIONARY);
+        let actual = futures::executor::block_on(parser.collect::<Vec<_>>())
+            .into_iter()
+            .collect::<ParseResult<Vec<DicomElement>>>()?;
+        assert_same_elements(&expected, &actual);
+        Ok(())
+    }
+
     #[test]
     fn test_parser_state_with_std() -> ParseResult<()> {
         test_parser_state(true)
@@ -298,7 +1590,7 @@ mod parsing_tests {
             .expect("Should have StructureSetTime")
             .element();
         // pull value into local var so it can be typed properly, otherwise it defaults type to &Vec<u8>
-        let ss_time_bytes: &[u8] = ss_time.data().as_ref();
+        let ss_time_bytes: &[u8] = ss_time.data();
         assert_eq!("092108.000".as_bytes(), ss_time_bytes);
 
         // walk the depths of the first sequence to make sure the structure is setup as we expect
@@ -352,7 +1644,7 @@ mod parsing_tests {
             let item_foruid: &DicomObject = item_obj
                 .get_child_by_tag(&FrameofReferenceUID)
                 .expect("Have FORUID");
-            let item_foruid_bytes: &[u8] = item_foruid.element().data().as_ref();
+            let item_foruid_bytes: &[u8] = item_foruid.element().data();
             assert_eq!(
                 "1.2.246.352.91.0000217.20050503182534.1.1\0".as_bytes(),
                 item_foruid_bytes,
@@ -379,14 +1671,14 @@ mod parsing_tests {
                 .get_child_by_tag(&ReferencedSOPClassUID)
                 .expect("Have ref sop class")
                 .element();
-            let ref_sopclass_bytes: &[u8] = ref_sopclass.data().as_ref();
+            let ref_sopclass_bytes: &[u8] = ref_sopclass.data();
             assert_eq!("1.2.840.10008.3.1.2.3.1\0".as_bytes(), ref_sopclass_bytes);
 
             let ref_sopuid: &DicomElement = rtrss_sq_item
                 .get_child_by_tag(&ReferencedSOPInstanceUID)
                 .expect("Have ref sop instance uid")
                 .element();
-            let ref_sopuid_bytes: &[u8] = ref_sopuid.data().as_ref();
+            let ref_sopuid_bytes: &[u8] = ref_sopuid.data();
             assert_eq!(
                 "1.2.246.352.91.0000217.20050503182534\0".as_bytes(),
                 ref_sopuid_bytes,
@@ -405,7 +1697,7 @@ mod parsing_tests {
                 .get_child_by_tag(&SeriesInstanceUID)
                 .expect("Have series uid")
                 .element();
-            let rtref_ser_uid_bytes: &[u8] = rtref_ser_uid.data().as_ref();
+            let rtref_ser_uid_bytes: &[u8] = rtref_ser_uid.data();
             assert_eq!(
                 "1.2.246.352.91.0000217.20050503182534.1\0".as_bytes(),
                 rtref_ser_uid_bytes,
@@ -435,7 +1727,7 @@ mod parsing_tests {
                 .get_child_by_tag(&ReferencedSOPInstanceUID)
                 .expect("Get last item's ref sop uid")
                 .element();
-            let last_sop_uid_bytes: &[u8] = last_sop_uid.data().as_ref();
+            let last_sop_uid_bytes: &[u8] = last_sop_uid.data();
             assert_eq!(
                 "1.2.246.352.91.0000217.20050503182534671465\0".as_bytes(),
                 last_sop_uid_bytes,
@@ -615,7 +1907,7 @@ mod parsing_tests {
         assert_eq!(dcmroot.ts(), sis_elem.ts());
 
         // Manually parse the contents of Source Image Sequence as a sequence.
-        let data: &Vec<u8> = sis_obj.element().data();
+        let data: &[u8] = sis_obj.element().data();
         // Initialize the parser to start with Element rather than file-stuff, specifying IVRLE since
         // the contents _must_ be encoded that way in a sequence.
         let mut parser = ParserBuilder::default()
@@ -20,40 +20,66 @@ mod common;
 mod writing_tests {
     use std::{
         fs::File,
-        io::{BufReader, Read},
+        io::{BufReader, Cursor, Read},
         iter::once,
-        path::PathBuf,
+        path::{Path, PathBuf},
     };
 
     use medicom::{
         core::{
             charset::CSRef,
             dcmelement::DicomElement,
-            dcmobject::DicomRoot,
+            dcmobject::{DicomObject, DicomRoot},
             defn::{
-                constants::ts::{ExplicitVRLittleEndian, ImplicitVRBigEndian},
+                constants::{
+                    lookup::MINIMAL_DICOM_DICTIONARY,
+                    tags::ITEM,
+                    ts::{
+                        ExplicitVRBigEndian, ExplicitVRLittleEndian, ImplicitVRBigEndian,
+                        ImplicitVRLittleEndian,
+                    },
+                },
                 tag::TagPath,
+                ts::TSRef,
                 vl::ValueLength,
                 vr::{
-                    AE, AT, CS, CS_SEPARATOR_BYTE, DS, FD, IS, LO, NULL_PADDING, OB, SH,
-                    SPACE_PADDING, UI, US, UV,
+                    AE, AT, CS, CS_SEPARATOR_BYTE, DS, FD, IS, LO, LT, NULL_PADDING, OB, OW, SH,
+                    SPACE_PADDING, UI, UN, US, UV,
                 },
             },
-            read::{ParserBuilder, ParserState},
-            values::{Attribute, RawValue},
+            diff::{diff, DiffKind},
+            edit::EditError,
+            pixeldata::pdencap::EncapsulatedPixelData,
+            read::{deferred::DeferValues, ParserBuilder, ParserState},
+            values::{
+                age::{AgeUnit, DicomAge},
+                datetime::{DicomDate, DicomDateTime, DicomRange, DicomTime},
+                pn::PersonName,
+                Attribute, RawValue,
+            },
             write::{
                 builder::WriterBuilder,
+                encap::OffsetTable,
                 error::WriteError,
+                file::{write_dcmroot_to_path, WriteFileOptions},
+                recode::recode_to_utf8,
+                sqlength::SequenceLength,
+                transcode::transcode,
                 writer::{Writer, WriterState},
             },
+            MEDICOM_IMPLEMENTATION_CLASS_UID, MEDICOM_IMPLEMENTATION_VERSION_NAME,
         },
         dict::{
             stdlookup::STANDARD_DICOM_DICTIONARY,
             tags::{
-                FileMetaInformationVersion, FrameIncrementPointer, ImplementationClassUID,
-                ImplementationVersionName, MediaStorageSOPClassUID, MediaStorageSOPInstanceUID,
-                ReferencedWaveformChannels, SourceApplicationEntityTitle, SpecificCharacterSet,
-                StudyComments, TransferSyntaxUID,
+                AcquisitionDateTime, FileMetaInformationVersion, FilesetID, FrameIncrementPointer,
+                ImplementationClassUID, ImplementationVersionName, InstitutionName,
+                MediaStorageSOPClassUID, MediaStorageSOPInstanceUID, PatientsAge, PatientsName,
+                PixelData, ReferencedSOPClassUID, ReferencedSOPInstanceUID,
+                ReferencedSOPInstanceUIDinFile, ReferencedSeriesSequence, ReferencedStudySequence,
+                ReferencedWaveformChannels, Rows, SOPClassUID, SOPInstanceUID, SeriesInstanceUID,
+                SourceApplicationEntityTitle, SpecificCharacterSet, StudyComments, StudyDate,
+                StudyDescription, StudyInstanceUID, StudyTime, TransferSyntaxUID,
             },
             transfer_syntaxes::{JPEGBaselineProcess1, RLELossless},
             uids::CTImageStorage,
@@ -62,7 +88,10 @@ mod writing_tests {
 
     use crate::common::{
         common_stddicom::{fixture, get_dicom_file_paths},
-        mockdata,
+        mockdata::{
+            self, ENCAPSULATED_PIXEL_DATA_ELEMENT, PATIENT_ELEMENTS, PIXEL_DATA_ELEMENT,
+            REFERENCED_STUDY_SEQUENCE_ELEMENT, STANDARD_HEADER, STUDY_DESCRIPTION_ELEMENT,
+        },
     };
 
     /// This builds up an in-memory dicom dataset that when written out will result in the same bytes
@@ -351,6 +380,1846 @@ mod writing_tests {
         Ok(())
     }
 
+    /// Values which were deferred during parsing should be loaded from the original dataset when
+    /// written out.
+    #[test]
+    fn test_write_deferred_values() -> Result<(), WriteError> {
+        let dataset = [
+            STANDARD_HEADER,
+            STUDY_DESCRIPTION_ELEMENT,
+            PIXEL_DATA_ELEMENT,
+        ]
+        .concat();
+        let mut parser = ParserBuilder::default()
+            .defer_values(DeferValues::BulkDataOrAboveLength(10))
+            .build_seekable(Cursor::new(dataset.clone()), &STANDARD_DICOM_DICTIONARY);
+        let dcmroot = DicomRoot::parse(&mut parser)?.expect("Parse into DicomRoot");
+        assert!(dcmroot.flatten().iter().any(|e| e.is_deferred()));
+
+        let mut writer: Writer<Vec<u8>> =
+            WriterBuilder::for_file().ts(parser.ts()).build(Vec::new());
+        writer.write_dcmroot(&dcmroot)?;
+        assert_eq!(dataset, writer.into_dataset());
+
+        Ok(())
+    }
+
+    /// Parses elements encoded in the given transfer syntax into a `DicomRoot`, without File Meta.
+    fn parse_elements(dataset: &[u8], ts: TSRef) -> Result<DicomRoot, WriteError> {
+        let mut parser = ParserBuilder::default()
+            .state(ParserState::ReadElement)
+            .dataset_ts(ts)
+            .build(dataset, &STANDARD_DICOM_DICTIONARY);
+        Ok(DicomRoot::parse(&mut parser)?.expect("Parse into DicomRoot"))
+    }
+
+    /// Writes the elements of the `DicomRoot` in its transfer syntax, without File Meta.
+    fn write_elements(dcmroot: &DicomRoot) -> Result<Vec<u8>, WriteError> {
+        let mut writer = WriterBuilder::default()
+            .ts(dcmroot.ts())
+            .state(WriterState::WriteElement)
+            .build(Vec::new());
+        writer.write_dcmroot(dcmroot)?;
+        writer.finish()
+    }
+
+    /// Transcoding through Big Endian and Implicit VR and back to the original transfer syntax
+    /// results in the original bytes.
+    #[test]
+    fn test_transcode_round_trip() -> Result<(), WriteError> {
+        let dataset = [
+            STUDY_DESCRIPTION_ELEMENT,
+            REFERENCED_STUDY_SEQUENCE_ELEMENT,
+            PIXEL_DATA_ELEMENT,
+        ]
+        .concat();
+        let dcmroot = parse_elements(&dataset, &ExplicitVRLittleEndian)?;
+
+        let big_endian = transcode(&dcmroot, &ExplicitVRBigEndian, &STANDARD_DICOM_DICTIONARY)?;
+        assert_eq!(&ExplicitVRBigEndian, big_endian.ts());
+        let pixel_data = big_endian.get_child_by_tag(&PixelData).expect("PixelData");
+        assert_eq!(
+            &[0x00, 0x01, 0x00, 0x02, 0x00, 0x03, 0x00, 0x04],
+            pixel_data.element().data()
+        );
+        let big_endian = parse_elements(&write_elements(&big_endian)?, &ExplicitVRBigEndian)?;
+        assert_eq!(
+            Some(RawValue::Words(vec![1, 2, 3, 4])),
+            big_endian.get_value_by_tag(&PixelData)
+        );
+
+        let implicit = transcode(
+            &big_endian,
+            &ImplicitVRLittleEndian,
+            &STANDARD_DICOM_DICTIONARY,
+        )?;
+        let implicit = parse_elements(&write_elements(&implicit)?, &ImplicitVRLittleEndian)?;
+
+        let explicit = transcode(
+            &implicit,
+            &ExplicitVRLittleEndian,
+            &STANDARD_DICOM_DICTIONARY,
+        )?;
+        assert_eq!(dataset, write_elements(&explicit)?);
+
+        Ok(())
+    }
+
+    /// Sequences with explicit length are changed to undefined length when transcoding between
+    /// Implicit and Explicit VR, as the length of their contents changes.
+    #[test]
+    fn test_transcode_defined_length_sequence() -> Result<(), WriteError> {
+        let dcmroot = parse_elements(
+            mockdata::IMPLICIT_DEFINED_LENGTH_SEQUENCE,
+            &ImplicitVRLittleEndian,
+        )?;
+        let explicit = transcode(
+            &dcmroot,
+            &ExplicitVRLittleEndian,
+            &STANDARD_DICOM_DICTIONARY,
+        )?;
+
+        #[rustfmt::skip]
+        let expected: &[u8] = &[
+            // ReferencedStudySequence  VR: SQ      VL: Undefined
+            0x08, 0x00, 0x10, 0x11, 0x53, 0x51, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF,
+            // Item                     VL: Undefined
+            0xFE, 0xFF, 0x00, 0xE0, 0xFF, 0xFF, 0xFF, 0xFF,
+            // ReferencedSOPClassUID    VR: UI      VL: 6
+            0x08, 0x00, 0x50, 0x11, 0x55, 0x49, 0x06, 0x00,
+            0x31, 0x2E, 0x32, 0x2E, 0x33, 0x00,
+            // ItemDelimitationItem     VL: 0
+            0xFE, 0xFF, 0x0D, 0xE0, 0x00, 0x00, 0x00, 0x00,
+            // SequenceDelimitationItem VL: 0
+            0xFE, 0xFF, 0xDD, 0xE0, 0x00, 0x00, 0x00, 0x00,
+        ];
+        assert_eq!(expected, write_elements(&explicit)?);
+
+        Ok(())
+    }
+
+    /// Writes the elements of the `DicomRoot` in its transfer syntax, without File Meta, using the
+    /// given `SequenceLength` for all sequences except `ReferencedStudySequence`, if given.
+    fn write_elements_with_lengths(
+        dcmroot: &DicomRoot,
+        sequence_length: SequenceLength,
+        ref_study_length: Option<SequenceLength>,
+    ) -> Result<Vec<u8>, WriteError> {
+        let mut builder = WriterBuilder::default()
+            .ts(dcmroot.ts())
+            .state(WriterState::WriteElement)
+            .sequence_length(sequence_length);
+        if let Some(ref_study_length) = ref_study_length {
+            builder = builder.sequence_length_for(&ReferencedStudySequence, ref_study_length);
+        }
+        let mut writer = builder.build(Vec::new());
+        writer.write_dcmroot(dcmroot)?;
+        writer.finish()
+    }
+
+    /// Editing a value within a sequence of explicit length results in the lengths of the item and
+    /// sequence being recomputed when written.
+    #[test]
+    fn test_write_recomputes_sequence_lengths() -> Result<(), WriteError> {
+        let mut dcmroot = parse_elements(
+            mockdata::IMPLICIT_DEFINED_LENGTH_SEQUENCE,
+            &ImplicitVRLittleEndian,
+        )?;
+        let tagpath = TagPath::from(vec![&ReferencedStudySequence, &ReferencedSOPClassUID]);
+        dcmroot
+            .get_child_by_tagpath_mut(&tagpath)
+            .expect("ReferencedSOPClassUID")
+            .element_mut()
+            .encode_val(RawValue::of_uid("1.2.3.4.5"))?;
+
+        #[rustfmt::skip]
+        let expected: &[u8] = &[
+            // ReferencedStudySequence  VR: None    VL: 26
+            0x08, 0x00, 0x10, 0x11, 0x1A, 0x00, 0x00, 0x00,
+            // Item                     VL: 18
+            0xFE, 0xFF, 0x00, 0xE0, 0x12, 0x00, 0x00, 0x00,
+            // ReferencedSOPClassUID    VR: None    VL: 10
+            0x08, 0x00, 0x50, 0x11, 0x0A, 0x00, 0x00, 0x00,
+            0x31, 0x2E, 0x32, 0x2E, 0x33, 0x2E, 0x34, 0x2E, 0x35, 0x00,
+        ];
+        let written = write_elements(&dcmroot)?;
+        assert_eq!(expected, written);
+
+        let reparsed = parse_elements(&written, &ImplicitVRLittleEndian)?;
+        assert_eq!(
+            Some(RawValue::of_uid("1.2.3.4.5")),
+            reparsed.get_value_by_tagpath(&tagpath)
+        );
+
+        Ok(())
+    }
+
+    /// Sequences of undefined length can be written with explicit length, and back again.
+    #[test]
+    fn test_write_sequence_length_explicit_and_undefined() -> Result<(), WriteError> {
+        let dcmroot = parse_elements(REFERENCED_STUDY_SEQUENCE_ELEMENT, &ExplicitVRLittleEndian)?;
+
+        #[rustfmt::skip]
+        let expected: &[u8] = &[
+            // ReferencedStudySequence  VR: SQ      VL: 58
+            0x08, 0x00, 0x10, 0x11, 0x53, 0x51, 0x00, 0x00, 0x3A, 0x00, 0x00, 0x00,
+            // Item                     VL: 28
+            0xFE, 0xFF, 0x00, 0xE0, 0x1C, 0x00, 0x00, 0x00,
+            // ReferencedSOPClassUID    VR: UI      VL: 6
+            0x08, 0x00, 0x50, 0x11, 0x55, 0x49, 0x06, 0x00,
+            0x31, 0x2E, 0x32, 0x2E, 0x33, 0x00,
+            // ReferencedSOPInstanceUID VR: UI      VL: 6
+            0x08, 0x00, 0x55, 0x11, 0x55, 0x49, 0x06, 0x00,
+            0x31, 0x2E, 0x32, 0x2E, 0x34, 0x00,
+            // Item                     VL: 14
+            0xFE, 0xFF, 0x00, 0xE0, 0x0E, 0x00, 0x00, 0x00,
+            // ReferencedSOPInstanceUID VR: UI      VL: 6
+            0x08, 0x00, 0x55, 0x11, 0x55, 0x49, 0x06, 0x00,
+            0x31, 0x2E, 0x32, 0x2E, 0x35, 0x00,
+        ];
+        let explicit = write_elements_with_lengths(&dcmroot, SequenceLength::Explicit, None)?;
+        assert_eq!(expected, explicit);
+
+        let dcmroot = parse_elements(&explicit, &ExplicitVRLittleEndian)?;
+        assert_eq!(expected, write_elements(&dcmroot)?);
+        assert_eq!(
+            REFERENCED_STUDY_SEQUENCE_ELEMENT,
+            write_elements_with_lengths(&dcmroot, SequenceLength::Undefined, None)?
+        );
+
+        Ok(())
+    }
+
+    /// The `SequenceLength` for a specific sequence takes precedence over that for all sequences.
+    #[test]
+    fn test_write_sequence_length_for_tag() -> Result<(), WriteError> {
+        let dcmroot = parse_elements(
+            mockdata::IMPLICIT_DEFINED_LENGTH_SEQUENCE,
+            &ImplicitVRLittleEndian,
+        )?;
+
+        #[rustfmt::skip]
+        let expected: &[u8] = &[
+            // ReferencedStudySequence  VR: None    VL: Undefined
+            0x08, 0x00, 0x10, 0x11, 0xFF, 0xFF, 0xFF, 0xFF,
+            // Item                     VL: Undefined
+            0xFE, 0xFF, 0x00, 0xE0, 0xFF, 0xFF, 0xFF, 0xFF,
+            // ReferencedSOPClassUID    VR: None    VL: 6
+            0x08, 0x00, 0x50, 0x11, 0x06, 0x00, 0x00, 0x00,
+            0x31, 0x2E, 0x32, 0x2E, 0x33, 0x00,
+            // ItemDelimitationItem     VL: 0
+            0xFE, 0xFF, 0x0D, 0xE0, 0x00, 0x00, 0x00, 0x00,
+            // SequenceDelimitationItem VL: 0
+            0xFE, 0xFF, 0xDD, 0xE0, 0x00, 0x00, 0x00, 0x00,
+        ];
+        assert_eq!(
+            expected,
+            write_elements_with_lengths(
+                &dcmroot,
+                SequenceLength::Explicit,
+                Some(SequenceLength::Undefined)
+            )?
+        );
+        assert_eq!(
+            mockdata::IMPLICIT_DEFINED_LENGTH_SEQUENCE,
+            write_elements_with_lengths(
+                &dcmroot,
+                SequenceLength::Undefined,
+                Some(SequenceLength::Preserve)
+            )?
+        );
+
+        Ok(())
+    }
+
+    /// Streaming a value from a reader results in the same bytes as writing the element with its
+    /// value in memory, including padding odd lengths.
+    #[test]
+    fn test_write_element_from() -> Result<(), WriteError> {
+        let mut writer = WriterBuilder::default()
+            .ts(&ExplicitVRLittleEndian)
+            .state(WriterState::WriteElement)
+            .build(Vec::new());
+        writer.write_element_from(&StudyComments, &LT, 3, [0x41u8, 0x42, 0x43].as_slice())?;
+        writer.write_element_from(&PixelData, &OW, 8, [1u8, 0, 2, 0, 3, 0, 4, 0].as_slice())?;
+        assert_eq!(32, writer.bytes_written());
+
+        #[rustfmt::skip]
+        let expected: Vec<u8> = [
+            // StudyComments            VR: LT      VL: 4
+            &[0x32, 0x00, 0x00, 0x40, 0x4C, 0x54, 0x04, 0x00, 0x41, 0x42, 0x43, 0x20],
+            PIXEL_DATA_ELEMENT,
+        ]
+        .concat();
+        assert_eq!(expected, writer.into_dataset());
+
+        let mut writer = WriterBuilder::default()
+            .ts(&ExplicitVRLittleEndian)
+            .state(WriterState::WriteElement)
+            .build(Vec::new());
+        let short = writer.write_element_from(&PixelData, &OW, 8, [1u8, 0].as_slice());
+        assert!(matches!(short, Err(WriteError::DetailedIOError { .. })));
+
+        Ok(())
+    }
+
+    /// Writes two frames of encapsulated pixel data, the first with two fragments with the second
+    /// being of odd length, and the second frame with a single fragment.
+    fn write_encapsulated_frames(offset_table: OffsetTable) -> Result<DicomRoot, WriteError> {
+        let mut writer = WriterBuilder::default()
+            .ts(&JPEGBaselineProcess1)
+            .state(WriterState::WriteElement)
+            .build(Cursor::new(Vec::new()));
+        writer.write_elements(once(&writer.create_element(
+            &Rows,
+            &US,
+            RawValue::of_ushort(1),
+        )?))?;
+
+        let mut encap = writer.begin_encapsulated(offset_table, 2)?;
+        encap.begin_frame()?;
+        encap.write_fragment(&[0xFF, 0xD8, 0x01, 0x02])?;
+        encap.write_fragment_from([0x03u8, 0x04, 0x05].as_slice(), 3)?;
+        encap.write_frame(&[0xFF, 0xD8, 0x06, 0x07])?;
+        encap.finish()?;
+
+        let written = writer.into_dataset().into_inner();
+        parse_elements(&written, &JPEGBaselineProcess1)
+    }
+
+    #[test]
+    fn test_write_encapsulated_basic_offset_table() -> Result<(), WriteError> {
+        let dcmroot = write_encapsulated_frames(OffsetTable::Basic)?;
+        assert_eq!(
+            Some(RawValue::of_ushort(1)),
+            dcmroot.get_value_by_tag(&Rows)
+        );
+
+        let encap = EncapsulatedPixelData::from_dcmroot(&dcmroot)
+            .expect("parse PixelData")
+            .expect("encapsulated");
+        assert_eq!(&[0, 24], encap.basic_offset_table());
+        assert!(encap.extended_offset_table().is_empty());
+        assert_eq!(3, encap.fragments().len());
+        assert_eq!(
+            &[0xFF, 0xD8, 0x01, 0x02, 0x03, 0x04, 0x05, 0x00],
+            encap.frame(0).expect("frame 0").as_ref()
+        );
+        assert_eq!(
+            &[0xFF, 0xD8, 0x06, 0x07],
+            encap.frame(1).expect("frame 1").as_ref()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_encapsulated_extended_offset_table() -> Result<(), WriteError> {
+        let dcmroot = write_encapsulated_frames(OffsetTable::Extended)?;
+
+        let encap = EncapsulatedPixelData::from_dcmroot(&dcmroot)
+            .expect("parse PixelData")
+            .expect("encapsulated");
+        assert!(encap.basic_offset_table().is_empty());
+        assert_eq!(&[0, 24], encap.extended_offset_table());
+        assert_eq!(&[7, 4], encap.extended_offset_table_lengths());
+        assert_eq!(
+            &[0xFF, 0xD8, 0x01, 0x02, 0x03, 0x04, 0x05],
+            encap.frame(0).expect("frame 0").as_ref()
+        );
+        assert_eq!(
+            &[0xFF, 0xD8, 0x06, 0x07],
+            encap.frame(1).expect("frame 1").as_ref()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "compress")]
+    fn test_write_encapsulated_deflated() -> Result<(), WriteError> {
+        use medicom::core::defn::constants::ts::DeflatedExplicitVRLittleEndian;
+
+        let mut writer = WriterBuilder::default()
+            .ts(&DeflatedExplicitVRLittleEndian)
+            .state(WriterState::WriteElement)
+            .build(Cursor::new(Vec::new()));
+        for offset_table in [OffsetTable::Basic, OffsetTable::Extended] {
+            assert!(matches!(
+                writer.begin_encapsulated(offset_table, 1),
+                Err(WriteError::DeflatedOffsetTable(_))
+            ));
+        }
+
+        // Without an offset table nothing needs to be filled in afterwards.
+        let mut encap = writer.begin_encapsulated(OffsetTable::Empty, 1)?;
+        encap.write_frame(&[0xFF, 0xD8])?;
+        encap.finish()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_encapsulated_frame_count_mismatch() -> Result<(), WriteError> {
+        let mut writer = WriterBuilder::default()
+            .ts(&JPEGBaselineProcess1)
+            .state(WriterState::WriteElement)
+            .build(Cursor::new(Vec::new()));
+        let mut encap = writer.begin_encapsulated(OffsetTable::Basic, 2)?;
+        encap.write_frame(&[0xFF, 0xD8])?;
+        assert!(matches!(
+            encap.finish(),
+            Err(WriteError::FrameCountMismatch(2, 1))
+        ));
+
+        let mut encap = writer.begin_encapsulated(OffsetTable::Basic, 1)?;
+        encap.write_frame(&[0xFF, 0xD8])?;
+        assert!(matches!(
+            encap.write_frame(&[0xFF, 0xD8]),
+            Err(WriteError::FrameCountMismatch(1, 2))
+        ));
+
+        Ok(())
+    }
+
+    /// Builds a `DicomRoot` with File Meta in `ExplicitVRLittleEndian`.
+    fn build_file_dcmroot() -> DicomRoot {
+        let mut dcmroot = DicomRoot::new_empty(&ExplicitVRLittleEndian, CSRef::default());
+        dcmroot.add_child_with_val(
+            &MediaStorageSOPClassUID,
+            RawValue::of_uid(CTImageStorage.uid()),
+        );
+        dcmroot.add_child_with_val(
+            &TransferSyntaxUID,
+            RawValue::of_uid(ExplicitVRLittleEndian.uid().uid()),
+        );
+        dcmroot.add_child_with_val(&StudyComments, RawValue::of_string("TRANSCODED"));
+        dcmroot.add_child_with_val(&Rows, RawValue::of_ushort(512));
+        dcmroot
+    }
+
+    /// Transcoding updates the `TransferSyntaxUID` in File Meta, which the parser then uses.
+    #[test]
+    fn test_transcode_file_meta() -> Result<(), WriteError> {
+        let dcmroot = build_file_dcmroot();
+        let implicit = transcode(
+            &dcmroot,
+            &ImplicitVRLittleEndian,
+            &STANDARD_DICOM_DICTIONARY,
+        )?;
+        assert_eq!(
+            Some(RawValue::Uid(
+                ImplicitVRLittleEndian.uid().uid().to_string()
+            )),
+            implicit.get_value_by_tag(&TransferSyntaxUID)
+        );
+
+        let mut writer = WriterBuilder::for_file()
+            .ts(implicit.ts())
+            .build(Vec::new());
+        writer.write_dcmroot(&implicit)?;
+        let bytes = writer.finish()?;
+
+        let mut parser =
+            ParserBuilder::default().build(bytes.as_slice(), &STANDARD_DICOM_DICTIONARY);
+        let parsed = DicomRoot::parse(&mut parser)?.expect("Parse into DicomRoot");
+        assert_eq!(&ImplicitVRLittleEndian, parsed.ts());
+        assert_eq!(
+            Some(RawValue::of_string("TRANSCODED")),
+            parsed.get_value_by_tag(&StudyComments)
+        );
+        assert_eq!(
+            Some(RawValue::of_ushort(512)),
+            parsed.get_value_by_tag(&Rows)
+        );
+
+        Ok(())
+    }
+
+    /// Transcoding to or from a transfer syntax with encapsulated pixel data is not supported.
+    #[test]
+    fn test_transcode_encapsulated() {
+        let dcmroot = build_file_dcmroot();
+        let result = transcode(&dcmroot, &RLELossless, &STANDARD_DICOM_DICTIONARY);
+        assert!(matches!(
+            result,
+            Err(WriteError::UnsupportedTranscode(_, _))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "compress")]
+    fn test_transcode_deflated() -> Result<(), WriteError> {
+        use medicom::core::defn::constants::ts::DeflatedExplicitVRLittleEndian;
+
+        let dcmroot = build_file_dcmroot();
+        let deflated = transcode(
+            &dcmroot,
+            &DeflatedExplicitVRLittleEndian,
+            &STANDARD_DICOM_DICTIONARY,
+        )?;
+
+        let mut writer = WriterBuilder::for_file()
+            .ts(deflated.ts())
+            .build(Vec::new());
+        writer.write_dcmroot(&deflated)?;
+        let bytes = writer.finish()?;
+
+        let mut parser =
+            ParserBuilder::default().build(bytes.as_slice(), &STANDARD_DICOM_DICTIONARY);
+        let parsed = DicomRoot::parse(&mut parser)?.expect("Parse into DicomRoot");
+        assert_eq!(&DeflatedExplicitVRLittleEndian, parsed.ts());
+        assert_eq!(
+            Some(RawValue::of_string("TRANSCODED")),
+            parsed.get_value_by_tag(&StudyComments)
+        );
+        assert_eq!(
+            Some(RawValue::of_ushort(512)),
+            parsed.get_value_by_tag(&Rows)
+        );
+
+        Ok(())
+    }
+
+    /// Text values are re-encoded into UTF-8 from the character set of the dataset, or of the item
+    /// they're in, and `SpecificCharacterSet` is updated along with the lengths of sequences.
+    #[test]
+    fn test_recode_to_utf8() -> Result<(), WriteError> {
+        #[rustfmt::skip]
+        let dataset: &[u8] = &[
+            // SpecificCharacterSet     VR: CS      VL: 10      "ISO_IR 100"
+            0x08, 0x00, 0x05, 0x00, 0x43, 0x53, 0x0A, 0x00,
+            0x49, 0x53, 0x4F, 0x5F, 0x49, 0x52, 0x20, 0x31, 0x30, 0x30,
+            // InstitutionName          VR: LO      VL: 6       "Zürich"
+            0x08, 0x00, 0x80, 0x00, 0x4C, 0x4F, 0x06, 0x00,
+            0x5A, 0xFC, 0x72, 0x69, 0x63, 0x68,
+            // ReferencedStudySequence  VR: SQ      VL: 38
+            0x08, 0x00, 0x10, 0x11, 0x53, 0x51, 0x00, 0x00, 0x26, 0x00, 0x00, 0x00,
+            // Item                     VL: 30
+            0xFE, 0xFF, 0x00, 0xE0, 0x1E, 0x00, 0x00, 0x00,
+            // SpecificCharacterSet     VR: CS      VL: 10      "ISO_IR 144"
+            0x08, 0x00, 0x05, 0x00, 0x43, 0x53, 0x0A, 0x00,
+            0x49, 0x53, 0x4F, 0x5F, 0x49, 0x52, 0x20, 0x31, 0x34, 0x34,
+            // StudyDescription         VR: LO      VL: 4       "Люк "
+            0x08, 0x00, 0x30, 0x10, 0x4C, 0x4F, 0x04, 0x00,
+            0xBB, 0xEE, 0xDA, 0x20,
+            // PatientsName             VR: PN      VL: 10      "Buc^Jérôme"
+            0x10, 0x00, 0x10, 0x00, 0x50, 0x4E, 0x0A, 0x00,
+            0x42, 0x75, 0x63, 0x5E, 0x4A, 0xE9, 0x72, 0xF4, 0x6D, 0x65,
+        ];
+        let dcmroot = parse_elements(dataset, &ExplicitVRLittleEndian)?;
+
+        let recoded = recode_to_utf8(&dcmroot)?;
+        assert!(recoded.is_lossless());
+        assert_eq!("UTF-8", recoded.dcmroot().cs().name());
+
+        let parsed = parse_elements(&write_elements(recoded.dcmroot())?, &ExplicitVRLittleEndian)?;
+        assert_eq!("UTF-8", parsed.cs().name());
+        assert_eq!(
+            Some(RawValue::of_string("ISO_IR 192")),
+            parsed.get_value_by_tag(&SpecificCharacterSet)
+        );
+        assert_eq!(
+            Some(RawValue::of_string("Zürich")),
+            parsed.get_value_by_tag(&InstitutionName)
+        );
+        assert_eq!(
+            Some(RawValue::of_string("Buc^Jérôme")),
+            parsed.get_value_by_tag(&PatientsName)
+        );
+
+        let item = parsed
+            .get_child_by_tag(&ReferencedStudySequence)
+            .and_then(|sq| sq.get_item_by_index(1))
+            .expect("ReferencedStudySequence item");
+        assert_eq!(
+            Some(RawValue::of_string("ISO_IR 192")),
+            item.get_value_by_tag(&SpecificCharacterSet)
+        );
+        assert_eq!(
+            Some(RawValue::of_string("Люк")),
+            item.get_value_by_tag(&StudyDescription)
+        );
+
+        Ok(())
+    }
+
+    /// Values which can't be decoded losslessly are reported, and `SpecificCharacterSet` is added
+    /// if the dataset didn't have one.
+    #[test]
+    fn test_recode_to_utf8_lossy() -> Result<(), WriteError> {
+        #[rustfmt::skip]
+        let dataset: &[u8] = &[
+            // SpecificCharacterSet     VR: CS      VL: 8       "GB18030 "
+            0x08, 0x00, 0x05, 0x00, 0x43, 0x53, 0x08, 0x00,
+            0x47, 0x42, 0x31, 0x38, 0x30, 0x33, 0x30, 0x20,
+            // StudyDescription         VR: LO      VL: 2       "王"
+            0x08, 0x00, 0x30, 0x10, 0x4C, 0x4F, 0x02, 0x00,
+            0xCD, 0xF5,
+            // PatientsName             VR: PN      VL: 6       "Wang^" followed by an invalid byte
+            0x10, 0x00, 0x10, 0x00, 0x50, 0x4E, 0x06, 0x00,
+            0x57, 0x61, 0x6E, 0x67, 0x5E, 0xFF,
+        ];
+        let dcmroot = parse_elements(dataset, &ExplicitVRLittleEndian)?;
+
+        let recoded = recode_to_utf8(&dcmroot)?;
+        assert!(!recoded.is_lossless());
+        let lossy = recoded.lossy_values();
+        assert_eq!(1, lossy.len());
+        assert_eq!(&TagPath::from(vec![&PatientsName]), lossy[0].path());
+        assert_eq!("gb18030", lossy[0].cs().name());
+        assert_eq!("Wang^\u{FFFD}", lossy[0].text());
+
+        let parsed = parse_elements(&write_elements(recoded.dcmroot())?, &ExplicitVRLittleEndian)?;
+        assert_eq!(
+            Some(RawValue::of_string("王")),
+            parsed.get_value_by_tag(&StudyDescription)
+        );
+        assert_eq!(
+            Some(RawValue::of_string("Wang^\u{FFFD}")),
+            parsed.get_value_by_tag(&PatientsName)
+        );
+
+        let without_scs = parse_elements(&dataset[16..26], &ExplicitVRLittleEndian)?;
+        let recoded = recode_to_utf8(&without_scs)?;
+        assert!(recoded.is_lossless());
+        assert_eq!(
+            Some(RawValue::of_string("ISO_IR 192")),
+            recoded.dcmroot().get_value_by_tag(&SpecificCharacterSet)
+        );
+
+        Ok(())
+    }
+
+    /// Builds a `DicomRoot` without File Meta, other than a stale `TransferSyntaxUID`.
+    fn build_dcmroot_for_file_meta() -> DicomRoot {
+        let mut dcmroot = DicomRoot::new_empty(&ExplicitVRLittleEndian, CSRef::default());
+        dcmroot.add_child_with_val(
+            &TransferSyntaxUID,
+            RawValue::of_uid(RLELossless.uid().uid()),
+        );
+        dcmroot.add_child_with_val(&SOPClassUID, RawValue::of_uid(CTImageStorage.uid()));
+        dcmroot.add_child_with_val(&SOPInstanceUID, RawValue::of_uid("1.2.3.4"));
+        dcmroot.add_child_with_val(&StudyComments, RawValue::of_string("GENERATED"));
+        dcmroot
+    }
+
+    /// The `Writer` generates a complete File Meta group from the dataset and its settings.
+    #[test]
+    fn test_generate_file_meta() -> Result<(), WriteError> {
+        let dcmroot = build_dcmroot_for_file_meta();
+        let mut writer = WriterBuilder::for_file()
+            .ts(&ImplicitVRLittleEndian)
+            .generate_file_meta(true)
+            .source_ae_title("MEDICOM_SCP")
+            .build(Vec::new());
+        writer.write_dcmroot(&dcmroot)?;
+        let bytes = writer.finish()?;
+
+        let mut parser =
+            ParserBuilder::default().build(bytes.as_slice(), &STANDARD_DICOM_DICTIONARY);
+        let parsed = DicomRoot::parse(&mut parser)?.expect("Parse into DicomRoot");
+        assert_eq!(&ImplicitVRLittleEndian, parsed.ts());
+        assert_eq!(
+            Some([0x00, 0x01].as_slice()),
+            parsed
+                .get_child_by_tag(&FileMetaInformationVersion)
+                .map(|o| o.element().data())
+        );
+        assert_eq!(
+            Some(RawValue::of_uid(CTImageStorage.uid())),
+            parsed.get_value_by_tag(&MediaStorageSOPClassUID)
+        );
+        assert_eq!(
+            Some(RawValue::of_uid("1.2.3.4")),
+            parsed.get_value_by_tag(&MediaStorageSOPInstanceUID)
+        );
+        assert_eq!(
+            Some(RawValue::of_uid(ImplicitVRLittleEndian.uid().uid())),
+            parsed.get_value_by_tag(&TransferSyntaxUID)
+        );
+        assert_eq!(
+            Some(RawValue::of_uid(MEDICOM_IMPLEMENTATION_CLASS_UID)),
+            parsed.get_value_by_tag(&ImplementationClassUID)
+        );
+        assert_eq!(
+            Some(RawValue::of_string(MEDICOM_IMPLEMENTATION_VERSION_NAME)),
+            parsed.get_value_by_tag(&ImplementationVersionName)
+        );
+        assert_eq!(
+            Some(RawValue::of_string("MEDICOM_SCP")),
+            parsed.get_value_by_tag(&SourceApplicationEntityTitle)
+        );
+        assert_eq!(
+            Some(RawValue::of_string("GENERATED")),
+            parsed.get_value_by_tag(&StudyComments)
+        );
+
+        Ok(())
+    }
+
+    /// Generating File Meta requires the SOP Class and Instance UIDs.
+    #[test]
+    fn test_generate_file_meta_missing_uids() {
+        let mut dcmroot = DicomRoot::new_empty(&ExplicitVRLittleEndian, CSRef::default());
+        dcmroot.add_child_with_val(&SOPClassUID, RawValue::of_uid(CTImageStorage.uid()));
+        dcmroot.add_child_with_val(&StudyComments, RawValue::of_string("GENERATED"));
+
+        let mut writer = WriterBuilder::for_file()
+            .generate_file_meta(true)
+            .build(Vec::new());
+        let result = writer.write_dcmroot(&dcmroot);
+        assert!(matches!(result, Err(WriteError::IncompleteFileMeta(_))));
+    }
+
+    /// Creates an empty directory for a test to write files into.
+    fn test_output_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("medicom-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("create test output directory");
+        dir
+    }
+
+    /// Lists the names of files in the directory.
+    fn list_dir(dir: &PathBuf) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .expect("read test output directory")
+            .map(|entry| {
+                entry
+                    .expect("dir entry")
+                    .file_name()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Writing to a path results in only the complete destination file, which parses back.
+    #[test]
+    fn test_write_dcmroot_to_path() -> Result<(), WriteError> {
+        let dir = test_output_dir("write-to-path");
+        let path = dir.join("1.2.3.4.dcm");
+        let dcmroot = build_dcmroot_for_file_meta();
+        let options = WriteFileOptions::default()
+            .writer(
+                WriterBuilder::for_file()
+                    .ts(&ExplicitVRLittleEndian)
+                    .generate_file_meta(true),
+            )
+            .verify_with(&STANDARD_DICOM_DICTIONARY);
+
+        let bytes_written = write_dcmroot_to_path(&dcmroot, &path, &options)?;
+        assert_eq!(vec!["1.2.3.4.dcm".to_string()], list_dir(&dir));
+        assert_eq!(std::fs::metadata(&path)?.len(), bytes_written);
+
+        let mut parser = ParserBuilder::default().build(
+            BufReader::new(File::open(&path)?),
+            &STANDARD_DICOM_DICTIONARY,
+        );
+        let written = DicomRoot::parse(&mut parser)?.expect("parse written file");
+        assert_eq!(
+            Some(RawValue::of_string("GENERATED")),
+            written.get_value_by_tag(&StudyComments)
+        );
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    /// The size returned for a deflated transfer syntax is that of the compressed file.
+    #[test]
+    #[cfg(feature = "compress")]
+    fn test_write_dcmroot_to_path_deflated() -> Result<(), WriteError> {
+        use medicom::core::defn::constants::ts::DeflatedExplicitVRLittleEndian;
+
+        let dir = test_output_dir("write-to-path-deflated");
+        let path = dir.join("1.2.3.4.dcm");
+        let options = WriteFileOptions::default()
+            .writer(
+                WriterBuilder::for_file()
+                    .ts(&DeflatedExplicitVRLittleEndian)
+                    .generate_file_meta(true),
+            )
+            .verify_with(&STANDARD_DICOM_DICTIONARY);
+
+        let bytes_written = write_dcmroot_to_path(&build_dcmroot_for_file_meta(), &path, &options)?;
+        assert_eq!(std::fs::metadata(&path)?.len(), bytes_written);
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    /// A file which fails verification is not moved into place, leaving an existing file as-is,
+    /// and the temporary file is removed.
+    #[test]
+    fn test_write_dcmroot_to_path_verify_failure() -> Result<(), WriteError> {
+        let dir = test_output_dir("write-to-path-verify");
+        let path = dir.join("1.2.3.4.dcm");
+        let dcmroot = build_dcmroot_for_file_meta();
+        let options = WriteFileOptions::default().writer(
+            WriterBuilder::for_file()
+                .ts(&ExplicitVRLittleEndian)
+                .generate_file_meta(true),
+        );
+        write_dcmroot_to_path(&dcmroot, &path, &options)?;
+        let original: Vec<u8> = std::fs::read(&path)?;
+
+        // The minimal dictionary is unable to resolve the transfer syntax.
+        let options = WriteFileOptions::default()
+            .writer(
+                WriterBuilder::for_file()
+                    .ts(&JPEGBaselineProcess1)
+                    .generate_file_meta(true),
+            )
+            .verify_with(&MINIMAL_DICOM_DICTIONARY);
+        let result = write_dcmroot_to_path(&dcmroot, &path, &options);
+        assert!(matches!(result, Err(WriteError::VerificationFailed(_))));
+        assert_eq!(vec!["1.2.3.4.dcm".to_string()], list_dir(&dir));
+        assert_eq!(original, std::fs::read(&path)?);
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    /// Writes an instance with the given patient, study, series, and SOP Instance UID to the path
+    /// within the directory.
+    fn write_dicomdir_instance(dir: &Path, path: &str, uids: [&str; 4]) -> Result<(), WriteError> {
+        use medicom::dict::tags::{Modality, PatientID};
+
+        let [patient_id, study_uid, series_uid, sop_uid] = uids;
+        let mut dcmroot = DicomRoot::new_empty(&ExplicitVRLittleEndian, CSRef::default());
+        dcmroot.add_child_with_val(&SOPClassUID, RawValue::of_uid(CTImageStorage.uid()));
+        dcmroot.add_child_with_val(&SOPInstanceUID, RawValue::of_uid(sop_uid));
+        dcmroot.add_child_with_val(&PatientID, RawValue::of_string(patient_id));
+        dcmroot.add_child_with_val(&PatientsName, RawValue::of_string("DOE^JOHN"));
+        dcmroot.add_child_with_val(&StudyInstanceUID, RawValue::of_uid(study_uid));
+        dcmroot.add_child_with_val(&SeriesInstanceUID, RawValue::of_uid(series_uid));
+        dcmroot.add_child_with_val(&Modality, RawValue::of_string("CT"));
+
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().expect("parent"))?;
+        let options = WriteFileOptions::default().writer(
+            WriterBuilder::for_file()
+                .ts(&ExplicitVRLittleEndian)
+                .generate_file_meta(true),
+        );
+        write_dcmroot_to_path(&dcmroot, &path, &options)?;
+        Ok(())
+    }
+
+    /// A generated DICOMDIR has a record for each patient, study, series, and instance, linked by
+    /// offsets which refer to the items of the records within the written file.
+    #[test]
+    fn test_dicomdir_build_and_open() -> Result<(), WriteError> {
+        use medicom::core::dicomdir::{DicomDir, DicomDirBuilder, DicomDirError};
+
+        let dir = test_output_dir("dicomdir");
+        write_dicomdir_instance(&dir, "P1/IM1", ["P1", "1.1", "1.1.1", "1.1.1.1"])?;
+        write_dicomdir_instance(&dir, "P1/IM2", ["P1", "1.1", "1.1.1", "1.1.1.2"])?;
+        write_dicomdir_instance(&dir, "P1/IM3", ["P1", "1.1", "1.1.2", "1.1.2.1"])?;
+        write_dicomdir_instance(&dir, "P2/IM1", ["P2", "2.1", "2.1.1", "2.1.1.1"])?;
+        std::fs::write(dir.join("README"), b"not dicom")?;
+
+        let path = DicomDirBuilder::default()
+            .file_set_id("TESTSET")
+            .write(&dir, &STANDARD_DICOM_DICTIONARY)
+            .expect("write DICOMDIR");
+        let dicomdir = DicomDir::open(&path, &STANDARD_DICOM_DICTIONARY).expect("open DICOMDIR");
+        assert_eq!(
+            Some(RawValue::of_string("TESTSET")),
+            dicomdir.dcmroot().get_value_by_tag(&FilesetID)
+        );
+
+        let records: Vec<(usize, String)> = dicomdir
+            .iter()
+            .map(|(depth, record)| (depth, record.record_type().to_string()))
+            .collect();
+        let expected: Vec<(usize, &str)> = vec![
+            (0, "PATIENT"),
+            (1, "STUDY"),
+            (2, "SERIES"),
+            (3, "IMAGE"),
+            (3, "IMAGE"),
+            (2, "SERIES"),
+            (3, "IMAGE"),
+            (0, "PATIENT"),
+            (1, "STUDY"),
+            (2, "SERIES"),
+            (3, "IMAGE"),
+        ];
+        assert_eq!(
+            expected,
+            records
+                .iter()
+                .map(|(depth, record_type)| (*depth, record_type.as_str()))
+                .collect::<Vec<(usize, &str)>>()
+        );
+
+        // Each record's offset is that of its item in the file.
+        let bytes: Vec<u8> = std::fs::read(&path)?;
+        for (_depth, record) in dicomdir.iter() {
+            let offset = record.offset() as usize;
+            assert_eq!(&[0xFE, 0xFF, 0x00, 0xE0], &bytes[offset..offset + 4]);
+            assert!(record.is_in_use());
+        }
+
+        let images: Vec<PathBuf> = dicomdir
+            .iter()
+            .filter_map(|(_depth, record)| dicomdir.file_path(record))
+            .collect();
+        assert_eq!(
+            vec![
+                dir.join("P1").join("IM1"),
+                dir.join("P1").join("IM2"),
+                dir.join("P1").join("IM3"),
+                dir.join("P2").join("IM1"),
+            ],
+            images
+        );
+        let image = &dicomdir.records()[0].children()[0].children()[0].children()[1];
+        assert_eq!(&["P1".to_string(), "IM2".to_string()], image.file_id());
+        assert_eq!(
+            Some(RawValue::of_uid("1.1.1.2")),
+            image
+                .item()
+                .get_value_by_tag(&ReferencedSOPInstanceUIDinFile)
+        );
+
+        // Regenerating skips the existing DICOMDIR, while non-conforming file names are an error.
+        DicomDirBuilder::default()
+            .write(&dir, &STANDARD_DICOM_DICTIONARY)
+            .expect("rewrite DICOMDIR");
+        write_dicomdir_instance(&dir, "P2/image.dcm", ["P2", "2.1", "2.1.1", "2.1.1.2"])?;
+        let result = DicomDirBuilder::default().build(&dir, &STANDARD_DICOM_DICTIONARY);
+        assert!(matches!(result, Err(DicomDirError::InvalidFileId { .. })));
+        std::fs::remove_file(dir.join("P2").join("image.dcm"))?;
+
+        // Files which are DICOM but fail to parse are an error rather than left out.
+        let truncated: PathBuf = dir.join("P2").join("IM2");
+        let bytes: Vec<u8> = std::fs::read(dir.join("P2").join("IM1"))?;
+        std::fs::write(&truncated, &bytes[..bytes.len() / 2])?;
+        let result = DicomDirBuilder::default().build(&dir, &STANDARD_DICOM_DICTIONARY);
+        assert!(
+            matches!(result, Err(DicomDirError::InvalidFile { path, .. }) if path == truncated)
+        );
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    /// Encodes an explicit VR little endian element with a 2-byte value length.
+    fn explicit_element(tag: u32, vr: &str, value: &[u8]) -> Vec<u8> {
+        let length = u16::try_from(value.len()).expect("short value");
+        let tag: [u8; 4] = tag.to_le_bytes();
+        [
+            &tag[2..],
+            &tag[..2],
+            vr.as_bytes(),
+            &length.to_le_bytes(),
+            value,
+        ]
+        .concat()
+    }
+
+    /// Encodes a directory record as an undefined length item.
+    fn undefined_length_record(next: u32, lower: u32, record_type: &str, file_id: &str) -> Vec<u8> {
+        use medicom::dict::tags::{
+            DirectoryRecordType, OffsetofReferencedLowerLevelDirectoryEntity,
+            OffsetoftheNextDirectoryRecord, RecordInuseFlag, ReferencedFileID,
+        };
+
+        let mut bytes: Vec<u8> = [
+            &[0xFE, 0xFF, 0x00, 0xE0, 0xFF, 0xFF, 0xFF, 0xFF][..],
+            &explicit_element(
+                OffsetoftheNextDirectoryRecord.tag(),
+                "UL",
+                &next.to_le_bytes(),
+            ),
+            &explicit_element(RecordInuseFlag.tag(), "US", &0xFFFFu16.to_le_bytes()),
+            &explicit_element(
+                OffsetofReferencedLowerLevelDirectoryEntity.tag(),
+                "UL",
+                &lower.to_le_bytes(),
+            ),
+            &explicit_element(DirectoryRecordType.tag(), "CS", record_type.as_bytes()),
+        ]
+        .concat();
+        if !file_id.is_empty() {
+            bytes.extend(explicit_element(
+                ReferencedFileID.tag(),
+                "CS",
+                file_id.as_bytes(),
+            ));
+        }
+        // Item Delimitation Item
+        bytes.extend([0xFE, 0xFF, 0x0D, 0xE0, 0, 0, 0, 0]);
+        bytes
+    }
+
+    /// Encodes a DICOMDIR up to the first item of its undefined length `DirectoryRecordSequence`.
+    /// The File Meta has no group length.
+    fn undefined_length_dicomdir_header(first: u32) -> Vec<u8> {
+        use medicom::dict::tags::{
+            DirectoryRecordSequence, OffsetoftheFirstDirectoryRecordoftheRootDirectoryEntity,
+            OffsetoftheLastDirectoryRecordoftheRootDirectoryEntity,
+        };
+
+        [
+            &[0u8; 128][..],
+            b"DICM",
+            &explicit_element(MediaStorageSOPClassUID.tag(), "UI", b"1.2.840.10008.1.3.10"),
+            &explicit_element(MediaStorageSOPInstanceUID.tag(), "UI", b"1.2.3.4\0"),
+            &explicit_element(TransferSyntaxUID.tag(), "UI", b"1.2.840.10008.1.2.1\0"),
+            &explicit_element(
+                OffsetoftheFirstDirectoryRecordoftheRootDirectoryEntity.tag(),
+                "UL",
+                &first.to_le_bytes(),
+            ),
+            &explicit_element(
+                OffsetoftheLastDirectoryRecordoftheRootDirectoryEntity.tag(),
+                "UL",
+                &first.to_le_bytes(),
+            ),
+            &explicit_element(DirectoryRecordSequence.tag(), "SQ", &[])[..6],
+            &[0, 0, 0xFF, 0xFF, 0xFF, 0xFF],
+        ]
+        .concat()
+    }
+
+    /// A DICOMDIR encoded differently from the writer, with no File Meta group length, undefined
+    /// length sequence and items, and records in an order other than their hierarchy, resolves
+    /// records by their parsed positions.
+    #[test]
+    fn test_dicomdir_parse_undefined_lengths() {
+        use medicom::core::dicomdir::{DicomDir, DicomDirError};
+
+        let record = undefined_length_record;
+        let header = undefined_length_dicomdir_header;
+
+        // The records in order are the second image, the patient, then the first image.
+        let offset = |bytes: &[u8]| u32::try_from(bytes.len()).expect("offset");
+        let image2: u32 = offset(&header(0));
+        let patient: u32 = image2 + offset(&record(0, 0, "IMAGE ", "IM2 "));
+        let image1: u32 = patient + offset(&record(0, 0, "PATIENT ", ""));
+        let bytes: Vec<u8> = [
+            header(patient),
+            record(0, 0, "IMAGE ", "IM2 "),
+            record(0, image1, "PATIENT ", ""),
+            record(image2, 0, "IMAGE ", "IM1 "),
+            // Sequence Delimitation Item
+            vec![0xFE, 0xFF, 0xDD, 0xE0, 0, 0, 0, 0],
+        ]
+        .concat();
+
+        let mut parser =
+            ParserBuilder::default().build(bytes.as_slice(), &STANDARD_DICOM_DICTIONARY);
+        let dicomdir =
+            DicomDir::parse(&mut parser, PathBuf::from("fileset")).expect("parse DICOMDIR");
+        let records: Vec<(usize, u32, &str, Vec<String>)> = dicomdir
+            .iter()
+            .map(|(depth, record)| {
+                (
+                    depth,
+                    record.offset(),
+                    record.record_type(),
+                    record.file_id().to_vec(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (0, patient, "PATIENT", Vec::new()),
+                (1, image1, "IMAGE", vec!["IM1".to_string()]),
+                (1, image2, "IMAGE", vec!["IM2".to_string()]),
+            ],
+            records
+        );
+
+        // Offsets computed for the writer's encoding don't match this file.
+        assert!(matches!(
+            DicomDir::from_root(dicomdir.dcmroot().clone(), PathBuf::from("fileset")),
+            Err(DicomDirError::InvalidOffset(_))
+        ));
+    }
+
+    /// Records from untrusted media can't refer to files outside of the File-set, and a deep chain
+    /// of lower-level records is resolved without exhausting the stack.
+    #[test]
+    fn test_dicomdir_untrusted_records() {
+        use medicom::core::dicomdir::DicomDir;
+
+        let record = undefined_length_record;
+        let header = undefined_length_dicomdir_header;
+        let parse = |bytes: &[u8]| {
+            let mut parser = ParserBuilder::default().build(bytes, &STANDARD_DICOM_DICTIONARY);
+            DicomDir::parse(&mut parser, PathBuf::from("fileset")).expect("parse DICOMDIR")
+        };
+
+        let file_ids = ["IM1 ", "..\\..\\ETC", "/ETC", "SUB\\../IM2 "];
+        let mut offset: u32 = u32::try_from(header(0).len()).expect("offset");
+        let mut records: Vec<Vec<u8>> = Vec::new();
+        for (index, file_id) in file_ids.iter().enumerate() {
+            let length = u32::try_from(record(0, 0, "IMAGE ", file_id).len()).expect("offset");
+            let next: u32 = if index + 1 < file_ids.len() {
+                offset + length
+            } else {
+                0
+            };
+            records.push(record(next, 0, "IMAGE ", file_id));
+            offset += length;
+        }
+        let first: u32 = u32::try_from(header(0).len()).expect("offset");
+        let bytes: Vec<u8> = [
+            header(first),
+            records.concat(),
+            vec![0xFE, 0xFF, 0xDD, 0xE0, 0, 0, 0, 0],
+        ]
+        .concat();
+        let dicomdir = parse(&bytes);
+        let paths: Vec<Option<PathBuf>> = dicomdir
+            .records()
+            .iter()
+            .map(|record| dicomdir.file_path(record))
+            .collect();
+        assert_eq!(
+            vec![Some(PathBuf::from("fileset").join("IM1")), None, None, None],
+            paths
+        );
+
+        // Each record is the only record of the level below the previous one.
+        let depth: u32 = 10_000;
+        let length = u32::try_from(record(0, 0, "IMAGE ", "").len()).expect("offset");
+        let mut bytes: Vec<u8> = header(first);
+        for index in 0..depth {
+            let lower: u32 = if index + 1 < depth {
+                first + (index + 1) * length
+            } else {
+                0
+            };
+            bytes.extend(record(0, lower, "IMAGE ", ""));
+        }
+        bytes.extend([0xFE, 0xFF, 0xDD, 0xE0, 0, 0, 0, 0]);
+        let dicomdir = parse(&bytes);
+        assert_eq!(depth as usize, dicomdir.iter().count());
+        assert_eq!(
+            Some(depth as usize - 1),
+            dicomdir.iter().map(|(depth, _record)| depth).max()
+        );
+    }
+
+    /// The `ReferencedStudySequence` mock with the value of the second item's
+    /// `ReferencedSOPInstanceUID` changed, or with only the first item.
+    fn edited_referenced_study_sequence(remove_second_item: bool) -> Vec<u8> {
+        let mut sequence: Vec<u8> = REFERENCED_STUDY_SEQUENCE_ELEMENT.to_vec();
+        if remove_second_item {
+            sequence.drain(56..86);
+        } else {
+            sequence[76] = b'6';
+        }
+        sequence
+    }
+
+    /// The same dataset in different transfer syntaxes has no differences.
+    #[test]
+    fn test_diff_transcoded_is_empty() -> Result<(), WriteError> {
+        let dataset = [
+            STUDY_DESCRIPTION_ELEMENT,
+            REFERENCED_STUDY_SEQUENCE_ELEMENT,
+            PIXEL_DATA_ELEMENT,
+        ]
+        .concat();
+        let dcmroot = parse_elements(&dataset, &ExplicitVRLittleEndian)?;
+        let implicit = transcode(
+            &dcmroot,
+            &ImplicitVRLittleEndian,
+            &STANDARD_DICOM_DICTIONARY,
+        )?;
+        let implicit = parse_elements(&write_elements(&implicit)?, &ImplicitVRLittleEndian)?;
+
+        assert!(diff(&dcmroot, &implicit).is_empty());
+        Ok(())
+    }
+
+    /// Added, removed, and changed elements are found within sequences, and applying the patch to
+    /// the original results in the same dataset as the edited one.
+    #[test]
+    fn test_diff_and_patch_elements() -> Result<(), WriteError> {
+        let old_dataset = [
+            STUDY_DESCRIPTION_ELEMENT,
+            REFERENCED_STUDY_SEQUENCE_ELEMENT,
+            PIXEL_DATA_ELEMENT,
+        ]
+        .concat();
+        let new_dataset = [
+            edited_referenced_study_sequence(false).as_slice(),
+            PATIENT_ELEMENTS,
+            PIXEL_DATA_ELEMENT,
+        ]
+        .concat();
+        let old = parse_elements(&old_dataset, &ExplicitVRLittleEndian)?;
+        let new = parse_elements(&new_dataset, &ExplicitVRLittleEndian)?;
+
+        let changes = diff(&old, &new);
+        let summary: Vec<(String, DiffKind)> = changes
+            .entries()
+            .iter()
+            .map(|entry| {
+                let path = TagPath::format_tagpath_to_display(
+                    entry.path(),
+                    Some(&STANDARD_DICOM_DICTIONARY),
+                );
+                (path, entry.kind())
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("StudyDescription".to_string(), DiffKind::Removed),
+                (
+                    "ReferencedStudySequence[2].ReferencedSOPInstanceUID".to_string(),
+                    DiffKind::Changed
+                ),
+                ("PatientsName".to_string(), DiffKind::Added),
+                ("PatientID".to_string(), DiffKind::Added),
+            ],
+            summary
+        );
+        let uid_entry = &changes.entries()[1];
+        assert_eq!(Some(RawValue::of_uid("1.2.5")), uid_entry.old_value());
+        assert_eq!(Some(RawValue::of_uid("1.2.6")), uid_entry.new_value());
+
+        let mut patched = parse_elements(&old_dataset, &ExplicitVRLittleEndian)?;
+        changes
+            .to_patch()
+            .apply(&mut patched, &STANDARD_DICOM_DICTIONARY)
+            .expect("apply patch");
+        assert!(diff(&patched, &new).is_empty());
+        assert_eq!(new_dataset, write_elements(&patched)?);
+
+        Ok(())
+    }
+
+    /// Items added to or removed from a sequence are patched by inserting or removing the items.
+    #[test]
+    fn test_diff_and_patch_items() -> Result<(), WriteError> {
+        let two_items = parse_elements(REFERENCED_STUDY_SEQUENCE_ELEMENT, &ExplicitVRLittleEndian)?;
+        let one_item_dataset = edited_referenced_study_sequence(true);
+        let one_item = parse_elements(&one_item_dataset, &ExplicitVRLittleEndian)?;
+
+        let removed = diff(&two_items, &one_item);
+        assert_eq!(1, removed.entries().len());
+        let entry = &removed.entries()[0];
+        assert_eq!(DiffKind::Removed, entry.kind());
+        assert!(entry.is_item());
+        assert_eq!(
+            "ReferencedStudySequence[2]",
+            TagPath::format_tagpath_to_display(entry.path(), Some(&STANDARD_DICOM_DICTIONARY))
+        );
+
+        let mut patched =
+            parse_elements(REFERENCED_STUDY_SEQUENCE_ELEMENT, &ExplicitVRLittleEndian)?;
+        removed
+            .to_patch()
+            .apply(&mut patched, &STANDARD_DICOM_DICTIONARY)
+            .expect("apply patch");
+        assert_eq!(one_item_dataset, write_elements(&patched)?);
+
+        let added = diff(&one_item, &two_items);
+        assert_eq!(1, added.entries().len());
+        assert_eq!(DiffKind::Added, added.entries()[0].kind());
+        added
+            .to_patch()
+            .apply(&mut patched, &STANDARD_DICOM_DICTIONARY)
+            .expect("apply patch");
+        assert_eq!(REFERENCED_STUDY_SEQUENCE_ELEMENT, write_elements(&patched)?);
+
+        Ok(())
+    }
+
+    /// Patches created from datasets in one transfer syntax are re-encoded when applied to a dataset
+    /// in another, which is then written and parsed again the same as the edited dataset.
+    #[test]
+    fn test_diff_and_patch_across_transfer_syntaxes() -> Result<(), WriteError> {
+        let old_dataset = [
+            STUDY_DESCRIPTION_ELEMENT,
+            REFERENCED_STUDY_SEQUENCE_ELEMENT,
+            PIXEL_DATA_ELEMENT,
+        ]
+        .concat();
+        let new_dataset = [
+            edited_referenced_study_sequence(true).as_slice(),
+            PATIENT_ELEMENTS,
+            PIXEL_DATA_ELEMENT,
+        ]
+        .concat();
+        let old = parse_elements(&old_dataset, &ExplicitVRLittleEndian)?;
+        let new = parse_elements(&new_dataset, &ExplicitVRLittleEndian)?;
+
+        for ts in [&ImplicitVRLittleEndian, &ExplicitVRBigEndian] {
+            let old_ts = parse_elements(
+                &write_elements(&transcode(&old, ts, &STANDARD_DICOM_DICTIONARY)?)?,
+                ts,
+            )?;
+            let new_ts = parse_elements(
+                &write_elements(&transcode(&new, ts, &STANDARD_DICOM_DICTIONARY)?)?,
+                ts,
+            )?;
+
+            let mut patched = parse_elements(&old_dataset, &ExplicitVRLittleEndian)?;
+            diff(&old_ts, &new_ts)
+                .to_patch()
+                .apply(&mut patched, &STANDARD_DICOM_DICTIONARY)
+                .expect("apply patch");
+            assert_same_encoding(&patched, &new_dataset)?;
+
+            diff(&new_ts, &old_ts)
+                .to_patch()
+                .apply(&mut patched, &STANDARD_DICOM_DICTIONARY)
+                .expect("apply patch");
+            assert_same_encoding(&patched, &old_dataset)?;
+        }
+
+        Ok(())
+    }
+
+    /// Asserts the Explicit VR Little Endian `DicomRoot` is written as the dataset, and that each of
+    /// its elements has the transfer syntax, VR, length, and `sq_path` it's parsed with again.
+    fn assert_same_encoding(dcmroot: &DicomRoot, dataset: &[u8]) -> Result<(), WriteError> {
+        assert_eq!(dataset, write_elements(dcmroot)?);
+        let reparsed = parse_elements(dataset, &ExplicitVRLittleEndian)?;
+        let summary = |root: &DicomRoot| {
+            root.flatten()
+                .iter()
+                .map(|e| {
+                    let sq_path: Vec<(u32, Option<usize>, ValueLength)> = e
+                        .sq_path()
+                        .iter()
+                        .map(|sq| (sq.sq_tag(), sq.item(), sq.vl()))
+                        .collect();
+                    // Items and delimiters are always encoded as Implicit VR.
+                    let ts: Option<TSRef> = (e.tag() < ITEM).then(|| e.ts());
+                    (e.tag(), ts, e.vr(), e.vl(), sq_path)
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(summary(&reparsed), summary(dcmroot));
+        Ok(())
+    }
+
+    /// Setting values creates sequences and items along the path, with lengths and item numbers
+    /// matching the dataset as it's written and parsed again.
+    #[test]
+    fn test_edit_set_by_tagpath() -> Result<(), WriteError> {
+        let mut dcmroot = parse_elements(
+            mockdata::IMPLICIT_DEFINED_LENGTH_SEQUENCE,
+            &ImplicitVRLittleEndian,
+        )?;
+
+        let instance_uid = TagPath::from(vec![
+            ReferencedStudySequence.as_item_node(1),
+            ReferencedSOPInstanceUID.as_node(),
+        ]);
+        dcmroot
+            .set_by_tagpath(
+                &instance_uid,
+                RawValue::of_uid("1.2.4"),
+                &STANDARD_DICOM_DICTIONARY,
+            )
+            .expect("set in existing item");
+        let new_item_uid = TagPath::from(vec![
+            ReferencedStudySequence.as_item_node(2),
+            ReferencedSOPClassUID.as_node(),
+        ]);
+        dcmroot
+            .set_by_tagpath(
+                &new_item_uid,
+                RawValue::of_uid("1.2.5"),
+                &STANDARD_DICOM_DICTIONARY,
+            )
+            .expect("set in new item");
+        let new_sequence_uid = TagPath::from(vec![
+            ReferencedSeriesSequence.as_node(),
+            SeriesInstanceUID.as_node(),
+        ]);
+        dcmroot
+            .set_by_tagpath(
+                &new_sequence_uid,
+                RawValue::of_uid("1.2.6"),
+                &STANDARD_DICOM_DICTIONARY,
+            )
+            .expect("set in new sequence");
+
+        // The explicit lengths of the sequence and first item include the added elements.
+        let sequence = dcmroot
+            .get_child_by_tag(&ReferencedStudySequence)
+            .expect("ReferencedStudySequence");
+        assert_eq!(ValueLength::Explicit(58), sequence.element().vl());
+        assert_eq!(
+            ValueLength::Explicit(28),
+            sequence.iter_items().next().expect("item").element().vl()
+        );
+
+        let element = dcmroot
+            .get_child_by_tagpath(&new_item_uid)
+            .expect("new element");
+        assert_eq!(&UI, element.element().vr());
+        assert_eq!(new_item_uid, element.element().create_tagpath());
+
+        let reparsed = parse_elements(&write_elements(&dcmroot)?, &ImplicitVRLittleEndian)?;
+        assert!(diff(&dcmroot, &reparsed).is_empty());
+        assert_eq!(
+            Some(RawValue::of_uid("1.2.6")),
+            reparsed.get_value_by_tagpath(&TagPath::from(vec![
+                ReferencedSeriesSequence.as_item_node(1),
+                SeriesInstanceUID.as_node(),
+            ]))
+        );
+
+        Ok(())
+    }
+
+    /// Inserting and removing items renumbers the items which follow.
+    #[test]
+    fn test_edit_insert_and_remove_items() -> Result<(), WriteError> {
+        let mut dcmroot =
+            parse_elements(REFERENCED_STUDY_SEQUENCE_ELEMENT, &ExplicitVRLittleEndian)?;
+        let sequence = TagPath::from(&ReferencedStudySequence);
+
+        let item = DicomObject::new(DicomElement::new_empty(ITEM, &UN, &ExplicitVRLittleEndian));
+        dcmroot
+            .insert_item(&sequence, 1, item)
+            .expect("insert item");
+        let inserted_uid = TagPath::from(vec![
+            ReferencedStudySequence.as_item_node(1),
+            ReferencedSOPInstanceUID.as_node(),
+        ]);
+        dcmroot
+            .set_by_tagpath(
+                &inserted_uid,
+                RawValue::of_uid("1.2.9"),
+                &STANDARD_DICOM_DICTIONARY,
+            )
+            .expect("set in inserted item");
+
+        let moved_uid = TagPath::from(vec![
+            ReferencedStudySequence.as_item_node(2),
+            ReferencedSOPClassUID.as_node(),
+        ]);
+        let moved = dcmroot
+            .get_child_by_tagpath(&moved_uid)
+            .expect("moved item");
+        assert_eq!(moved_uid, moved.element().create_tagpath());
+        let reparsed = parse_elements(&write_elements(&dcmroot)?, &ExplicitVRLittleEndian)?;
+        assert!(diff(&dcmroot, &reparsed).is_empty());
+
+        let removed = dcmroot.remove_item(&sequence, 1).expect("removed item");
+        assert_eq!(
+            Some(RawValue::of_uid("1.2.9")),
+            removed.get_value_by_tag(&ReferencedSOPInstanceUID)
+        );
+        assert!(dcmroot.remove_item(&sequence, 3).is_none());
+        assert_eq!(REFERENCED_STUDY_SEQUENCE_ELEMENT, write_elements(&dcmroot)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_edit_remove_by_tagpath() -> Result<(), WriteError> {
+        let dataset = [STUDY_DESCRIPTION_ELEMENT, REFERENCED_STUDY_SEQUENCE_ELEMENT].concat();
+        let mut dcmroot = parse_elements(&dataset, &ExplicitVRLittleEndian)?;
+
+        let second_item = TagPath::from(vec![
+            ReferencedStudySequence.as_item_node(2),
+            ReferencedSOPInstanceUID.as_node(),
+        ]);
+        assert!(dcmroot.remove_by_tagpath(&second_item).is_some());
+        assert!(dcmroot.remove_by_tagpath(&second_item).is_none());
+        assert!(dcmroot
+            .remove_by_tagpath(&TagPath::from(&StudyDescription))
+            .is_some());
+        let removed_item = dcmroot
+            .remove_by_tagpath(&TagPath::from(ReferencedStudySequence.as_item_node(2)))
+            .expect("removed item");
+        assert!(removed_item
+            .get_child_by_tag(&ReferencedSOPInstanceUID)
+            .is_none());
+        assert_eq!(1, dcmroot.get_child_count());
+        assert_eq!(
+            1,
+            dcmroot
+                .get_child_by_tag(&ReferencedStudySequence)
+                .expect("sequence")
+                .item_count()
+        );
+
+        let under_element = TagPath::from(vec![
+            ReferencedStudySequence.as_item_node(1),
+            ReferencedSOPClassUID.as_item_node(1),
+            ReferencedSOPInstanceUID.as_node(),
+        ]);
+        assert!(matches!(
+            dcmroot.set_by_tagpath(
+                &under_element,
+                RawValue::of_uid("1.2.3"),
+                &STANDARD_DICOM_DICTIONARY
+            ),
+            Err(EditError::NotASequence(_))
+        ));
+        let past_end = TagPath::from(vec![
+            ReferencedStudySequence.as_item_node(3),
+            ReferencedSOPClassUID.as_node(),
+        ]);
+        assert!(matches!(
+            dcmroot.set_by_tagpath(
+                &past_end,
+                RawValue::of_uid("1.2.3"),
+                &STANDARD_DICOM_DICTIONARY
+            ),
+            Err(EditError::ItemNotFound(_))
+        ));
+        assert!(matches!(
+            dcmroot.set_by_tagpath(
+                &TagPath::empty(),
+                RawValue::of_uid("1.2.3"),
+                &STANDARD_DICOM_DICTIONARY
+            ),
+            Err(EditError::InvalidPath(_))
+        ));
+
+        Ok(())
+    }
+
+    /// Typed values are encoded as strings through the element's VR, and parsed back from the
+    /// written dataset.
+    #[test]
+    fn test_write_typed_values() -> Result<(), WriteError> {
+        let mut dcmroot = parse_elements(PATIENT_ELEMENTS, &ExplicitVRLittleEndian)?;
+        let name: PersonName = dcmroot
+            .get_value_by_tag(&PatientsName)
+            .and_then(|value| value.person_name())
+            .expect("person name");
+        assert_eq!(
+            ("DOE", "JOHN"),
+            (name.alphabetic().family(), name.alphabetic().given())
+        );
+
+        let study_date: DicomDate = "2020.06.15".parse().expect("date");
+        let study_time: DicomTime = "10:30:05.25".parse().expect("time");
+        let acquisition: DicomDateTime = "20200615103005-0500".parse().expect("date time");
+        let age = DicomAge::new(42, AgeUnit::Years).expect("age");
+        let renamed = PersonName::alphabetic_only(
+            name.alphabetic().clone().with_middle("Q").with_suffix("JR"),
+        );
+        for (tag, value) in [
+            (&StudyDate, RawValue::from(study_date)),
+            (&StudyTime, RawValue::from(study_time)),
+            (&AcquisitionDateTime, RawValue::from(acquisition)),
+            (&PatientsAge, RawValue::from(age)),
+            (&PatientsName, RawValue::from(renamed.clone())),
+        ] {
+            dcmroot
+                .set_by_tagpath(&TagPath::from(tag), value, &STANDARD_DICOM_DICTIONARY)
+                .expect("set typed value");
+        }
+
+        let written = write_elements(&dcmroot)?;
+        let reparsed = parse_elements(&written, &ExplicitVRLittleEndian)?;
+        let value_of = |tag| reparsed.get_value_by_tag(tag).expect("value");
+        assert_eq!(Some(study_date), value_of(&StudyDate).date());
+        assert_eq!(Some(study_time), value_of(&StudyTime).time());
+        assert_eq!(Some(acquisition), value_of(&AcquisitionDateTime).datetime());
+        assert_eq!(Some(age), value_of(&PatientsAge).age());
+        assert_eq!(Some(renamed), value_of(&PatientsName).person_name());
+        assert_eq!(
+            "20200615",
+            value_of(&StudyDate)
+                .string()
+                .expect("encoded date")
+                .as_str()
+        );
+
+        let range: DicomRange<DicomDate> = "20200101-20201231".parse().expect("range");
+        assert!(range.contains(&study_date));
+        assert_eq!(Some(range), RawValue::from(range).range::<DicomDate>());
+
+        Ok(())
+    }
+
+    /// Datasets round-trip through serde formats, keeping the transfer syntax, character set, and
+    /// every element's tag, VR, length, and value.
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() -> Result<(), WriteError> {
+        let dataset = [STUDY_DESCRIPTION_ELEMENT, REFERENCED_STUDY_SEQUENCE_ELEMENT].concat();
+        for ts in [&ExplicitVRLittleEndian, &ExplicitVRBigEndian] {
+            let dataset = if ts == &ExplicitVRLittleEndian {
+                dataset.clone()
+            } else {
+                let dcmroot = parse_elements(&dataset, &ExplicitVRLittleEndian)?;
+                let mut writer = WriterBuilder::default()
+                    .ts(ts)
+                    .state(WriterState::WriteElement)
+                    .build(Vec::new());
+                writer.write_dcmroot(&dcmroot)?;
+                writer.finish()?
+            };
+            let dcmroot = parse_elements(&dataset, ts)?;
+
+            let encoded: Vec<u8> = bincode::serialize(&dcmroot).expect("bincode serialize");
+            let from_bincode: DicomRoot =
+                bincode::deserialize(&encoded).expect("bincode deserialize");
+            let mut encoded: Vec<u8> = Vec::new();
+            ciborium::into_writer(&dcmroot, &mut encoded).expect("cbor serialize");
+            let from_cbor: DicomRoot =
+                ciborium::from_reader(encoded.as_slice()).expect("cbor deserialize");
+
+            for restored in [from_bincode, from_cbor] {
+                assert_eq!(ts, restored.ts());
+                assert_eq!(dcmroot.cs().name(), restored.cs().name());
+                assert!(diff(&dcmroot, &restored).is_empty());
+                assert_eq!(dataset, write_elements(&restored)?);
+
+                let item = restored
+                    .get_child_by_tagpath(&TagPath::from(vec![
+                        ReferencedStudySequence.as_item_node(2),
+                        ReferencedSOPInstanceUID.as_node(),
+                    ]))
+                    .expect("element in item");
+                assert_eq!(2, item.element().sq_path().len());
+                assert_eq!(
+                    Some(2),
+                    item.element().sq_path()[0].item(),
+                    "item number within sequence"
+                );
+            }
+        }
+
+        let values = vec![
+            RawValue::of_string("DOE^JOHN"),
+            RawValue::Doubles(vec![1.5, -2.0]),
+            RawValue::Attributes(vec![Attribute(0x0010_0010)]),
+            RawValue::Bytes(vec![1, 2, 3]),
+        ];
+        let encoded: Vec<u8> = bincode::serialize(&values).expect("bincode serialize");
+        let restored: Vec<RawValue> = bincode::deserialize(&encoded).expect("bincode deserialize");
+        assert_eq!(values, restored);
+
+        Ok(())
+    }
+
+    /// Datasets convert to and from the DICOM JSON Model, with binary values in little endian
+    /// regardless of the transfer syntax, and large binary values optionally as bulk data.
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_round_trip() -> Result<(), WriteError> {
+        use medicom::core::json::{
+            from_json, read_json, to_json, write_json, JsonError, JsonReadOptions, JsonWriteOptions,
+        };
+        use medicom::dict::tags::PixelSpacing;
+        use serde_json::Value;
+
+        let dataset = [
+            STUDY_DESCRIPTION_ELEMENT,
+            REFERENCED_STUDY_SEQUENCE_ELEMENT,
+            PATIENT_ELEMENTS,
+        ]
+        .concat();
+        let mut dcmroot = parse_elements(&dataset, &ExplicitVRLittleEndian)?;
+        for (tag, value) in [
+            (&PixelSpacing, RawValue::Doubles(vec![0.5, 0.25])),
+            (&Rows, RawValue::UShorts(vec![512])),
+            (
+                &FrameIncrementPointer,
+                RawValue::Attributes(vec![Attribute(0x0018_1063)]),
+            ),
+            (&PixelData, RawValue::Words(vec![0x0102, 0x0304, 0x0506])),
+        ] {
+            dcmroot
+                .set_by_tagpath(&TagPath::from(tag), value, &STANDARD_DICOM_DICTIONARY)
+                .expect("set value");
+        }
+        let big_endian = transcode(&dcmroot, &ExplicitVRBigEndian, &STANDARD_DICOM_DICTIONARY)?;
+
+        let json: Value = to_json(&dcmroot, &JsonWriteOptions::default()).expect("to json");
+        assert_eq!(
+            json,
+            to_json(&big_endian, &JsonWriteOptions::default()).expect("to json")
+        );
+        assert_eq!("PN", json["00100010"]["vr"]);
+        assert!(json["00100010"]["Value"][0]["Alphabetic"]
+            .as_str()
+            .is_some_and(|name| name.starts_with("DOE^JOHN")));
+        assert_eq!(serde_json::json!([0.5, 0.25]), json["00280030"]["Value"]);
+        assert_eq!(serde_json::json!([512]), json["00280010"]["Value"]);
+        assert_eq!(serde_json::json!(["00181063"]), json["00280009"]["Value"]);
+        assert_eq!("SQ", json["00081110"]["vr"]);
+        assert_eq!(2, json["00081110"]["Value"].as_array().map_or(0, Vec::len));
+        assert_eq!("AgEEAwYF", json["7FE00010"]["InlineBinary"]);
+
+        let mut written: Vec<u8> = Vec::new();
+        write_json(&dcmroot, &mut written, &JsonWriteOptions::default()).expect("write json");
+        let restored = read_json(written.as_slice(), &JsonReadOptions::default()).expect("read");
+        assert_eq!(&ExplicitVRLittleEndian, restored.ts());
+        assert!(diff(&dcmroot, &restored).is_empty());
+        assert!(diff(
+            &dcmroot,
+            &parse_elements(&write_elements(&restored)?, &ExplicitVRLittleEndian)?
+        )
+        .is_empty());
+
+        let bulk_data_uri = |element: &DicomElement| Some(format!("bulk/{:08X}", element.tag()));
+        let options = JsonWriteOptions::default().bulk_data(4, &bulk_data_uri);
+        let json: Value = to_json(&dcmroot, &options).expect("to json");
+        assert_eq!("bulk/7FE00010", json["7FE00010"]["BulkDataURI"]);
+        assert!(json["7FE00010"].get("InlineBinary").is_none());
+        assert!(matches!(
+            from_json(&json, &JsonReadOptions::default()),
+            Err(JsonError::UnresolvedBulkData { .. })
+        ));
+
+        let resolver = |uri: &str| {
+            if uri == "bulk/7FE00010" {
+                Ok(vec![2, 1, 4, 3, 6, 5])
+            } else {
+                Err(format!("unknown uri: {uri}"))
+            }
+        };
+        let options = JsonReadOptions::default().bulk_data_resolver(&resolver);
+        let restored = from_json(&json, &options).expect("from json");
+        assert!(diff(&dcmroot, &restored).is_empty());
+
+        Ok(())
+    }
+
+    /// Encapsulated pixel data whose fragments were deferred during parsing is loaded when
+    /// converted to JSON, rather than exported as empty fragments.
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_round_trip_deferred() -> Result<(), WriteError> {
+        use medicom::core::json::{from_json, to_json, JsonReadOptions, JsonWriteOptions};
+        use serde_json::Value;
+
+        let dataset = [STUDY_DESCRIPTION_ELEMENT, ENCAPSULATED_PIXEL_DATA_ELEMENT].concat();
+        let dcmroot = parse_elements(&dataset, &ExplicitVRLittleEndian)?;
+        let mut parser = ParserBuilder::default()
+            .state(ParserState::ReadElement)
+            .dataset_ts(&ExplicitVRLittleEndian)
+            .defer_values(DeferValues::AboveLength(4))
+            .build_seekable(Cursor::new(dataset), &STANDARD_DICOM_DICTIONARY);
+        let deferred = DicomRoot::parse(&mut parser)?.expect("Parse into DicomRoot");
+        assert!(deferred.flatten().iter().any(|e| e.is_deferred()));
+
+        let json: Value = to_json(&deferred, &JsonWriteOptions::default()).expect("to json");
+        assert_eq!(
+            json,
+            to_json(&dcmroot, &JsonWriteOptions::default()).expect("to json")
+        );
+
+        // Without an encapsulated transfer syntax the restored value field is kept as bytes, which
+        // are the fragments as originally encoded.
+        let restored = from_json(&json, &JsonReadOptions::default()).expect("from json");
+        let pixel_data = restored.get_child_by_tag(&PixelData).expect("pixel data");
+        assert_eq!(
+            &ENCAPSULATED_PIXEL_DATA_ELEMENT[12..],
+            pixel_data.element().data()
+        );
+
+        Ok(())
+    }
+
+    /// Datasets convert to and from the Native DICOM Model XML, with binary values in little endian
+    /// regardless of the transfer syntax, and large binary values optionally as bulk data.
+    #[cfg(feature = "xml")]
+    #[test]
+    fn test_xml_round_trip() -> Result<(), WriteError> {
+        use medicom::core::xml::{read_xml, write_xml, XmlError, XmlReadOptions, XmlWriteOptions};
+
+        let dataset = [
+            STUDY_DESCRIPTION_ELEMENT,
+            REFERENCED_STUDY_SEQUENCE_ELEMENT,
+            PATIENT_ELEMENTS,
+        ]
+        .concat();
+        let mut dcmroot = parse_elements(&dataset, &ExplicitVRLittleEndian)?;
+        for (path, value) in [
+            (TagPath::from(&Rows), RawValue::UShorts(vec![512])),
+            (
+                TagPath::from(&FrameIncrementPointer),
+                RawValue::Attributes(vec![Attribute(0x0018_1063)]),
+            ),
+            (
+                TagPath::from(&PixelData),
+                RawValue::Words(vec![0x0102, 0x0304, 0x0506]),
+            ),
+            (TagPath::from(0x0009_0010), RawValue::of_string("ACME")),
+            (TagPath::from(0x0009_1001), RawValue::of_string("PRIVATE")),
+        ] {
+            dcmroot
+                .set_by_tagpath(&path, value, &STANDARD_DICOM_DICTIONARY)
+                .expect("set value");
+        }
+        let big_endian = transcode(&dcmroot, &ExplicitVRBigEndian, &STANDARD_DICOM_DICTIONARY)?;
+
+        let write = |dcmroot: &DicomRoot, options: &XmlWriteOptions<'_>| {
+            let mut written: Vec<u8> = Vec::new();
+            write_xml(dcmroot, &mut written, options).expect("write xml");
+            String::from_utf8(written).expect("utf-8")
+        };
+        let options = XmlWriteOptions::default().dictionary(&STANDARD_DICOM_DICTIONARY);
+        let xml: String = write(&dcmroot, &options);
+        assert_eq!(xml, write(&big_endian, &options));
+        assert!(xml.contains(r#"<DicomAttribute tag="00100010" vr="PN" keyword="PatientsName"><PersonName number="1"><Alphabetic><FamilyName>DOE</FamilyName><GivenName>JOHN</GivenName>"#));
+        assert!(xml.contains(r#"<DicomAttribute tag="00280010" vr="US" keyword="Rows"><Value number="1">512</Value></DicomAttribute>"#));
+        assert!(xml.contains(r#"<Value number="1">00181063</Value>"#));
+        assert!(xml.contains(r#"<DicomAttribute tag="00081110" vr="SQ" keyword="ReferencedStudySequence"><Item number="1">"#));
+        assert!(xml.contains(r#"<Item number="2">"#));
+        assert!(xml.contains("<InlineBinary>AgEEAwYF</InlineBinary>"));
+        assert!(xml.contains(r#"<DicomAttribute tag="00091001" vr="UN" privateCreator="ACME">"#));
+
+        let restored = read_xml(xml.as_bytes(), &XmlReadOptions::default()).expect("read xml");
+        assert_eq!(&ExplicitVRLittleEndian, restored.ts());
+        assert!(diff(&dcmroot, &restored).is_empty());
+        let pretty: String = write(&dcmroot, &options.pretty(true));
+        let restored = read_xml(pretty.as_bytes(), &XmlReadOptions::default()).expect("read xml");
+        assert!(diff(&dcmroot, &restored).is_empty());
+
+        // Only Pixel Data is stored as bulk data, other large values remain inline.
+        let bulk_data_uri = |element: &DicomElement| {
+            (element.tag() == PixelData.tag()).then(|| format!("bulk/{:08X}", element.tag()))
+        };
+        let xml: String = write(&dcmroot, &options.bulk_data(4, &bulk_data_uri));
+        assert!(xml.contains(r#"<BulkData uri="bulk/7FE00010"/>"#));
+        assert!(matches!(
+            read_xml(xml.as_bytes(), &XmlReadOptions::default()),
+            Err(XmlError::UnresolvedBulkData { .. })
+        ));
+
+        let resolver = |uri: &str| {
+            if uri == "bulk/7FE00010" {
+                Ok(vec![2, 1, 4, 3, 6, 5])
+            } else {
+                Err(format!("unknown uri: {uri}"))
+            }
+        };
+        let options = XmlReadOptions::default().bulk_data_resolver(&resolver);
+        let restored = read_xml(xml.as_bytes(), &options).expect("read xml");
+        assert!(diff(&dcmroot, &restored).is_empty());
+
+        // Value numbers beyond the count of values are rejected rather than sizing the values.
+        for number in ["0", "2", "1000000000", "18446744073709551615"] {
+            let xml: String = format!(
+                r#"<NativeDicomModel><DicomAttribute tag="00280010" vr="US"><Value number="{number}">512</Value></DicomAttribute></NativeDicomModel>"#
+            );
+            assert!(matches!(
+                read_xml(xml.as_bytes(), &XmlReadOptions::default()),
+                Err(XmlError::InvalidAttribute { .. })
+            ));
+        }
+
+        Ok(())
+    }
+
     #[test]
     #[ignore]
     fn test_reencoded_values_all_files() -> Result<(), WriteError> {
@@ -380,7 +2249,7 @@ mod writing_tests {
     }
 
     fn assert_reencode_element(path_str: &str, elem: &DicomElement) -> Result<(), WriteError> {
-        let orig_parsed_data = elem.data().clone();
+        let orig_parsed_data = elem.data();
         let value = elem.parse_value();
         if let Err(e) = value {
             eprintln!("Parsing error in file.\n\tfile: {path_str}\n\terr: {e:?}");
@@ -389,7 +2258,7 @@ mod writing_tests {
         let value = value?;
         let mut re_encoded = DicomElement::new_empty(elem.tag(), elem.vr(), elem.ts());
         re_encoded.encode_val_with_vl(value.clone(), Some(elem.vl()))?;
-        let reencoded_data = elem.data().clone();
+        let reencoded_data = elem.data();
 
         if orig_parsed_data == reencoded_data {
             return Ok(());
@@ -409,7 +2278,7 @@ mod writing_tests {
         // If strings consist of only the padding character then ignore size differences.
         if elem.vr().is_character_string {
             // Some character-based elements seem to include trailing null-byte padding.
-            let trimmer = |v: &Vec<u8>| {
+            let trimmer = |v: &[u8]| {
                 v.iter()
                     .rev()
                     .map(std::borrow::ToOwned::to_owned)
@@ -422,8 +2291,8 @@ mod writing_tests {
                     .collect::<Vec<u8>>()
             };
 
-            let orig_end_trimmed = trimmer(&orig_parsed_data);
-            let reencoded_end_trimmed = trimmer(&reencoded_data);
+            let orig_end_trimmed = trimmer(orig_parsed_data);
+            let reencoded_end_trimmed = trimmer(reencoded_data);
 
             if orig_end_trimmed == reencoded_end_trimmed {
                 return Ok(());
@@ -42,6 +42,7 @@ use medicom::{
         dcmobject::{DicomObject, DicomRoot},
         defn::{
             constants::tags::ITEM,
+            rtdict::RuntimeDicomDictionary,
             tag::{Tag, TagNode, TagPath},
         },
         inspect::{FormattedElement, FormattedTagType, FormattedTagValue},
@@ -57,7 +58,10 @@ use ratatui::{
     Frame, Terminal,
 };
 
-use crate::{app::CommandApplication, args::BrowseArgs};
+use crate::{
+    app::{load_dictionaries, with_standard_dictionary, CommandApplication},
+    args::BrowseArgs,
+};
 
 pub struct BrowseApp {
     args: BrowseArgs,
@@ -91,6 +95,8 @@ struct DisplayOpts {
     max_items: usize,
     hide_delims: bool,
     hide_groups: bool,
+    /// Dictionaries of additional tags, used before the standard dictionary.
+    dicts: Vec<RuntimeDicomDictionary>,
 }
 
 impl DisplayOpts {
@@ -100,6 +106,7 @@ impl DisplayOpts {
             .with_max_items(self.max_items)
             .with_hide_delims(self.hide_delims)
             .with_hide_groups(self.hide_groups)
+            .with_dict(with_standard_dictionary(&self.dicts))
     }
 
     fn should_omit(&self, obj: &DicomObject) -> bool {
@@ -170,6 +177,7 @@ impl CommandApplication for BrowseApp {
             max_items: 16,
             hide_delims: true,
             hide_groups: true,
+            dicts: load_dictionaries(&self.args.dict)?,
         };
 
         let doc_model = DicomDocumentModel::parse(path, &dcmroot, &display_opts);
@@ -489,7 +497,7 @@ impl<'m> DicomNodeModel<'m> {
 
         for item in dcmobj.iter_items() {
             if let Some((row, child_map, name_len)) =
-                DicomNodeModel::parse_dcmobj(item, display_opts)
+                DicomNodeModel::parse_dcmobj(dcmobj, item, display_opts)
             {
                 rows.push(row);
                 map.extend(child_map);
@@ -498,7 +506,7 @@ impl<'m> DicomNodeModel<'m> {
         }
         for (_child_tag, child) in dcmobj.iter_child_nodes() {
             if let Some((row, child_map, name_len)) =
-                DicomNodeModel::parse_dcmobj(child, display_opts)
+                DicomNodeModel::parse_dcmobj(dcmobj, child, display_opts)
             {
                 rows.push(row);
                 map.extend(child_map);
@@ -518,6 +526,7 @@ impl<'m> DicomNodeModel<'m> {
     }
 
     fn parse_dcmobj(
+        parent: &DicomObject,
         child: &DicomObject,
         display_opts: &DisplayOpts,
     ) -> Option<(Row<'m>, HashMap<TagPath, DicomNodeModel<'m>>, u16)> {
@@ -528,11 +537,9 @@ impl<'m> DicomNodeModel<'m> {
             map.extend(child_map);
         }
 
-        let formatted_elem = FormattedElement::new(child.element())
-            .with_multiline(display_opts.multiline)
-            .with_max_items(display_opts.max_items)
-            .with_hide_delims(display_opts.hide_delims)
-            .with_hide_groups(display_opts.hide_groups);
+        let formatted_elem = display_opts
+            .for_obj(child)
+            .with_private_creator(parent.get_private_creator(child_tag));
         if formatted_elem.should_omit() {
             return None;
         }
@@ -0,0 +1,104 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! The dicomdir command prints the directory records of a DICOMDIR as a tree, or creates a
+//! DICOMDIR for the DICOM files within a folder.
+
+use std::{
+    fmt::Write as _,
+    io::{self, Write},
+};
+
+use anyhow::Result;
+
+use medicom::{
+    core::dicomdir::{DicomDir, DicomDirBuilder, DirectoryRecord},
+    dict::{
+        stdlookup::STANDARD_DICOM_DICTIONARY,
+        tags::{
+            PatientID, PatientsName, ReferencedSOPInstanceUIDinFile, SeriesInstanceUID,
+            StudyInstanceUID,
+        },
+    },
+};
+
+use crate::{app::CommandApplication, args::DicomDirArgs};
+
+pub struct DicomDirApp {
+    args: DicomDirArgs,
+}
+
+impl DicomDirApp {
+    pub fn new(args: DicomDirArgs) -> DicomDirApp {
+        DicomDirApp { args }
+    }
+}
+
+impl CommandApplication for DicomDirApp {
+    fn run(&mut self) -> Result<()> {
+        if self.args.create {
+            let mut builder = DicomDirBuilder::default();
+            if let Some(file_set_id) = &self.args.file_set_id {
+                builder = builder.file_set_id(file_set_id);
+            }
+            let path = builder.write(&self.args.path, &STANDARD_DICOM_DICTIONARY)?;
+            println!("Created {}", path.display());
+            return Ok(());
+        }
+
+        let dicomdir: DicomDir = DicomDir::open(&self.args.path, &STANDARD_DICOM_DICTIONARY)?;
+        let mut stdout = io::stdout().lock();
+        for (depth, record) in dicomdir.iter() {
+            let indent: String = "  ".repeat(depth);
+            let mut line: String = format!("{indent}{}", record.record_type());
+            let description: String = describe(record);
+            if !description.is_empty() {
+                let _ = write!(line, " {description}");
+            }
+            if let Some(path) = dicomdir.file_path(record) {
+                let _ = write!(line, " -> {}", path.display());
+            }
+            if !record.is_in_use() {
+                line.push_str(" (inactive)");
+            }
+            let _ = writeln!(line, " @{}", record.offset());
+            stdout.write_all(line.as_ref())?;
+        }
+        Ok(())
+    }
+}
+
+/// The identifying values of the record, for the levels of records generated by medicom.
+fn describe(record: &DirectoryRecord) -> String {
+    [
+        &PatientsName,
+        &PatientID,
+        &StudyInstanceUID,
+        &SeriesInstanceUID,
+        &ReferencedSOPInstanceUIDinFile,
+    ]
+    .iter()
+    .filter_map(|tag| {
+        record
+            .item()
+            .get_value_by_tag(*tag)
+            .and_then(|value| value.string().map(|v| v.trim().to_string()))
+    })
+    .filter(|value| !value.is_empty())
+    .map(|value| format!("[{value}]"))
+    .collect::<Vec<String>>()
+    .join(" ")
+}
@@ -0,0 +1,136 @@
+/*
+   Copyright 2024-2025 Christopher Speck
+
+   Licensed under the Apache License, Version 2.0 (the "License");
+   you may not use this file except in compliance with the License.
+   You may obtain a copy of the License at
+
+       http://www.apache.org/licenses/LICENSE-2.0
+
+   Unless required by applicable law or agreed to in writing, software
+   distributed under the License is distributed on an "AS IS" BASIS,
+   WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+   See the License for the specific language governing permissions and
+   limitations under the License.
+*/
+
+//! The diff command compares two DICOM datasets and prints the elements which were added, removed,
+//! or changed, rendered in the same format as the print command. The exit status follows diff(1).
+
+use std::{
+    io::{self, Write},
+    path::Path,
+    process,
+};
+
+use anyhow::{anyhow, Result};
+
+use medicom::{
+    core::{
+        dcmobject::{DicomObject, DicomRoot},
+        defn::tag::TagPath,
+        diff::{diff, DicomDiff, DiffKind},
+        inspect::FormattedElement,
+    },
+    dict::stdlookup::STANDARD_DICOM_DICTIONARY,
+};
+
+use crate::{
+    app::{parse_file, CommandApplication},
+    args::DiffArgs,
+};
+
+pub struct DiffApp {
+    args: DiffArgs,
+}
+
+impl DiffApp {
+    pub fn new(args: DiffArgs) -> DiffApp {
+        DiffApp { args }
+    }
+}
+
+/// The exit status when the datasets differ.
+const EXIT_DIFFERENT: i32 = 1;
+
+/// The exit status when the datasets could not be compared.
+const EXIT_TROUBLE: i32 = 2;
+
+impl CommandApplication for DiffApp {
+    fn run(&mut self) -> Result<()> {
+        match self.compare() {
+            Ok(false) => Ok(()),
+            Ok(true) => process::exit(EXIT_DIFFERENT),
+            Err(e) => {
+                eprintln!("Error: {e:?}");
+                process::exit(EXIT_TROUBLE);
+            }
+        }
+    }
+}
+
+impl DiffApp {
+    /// Prints the differences between the datasets, returning whether there are any.
+    fn compare(&self) -> Result<bool> {
+        let left: DicomRoot = parse_root(&self.args.left)?;
+        let right: DicomRoot = parse_root(&self.args.right)?;
+        let diff: DicomDiff = diff(&left, &right);
+
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(
+            format!(
+                "--- {}\n+++ {}\n",
+                self.args.left.display(),
+                self.args.right.display()
+            )
+            .as_ref(),
+        )?;
+
+        for entry in diff.entries() {
+            let kind: &str = match entry.kind() {
+                DiffKind::Added => "added",
+                DiffKind::Removed => "removed",
+                DiffKind::Changed => "changed",
+            };
+            stdout.write_all(
+                format!(
+                    "\n# {kind}: {}\n",
+                    TagPath::format_tagpath_to_display(
+                        entry.path(),
+                        Some(&STANDARD_DICOM_DICTIONARY)
+                    )
+                )
+                .as_ref(),
+            )?;
+            if let Some(old) = entry.old_obj() {
+                print_object(&mut stdout, '-', old)?;
+            }
+            if let Some(new) = entry.new_obj() {
+                print_object(&mut stdout, '+', new)?;
+            }
+        }
+        stdout.flush()?;
+
+        Ok(!diff.is_empty())
+    }
+}
+
+fn parse_root(path: &Path) -> Result<DicomRoot> {
+    let mut parser = parse_file(path, false)?;
+    match DicomRoot::parse(&mut parser) {
+        Ok(Some(dcmroot)) => Ok(dcmroot),
+        Ok(None) => Err(anyhow!("Not valid DICOM: {}", path.display())),
+        Err(err) => Err(anyhow!(err)),
+    }
+}
+
+/// Prints the element and all of its contents, each line prefixed with the marker.
+fn print_object<W: Write>(stdout: &mut W, marker: char, obj: &DicomObject) -> Result<()> {
+    for elem in obj.flatten() {
+        let printed: String = FormattedElement::new(elem).to_string();
+        if !printed.is_empty() {
+            stdout.write_all(format!("{marker} {printed}\n").as_ref())?;
+        }
+    }
+    Ok(())
+}
@@ -22,6 +22,7 @@ use std::{
 };
 
 use bson::{doc, oid::ObjectId, spec::BinarySubtype, Binary, Bson, Document};
+use memmap2::Mmap;
 use mongodb::sync::{Client, Collection, Cursor, Database};
 use walkdir::WalkDir;
 
@@ -30,8 +31,12 @@ use medicom::{
     core::{
         dcmelement::DicomElement,
         dcmobject::DicomRoot,
+        defn::dcmdict::DicomDictionary,
         defn::vr::LT,
-        read::{stop::ParseStop, ParserBuilder},
+        read::{
+            archive::ArchiveFormat, parallel::DatasetSource, stop::ParseStop, ParseResult,
+            ParserBuilder,
+        },
         values::RawValue,
     },
     dict::{
@@ -41,7 +46,7 @@ use medicom::{
 };
 
 use crate::{
-    app::CommandApplication,
+    app::{archive_entry_path, is_file_or_archive_entry, CommandApplication},
     args::{IndexArgs, IndexCommand},
 };
 
@@ -80,6 +85,24 @@ impl DicomDoc {
     }
 }
 
+/// A file scanned for indexing, parsed from a memory mapping of the file so the parsed element
+/// values are views of the file rather than copies.
+struct MappedFile(PathBuf);
+
+impl DatasetSource for MappedFile {
+    fn parse(
+        &mut self,
+        builder: &ParserBuilder,
+        dictionary: &dyn DicomDictionary,
+    ) -> ParseResult<Option<DicomRoot>> {
+        // SAFETY: The mapping is only read from, and is dropped along with the parsed elements
+        // once the file has been indexed. Modifications to the file by other processes while it's
+        // being scanned are not guarded against.
+        let input = unsafe { Mmap::map(&File::open(&self.0)?)? };
+        DicomRoot::parse(&mut builder.build_shared(input, dictionary))
+    }
+}
+
 pub struct IndexApp {
     args: IndexArgs,
 }
@@ -128,78 +151,126 @@ impl IndexApp {
     fn scan_dir(folder: PathBuf) -> Result<HashMap<String, DicomDoc>> {
         let mut uid_to_doc: HashMap<String, DicomDoc> = HashMap::new();
 
-        let walkdir = WalkDir::new(folder).into_iter().filter_map(Result::ok);
-
-        let parser_builder = ParserBuilder::default().stop(ParseStop::before(&PixelData));
-        for entry in walkdir {
+        let mut archives: Vec<(PathBuf, ArchiveFormat)> = Vec::new();
+        let mut files: Vec<MappedFile> = Vec::new();
+        for entry in WalkDir::new(folder).into_iter().filter_map(Result::ok) {
             if !entry.metadata()?.file_type().is_file() {
                 continue;
             }
+            let path = entry.into_path();
+            match ArchiveFormat::from_path(&path) {
+                Some(format) => archives.push((path, format)),
+                None => files.push(MappedFile(path)),
+            }
+        }
 
-            let input = BufReader::with_capacity(16 * 1024, File::open(entry.path())?);
-            let mut parser = parser_builder.build(input, &STANDARD_DICOM_DICTIONARY);
-
-            let dcm_root = DicomRoot::parse(&mut parser)?;
-            let Some(dcm_root) = dcm_root else {
-                continue;
-            };
+        let parser_builder = ParserBuilder::default().stop(ParseStop::before(&PixelData));
+        for parsed in parser_builder
+            .build_parallel(&STANDARD_DICOM_DICTIONARY)
+            .parse(files)
+        {
+            // Files which fail to parse are logged and skipped rather than ending the scan.
+            let (MappedFile(path), result) = parsed.into_parts();
+            match result {
+                Ok(Some(dcm_root)) => Self::index_root(&mut uid_to_doc, &dcm_root, &path)?,
+                Ok(None) => {}
+                Err(e) => eprintln!("Failed to parse {}: {e}", path.display()),
+            }
+        }
 
-            let Some(sop_uid) = dcm_root
-                .get_value_by_tag(&SOPInstanceUID)
-                .and_then(|v| v.string().cloned())
-            else {
-                continue;
-            };
+        // DICOM files within archives are indexed with a path of the entry appended to the path of
+        // the archive. Entries which can't be read or parsed are logged and skipped.
+        for (path, format) in archives {
+            let archive = BufReader::new(File::open(&path)?);
+            let walker =
+                match parser_builder.build_archive(archive, format, &STANDARD_DICOM_DICTIONARY) {
+                    Ok(walker) => walker,
+                    Err(e) => {
+                        eprintln!("Failed to read archive {}: {e}", path.display());
+                        continue;
+                    }
+                };
+            for archive_entry in walker {
+                let (entry_path, mut parser) = match archive_entry {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        eprintln!("Failed to read entry of {}: {e}", path.display());
+                        continue;
+                    }
+                };
+                let file_path = archive_entry_path(&path, &entry_path);
+                match DicomRoot::parse(&mut parser) {
+                    Ok(Some(dcm_root)) => Self::index_root(&mut uid_to_doc, &dcm_root, &file_path)?,
+                    Ok(None) => {}
+                    Err(e) => eprintln!("Failed to parse {}: {e}", file_path.display()),
+                }
+            }
+        }
 
-            let Some(series_uid) = dcm_root
-                .get_value_by_tag(&SeriesInstanceUID)
-                .and_then(|v| v.string().cloned())
-            else {
-                continue;
-            };
+        Ok(uid_to_doc)
+    }
 
-            let entry_key: String = series_uid.clone();
-            let dicom_doc: &mut DicomDoc = uid_to_doc
-                .entry(entry_key)
-                .or_insert_with(|| DicomDoc::new(series_uid.clone()));
+    /// Adds the parsed DICOM dataset at the given path to the documents of scanned series.
+    fn index_root(
+        uid_to_doc: &mut HashMap<String, DicomDoc>,
+        dcm_root: &DicomRoot,
+        file_path: &Path,
+    ) -> Result<()> {
+        let Some(sop_uid) = dcm_root
+            .get_value_by_tag(&SOPInstanceUID)
+            .and_then(|v| v.string().cloned())
+        else {
+            return Ok(());
+        };
 
-            let metadata_doc: &mut Document = dicom_doc
-                .doc
-                .entry("metadata".to_owned())
-                .or_insert_with(|| Document::new().into())
-                .as_document_mut()
-                .ok_or_else(|| anyhow!("Field failure: metadata"))?;
+        let Some(series_uid) = dcm_root
+            .get_value_by_tag(&SeriesInstanceUID)
+            .and_then(|v| v.string().cloned())
+        else {
+            return Ok(());
+        };
 
-            metadata_doc.insert("serieskey", series_uid);
+        let entry_key: String = series_uid.clone();
+        let dicom_doc: &mut DicomDoc = uid_to_doc
+            .entry(entry_key)
+            .or_insert_with(|| DicomDoc::new(series_uid.clone()));
+
+        let metadata_doc: &mut Document = dicom_doc
+            .doc
+            .entry("metadata".to_owned())
+            .or_insert_with(|| Document::new().into())
+            .as_document_mut()
+            .ok_or_else(|| anyhow!("Field failure: metadata"))?;
+
+        metadata_doc.insert("serieskey", series_uid);
+
+        let files_key: String = "files".to_owned();
+        let files_val: Bson = format!("{}", file_path.display()).into();
+        if let Ok(files_field) = metadata_doc.get_array_mut(files_key.clone()) {
+            files_field.push(files_val);
+        } else {
+            metadata_doc.insert(files_key, vec![files_val].to_owned());
+        }
 
-            let files_key: String = "files".to_owned();
-            let files_val: Bson = format!("{}", entry.path().display()).into();
-            if let Ok(files_field) = metadata_doc.get_array_mut(files_key.clone()) {
-                files_field.push(files_val);
-            } else {
-                metadata_doc.insert(files_key, vec![files_val].to_owned());
-            }
+        let sops_key: String = "sops".to_owned();
+        let sops_val: Bson = sop_uid.into();
+        if let Ok(sops_field) = metadata_doc.get_array_mut(sops_key.clone()) {
+            sops_field.push(sops_val);
+        } else {
+            metadata_doc.insert(sops_key, vec![sops_val].to_owned());
+        }
 
-            let sops_key: String = "sops".to_owned();
-            let sops_val: Bson = sop_uid.into();
-            if let Ok(sops_field) = metadata_doc.get_array_mut(sops_key.clone()) {
-                sops_field.push(sops_val);
+        for (_child_tag, child_obj) in dcm_root.iter_child_nodes() {
+            let child_elem: &DicomElement = child_obj.element();
+            if child_elem.is_sq_like() {
+                // TODO: handle sequences
             } else {
-                metadata_doc.insert(sops_key, vec![sops_val].to_owned());
-            }
-
-            for (_child_tag, child_obj) in dcm_root.iter_child_nodes() {
-                let child_elem: &DicomElement = child_obj.element();
-                if child_elem.is_sq_like() {
-                    // TODO: handle sequences
-                } else {
-                    Self::insert_elem_entry(child_elem, &mut dicom_doc.doc)
-                        .with_context(|| format!("Scan of file {:?}", entry.path()))?;
-                }
+                Self::insert_elem_entry(child_elem, &mut dicom_doc.doc)
+                    .with_context(|| format!("Scan of file {file_path:?}"))?;
             }
         }
 
-        Ok(uid_to_doc)
+        Ok(())
     }
 
     /// Queries mongo for existing documents and updates `self.uid_to_doc` with a related id field
@@ -283,7 +354,7 @@ impl IndexApp {
             let num_files: usize = files_array.len();
             files_array.retain(|bson| match bson.as_str() {
                 None => false,
-                Some(path) => Path::new(path).is_file(),
+                Some(path) => is_file_or_archive_entry(Path::new(path)),
             });
 
             match files_array.len() {
@@ -19,13 +19,17 @@ use anyhow::{anyhow, Result};
 use std::{
     fs::File,
     io::{BufReader, Write},
-    path::Path,
+    path::{Component, Path, PathBuf},
 };
 
 use medicom::{
     core::{
         dcmobject::DicomRoot,
-        defn::ts::TSRef,
+        defn::{
+            dcmdict::{DicomDictionary, MultiDicomDictionary},
+            rtdict::RuntimeDicomDictionary,
+            ts::TSRef,
+        },
         read::{stop::ParseStop, Parser, ParserBuilder},
     },
     dict::{stdlookup::STANDARD_DICOM_DICTIONARY, tags::SOPInstanceUID},
@@ -38,6 +42,8 @@ use medicom::{
 
 pub(crate) mod archiveapp;
 pub(crate) mod browseapp;
+pub(crate) mod dicomdirapp;
+pub(crate) mod diffapp;
 pub(crate) mod imageapp;
 #[cfg(feature = "index")]
 pub(crate) mod indexapp;
@@ -72,6 +78,49 @@ fn parse_file(path: &Path, allow_partial_object: bool) -> Result<Parser<'_, BufR
     Ok(parser)
 }
 
+/// Loads the runtime dictionaries from the given paths.
+fn load_dictionaries(paths: &[PathBuf]) -> Result<Vec<RuntimeDicomDictionary>> {
+    paths
+        .iter()
+        .map(|path| {
+            RuntimeDicomDictionary::from_path(path)
+                .map_err(|e| anyhow!("failed loading dictionary {}: {e}", path.display()))
+        })
+        .collect()
+}
+
+/// Combines the runtime dictionaries with the standard dictionary, with the runtime dictionaries
+/// taking precedence.
+fn with_standard_dictionary(dicts: &[RuntimeDicomDictionary]) -> MultiDicomDictionary<'_> {
+    let mut dict = MultiDicomDictionary::new(
+        dicts
+            .iter()
+            .map(|d| d as &dyn DicomDictionary)
+            .collect::<Vec<&dyn DicomDictionary>>(),
+    );
+    dict.add(&STANDARD_DICOM_DICTIONARY);
+    dict
+}
+
+/// The path used to refer to an entry within an archive, which is the path of the entry appended
+/// to the path of the archive. Only the named components of the entry's path are kept, so an
+/// absolute entry path or one with `..` remains within the archive's path.
+fn archive_entry_path(archive: &Path, entry_path: &str) -> PathBuf {
+    let mut path: PathBuf = archive.to_path_buf();
+    path.extend(
+        Path::new(entry_path)
+            .components()
+            .filter(|component| matches!(component, Component::Normal(_))),
+    );
+    path
+}
+
+/// Whether the path refers to an existing file, or to an entry within an existing archive as from
+/// `archive_entry_path()`. Entries within an archive are not checked for.
+fn is_file_or_archive_entry(path: &Path) -> bool {
+    path.ancestors().any(Path::is_file)
+}
+
 /// Returns a log statement appropriate for the result of an association.
 fn handle_assoc_result<W: Write>(
     result: Result<DimseMsg, AssocError>,
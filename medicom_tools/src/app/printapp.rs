@@ -15,22 +15,36 @@
 */
 
 //! The print command renders the contents of a DICOM dataset to stdout, in a format similar to the
-//! dcmdump tool.
+//! dcmdump tool, or as the DICOM JSON or XML models. If given a zip or tar archive, each DICOM
+//! dataset within it is printed.
 
 use std::{
-    io::{self, Write},
+    fs::File,
+    io::{self, BufReader, Read, Write},
     path::{Path, PathBuf},
 };
 
-use anyhow::Result;
-
-use medicom::core::{
-    dcmelement::DicomElement, defn::constants::tags::FILE_META_GROUP_END, inspect::FormattedElement,
+use anyhow::{anyhow, Result};
+
+use medicom::{
+    core::{
+        dcmelement::DicomElement,
+        dcmobject::DicomRoot,
+        defn::{constants::tags::FILE_META_GROUP_END, rtdict::RuntimeDicomDictionary},
+        inspect::{FormattedElement, PrivateCreators},
+        json::{write_json, JsonWriteOptions},
+        read::{archive::ArchiveFormat, Parser, ParserBuilder},
+        xml::{write_xml, XmlWriteOptions},
+    },
+    dict::stdlookup::STANDARD_DICOM_DICTIONARY,
 };
 
 use crate::{
-    app::{parse_file, CommandApplication},
-    args::PrintArgs,
+    app::{
+        archive_entry_path, load_dictionaries, parse_file, with_standard_dictionary,
+        CommandApplication,
+    },
+    args::{PrintArgs, PrintFormat},
 };
 
 pub struct PrintApp {
@@ -47,35 +61,122 @@ impl CommandApplication for PrintApp {
     fn run(&mut self) -> Result<()> {
         let path_buf: PathBuf = self.args.file.clone();
         let path: &Path = path_buf.as_path();
-        let mut parser = parse_file(path, true)?;
-
         let mut stdout = io::stdout().lock();
-        stdout.write_all(format!(
-            "\n# Dicom-File-Format File: {:#?}\n\n# Dicom-Meta-Information-Header\n# Used TransferSyntax: {}\n",
-            path,
-            parser.ts().uid().ident()).as_ref()
-        )?;
-
-        let mut prev_was_file_meta: bool = true;
-
-        while let Some(elem) = parser.next() {
-            let elem: DicomElement = elem?;
-
-            if prev_was_file_meta && elem.tag() > FILE_META_GROUP_END {
-                stdout.write_all(
-                    format!(
-                        "\n# Dicom-Data-Set\n# Used TransferSyntax: {}\n",
-                        parser.ts().uid().ident()
-                    )
-                    .as_ref(),
+        if self.args.format == PrintFormat::Json && !self.args.dict.is_empty() {
+            // The DICOM JSON Model has no keywords for the dictionaries to provide.
+            return Err(anyhow!("--dict is not supported with the json format"));
+        }
+        let dicts: Vec<RuntimeDicomDictionary> = load_dictionaries(&self.args.dict)?;
+
+        if let Some(format) = ArchiveFormat::from_path(path) {
+            let archive = BufReader::new(File::open(path)?);
+            let walker = ParserBuilder::default()
+                .allow_partial_object(true)
+                .build_archive(archive, format, &STANDARD_DICOM_DICTIONARY)?;
+            for entry in walker {
+                let (entry_path, mut parser) = entry?;
+                self.print(
+                    &mut stdout,
+                    &archive_entry_path(path, &entry_path),
+                    &mut parser,
+                    &dicts,
                 )?;
-                prev_was_file_meta = false;
             }
+            return Ok(());
+        }
 
-            let printed: String = FormattedElement::new(&elem).to_string();
-            stdout.write_all(format!("{printed}\n").as_ref())?;
+        let mut parser = parse_file(path, true)?;
+        self.print(&mut stdout, path, &mut parser, &dicts)
+    }
+}
+
+impl PrintApp {
+    fn print<W: Write, R: Read>(
+        &self,
+        stdout: &mut W,
+        path: &Path,
+        parser: &mut Parser<'_, R>,
+        dicts: &[RuntimeDicomDictionary],
+    ) -> Result<()> {
+        match self.args.format {
+            PrintFormat::Text => print_dataset(stdout, path, parser, dicts),
+            PrintFormat::Json => print_json(stdout, parser),
+            PrintFormat::Xml => print_xml(stdout, parser, dicts),
         }
+    }
+}
 
-        Ok(())
+/// Prints all elements of the dataset being parsed, with headers for the File Meta and dataset
+/// sections. Private tags are resolved with the given dictionaries by their private creator.
+fn print_dataset<W: Write, R: Read>(
+    stdout: &mut W,
+    path: &Path,
+    parser: &mut Parser<'_, R>,
+    dicts: &[RuntimeDicomDictionary],
+) -> Result<()> {
+    stdout.write_all(format!(
+        "\n# Dicom-File-Format File: {:#?}\n\n# Dicom-Meta-Information-Header\n# Used TransferSyntax: {}\n",
+        path,
+        parser.ts().uid().ident()).as_ref()
+    )?;
+
+    let mut prev_was_file_meta: bool = true;
+    let mut creators = PrivateCreators::default();
+
+    while let Some(elem) = parser.next() {
+        let elem: DicomElement = elem?;
+
+        if prev_was_file_meta && elem.tag() > FILE_META_GROUP_END {
+            stdout.write_all(
+                format!(
+                    "\n# Dicom-Data-Set\n# Used TransferSyntax: {}\n",
+                    parser.ts().uid().ident()
+                )
+                .as_ref(),
+            )?;
+            prev_was_file_meta = false;
+        }
+
+        creators.record(&elem);
+        let printed: String = FormattedElement::new(&elem)
+            .with_dict(with_standard_dictionary(dicts))
+            .with_private_creator(creators.creator_of(&elem).map(str::to_string))
+            .to_string();
+        stdout.write_all(format!("{printed}\n").as_ref())?;
     }
+
+    Ok(())
+}
+
+/// Prints the dataset being parsed in the DICOM JSON Model.
+fn print_json<W: Write, R: Read>(stdout: &mut W, parser: &mut Parser<'_, R>) -> Result<()> {
+    let Some(dcmroot) = DicomRoot::parse(parser)? else {
+        return Ok(());
+    };
+    write_json(
+        &dcmroot,
+        &mut *stdout,
+        &JsonWriteOptions::default().pretty(true),
+    )?;
+    stdout.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Prints the dataset being parsed in the Native DICOM Model XML. Keywords of private attributes
+/// are resolved with the given dictionaries by their private creator.
+fn print_xml<W: Write, R: Read>(
+    stdout: &mut W,
+    parser: &mut Parser<'_, R>,
+    dicts: &[RuntimeDicomDictionary],
+) -> Result<()> {
+    let Some(dcmroot) = DicomRoot::parse(parser)? else {
+        return Ok(());
+    };
+    let dictionary = with_standard_dictionary(dicts);
+    let options = XmlWriteOptions::default()
+        .dictionary(&dictionary)
+        .pretty(true);
+    write_xml(&dcmroot, &mut *stdout, &options)?;
+    stdout.write_all(b"\n")?;
+    Ok(())
 }
@@ -109,6 +109,7 @@ impl CommandApplication for SvcProviderApp {
         for (stream_id, stream) in listener.incoming().enumerate() {
             let stream = stream?;
             let db = self.args.db.clone();
+            let store_dir = self.args.store_dir.clone();
             let assoc = ServiceAssocBuilder::new()
                 .id(stream_id)
                 .host_ae(self.args.aetitle.clone())
@@ -125,6 +126,7 @@ impl CommandApplication for SvcProviderApp {
                     reader,
                     writer,
                     db,
+                    store_dir,
                 };
                 assoc_dev.start();
             })?;
@@ -200,6 +202,7 @@ struct AssociationDevice<R: Read, W: Write> {
     reader: R,
     writer: W,
     db: Option<String>,
+    store_dir: Option<PathBuf>,
 }
 
 impl<R: Read, W: Write> AssociationDevice<R, W> {
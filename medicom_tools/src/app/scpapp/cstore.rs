@@ -14,13 +14,27 @@
    limitations under the License.
 */
 
-use std::io::{Read, Write};
+use std::{
+    io::{Read, Write},
+    path::Path,
+};
 
-use medicom::dimse::{
-    assoc::CommonAssoc,
-    commands::{messages::CommandMessage, CommandStatus},
-    error::AssocError,
-    svcops::StoreSvcOp,
+use anyhow::anyhow;
+use medicom::{
+    core::{
+        dcmobject::DicomRoot,
+        write::{
+            builder::WriterBuilder,
+            file::{write_dcmroot_to_path, WriteFileOptions},
+        },
+    },
+    dict::tags::SOPInstanceUID,
+    dimse::{
+        assoc::CommonAssoc,
+        commands::{messages::CommandMessage, CommandStatus},
+        error::AssocError,
+        svcops::StoreSvcOp,
+    },
 };
 
 use crate::app::scpapp::AssociationDevice;
@@ -33,19 +47,59 @@ impl<R: Read, W: Write> AssociationDevice<R, W> {
     ) -> Result<(), AssocError> {
         op.process_req(cmd)?;
 
-        // TODO: Tuck this away somewhere. Add appropriate FileMeta elements.
-        let mut empty = std::io::empty();
-        CommonAssoc::read_dataset(
-            &mut self.reader,
-            &mut self.writer,
-            self.assoc.common().get_pdu_max_rcv_size(),
-            &mut empty,
-        )?;
+        let status = if let Some(store_dir) = &self.store_dir {
+            let (_pres_ctx, ts) = self.assoc.common().get_pres_ctx_and_ts(cmd.ctx_id())?;
+            let dcmroot = CommonAssoc::read_dataset_in_mem(
+                &mut self.reader,
+                &mut self.writer,
+                self.assoc.common().get_pdu_max_rcv_size(),
+                ts,
+            )?;
+            match Self::store_dataset(store_dir, self.assoc.common().this_ae(), &dcmroot) {
+                Ok(()) => CommandStatus::success(),
+                Err(e) => {
+                    eprintln!("[warn <>]: Failed to store dataset: {e}");
+                    CommandStatus::fail_rsrc()
+                }
+            }
+        } else {
+            let mut empty = std::io::empty();
+            CommonAssoc::read_dataset(
+                &mut self.reader,
+                &mut self.writer,
+                self.assoc.common().get_pdu_max_rcv_size(),
+                &mut empty,
+            )?;
+            CommandStatus::success()
+        };
 
         op.write_response(
             &mut self.writer,
             self.assoc.common().get_pdu_max_snd_size(),
-            &CommandStatus::success(),
+            &status,
         )
     }
+
+    /// Saves the dataset as a DICOM file in the given directory, named by its SOP Instance UID. The
+    /// file is written atomically so an interrupted store never leaves a partial file.
+    fn store_dataset(store_dir: &Path, aetitle: &str, dcmroot: &DicomRoot) -> anyhow::Result<()> {
+        let sop_instance_uid = dcmroot
+            .get_value_by_tag(&SOPInstanceUID)
+            .and_then(|v| v.string().cloned())
+            .filter(|uid| !uid.is_empty() && uid.chars().all(|c| c.is_ascii_digit() || c == '.'))
+            .ok_or_else(|| anyhow!("dataset has no valid SOPInstanceUID"))?;
+
+        let options = WriteFileOptions::default().writer(
+            WriterBuilder::for_file()
+                .ts(dcmroot.ts())
+                .generate_file_meta(true)
+                .source_ae_title(aetitle.trim()),
+        );
+        write_dcmroot_to_path(
+            dcmroot,
+            store_dir.join(format!("{sop_instance_uid}.dcm")),
+            &options,
+        )?;
+        Ok(())
+    }
 }
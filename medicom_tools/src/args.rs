@@ -16,7 +16,7 @@
 
 use std::{error::Error, path::PathBuf};
 
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use medicom::dimse::assoc::QueryLevel;
 
 #[derive(Parser, Debug)]
@@ -29,6 +29,8 @@ pub struct Arguments {
 #[derive(Subcommand, Debug)]
 pub enum Command {
     /// Parses a single file and prints the DICOM elements to stdout.
+    ///
+    /// If the file is a zip or tar archive then each DICOM dataset within it is printed.
     Print(PrintArgs),
 
     /// Encodes Pixel Data into a standard image format.
@@ -37,9 +39,25 @@ pub enum Command {
     /// Browse a DICOM dataset in a text-based user interface.
     Browse(BrowseArgs),
 
+    /// Compares two DICOM datasets and prints the differences to stdout.
+    ///
+    /// Elements are compared by value, recursing into sequences, so datasets encoded in different
+    /// transfer syntaxes can be compared.
+    ///
+    /// As with diff(1), the exit status is 0 if the datasets are the same, 1 if they differ, and 2
+    /// if an error occurs.
+    Diff(DiffArgs),
+
+    /// Prints the directory records of a DICOMDIR, or creates a DICOMDIR for a folder.
+    ///
+    /// The records are printed as a tree of PATIENT, STUDY, SERIES, and IMAGE records, with the
+    /// path of each file a record refers to.
+    Dicomdir(DicomDirArgs),
+
     /// Manage a database index of DICOM on disk.
     ///
-    /// Recursively scans a folder for DICOM datasets, indexing them into a database.
+    /// Recursively scans a folder for DICOM datasets, indexing them into a database. DICOM datasets
+    /// within zip and tar archives are also indexed.
     #[cfg(feature = "index")]
     Index(IndexArgs),
 
@@ -60,8 +78,31 @@ pub enum Command {
 
 #[derive(Args, Debug)]
 pub struct PrintArgs {
-    /// The file to process as a DICOM dataset.
+    /// The file to process as a DICOM dataset, or a `.zip`, `.tar`, `.tar.gz`, or `.tgz` archive
+    /// of DICOM datasets.
     pub file: PathBuf,
+
+    #[arg(short, long, value_enum, default_value_t = PrintFormat::Text)]
+    /// The output format.
+    pub format: PrintFormat,
+
+    #[arg(short, long)]
+    /// A dictionary of additional tags, such as vendor private tags, in the dcmtk `.dic`, `.csv`,
+    /// or `.json` format. May be given multiple times. Not supported with the `json` format, which
+    /// has no attribute names.
+    pub dict: Vec<PathBuf>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrintFormat {
+    /// Each element on a line, similar to dcmdump.
+    Text,
+
+    /// The DICOM JSON Model, with binary values included inline as base64.
+    Json,
+
+    /// The Native DICOM Model XML, with binary values included inline as base64.
+    Xml,
 }
 
 #[derive(Args, Debug)]
@@ -77,6 +118,34 @@ pub struct ImageArgs {
 pub struct BrowseArgs {
     /// The file to process as a DICOM dataset.
     pub file: PathBuf,
+
+    #[arg(short, long)]
+    /// A dictionary of additional tags, such as vendor private tags, in the dcmtk `.dic`, `.csv`,
+    /// or `.json` format. May be given multiple times.
+    pub dict: Vec<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    /// The original DICOM file.
+    pub left: PathBuf,
+
+    /// The DICOM file to compare against the original.
+    pub right: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct DicomDirArgs {
+    /// The DICOMDIR file to print, or with `--create` the folder to create a DICOMDIR for.
+    pub path: PathBuf,
+
+    #[arg(short, long)]
+    /// Create a DICOMDIR in the folder for the DICOM files within it.
+    pub create: bool,
+
+    #[arg(long)]
+    /// The File-set ID of the created DICOMDIR.
+    pub file_set_id: Option<String>,
 }
 
 #[derive(Args, Debug)]
@@ -94,7 +163,8 @@ pub struct IndexArgs {
 pub enum IndexCommand {
     /// Recursively scans a folder for DICOM datasets, indexing them into a database.
     Scan {
-        /// The folder to scan for DICOM datasets.
+        /// The folder to scan for DICOM datasets, or a `.zip`, `.tar`, `.tar.gz`, or `.tgz` archive
+        /// of DICOM datasets.
         folder: PathBuf,
     },
     /// Verify records in the database reference valid files on-disk.
@@ -147,6 +217,12 @@ pub struct SvcProviderArgs {
     /// If no accepted AE Titles are specified then all AE Titles are accepted, but cannot be
     /// connected to, such as for handling C-MOVE requests.
     pub accept_aet: Vec<(String, String)>,
+
+    #[arg(long)]
+    /// A directory to save datasets received by C-STORE into, named by their SOP Instance UID.
+    ///
+    /// If not specified then received datasets are discarded.
+    pub store_dir: Option<PathBuf>,
 }
 
 #[derive(Args, Debug)]
@@ -23,8 +23,9 @@ use clap::Parser;
 #[cfg(feature = "index")]
 use crate::{
     app::{
-        archiveapp::ArchiveApp, browseapp::BrowseApp, imageapp::ImageApp, indexapp::IndexApp,
-        printapp::PrintApp, scpapp::SvcProviderApp, scuapp::SvcUserApp, CommandApplication,
+        archiveapp::ArchiveApp, browseapp::BrowseApp, dicomdirapp::DicomDirApp, diffapp::DiffApp,
+        imageapp::ImageApp, indexapp::IndexApp, printapp::PrintApp, scpapp::SvcProviderApp,
+        scuapp::SvcUserApp, CommandApplication,
     },
     args::{Arguments, Command},
 };
@@ -48,6 +49,8 @@ fn make_app() -> Box<dyn CommandApplication> {
         Command::Print(args) => Box::new(PrintApp::new(args)),
         Command::Image(args) => Box::new(ImageApp::new(args)),
         Command::Browse(args) => Box::new(BrowseApp::new(args)),
+        Command::Diff(args) => Box::new(DiffApp::new(args)),
+        Command::Dicomdir(args) => Box::new(DicomDirApp::new(args)),
         #[cfg(feature = "index")]
         Command::Index(args) => Box::new(IndexApp::new(args)),
         Command::Archive(args) => Box::new(ArchiveApp::new(args)),
@@ -18,6 +18,10 @@
 //!
 //! <https://doc.rust-lang.org/book/ch20-02-multithreaded.html>
 //! <https://doc.rust-lang.org/book/ch20-03-graceful-shutdown-and-cleanup.html>
+//!
+//! This runs arbitrary jobs, and is used by the SCP to limit the number of associations handled
+//! at once. Parsing many datasets should instead use `medicom::core::read::parallel`, whose
+//! `ParallelParser` only runs parse jobs.
 use anyhow::{anyhow, Result};
 
 use std::{